@@ -17,7 +17,7 @@ fn create_sample_data(size: usize) -> LLSDValue {
         let value = match i % 5 {
             0 => LLSDValue::String(format!("string_value_{}", i)),
             1 => LLSDValue::Integer(i as i32),
-            2 => LLSDValue::Real(i as f64 * 3.14159),
+            2 => LLSDValue::Real(i as f64 * std::f64::consts::PI),
             3 => LLSDValue::Boolean(i % 2 == 0),
             4 => LLSDValue::UUID(Uuid::new_v4()),
             _ => unreachable!(),
@@ -129,6 +129,13 @@ fn bench_deep_cloning(c: &mut Criterion) {
                 b.iter(|| LLSDUtils::deep_clone(data));
             }
         );
+        group.bench_with_input(
+            BenchmarkId::new("clone_safe", size),
+            &data,
+            |b, data| {
+                b.iter(|| LLSDUtils::deep_clone_safe(data));
+            }
+        );
     }
     
     group.finish();
@@ -167,6 +174,12 @@ fn bench_path_navigation(c: &mut Criterion) {
         });
     });
     
+    group.bench_function("utils_get_str", |b| {
+        b.iter(|| {
+            LLSDUtils::get_str(&nested_data, "level1_50.level2_5").unwrap();
+        });
+    });
+    
     group.finish();
 }
 
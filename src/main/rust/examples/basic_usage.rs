@@ -54,10 +54,8 @@ fn basic_usage_example() -> LLSDResult<()> {
     println!("User: {} (age: {}, premium: {})", name, age, is_premium);
 
     // Navigate nested structures
-    if let Some(scores_array) = document.content().get_path("scores") {
-        if let LLSDValue::Array(scores) = scores_array {
-            println!("Test scores: {:?}", scores);
-        }
+    if let Some(LLSDValue::Array(scores)) = document.content().get_path("scores") {
+        println!("Test scores: {:?}", scores);
     }
 
     Ok(())
@@ -163,7 +161,7 @@ fn firestorm_example() -> LLSDResult<()> {
 
     // RLV Command
     let rlv_command = RLVCommand::new("@sit", "ground", "=force", Uuid::new_v4());
-    println!("RLV Command: {}", rlv_command.to_string());
+    println!("RLV Command: {}", rlv_command);
 
     // Performance stats
     let perf_stats = FirestormLLSDUtils::create_performance_stats(
@@ -0,0 +1,13 @@
+//! Feed arbitrary bytes to the binary parser. It must return `Ok` or `Err`
+//! for every input, never panic or exhaust memory.
+//!
+//! Run with `cargo fuzz run binary_parser` from the crate root.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use llsd::LLSDBinaryParser;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = LLSDBinaryParser::new().parse(data);
+});
@@ -5,16 +5,44 @@
  * Copyright (C) 2024 Linden Lab
  */
 
-use crate::types::{LLSDValue, LLSDDocument};
+use crate::types::{LLSDValue, LLSDDocument, LLSDType};
 use crate::error::{LLSDError, LLSDResult};
+use crate::utils::{BudgetMeter, ParseBudget, BUDGET_CHECK_INTERVAL};
 use std::collections::HashMap;
-use std::io::{Cursor, Read, Write};
+use std::io::{Cursor, Read};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 use uuid::Uuid;
-use chrono::{DateTime, Utc, TimeZone};
-use bytes::{Buf, BufMut, BytesMut};
+use chrono::{Utc, TimeZone};
+use bytes::{BufMut, BytesMut};
 
 /// LLSD Binary format magic number
-const LLSD_BINARY_MAGIC: u32 = 0x6C6C7364; // 'llsd' in big-endian
+pub(crate) const LLSD_BINARY_MAGIC: u32 = 0x6C6C7364; // 'llsd' in big-endian
+
+/// Magic number for this crate's extended binary format, followed by a
+/// flags byte. Extended payloads only round-trip within this crate.
+pub(crate) const LLSD_EXTENDED_MAGIC: u32 = 0x6C6C7378; // 'llsx' in big-endian
+
+/// Extended header flag: strings are deduplicated through a string table
+const FLAG_STRING_TABLE: u8 = 0x01;
+
+/// Extended header flag: string and binary lengths are `u64` rather than `u32`
+const FLAG_LARGE_SIZES: u8 = 0x02;
+
+/// Extended header flag: arrays and maps have no element count and end
+/// with a [`BinaryType::End`] marker instead
+const FLAG_TERMINATED: u8 = 0x04;
+
+/// Flags understood by this version of the parser
+const KNOWN_FLAGS: u8 = FLAG_STRING_TABLE | FLAG_LARGE_SIZES | FLAG_TERMINATED;
+
+/// In string-table mode, a string length with this bit set is instead a
+/// back-reference to the string table entry at the remaining index
+const STRING_REF_BIT: u32 = 0x8000_0000;
+
+/// [`STRING_REF_BIT`] for 64-bit size prefixes
+const LARGE_STRING_REF_BIT: u64 = 0x8000_0000_0000_0000;
 
 /// LLSD binary type identifiers
 #[repr(u8)]
@@ -25,12 +53,18 @@ enum BinaryType {
     Integer = 2,
     Real = 3,
     String = 4,
-    UUID = 5,
+    Uuid = 5,
     Date = 6,
-    URI = 7,
+    Uri = 7,
     Binary = 8,
     Array = 9,
     Map = 10,
+    /// 64-bit signed integer, 8 bytes big-endian. An extension of this
+    /// crate; reference LLSD binary has no 64-bit integer type.
+    Integer64 = 11,
+    /// Closes an array or map in terminated-container payloads. Also an
+    /// extension; not a value and invalid anywhere else.
+    End = 12,
 }
 
 impl TryFrom<u8> for BinaryType {
@@ -43,35 +77,117 @@ impl TryFrom<u8> for BinaryType {
             2 => Ok(BinaryType::Integer),
             3 => Ok(BinaryType::Real),
             4 => Ok(BinaryType::String),
-            5 => Ok(BinaryType::UUID),
+            5 => Ok(BinaryType::Uuid),
             6 => Ok(BinaryType::Date),
-            7 => Ok(BinaryType::URI),
+            7 => Ok(BinaryType::Uri),
             8 => Ok(BinaryType::Binary),
             9 => Ok(BinaryType::Array),
             10 => Ok(BinaryType::Map),
+            11 => Ok(BinaryType::Integer64),
+            12 => Ok(BinaryType::End),
             _ => Err(LLSDError::InvalidType { type_id: value }),
         }
     }
 }
 
+impl BinaryType {
+    /// The LLSD type of values with this tag; `None` for [`BinaryType::End`]
+    fn llsd_type(self) -> Option<LLSDType> {
+        match self {
+            BinaryType::Undefined => Some(LLSDType::Unknown),
+            BinaryType::Boolean => Some(LLSDType::Boolean),
+            BinaryType::Integer => Some(LLSDType::Integer),
+            BinaryType::Real => Some(LLSDType::Real),
+            BinaryType::String => Some(LLSDType::String),
+            BinaryType::Uuid => Some(LLSDType::UUID),
+            BinaryType::Date => Some(LLSDType::Date),
+            BinaryType::Uri => Some(LLSDType::URI),
+            BinaryType::Binary => Some(LLSDType::Binary),
+            BinaryType::Array => Some(LLSDType::Array),
+            BinaryType::Map => Some(LLSDType::Map),
+            BinaryType::Integer64 => Some(LLSDType::Integer64),
+            BinaryType::End => None,
+        }
+    }
+}
+
+/// Upper bound on container capacity reserved from a declared length, so
+/// a short payload claiming a huge array cannot force a large allocation
+const MAX_PREALLOCATED_ELEMENTS: usize = 1024;
+
+/// Byte order of multi-byte numbers in binary LLSD
+///
+/// LLSD is defined as big-endian (network order); little-endian is only
+/// for interoperating with tools that wrote host-order captures. The magic
+/// number is written in the same order, so data in one order is rejected
+/// by a parser expecting the other.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ByteOrder {
+    #[default]
+    BigEndian,
+    LittleEndian,
+}
+
+macro_rules! byte_order_methods {
+    ($($ty:ty => $get:ident, $put:ident, $put_be:ident, $put_le:ident;)*) => {
+        impl ByteOrder {
+            $(
+                fn $get(self, bytes: [u8; std::mem::size_of::<$ty>()]) -> $ty {
+                    match self {
+                        ByteOrder::BigEndian => <$ty>::from_be_bytes(bytes),
+                        ByteOrder::LittleEndian => <$ty>::from_le_bytes(bytes),
+                    }
+                }
+
+                fn $put(self, buffer: &mut BytesMut, value: $ty) {
+                    match self {
+                        ByteOrder::BigEndian => buffer.$put_be(value),
+                        ByteOrder::LittleEndian => buffer.$put_le(value),
+                    }
+                }
+            )*
+        }
+    };
+}
+
+byte_order_methods! {
+    u32 => get_u32, put_u32, put_u32, put_u32_le;
+    i32 => get_i32, put_i32, put_i32, put_i32_le;
+    i64 => get_i64, put_i64, put_i64, put_i64_le;
+    u64 => get_u64, put_u64, put_u64, put_u64_le;
+    f64 => get_f64, put_f64, put_f64, put_f64_le;
+}
+
 /// LLSD binary parser
 #[derive(Debug, Default)]
 pub struct LLSDBinaryParser {
+    byte_order: ByteOrder,
     validate_magic: bool,
     max_depth: usize,
     max_elements: usize,
+    budget: ParseBudget,
+    cancel_flag: Option<Arc<AtomicBool>>,
 }
 
 impl LLSDBinaryParser {
     /// Create a new binary parser
     pub fn new() -> Self {
         Self {
+            byte_order: ByteOrder::BigEndian,
             validate_magic: true,
             max_depth: 1000,
             max_elements: 1000000,
+            budget: ParseBudget::default(),
+            cancel_flag: None,
         }
     }
 
+    /// Read multi-byte numbers in `order`; big-endian by default
+    pub fn with_byte_order(mut self, order: ByteOrder) -> Self {
+        self.byte_order = order;
+        self
+    }
+
     /// Disable magic number validation (for parsing partial data)
     pub fn without_magic_validation(mut self) -> Self {
         self.validate_magic = false;
@@ -90,83 +206,385 @@ impl LLSDBinaryParser {
         self
     }
 
+    /// Abort with [`LLSDError::ParseAborted`] if parsing takes longer than
+    /// `budget`. Shorthand for setting only the time limit of the
+    /// [parse budget](Self::with_parse_budget); its byte limit is kept.
+    pub fn with_time_budget(self, budget: Duration) -> Self {
+        let budget = ParseBudget { max_duration: Some(budget), ..self.budget };
+        self.with_parse_budget(budget)
+    }
+
+    /// Abort with [`LLSDError::ParseAborted`] once parsing exceeds either
+    /// limit of `budget`. Both limits are replaced, including a time limit
+    /// from [`with_time_budget`](Self::with_time_budget).
+    /// Copies made through the extended format's string table count in
+    /// full, so small payloads cannot expand without bound.
+    pub fn with_parse_budget(mut self, budget: ParseBudget) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Abort with [`LLSDError::ParseAborted`] once `flag` is set, e.g. from
+    /// another thread or when an async request is dropped
+    pub fn with_cancel_flag(mut self, flag: Arc<AtomicBool>) -> Self {
+        self.cancel_flag = Some(flag);
+        self
+    }
+
     /// Parse LLSD from binary data
     pub fn parse(&self, data: &[u8]) -> LLSDResult<LLSDDocument> {
-        let mut cursor = Cursor::new(data);
+        if data.is_empty() {
+            return Err(LLSDError::EmptyInput);
+        }
+
+        self.parse_document(Cursor::new(data))
+    }
+
+    /// Type of the root value, read from the header and first type byte
+    /// only, so callers can route a payload without decoding it. The rest
+    /// of the payload is not validated. The header is read with this
+    /// parser's byte order and magic validation settings.
+    pub fn probe_root_type(&self, data: &[u8]) -> LLSDResult<LLSDType> {
+        if data.is_empty() {
+            return Err(LLSDError::EmptyInput);
+        }
+
+        let mut state = self.begin(Cursor::new(data))?;
+        self.read_type(&mut state, 0)?.llsd_type().ok_or_else(|| {
+            LLSDError::binary_error("Unexpected container end marker at the root")
+        })
+    }
+
+    /// Parse LLSD from binary data without native recursion.
+    ///
+    /// Containers are assembled on a heap-allocated work stack, so nesting
+    /// is bounded only by `with_max_depth` and memory rather than by the
+    /// thread's stack size. Results and errors are identical to
+    /// [`parse`](Self::parse), which is faster for ordinary documents.
+    pub fn parse_iterative(&self, data: &[u8]) -> LLSDResult<LLSDDocument> {
+        if data.is_empty() {
+            return Err(LLSDError::EmptyInput);
+        }
+
+        let mut state = self.begin(Cursor::new(data))?;
+        let value = self.parse_value_iterative(&mut state)?;
+        Ok(LLSDDocument::new(value))
+    }
+
+    /// Parse LLSD incrementally from a reader, without buffering the whole
+    /// payload in memory first
+    pub fn parse_reader<R: Read>(&self, mut reader: R) -> LLSDResult<LLSDDocument> {
+        let mut first = [0u8; 1];
+        loop {
+            match reader.read(&mut first) {
+                Ok(0) => return Err(LLSDError::EmptyInput),
+                Ok(_) => break,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(LLSDError::from(e)),
+            }
+        }
+
+        self.parse_document(Cursor::new(first).chain(reader))
+    }
+
+    /// Parse the header and root value from `reader`
+    fn parse_document<R: Read>(&self, reader: R) -> LLSDResult<LLSDDocument> {
+        let mut state = self.begin(reader)?;
+        let value = self.parse_value(&mut state, 0)?;
+        Ok(LLSDDocument::new(value))
+    }
+
+    /// Set up parse state and consume the header from `reader`
+    fn begin<R: Read>(&self, reader: R) -> LLSDResult<ParseState<R>> {
+        let mut state = ParseState {
+            reader,
+            string_table: None,
+            large_sizes: false,
+            terminated: false,
+            meter: BudgetMeter::start(self.budget),
+            values_until_check: 0,
+        };
         
         if self.validate_magic {
-            let magic = self.read_u32(&mut cursor)?;
-            if magic != LLSD_BINARY_MAGIC {
-                return Err(LLSDError::InvalidMagic);
+            match self.read_u32(&mut state)? {
+                LLSD_BINARY_MAGIC => {}
+                LLSD_EXTENDED_MAGIC => {
+                    let flags = self.read_u8(&mut state)?;
+                    if flags & !KNOWN_FLAGS != 0 {
+                        return Err(LLSDError::binary_error(format!(
+                            "Unsupported extended header flags: {:#04x}",
+                            flags
+                        )));
+                    }
+                    if flags & FLAG_STRING_TABLE != 0 {
+                        state.string_table = Some(Vec::new());
+                    }
+                    state.large_sizes = flags & FLAG_LARGE_SIZES != 0;
+                    state.terminated = flags & FLAG_TERMINATED != 0;
+                }
+                _ => return Err(LLSDError::InvalidMagic),
             }
         }
 
-        let value = self.parse_value(&mut cursor, 0)?;
-        Ok(LLSDDocument::new(value))
+        Ok(state)
     }
 
     /// Parse a single value from binary data
-    fn parse_value(&self, cursor: &mut Cursor<&[u8]>, depth: usize) -> LLSDResult<LLSDValue> {
+    fn parse_value<R: Read>(&self, state: &mut ParseState<R>, depth: usize) -> LLSDResult<LLSDValue> {
+        let binary_type = self.read_type(state, depth)?;
+        self.parse_typed(state, binary_type, depth)
+    }
+
+    /// Parse the body of a value whose type byte has been read
+    fn parse_typed<R: Read>(
+        &self,
+        state: &mut ParseState<R>,
+        binary_type: BinaryType,
+        depth: usize,
+    ) -> LLSDResult<LLSDValue> {
+        match binary_type {
+            BinaryType::Array => self.parse_array(state, depth),
+            BinaryType::Map => self.parse_map(state, depth),
+            scalar => self.read_scalar(state, scalar),
+        }
+    }
+
+    /// Check limits and read the type byte of a value at `depth`
+    fn read_type<R: Read>(&self, state: &mut ParseState<R>, depth: usize) -> LLSDResult<BinaryType> {
+        self.enter_value(state, depth)?;
+        let type_byte = self.read_u8(state)?;
+        BinaryType::try_from(type_byte)
+    }
+
+    /// Check the depth limit and time budget before a value at `depth`
+    fn enter_value<R: Read>(&self, state: &mut ParseState<R>, depth: usize) -> LLSDResult<()> {
         if depth > self.max_depth {
             return Err(LLSDError::binary_error("Maximum parsing depth exceeded"));
         }
 
-        let type_byte = self.read_u8(cursor)?;
-        let binary_type = BinaryType::try_from(type_byte)?;
+        if state.values_until_check == 0 {
+            self.check_budget(state)?;
+            state.values_until_check = BUDGET_CHECK_INTERVAL;
+        }
+        state.values_until_check -= 1;
+        state.meter.charge(std::mem::size_of::<LLSDValue>())
+    }
 
+    /// In a terminated array holding `count` items, read the type of the
+    /// next item at `depth`, or `None` at the end marker
+    fn next_item_type<R: Read>(
+        &self,
+        state: &mut ParseState<R>,
+        depth: usize,
+        count: usize,
+    ) -> LLSDResult<Option<BinaryType>> {
+        let type_byte = self.read_u8(state)?;
+        if type_byte == BinaryType::End as u8 {
+            return Ok(None);
+        }
+        if count >= self.max_elements {
+            return Err(LLSDError::binary_error("Array too large"));
+        }
+        self.enter_value(state, depth)?;
+        BinaryType::try_from(type_byte).map(Some)
+    }
+
+    /// In a terminated map holding `count` entries, read the next key, or
+    /// `None` at the end marker. Each key is tagged as a string so it can be
+    /// told apart from the marker.
+    fn next_entry_key<R: Read>(&self, state: &mut ParseState<R>, count: usize) -> LLSDResult<Option<String>> {
+        let tag = self.read_u8(state)?;
+        if tag == BinaryType::End as u8 {
+            return Ok(None);
+        }
+        if tag != BinaryType::String as u8 {
+            return Err(LLSDError::binary_error(format!(
+                "Expected map key or end marker in terminated map, got type {}",
+                tag
+            )));
+        }
+        if count >= self.max_elements {
+            return Err(LLSDError::binary_error("Map too large"));
+        }
+        self.read_string(state).map(Some)
+    }
+
+    /// Read the body of a non-container value
+    fn read_scalar<R: Read>(&self, state: &mut ParseState<R>, binary_type: BinaryType) -> LLSDResult<LLSDValue> {
         match binary_type {
             BinaryType::Undefined => Ok(LLSDValue::Undefined),
             BinaryType::Boolean => {
-                let value = self.read_u8(cursor)? != 0;
+                let value = self.read_u8(state)? != 0;
                 Ok(LLSDValue::Boolean(value))
             }
             BinaryType::Integer => {
-                let value = self.read_i32(cursor)?;
+                let value = self.read_i32(state)?;
                 Ok(LLSDValue::Integer(value))
             }
+            BinaryType::Integer64 => {
+                let value = self.read_i64(state)?;
+                Ok(LLSDValue::Integer64(value))
+            }
             BinaryType::Real => {
-                let value = self.read_f64(cursor)?;
+                let value = self.read_f64(state)?;
                 Ok(LLSDValue::Real(value))
             }
             BinaryType::String => {
-                let string = self.read_string(cursor)?;
+                let string = self.read_string(state)?;
                 Ok(LLSDValue::String(string))
             }
-            BinaryType::UUID => {
-                let uuid = self.read_uuid(cursor)?;
+            BinaryType::Uuid => {
+                let uuid = self.read_uuid(state)?;
                 Ok(LLSDValue::UUID(uuid))
             }
             BinaryType::Date => {
-                let timestamp = self.read_f64(cursor)?;
-                let date = Utc.timestamp_opt(timestamp as i64, ((timestamp.fract() * 1e9) as u32))
+                let timestamp = self.read_f64(state)?;
+                let date = Utc.timestamp_opt(timestamp as i64, (timestamp.fract() * 1e9) as u32)
                     .single()
                     .ok_or_else(|| LLSDError::binary_error("Invalid timestamp"))?;
                 Ok(LLSDValue::Date(date))
             }
-            BinaryType::URI => {
-                let uri = self.read_string(cursor)?;
+            BinaryType::Uri => {
+                let uri = self.read_string(state)?;
                 Ok(LLSDValue::URI(uri))
             }
             BinaryType::Binary => {
-                let binary = self.read_binary(cursor)?;
+                let binary = self.read_binary(state)?;
                 Ok(LLSDValue::Binary(binary))
             }
-            BinaryType::Array => self.parse_array(cursor, depth),
-            BinaryType::Map => self.parse_map(cursor, depth),
+            BinaryType::Array | BinaryType::Map => {
+                Err(LLSDError::binary_error("Container is not a scalar value"))
+            }
+            BinaryType::End => Err(LLSDError::binary_error(
+                "Unexpected container end marker; terminated containers need the llsx header flag",
+            )),
         }
     }
 
-    /// Parse an array from binary data
-    fn parse_array(&self, cursor: &mut Cursor<&[u8]>, depth: usize) -> LLSDResult<LLSDValue> {
-        let length = self.read_u32(cursor)? as usize;
-        
+    /// Parse a value using an explicit stack of partially built containers
+    fn parse_value_iterative<R: Read>(&self, state: &mut ParseState<R>) -> LLSDResult<LLSDValue> {
+        let mut stack: Vec<Frame> = Vec::new();
+
+        loop {
+            // Read the next value's type, or `None` when a terminated
+            // container reaches its end marker
+            let depth = stack.len();
+            let next = match stack.last_mut() {
+                Some(Frame::Array { items, remaining: None }) => self.next_item_type(state, depth, items.len())?,
+                Some(Frame::Map { entries, key, remaining }) => {
+                    // Map entries carry their key before the value
+                    let entry_key = match remaining {
+                        None => self.next_entry_key(state, entries.len())?,
+                        Some(_) => Some(self.read_string(state)?),
+                    };
+                    match entry_key {
+                        Some(entry_key) => {
+                            *key = entry_key;
+                            Some(self.read_type(state, depth)?)
+                        }
+                        None => None,
+                    }
+                }
+                _ => Some(self.read_type(state, depth)?),
+            };
+
+            let mut value = match next {
+                None => match stack.pop() {
+                    Some(frame) => frame.into_value(),
+                    None => unreachable!("end markers are only read inside a container"),
+                },
+                Some(BinaryType::Array) => {
+                    if state.terminated {
+                        stack.push(Frame::Array { items: Vec::new(), remaining: None });
+                        continue;
+                    }
+                    let length = self.read_container_length(state, "Array")?;
+                    if length > 0 {
+                        stack.push(Frame::Array {
+                            items: Vec::with_capacity(length.min(MAX_PREALLOCATED_ELEMENTS)),
+                            remaining: Some(length),
+                        });
+                        continue;
+                    }
+                    LLSDValue::Array(Vec::new())
+                }
+                Some(BinaryType::Map) => {
+                    if state.terminated {
+                        stack.push(Frame::Map { entries: HashMap::new(), key: String::new(), remaining: None });
+                        continue;
+                    }
+                    let length = self.read_container_length(state, "Map")?;
+                    if length > 0 {
+                        stack.push(Frame::Map {
+                            entries: HashMap::with_capacity(length.min(MAX_PREALLOCATED_ELEMENTS)),
+                            key: String::new(),
+                            remaining: Some(length),
+                        });
+                        continue;
+                    }
+                    LLSDValue::Map(HashMap::new())
+                }
+                Some(scalar) => self.read_scalar(state, scalar)?,
+            };
+
+            // Hand the finished value to its parent, closing every
+            // length-prefixed container that it completes
+            loop {
+                let Some(frame) = stack.last_mut() else {
+                    return Ok(value);
+                };
+                let remaining = match frame {
+                    Frame::Array { items, remaining } => {
+                        items.push(value);
+                        remaining
+                    }
+                    Frame::Map { entries, key, remaining } => {
+                        entries.insert(std::mem::take(key), value);
+                        remaining
+                    }
+                };
+                let complete = match remaining {
+                    Some(count) => {
+                        *count -= 1;
+                        *count == 0
+                    }
+                    None => false,
+                };
+                if !complete {
+                    break;
+                }
+                value = match stack.pop() {
+                    Some(frame) => frame.into_value(),
+                    None => unreachable!("frame was just inspected"),
+                };
+            }
+        }
+    }
+
+    /// Read an array or map element count and check it against the limit
+    fn read_container_length<R: Read>(&self, state: &mut ParseState<R>, kind: &str) -> LLSDResult<usize> {
+        let length = self.read_u32(state)? as usize;
         if length > self.max_elements {
-            return Err(LLSDError::binary_error("Array too large"));
+            return Err(LLSDError::binary_error(format!("{} too large", kind)));
         }
+        Ok(length)
+    }
 
-        let mut array = Vec::with_capacity(length);
+    /// Parse an array from binary data
+    fn parse_array<R: Read>(&self, state: &mut ParseState<R>, depth: usize) -> LLSDResult<LLSDValue> {
+        if state.terminated {
+            let mut array = Vec::new();
+            while let Some(binary_type) = self.next_item_type(state, depth + 1, array.len())? {
+                array.push(self.parse_typed(state, binary_type, depth + 1)?);
+            }
+            return Ok(LLSDValue::Array(array));
+        }
+
+        let length = self.read_container_length(state, "Array")?;
+
+        let mut array = Vec::with_capacity(length.min(MAX_PREALLOCATED_ELEMENTS));
         for _ in 0..length {
-            let value = self.parse_value(cursor, depth + 1)?;
+            let value = self.parse_value(state, depth + 1)?;
             array.push(value);
         }
 
@@ -174,109 +592,412 @@ impl LLSDBinaryParser {
     }
 
     /// Parse a map from binary data
-    fn parse_map(&self, cursor: &mut Cursor<&[u8]>, depth: usize) -> LLSDResult<LLSDValue> {
-        let length = self.read_u32(cursor)? as usize;
-        
-        if length > self.max_elements {
-            return Err(LLSDError::binary_error("Map too large"));
+    fn parse_map<R: Read>(&self, state: &mut ParseState<R>, depth: usize) -> LLSDResult<LLSDValue> {
+        if state.terminated {
+            let mut map = HashMap::new();
+            while let Some(key) = self.next_entry_key(state, map.len())? {
+                let value = self.parse_value(state, depth + 1)?;
+                map.insert(key, value);
+            }
+            return Ok(LLSDValue::Map(map));
         }
 
-        let mut map = HashMap::with_capacity(length);
+        let length = self.read_container_length(state, "Map")?;
+
+        let mut map = HashMap::with_capacity(length.min(MAX_PREALLOCATED_ELEMENTS));
         for _ in 0..length {
-            let key = self.read_string(cursor)?;
-            let value = self.parse_value(cursor, depth + 1)?;
+            let key = self.read_string(state)?;
+            let value = self.parse_value(state, depth + 1)?;
             map.insert(key, value);
         }
 
         Ok(LLSDValue::Map(map))
     }
 
+    /// Fail if the time budget is spent or cancellation was requested
+    fn check_budget<R: Read>(&self, state: &ParseState<R>) -> LLSDResult<()> {
+        if let Some(flag) = &self.cancel_flag {
+            if flag.load(Ordering::Relaxed) {
+                return Err(LLSDError::ParseAborted { reason: "cancelled".to_string() });
+            }
+        }
+        state.meter.check_time()
+    }
+
     /// Read a single byte
-    fn read_u8(&self, cursor: &mut Cursor<&[u8]>) -> LLSDResult<u8> {
+    fn read_u8<R: Read>(&self, state: &mut ParseState<R>) -> LLSDResult<u8> {
         let mut buf = [0u8; 1];
-        cursor.read_exact(&mut buf).map_err(|_| LLSDError::UnexpectedEndOfData)?;
+        state.reader.read_exact(&mut buf).map_err(read_error)?;
         Ok(buf[0])
     }
 
-    /// Read a 32-bit unsigned integer (big-endian)
-    fn read_u32(&self, cursor: &mut Cursor<&[u8]>) -> LLSDResult<u32> {
+    /// Read a 32-bit unsigned integer
+    fn read_u32<R: Read>(&self, state: &mut ParseState<R>) -> LLSDResult<u32> {
         let mut buf = [0u8; 4];
-        cursor.read_exact(&mut buf).map_err(|_| LLSDError::UnexpectedEndOfData)?;
-        Ok(u32::from_be_bytes(buf))
+        state.reader.read_exact(&mut buf).map_err(read_error)?;
+        Ok(self.byte_order.get_u32(buf))
     }
 
-    /// Read a 32-bit signed integer (big-endian)
-    fn read_i32(&self, cursor: &mut Cursor<&[u8]>) -> LLSDResult<i32> {
+    /// Read a 32-bit signed integer
+    fn read_i32<R: Read>(&self, state: &mut ParseState<R>) -> LLSDResult<i32> {
         let mut buf = [0u8; 4];
-        cursor.read_exact(&mut buf).map_err(|_| LLSDError::UnexpectedEndOfData)?;
-        Ok(i32::from_be_bytes(buf))
+        state.reader.read_exact(&mut buf).map_err(read_error)?;
+        Ok(self.byte_order.get_i32(buf))
     }
 
-    /// Read a 64-bit floating point number (big-endian)
-    fn read_f64(&self, cursor: &mut Cursor<&[u8]>) -> LLSDResult<f64> {
+    /// Read a 64-bit signed integer
+    fn read_i64<R: Read>(&self, state: &mut ParseState<R>) -> LLSDResult<i64> {
         let mut buf = [0u8; 8];
-        cursor.read_exact(&mut buf).map_err(|_| LLSDError::UnexpectedEndOfData)?;
-        Ok(f64::from_be_bytes(buf))
+        state.reader.read_exact(&mut buf).map_err(read_error)?;
+        Ok(self.byte_order.get_i64(buf))
     }
 
-    /// Read a UTF-8 string
-    fn read_string(&self, cursor: &mut Cursor<&[u8]>) -> LLSDResult<String> {
-        let length = self.read_u32(cursor)? as usize;
-        let mut buf = vec![0u8; length];
-        cursor.read_exact(&mut buf).map_err(|_| LLSDError::UnexpectedEndOfData)?;
-        String::from_utf8(buf).map_err(LLSDError::from)
+    /// Read a 64-bit floating point number
+    fn read_f64<R: Read>(&self, state: &mut ParseState<R>) -> LLSDResult<f64> {
+        let mut buf = [0u8; 8];
+        state.reader.read_exact(&mut buf).map_err(read_error)?;
+        Ok(self.byte_order.get_f64(buf))
+    }
+
+    /// Read a UTF-8 string, resolving string table references if enabled
+    fn read_string<R: Read>(&self, state: &mut ParseState<R>) -> LLSDResult<String> {
+        let length = self.read_size(state)?;
+        let ref_bit = if state.large_sizes { LARGE_STRING_REF_BIT } else { STRING_REF_BIT as u64 };
+
+        if let Some(table) = &state.string_table {
+            if length & ref_bit != 0 {
+                let index = (length & !ref_bit) as usize;
+                let string = table.get(index).ok_or_else(|| {
+                    LLSDError::binary_error(format!("Invalid string table reference: {}", index))
+                })?;
+                state.meter.charge(string.len())?;
+                return Ok(string.clone());
+            }
+        }
+
+        let buf = self.read_bytes(state, length)?;
+        let string = String::from_utf8(buf).map_err(LLSDError::from)?;
+
+        if let Some(table) = &mut state.string_table {
+            table.push(string.clone());
+        }
+        Ok(string)
     }
 
     /// Read a UUID (16 bytes)
-    fn read_uuid(&self, cursor: &mut Cursor<&[u8]>) -> LLSDResult<Uuid> {
+    fn read_uuid<R: Read>(&self, state: &mut ParseState<R>) -> LLSDResult<Uuid> {
         let mut buf = [0u8; 16];
-        cursor.read_exact(&mut buf).map_err(|_| LLSDError::UnexpectedEndOfData)?;
+        state.reader.read_exact(&mut buf).map_err(read_error)?;
         Ok(Uuid::from_bytes(buf))
     }
 
     /// Read binary data
-    fn read_binary(&self, cursor: &mut Cursor<&[u8]>) -> LLSDResult<Vec<u8>> {
-        let length = self.read_u32(cursor)? as usize;
-        let mut buf = vec![0u8; length];
-        cursor.read_exact(&mut buf).map_err(|_| LLSDError::UnexpectedEndOfData)?;
+    fn read_binary<R: Read>(&self, state: &mut ParseState<R>) -> LLSDResult<Vec<u8>> {
+        let length = self.read_size(state)?;
+        self.read_bytes(state, length)
+    }
+
+    /// Read a string or binary length prefix
+    fn read_size<R: Read>(&self, state: &mut ParseState<R>) -> LLSDResult<u64> {
+        if state.large_sizes {
+            let mut buf = [0u8; 8];
+            state.reader.read_exact(&mut buf).map_err(read_error)?;
+            Ok(self.byte_order.get_u64(buf))
+        } else {
+            Ok(self.read_u32(state)? as u64)
+        }
+    }
+
+    /// Read exactly `length` bytes. The buffer grows as data arrives rather
+    /// than trusting the declared length up front.
+    fn read_bytes<R: Read>(&self, state: &mut ParseState<R>, length: u64) -> LLSDResult<Vec<u8>> {
+        state.meter.charge(usize::try_from(length).unwrap_or(usize::MAX))?;
+        let mut buf = Vec::new();
+        (&mut state.reader)
+            .take(length)
+            .read_to_end(&mut buf)
+            .map_err(LLSDError::from)?;
+        if (buf.len() as u64) < length {
+            return Err(LLSDError::UnexpectedEndOfData);
+        }
         Ok(buf)
     }
 }
 
+/// A container being filled by [`LLSDBinaryParser::parse_iterative`].
+/// `remaining` is `None` for terminated containers.
+enum Frame {
+    Array {
+        items: Vec<LLSDValue>,
+        remaining: Option<usize>,
+    },
+    Map {
+        entries: HashMap<String, LLSDValue>,
+        key: String,
+        remaining: Option<usize>,
+    },
+}
+
+impl Frame {
+    fn into_value(self) -> LLSDValue {
+        match self {
+            Frame::Array { items, .. } => LLSDValue::Array(items),
+            Frame::Map { entries, .. } => LLSDValue::Map(entries),
+        }
+    }
+}
+
+/// Mutable state for a single parse: the input reader, the string table
+/// for extended payloads, and progress tracked against the parse budget
+struct ParseState<R> {
+    reader: R,
+    string_table: Option<Vec<String>>,
+    large_sizes: bool,
+    terminated: bool,
+    meter: BudgetMeter,
+    values_until_check: usize,
+}
+
 /// LLSD binary serializer
 #[derive(Debug, Default)]
 pub struct LLSDBinarySerializer {
+    byte_order: ByteOrder,
     include_magic: bool,
+    string_table: bool,
+    large_sizes: bool,
+    terminated: bool,
+    max_output_size: Option<usize>,
 }
 
 impl LLSDBinarySerializer {
     /// Create a new binary serializer
     pub fn new() -> Self {
         Self {
+            byte_order: ByteOrder::BigEndian,
             include_magic: true,
+            string_table: false,
+            large_sizes: false,
+            terminated: false,
+            max_output_size: None,
         }
     }
 
-    /// Don't include magic number in output (for partial serialization)
+    /// Write multi-byte numbers in `order`; big-endian by default
+    pub fn with_byte_order(mut self, order: ByteOrder) -> Self {
+        self.byte_order = order;
+        self
+    }
+
+    /// Don't include magic number in output (for partial serialization).
+    ///
+    /// The format extensions need their `llsx` header, so serializing with
+    /// any of them enabled as well fails.
     pub fn without_magic(mut self) -> Self {
         self.include_magic = false;
         self
     }
 
+    /// Deduplicate repeated strings (map keys, strings, URIs) by writing each
+    /// distinct string once and back-references thereafter.
+    ///
+    /// This is a format extension: output starts with the extended `llsx`
+    /// header and can only be read by this crate's parser. It can't be
+    /// combined with [`without_magic`](Self::without_magic).
+    pub fn with_string_table(mut self, enabled: bool) -> Self {
+        self.string_table = enabled;
+        self
+    }
+
+    /// Write string and binary lengths as `u64`, lifting the 4 GiB limit on
+    /// a single value.
+    ///
+    /// Like [`with_string_table`](Self::with_string_table) this is a format
+    /// extension marked in the `llsx` header. Without it, a string or blob
+    /// over `u32::MAX` bytes is an error rather than a truncated length.
+    pub fn with_large_size_prefix(mut self, enabled: bool) -> Self {
+        self.large_sizes = enabled;
+        self
+    }
+
+    /// Write arrays and maps without an element count, closing each with an
+    /// end marker, so a producer can emit elements before knowing how many
+    /// there are.
+    ///
+    /// Another `llsx` format extension. Map keys gain a string type tag so
+    /// the parser can tell them from the end marker; the parser rejects
+    /// terminated containers in a payload not flagged as using them.
+    pub fn with_terminated_containers(mut self, enabled: bool) -> Self {
+        self.terminated = enabled;
+        self
+    }
+
+    /// Fail with [`LLSDError::OutputTooLarge`] once the output grows past
+    /// `bytes`. The check runs after each value is written, so a document
+    /// is abandoned early rather than after it has been fully encoded.
+    pub fn with_max_output_size(mut self, bytes: usize) -> Self {
+        self.max_output_size = Some(bytes);
+        self
+    }
+
     /// Serialize LLSD to binary data
     pub fn serialize(&self, document: &LLSDDocument) -> LLSDResult<Vec<u8>> {
         let mut buffer = BytesMut::new();
+        let mut strings = self.write_header(&mut buffer)?;
+        self.write_value(&mut buffer, &mut strings, document.content())?;
+        self.check_output_size(buffer.len())?;
+        Ok(buffer.to_vec())
+    }
+
+    /// Serialize LLSD to an async writer, such as a socket, without building
+    /// the whole payload in memory first.
+    ///
+    /// Output is identical to [`serialize`](Self::serialize). Data is written
+    /// and flushed in chunks of about 64 KiB as the document is walked.
+    #[cfg(feature = "async")]
+    pub async fn serialize_async<W: tokio::io::AsyncWrite + Unpin>(
+        &self,
+        document: &LLSDDocument,
+        writer: &mut W,
+    ) -> LLSDResult<()> {
+        use tokio::io::AsyncWriteExt;
 
-        if self.include_magic {
-            buffer.put_u32(LLSD_BINARY_MAGIC);
+        let mut buffer = BytesMut::new();
+        let mut strings = self.write_header(&mut buffer)?;
+        let mut stack = Vec::new();
+        let mut flushed = 0;
+        stack.extend(self.write_node(&mut buffer, &mut strings, document.content())?);
+        self.check_output_size(buffer.len())?;
+
+        while let Some(children) = stack.last_mut() {
+            let next = match children {
+                Children::Items(items) => items.next(),
+                Children::Entries(entries) => match entries.next() {
+                    Some((key, value)) => {
+                        self.write_key(&mut buffer, &mut strings, key)?;
+                        Some(value)
+                    }
+                    None => None,
+                },
+            };
+            match next {
+                Some(value) => stack.extend(self.write_node(&mut buffer, &mut strings, value)?),
+                None => {
+                    stack.pop();
+                    self.write_end(&mut buffer);
+                }
+            }
+            self.check_output_size(flushed + buffer.len())?;
+
+            if buffer.len() >= ASYNC_CHUNK_SIZE {
+                writer.write_all(&buffer).await?;
+                writer.flush().await?;
+                flushed += buffer.len();
+                buffer.clear();
+            }
         }
 
-        self.write_value(&mut buffer, document.content())?;
-        Ok(buffer.to_vec())
+        writer.write_all(&buffer).await?;
+        writer.flush().await?;
+        Ok(())
+    }
+
+    /// Write the magic number and extension flags, returning the string
+    /// table to use if enabled
+    fn write_header<'v>(&self, buffer: &mut BytesMut) -> LLSDResult<Option<HashMap<&'v str, u32>>> {
+        let mut strings = None;
+
+        if self.string_table || self.large_sizes || self.terminated {
+            if !self.include_magic {
+                return Err(LLSDError::BinaryError {
+                    message: "Format extensions need the llsx header, which without_magic omits".to_string(),
+                });
+            }
+
+            let mut flags = 0;
+            if self.string_table {
+                flags |= FLAG_STRING_TABLE;
+                strings = Some(HashMap::new());
+            }
+            if self.large_sizes {
+                flags |= FLAG_LARGE_SIZES;
+            }
+            if self.terminated {
+                flags |= FLAG_TERMINATED;
+            }
+            self.byte_order.put_u32(buffer, LLSD_EXTENDED_MAGIC);
+            buffer.put_u8(flags);
+        } else if self.include_magic {
+            self.byte_order.put_u32(buffer, LLSD_BINARY_MAGIC);
+        }
+
+        Ok(strings)
     }
 
     /// Write a single value to binary data
-    fn write_value(&self, buffer: &mut BytesMut, value: &LLSDValue) -> LLSDResult<()> {
+    fn write_value<'v>(
+        &self,
+        buffer: &mut BytesMut,
+        strings: &mut Option<HashMap<&'v str, u32>>,
+        value: &'v LLSDValue,
+    ) -> LLSDResult<()> {
+        let children = self.write_node(buffer, strings, value)?;
+        self.check_output_size(buffer.len())?;
+        match children {
+            None => {}
+            Some(Children::Items(items)) => {
+                for item in items {
+                    self.write_value(buffer, strings, item)?;
+                }
+                self.write_end(buffer);
+            }
+            Some(Children::Entries(entries)) => {
+                for (key, val) in entries {
+                    self.write_key(buffer, strings, key)?;
+                    self.write_value(buffer, strings, val)?;
+                }
+                self.write_end(buffer);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Fail if `len` bytes of output is over the configured maximum
+    fn check_output_size(&self, len: usize) -> LLSDResult<()> {
+        match self.max_output_size {
+            Some(limit) if len > limit => Err(LLSDError::OutputTooLarge { limit }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Write a map key, tagged in terminated mode
+    fn write_key<'v>(
+        &self,
+        buffer: &mut BytesMut,
+        strings: &mut Option<HashMap<&'v str, u32>>,
+        key: &'v str,
+    ) -> LLSDResult<()> {
+        if self.terminated {
+            buffer.put_u8(BinaryType::String as u8);
+        }
+        self.write_string(buffer, strings, key)
+    }
+
+    /// Close a container in terminated mode
+    fn write_end(&self, buffer: &mut BytesMut) {
+        if self.terminated {
+            buffer.put_u8(BinaryType::End as u8);
+        }
+    }
+
+    /// Write a scalar, or a container's type and length, returning the
+    /// container's children for the caller to write
+    fn write_node<'v>(
+        &self,
+        buffer: &mut BytesMut,
+        strings: &mut Option<HashMap<&'v str, u32>>,
+        value: &'v LLSDValue,
+    ) -> LLSDResult<Option<Children<'v>>> {
         match value {
             LLSDValue::Undefined => {
                 buffer.put_u8(BinaryType::Undefined as u8);
@@ -287,65 +1008,141 @@ impl LLSDBinarySerializer {
             }
             LLSDValue::Integer(i) => {
                 buffer.put_u8(BinaryType::Integer as u8);
-                buffer.put_i32(*i);
+                self.byte_order.put_i32(buffer, *i);
+            }
+            LLSDValue::Integer64(i) => {
+                buffer.put_u8(BinaryType::Integer64 as u8);
+                self.byte_order.put_i64(buffer, *i);
             }
             LLSDValue::Real(r) => {
                 buffer.put_u8(BinaryType::Real as u8);
-                buffer.put_f64(*r);
+                self.byte_order.put_f64(buffer, *r);
             }
             LLSDValue::String(s) => {
                 buffer.put_u8(BinaryType::String as u8);
-                self.write_string(buffer, s);
+                self.write_string(buffer, strings, s)?;
             }
             LLSDValue::UUID(u) => {
-                buffer.put_u8(BinaryType::UUID as u8);
+                buffer.put_u8(BinaryType::Uuid as u8);
                 buffer.put_slice(u.as_bytes());
             }
             LLSDValue::Date(d) => {
                 buffer.put_u8(BinaryType::Date as u8);
                 let timestamp = d.timestamp() as f64 + (d.timestamp_subsec_nanos() as f64 / 1e9);
-                buffer.put_f64(timestamp);
+                self.byte_order.put_f64(buffer, timestamp);
             }
             LLSDValue::URI(u) => {
-                buffer.put_u8(BinaryType::URI as u8);
-                self.write_string(buffer, u);
+                buffer.put_u8(BinaryType::Uri as u8);
+                self.write_string(buffer, strings, u)?;
             }
             LLSDValue::Binary(b) => {
                 buffer.put_u8(BinaryType::Binary as u8);
-                buffer.put_u32(b.len() as u32);
+                self.write_size(buffer, b.len())?;
                 buffer.put_slice(b);
             }
             LLSDValue::Array(arr) => {
                 buffer.put_u8(BinaryType::Array as u8);
-                buffer.put_u32(arr.len() as u32);
-                for item in arr {
-                    self.write_value(buffer, item)?;
+                if !self.terminated {
+                    self.byte_order.put_u32(buffer, container_len(arr.len())?);
                 }
+                return Ok(Some(Children::Items(arr.iter())));
             }
             LLSDValue::Map(map) => {
                 buffer.put_u8(BinaryType::Map as u8);
-                buffer.put_u32(map.len() as u32);
-                for (key, val) in map {
-                    self.write_string(buffer, key);
-                    self.write_value(buffer, val)?;
+                if !self.terminated {
+                    self.byte_order.put_u32(buffer, container_len(map.len())?);
                 }
+                return Ok(Some(Children::Entries(map.iter())));
             }
         }
 
-        Ok(())
+        Ok(None)
     }
 
-    /// Write a string to binary data
-    fn write_string(&self, buffer: &mut BytesMut, s: &str) {
+    /// Write a string to binary data, or a back-reference if it is already
+    /// in the string table
+    fn write_string<'v>(
+        &self,
+        buffer: &mut BytesMut,
+        strings: &mut Option<HashMap<&'v str, u32>>,
+        s: &'v str,
+    ) -> LLSDResult<()> {
         let bytes = s.as_bytes();
-        buffer.put_u32(bytes.len() as u32);
+
+        if let Some(table) = strings {
+            if let Some(index) = table.get(s) {
+                if self.large_sizes {
+                    self.byte_order.put_u64(buffer, LARGE_STRING_REF_BIT | *index as u64);
+                } else {
+                    self.byte_order.put_u32(buffer, STRING_REF_BIT | index);
+                }
+                return Ok(());
+            }
+            let limit = if self.large_sizes { LARGE_STRING_REF_BIT } else { STRING_REF_BIT as u64 };
+            if bytes.len() as u64 >= limit || table.len() as u64 >= STRING_REF_BIT as u64 {
+                return Err(LLSDError::binary_error("String too large for string table encoding"));
+            }
+            let index = table.len() as u32;
+            table.insert(s, index);
+        }
+
+        self.write_size(buffer, bytes.len())?;
         buffer.put_slice(bytes);
+        Ok(())
     }
+
+    /// Write a string or binary length prefix
+    fn write_size(&self, buffer: &mut BytesMut, length: usize) -> LLSDResult<()> {
+        if self.large_sizes {
+            self.byte_order.put_u64(buffer, length as u64);
+        } else {
+            self.byte_order.put_u32(buffer, size_prefix(length)?);
+        }
+        Ok(())
+    }
+}
+
+/// Remaining children of a container being serialized
+enum Children<'v> {
+    Items(std::slice::Iter<'v, LLSDValue>),
+    Entries(std::collections::hash_map::Iter<'v, String, LLSDValue>),
+}
+
+/// Bytes buffered by [`LLSDBinarySerializer::serialize_async`] between writes
+#[cfg(feature = "async")]
+const ASYNC_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Check that a string or binary length fits the standard `u32` prefix
+fn size_prefix(length: usize) -> LLSDResult<u32> {
+    u32::try_from(length).map_err(|_| {
+        LLSDError::custom(format!(
+            "Value of {} bytes exceeds the 4 GiB binary size limit; use with_large_size_prefix",
+            length
+        ))
+    })
+}
+
+/// Report input that ends mid-value as [`LLSDError::UnexpectedEndOfData`],
+/// and any other failure of the reader as the I/O error it is
+fn read_error(error: std::io::Error) -> LLSDError {
+    if error.kind() == std::io::ErrorKind::UnexpectedEof {
+        LLSDError::UnexpectedEndOfData
+    } else {
+        LLSDError::from(error)
+    }
+}
+
+/// Check that an array or map element count fits its `u32` prefix
+fn container_len(length: usize) -> LLSDResult<u32> {
+    u32::try_from(length)
+        .map_err(|_| LLSDError::custom(format!("Container of {} elements is too large", length)))
 }
 
 #[cfg(test)]
+#[allow(clippy::approx_constant)]
 mod tests {
     use super::*;
+    use std::time::Instant;
     use uuid::uuid;
 
     #[test]
@@ -532,7 +1329,7 @@ mod tests {
 
         // Large array
         let large_array: Vec<LLSDValue> = (0..1000)
-            .map(|i| LLSDValue::Integer(i))
+            .map(LLSDValue::Integer)
             .collect();
 
         let doc = LLSDDocument::new(LLSDValue::Array(large_array.clone()));
@@ -570,4 +1367,606 @@ mod tests {
         // Should fail due to depth limit
         assert!(parser.parse(&data).is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_string_table_round_trip() {
+        // 1,000 entries whose nested maps all repeat the same keys
+        let original = LLSDValue::Map(
+            (0..1000)
+                .map(|i| {
+                    let mut agent = HashMap::new();
+                    agent.insert("agent_id".to_string(), LLSDValue::Integer(i));
+                    agent.insert("display_name".to_string(), LLSDValue::String(format!("Resident {}", i % 10)));
+                    agent.insert("region".to_string(), LLSDValue::String("Da Boom".to_string()));
+                    agent.insert("home".to_string(), LLSDValue::URI("secondlife://Da Boom/128/128/25".to_string()));
+                    (format!("agent_{}", i), LLSDValue::Map(agent))
+                })
+                .collect(),
+        );
+        let doc = LLSDDocument::new(original.clone());
+
+        let plain = LLSDBinarySerializer::new().serialize(&doc).unwrap();
+        let compact = LLSDBinarySerializer::new().with_string_table(true).serialize(&doc).unwrap();
+        assert!(
+            compact.len() * 2 < plain.len(),
+            "string table output {} bytes vs plain {} bytes",
+            compact.len(),
+            plain.len()
+        );
+
+        let parser = LLSDBinaryParser::new().with_max_elements(10000);
+        assert_eq!(*parser.parse(&compact).unwrap().content(), original);
+        assert_eq!(*parser.parse(&plain).unwrap().content(), original);
+    }
+
+    #[test]
+    fn test_extensions_reject_headerless_output() {
+        let doc = LLSDDocument::new(LLSDValue::String("value".to_string()));
+
+        for serializer in [
+            LLSDBinarySerializer::new().with_string_table(true),
+            LLSDBinarySerializer::new().with_large_size_prefix(true),
+            LLSDBinarySerializer::new().with_terminated_containers(true),
+        ] {
+            assert!(matches!(
+                serializer.without_magic().serialize(&doc),
+                Err(LLSDError::BinaryError { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_string_table_invalid_data() {
+        let parser = LLSDBinaryParser::new();
+
+        // Unknown extended flags are rejected
+        let mut data = LLSD_EXTENDED_MAGIC.to_be_bytes().to_vec();
+        data.extend_from_slice(&[0x80, BinaryType::Undefined as u8]);
+        assert!(parser.parse(&data).is_err());
+
+        // Back-reference to a string that was never written
+        let mut data = LLSD_EXTENDED_MAGIC.to_be_bytes().to_vec();
+        data.push(FLAG_STRING_TABLE);
+        data.push(BinaryType::String as u8);
+        data.extend_from_slice(&(STRING_REF_BIT | 3).to_be_bytes());
+        assert!(parser.parse(&data).is_err());
+    }
+
+    /// Nest `depth` single-element arrays around an undefined value
+    fn nested_arrays(depth: usize) -> Vec<u8> {
+        let mut data = LLSD_BINARY_MAGIC.to_be_bytes().to_vec();
+        for _ in 0..depth {
+            data.push(BinaryType::Array as u8);
+            data.extend_from_slice(&1u32.to_be_bytes());
+        }
+        data.push(BinaryType::Undefined as u8);
+        data
+    }
+
+    #[test]
+    fn test_oversized_length_is_rejected() {
+        // A real 5 GiB string would not fit in most test environments, so
+        // check the length guard the serializer applies before writing it
+        let five_gib = 5usize << 30;
+        let err = size_prefix(five_gib).unwrap_err();
+        assert!(err.to_string().contains("with_large_size_prefix"));
+        assert_eq!(size_prefix(u32::MAX as usize).unwrap(), u32::MAX);
+        assert!(size_prefix(u32::MAX as usize + 1).is_err());
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    #[ignore = "allocates a value over 4 GiB"]
+    fn test_serialize_rejects_oversized_value() {
+        let document = LLSDDocument::new(LLSDValue::Binary(vec![0; u32::MAX as usize + 1]));
+        let err = LLSDBinarySerializer::new().serialize(&document).unwrap_err();
+        assert!(err.to_string().contains("with_large_size_prefix"));
+    }
+
+    #[test]
+    fn test_large_size_prefix_round_trip() {
+        let mut map = HashMap::new();
+        map.insert("name".to_string(), LLSDValue::String("name".to_string()));
+        map.insert("blob".to_string(), LLSDValue::Binary(vec![1, 2, 3]));
+        let value = LLSDValue::Array(vec![LLSDValue::Map(map), LLSDValue::String("name".to_string())]);
+        let document = LLSDDocument::new(value.clone());
+
+        let serializer = LLSDBinarySerializer::new().with_large_size_prefix(true);
+        let data = serializer.serialize(&document).unwrap();
+        assert_eq!(&data[..4], &LLSD_EXTENDED_MAGIC.to_be_bytes());
+        assert_eq!(data[4], FLAG_LARGE_SIZES);
+
+        let parser = LLSDBinaryParser::new();
+        assert_eq!(parser.parse(&data).unwrap().content(), &value);
+        assert_eq!(parser.parse_iterative(&data).unwrap().content(), &value);
+
+        let both = serializer.with_string_table(true).serialize(&document).unwrap();
+        assert_eq!(both[4], FLAG_LARGE_SIZES | FLAG_STRING_TABLE);
+        assert_eq!(parser.parse(&both).unwrap().content(), &value);
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_serialize_async_over_duplex() {
+        use tokio::io::AsyncReadExt;
+
+        let items: Vec<LLSDValue> = (0..10_000)
+            .map(|i| {
+                let mut map = HashMap::new();
+                map.insert("index".to_string(), LLSDValue::Integer(i));
+                map.insert("name".to_string(), LLSDValue::String(format!("item {}", i)));
+                LLSDValue::Map(map)
+            })
+            .collect();
+        let document = LLSDDocument::new(LLSDValue::Array(items));
+
+        // A small pipe forces the writer to wait on the reader between chunks
+        let (mut client, mut server) = tokio::io::duplex(4096);
+        let serializer = LLSDBinarySerializer::new();
+        let write = async {
+            serializer.serialize_async(&document, &mut client).await.unwrap();
+            drop(client);
+        };
+        let read = async {
+            let mut received = Vec::new();
+            server.read_to_end(&mut received).await.unwrap();
+            received
+        };
+        let ((), received) = tokio::join!(write, read);
+
+        assert_eq!(received, serializer.serialize(&document).unwrap());
+        let parsed = LLSDBinaryParser::new().with_max_elements(100_000).parse(&received).unwrap();
+        assert_eq!(parsed.content(), document.content());
+
+        for extended in [
+            LLSDBinarySerializer::new().with_string_table(true),
+            LLSDBinarySerializer::new().with_terminated_containers(true),
+        ] {
+            let mut streamed = Vec::new();
+            extended.serialize_async(&document, &mut streamed).await.unwrap();
+            assert_eq!(streamed, extended.serialize(&document).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_max_output_size() {
+        let items: Vec<LLSDValue> = (0..100_000)
+            .map(|i| LLSDValue::String(format!("item {}", i)))
+            .collect();
+        let document = LLSDDocument::new(LLSDValue::Array(items));
+
+        let capped = LLSDBinarySerializer::new().with_max_output_size(1024);
+        assert!(matches!(
+            capped.serialize(&document),
+            Err(LLSDError::OutputTooLarge { limit: 1024 })
+        ));
+
+        // A cap the document fits under changes nothing
+        let full = LLSDBinarySerializer::new().serialize(&document).unwrap();
+        let roomy = LLSDBinarySerializer::new().with_max_output_size(full.len());
+        assert_eq!(roomy.serialize(&document).unwrap(), full);
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_serialize_async_stops_at_max_output_size() {
+        let items: Vec<LLSDValue> = (0..100_000)
+            .map(|i| LLSDValue::String(format!("item {}", i)))
+            .collect();
+        let document = LLSDDocument::new(LLSDValue::Array(items));
+        let full_len = LLSDBinarySerializer::new().serialize(&document).unwrap().len();
+
+        let mut streamed = Vec::new();
+        let result = LLSDBinarySerializer::new()
+            .with_max_output_size(128 * 1024)
+            .serialize_async(&document, &mut streamed)
+            .await;
+        assert!(matches!(result, Err(LLSDError::OutputTooLarge { .. })));
+        // Only the chunks written before the cap was reached went out
+        assert!(streamed.len() <= 128 * 1024);
+        assert!(streamed.len() < full_len);
+    }
+
+    #[test]
+    fn test_terminated_containers_round_trip() {
+        let document = sample_document();
+        let parser = LLSDBinaryParser::new();
+
+        let serializer = LLSDBinarySerializer::new().with_terminated_containers(true);
+        let data = serializer.serialize(&document).unwrap();
+        assert_eq!(&data[..4], &LLSD_EXTENDED_MAGIC.to_be_bytes());
+        assert_eq!(data[4], FLAG_TERMINATED);
+        assert_eq!(parser.parse(&data).unwrap().content(), document.content());
+        assert_eq!(parser.parse_iterative(&data).unwrap().content(), document.content());
+
+        let combined = LLSDBinarySerializer::new()
+            .with_terminated_containers(true)
+            .with_string_table(true)
+            .with_large_size_prefix(true);
+        let data = combined.serialize(&document).unwrap();
+        assert_eq!(data[4], FLAG_TERMINATED | FLAG_STRING_TABLE | FLAG_LARGE_SIZES);
+        assert_eq!(parser.parse(&data).unwrap().content(), document.content());
+
+        // Empty containers are just a type byte and an end marker
+        let empty = LLSDDocument::new(LLSDValue::Array(vec![
+            LLSDValue::Array(Vec::new()),
+            LLSDValue::Map(HashMap::new()),
+        ]));
+        let data = serializer.serialize(&empty).unwrap();
+        assert_eq!(&data[5..], &[9, 9, 12, 10, 12, 12]);
+        assert_eq!(parser.parse(&data).unwrap().content(), empty.content());
+        assert_eq!(parser.parse_iterative(&data).unwrap().content(), empty.content());
+
+        let limited = LLSDBinaryParser::new().with_max_elements(1);
+        let two = LLSDDocument::new(LLSDValue::Array(vec![LLSDValue::Integer(1), LLSDValue::Integer(2)]));
+        let data = serializer.serialize(&two).unwrap();
+        assert!(limited.parse(&data).is_err());
+        assert!(same_result(&limited.parse(&data), &limited.parse_iterative(&data)));
+    }
+
+    #[test]
+    fn test_terminated_and_length_prefixed_do_not_mix() {
+        let parser = LLSDBinaryParser::new();
+        let message = |result: LLSDResult<LLSDDocument>| result.unwrap_err().to_string();
+
+        // An end marker in a length-prefixed payload
+        let mut data = LLSD_BINARY_MAGIC.to_be_bytes().to_vec();
+        data.extend_from_slice(&[9, 0, 0, 0, 1, 12]);
+        assert!(message(parser.parse(&data)).contains("end marker"));
+        assert!(message(parser.parse_iterative(&data)).contains("end marker"));
+
+        // A length-prefixed map body in a terminated payload
+        let mut data = LLSD_EXTENDED_MAGIC.to_be_bytes().to_vec();
+        data.push(FLAG_TERMINATED);
+        data.extend_from_slice(&[10, 0, 0, 0, 1]);
+        data.extend_from_slice(&[0, 0, 0, 1, b'k', 0]);
+        assert!(message(parser.parse(&data)).contains("Expected map key or end marker"));
+        assert!(message(parser.parse_iterative(&data)).contains("Expected map key or end marker"));
+
+        // A terminated array whose end marker never arrives
+        let terminated = LLSDBinarySerializer::new().with_terminated_containers(true);
+        let data = terminated
+            .serialize(&LLSDDocument::new(LLSDValue::Array(vec![LLSDValue::Integer(1)])))
+            .unwrap();
+        assert!(matches!(parser.parse(&data[..data.len() - 1]), Err(LLSDError::UnexpectedEndOfData)));
+    }
+
+    #[test]
+    fn test_time_budget() {
+        let data = nested_arrays(200);
+
+        let start = Instant::now();
+        let result = LLSDBinaryParser::new()
+            .with_time_budget(Duration::from_nanos(1))
+            .parse(&data);
+        assert!(matches!(result, Err(LLSDError::ParseAborted { .. })));
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        let parsed = LLSDBinaryParser::new()
+            .with_time_budget(Duration::from_secs(30))
+            .parse(&data);
+        assert!(parsed.is_ok());
+    }
+
+    #[test]
+    fn test_parse_budget() {
+        // 60 KB on the wire, but each reference copies the 10 KB string
+        let big = LLSDValue::String("x".repeat(10_000));
+        let doc = LLSDDocument::new(LLSDValue::Array(vec![big; 10_000]));
+        let data = LLSDBinarySerializer::new().with_string_table(true).serialize(&doc).unwrap();
+        assert!(data.len() < 100_000);
+
+        let parser = LLSDBinaryParser::new()
+            .with_parse_budget(ParseBudget { max_bytes: Some(1 << 20), max_duration: None });
+        for result in [parser.parse(&data), parser.parse_iterative(&data)] {
+            match result {
+                Err(LLSDError::ParseAborted { reason }) => assert!(reason.contains("parse budget exceeded")),
+                other => panic!("Expected the byte budget to trip, got {:?}", other.map(|_| ())),
+            }
+        }
+
+        let budget = ParseBudget { max_bytes: Some(1 << 20), max_duration: Some(Duration::from_secs(30)) };
+        let parsed = LLSDBinaryParser::new()
+            .with_parse_budget(budget)
+            .parse(&LLSDBinarySerializer::new().serialize(&sample_document()).unwrap())
+            .unwrap();
+        assert_eq!(parsed, sample_document());
+    }
+
+    #[test]
+    fn test_time_budget_keeps_byte_limit() {
+        let big = LLSDValue::String("x".repeat(10_000));
+        let data = LLSDBinarySerializer::new()
+            .serialize(&LLSDDocument::new(LLSDValue::Array(vec![big; 200])))
+            .unwrap();
+        let bytes_only = ParseBudget { max_bytes: Some(1 << 20), max_duration: None };
+
+        // Setting the time limit afterwards keeps the byte limit
+        let parser = LLSDBinaryParser::new()
+            .with_parse_budget(bytes_only)
+            .with_time_budget(Duration::from_secs(30));
+        match parser.parse(&data) {
+            Err(LLSDError::ParseAborted { reason }) => assert!(reason.contains("parse budget exceeded")),
+            other => panic!("Expected the byte budget to trip, got {:?}", other.map(|_| ())),
+        }
+
+        // Both limits apply: the time limit trips on small input
+        let parser = LLSDBinaryParser::new()
+            .with_parse_budget(bytes_only)
+            .with_time_budget(Duration::from_nanos(1));
+        assert!(matches!(parser.parse(&nested_arrays(200)), Err(LLSDError::ParseAborted { .. })));
+
+        // A later parse budget replaces both
+        let parser = LLSDBinaryParser::new()
+            .with_time_budget(Duration::from_nanos(1))
+            .with_parse_budget(ParseBudget::default());
+        assert!(parser.parse(&data).is_ok());
+    }
+
+    #[test]
+    fn test_cancel_flag() {
+        let data = nested_arrays(100);
+        let flag = Arc::new(AtomicBool::new(false));
+        let parser = LLSDBinaryParser::new().with_cancel_flag(Arc::clone(&flag));
+
+        assert!(parser.parse(&data).is_ok());
+        flag.store(true, Ordering::Relaxed);
+        assert!(matches!(parser.parse(&data), Err(LLSDError::ParseAborted { .. })));
+    }
+
+    /// Reader that hands out at most one byte per `read` call
+    struct OneByteReader<'a>(&'a [u8]);
+
+    impl Read for OneByteReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            match (self.0.split_first(), buf.first_mut()) {
+                (Some((byte, rest)), Some(slot)) => {
+                    *slot = *byte;
+                    self.0 = rest;
+                    Ok(1)
+                }
+                _ => Ok(0),
+            }
+        }
+    }
+
+    fn sample_document() -> LLSDDocument {
+        let mut map = HashMap::new();
+        map.insert("name".to_string(), LLSDValue::String("Alice".to_string()));
+        map.insert("id".to_string(), LLSDValue::UUID(uuid!("550e8400-e29b-41d4-a716-446655440000")));
+        map.insert("texture".to_string(), LLSDValue::Binary((0..=255).collect()));
+        map.insert("scores".to_string(), LLSDValue::Array(vec![
+            LLSDValue::Integer(95),
+            LLSDValue::Real(87.5),
+            LLSDValue::Undefined,
+        ]));
+        LLSDDocument::new(LLSDValue::Map(map))
+    }
+
+    #[test]
+    fn test_parse_reader_from_file() {
+        let doc = sample_document();
+        let data = LLSDBinarySerializer::new().serialize(&doc).unwrap();
+
+        let path = std::env::temp_dir().join(format!("llsd-binary-{}.bin", Uuid::new_v4()));
+        std::fs::write(&path, &data).unwrap();
+        let file = std::fs::File::open(&path).unwrap();
+        let parsed = LLSDBinaryParser::new().parse_reader(std::io::BufReader::new(file));
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(parsed.unwrap(), doc);
+    }
+
+    #[test]
+    fn test_parse_reader_one_byte_at_a_time() {
+        let doc = sample_document();
+        let parser = LLSDBinaryParser::new();
+
+        let data = LLSDBinarySerializer::new().serialize(&doc).unwrap();
+        assert_eq!(parser.parse_reader(OneByteReader(&data)).unwrap(), doc);
+
+        let compact = LLSDBinarySerializer::new().with_string_table(true).serialize(&doc).unwrap();
+        assert_eq!(parser.parse_reader(OneByteReader(&compact)).unwrap(), doc);
+
+        // Every proper prefix ends mid-value
+        for len in 1..data.len() {
+            let result = parser.parse_reader(OneByteReader(&data[..len]));
+            assert!(
+                matches!(result, Err(LLSDError::UnexpectedEndOfData) | Err(LLSDError::InvalidMagic)),
+                "prefix of {} bytes gave {:?}",
+                len,
+                result
+            );
+        }
+        assert!(matches!(parser.parse_reader(OneByteReader(&[])), Err(LLSDError::EmptyInput)));
+
+        // Limits still apply
+        assert!(LLSDBinaryParser::new()
+            .with_max_depth(10)
+            .parse_reader(OneByteReader(&nested_arrays(20)))
+            .is_err());
+        assert!(LLSDBinaryParser::new()
+            .with_max_elements(2)
+            .parse_reader(OneByteReader(&data))
+            .is_err());
+        let mut bad_magic = data.clone();
+        bad_magic[0] = b'x';
+        assert!(matches!(parser.parse_reader(OneByteReader(&bad_magic)), Err(LLSDError::InvalidMagic)));
+    }
+
+    #[test]
+    fn test_parse_reader_passes_read_errors_through() {
+        struct FailingReader<'a>(&'a [u8]);
+
+        impl Read for FailingReader<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                if self.0.is_empty() {
+                    return Err(std::io::Error::new(std::io::ErrorKind::ConnectionReset, "reset"));
+                }
+                OneByteReader(self.0).read(buf).inspect(|&n| self.0 = &self.0[n..])
+            }
+        }
+
+        let data = LLSDBinarySerializer::new().serialize(&sample_document()).unwrap();
+        for len in 1..data.len() {
+            let result = LLSDBinaryParser::new().parse_reader(FailingReader(&data[..len]));
+            assert!(
+                matches!(&result, Err(LLSDError::IoError(e)) if e.kind() == std::io::ErrorKind::ConnectionReset),
+                "prefix of {} bytes gave {:?}",
+                len,
+                result
+            );
+        }
+    }
+
+    /// Inputs that once were, or could plausibly be, crash or OOM cases for
+    /// the `binary_parser` fuzz target
+    #[test]
+    fn test_malformed_inputs_fail_gracefully() {
+        let magic = LLSD_BINARY_MAGIC.to_be_bytes();
+        let with_magic = |body: &[u8]| [&magic[..], body].concat();
+
+        let cases: Vec<(&str, Vec<u8>)> = vec![
+            ("empty", Vec::new()),
+            ("truncated magic", magic[..3].to_vec()),
+            ("magic only", magic.to_vec()),
+            ("wrong magic", b"llsq\x00".to_vec()),
+            ("extended magic without flags", LLSD_EXTENDED_MAGIC.to_be_bytes().to_vec()),
+            ("invalid type byte", with_magic(&[0xFF])),
+            ("first unused type byte", with_magic(&[12])),
+            ("truncated integer", with_magic(&[BinaryType::Integer as u8, 0x00, 0x01])),
+            ("truncated uuid", with_magic(&[BinaryType::Uuid as u8, 0xAB])),
+            ("huge string length", with_magic(&[BinaryType::String as u8, 0xFF, 0xFF, 0xFF, 0xFF, b'a'])),
+            ("huge binary length", with_magic(&[BinaryType::Binary as u8, 0x7F, 0xFF, 0xFF, 0xFF])),
+            ("huge array length", with_magic(&[BinaryType::Array as u8, 0xFF, 0xFF, 0xFF, 0xFF])),
+            ("huge map length", with_magic(&[BinaryType::Map as u8, 0x00, 0x0F, 0x42, 0x40])),
+            ("map key without value", with_magic(&[BinaryType::Map as u8, 0, 0, 0, 1, 0, 0, 0, 1, b'k'])),
+            ("invalid utf-8 string", with_magic(&[BinaryType::String as u8, 0, 0, 0, 2, 0xC3, 0x28])),
+            ("infinite date", with_magic(&[&[BinaryType::Date as u8][..], &f64::INFINITY.to_be_bytes()].concat())),
+        ];
+
+        let parser = LLSDBinaryParser::new();
+        for (name, data) in &cases {
+            assert!(parser.parse(data).is_err(), "{} should fail", name);
+        }
+
+        // Every level claims the maximum element count; without capped
+        // preallocation this would reserve gigabytes before failing
+        let mut nested = magic.to_vec();
+        for _ in 0..100 {
+            nested.push(BinaryType::Array as u8);
+            nested.extend_from_slice(&1_000_000u32.to_be_bytes());
+        }
+        assert!(matches!(parser.parse(&nested), Err(LLSDError::UnexpectedEndOfData)));
+    }
+
+    fn same_result(a: &LLSDResult<LLSDDocument>, b: &LLSDResult<LLSDDocument>) -> bool {
+        match (a, b) {
+            (Ok(a), Ok(b)) => a == b,
+            (Err(a), Err(b)) => format!("{:?}", a) == format!("{:?}", b),
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn test_parse_iterative_matches_recursive() {
+        let parser = LLSDBinaryParser::new();
+        let documents = [
+            LLSDBinarySerializer::new().serialize(&sample_document()).unwrap(),
+            LLSDBinarySerializer::new().with_string_table(true).serialize(&sample_document()).unwrap(),
+            nested_arrays(20),
+            LLSDBinarySerializer::new().with_terminated_containers(true).serialize(&sample_document()).unwrap(),
+        ];
+
+        // Every truncation point fails the same way in both parsers
+        for data in &documents {
+            for end in 0..=data.len() {
+                let recursive = parser.parse(&data[..end]);
+                let iterative = parser.parse_iterative(&data[..end]);
+                assert!(same_result(&recursive, &iterative), "prefix {}", end);
+            }
+        }
+
+        let limited = LLSDBinaryParser::new().with_max_depth(5).with_max_elements(2);
+        for data in [nested_arrays(5), nested_arrays(6), documents[0].clone()] {
+            assert!(same_result(&limited.parse(&data), &limited.parse_iterative(&data)));
+        }
+
+        // Headerless input starting with a type tag that is also ASCII
+        // whitespace is data, not empty input
+        let headerless = LLSDBinaryParser::new().without_magic_validation();
+        for data in [&b"\t"[..], b"\n", b""] {
+            assert!(same_result(&headerless.parse(data), &headerless.parse_iterative(data)));
+        }
+    }
+
+    #[test]
+    fn test_parse_iterative_deep_nesting() {
+        const DEPTH: usize = 50_000;
+        let data = nested_arrays(DEPTH);
+
+        assert!(LLSDBinaryParser::new().parse_iterative(&data).is_err());
+
+        let parser = LLSDBinaryParser::new().with_max_depth(DEPTH);
+        let mut document = parser.parse_iterative(&data).unwrap();
+
+        // Walk and dismantle the tree in a loop; comparing or dropping it
+        // recursively would itself overflow the stack
+        let mut value = std::mem::take(document.content_mut());
+        let mut depth = 0;
+        while let LLSDValue::Array(mut items) = value {
+            assert_eq!(items.len(), 1);
+            value = items.pop().unwrap();
+            depth += 1;
+        }
+        assert_eq!(depth, DEPTH);
+        assert_eq!(value, LLSDValue::Undefined);
+    }
+
+    #[test]
+    fn test_byte_order_round_trip() {
+        let mut doc = sample_document();
+        if let LLSDValue::Map(map) = doc.content_mut() {
+            map.insert("big".to_string(), LLSDValue::Integer64(-5_000_000_000));
+            map.insert("when".to_string(), LLSDValue::Date(chrono::Utc.timestamp_opt(1_700_000_000, 0).unwrap()));
+        }
+
+        for order in [ByteOrder::BigEndian, ByteOrder::LittleEndian] {
+            let parser = LLSDBinaryParser::new().with_byte_order(order);
+            for serializer in [
+                LLSDBinarySerializer::new(),
+                LLSDBinarySerializer::new().with_string_table(true).with_large_size_prefix(true),
+                LLSDBinarySerializer::new().with_terminated_containers(true),
+            ] {
+                let data = serializer.with_byte_order(order).serialize(&doc).unwrap();
+                assert_eq!(parser.parse(&data).unwrap(), doc, "{:?}", order);
+            }
+        }
+
+        let big = LLSDBinarySerializer::new().serialize(&doc).unwrap();
+        assert_eq!(&big[..4], b"llsd");
+        let int = LLSDDocument::new(LLSDValue::Integer(1));
+        let little = LLSDBinarySerializer::new().with_byte_order(ByteOrder::LittleEndian).serialize(&int).unwrap();
+        assert_eq!(little, [b"dsll".as_slice(), &[BinaryType::Integer as u8, 1, 0, 0, 0]].concat());
+    }
+
+    #[test]
+    fn test_byte_order_mismatch() {
+        let doc = sample_document();
+        let little = LLSDBinarySerializer::new()
+            .with_byte_order(ByteOrder::LittleEndian)
+            .serialize(&doc)
+            .unwrap();
+
+        assert!(matches!(LLSDBinaryParser::new().parse(&little), Err(LLSDError::InvalidMagic)));
+        let big = LLSDBinarySerializer::new().serialize(&doc).unwrap();
+        assert!(matches!(
+            LLSDBinaryParser::new().with_byte_order(ByteOrder::LittleEndian).parse(&big),
+            Err(LLSDError::InvalidMagic)
+        ));
+        assert_eq!(
+            LLSDBinaryParser::new().with_byte_order(ByteOrder::LittleEndian).parse(&little).unwrap(),
+            doc
+        );
+    }
+}
@@ -33,6 +33,22 @@ pub enum LLSDError {
     #[error("Unexpected end of data while parsing")]
     UnexpectedEndOfData,
 
+    /// Input was empty or contained only whitespace
+    #[error("Empty input: no LLSD data to parse")]
+    EmptyInput,
+
+    /// Parsing was stopped by a parse budget or cancellation request
+    #[error("Parse aborted: {reason}")]
+    ParseAborted { reason: String },
+
+    /// Serialized output grew past the serializer's configured maximum
+    #[error("Serialized output exceeds the maximum of {limit} bytes")]
+    OutputTooLarge { limit: usize },
+
+    /// Stored data no longer matches the checksum recorded when it was written
+    #[error("Checksum mismatch for cached item: {key}")]
+    ChecksumMismatch { key: String },
+
     /// Invalid UUID format
     #[error("Invalid UUID format: {uuid}")]
     InvalidUuid { uuid: String },
@@ -5,15 +5,18 @@
  * Copyright (C) 2024 Linden Lab
  */
 
+use crate::error::{LLSDError, LLSDResult};
 use crate::types::LLSDValue;
 use crate::utils::LLSDUtils;
 #[cfg(feature = "secondlife")]
 use crate::secondlife::{SLValidationRules, ValidationResult, validate_sl_structure};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use uuid::Uuid;
-use chrono::{DateTime, Utc};
+use chrono::Utc;
+use std::fmt;
 
 /// Firestorm specific LLSD utilities
 pub struct FirestormLLSDUtils;
@@ -67,7 +70,11 @@ impl FirestormLLSDUtils {
         message
     }
 
-    /// Create performance statistics structure
+    /// Create performance statistics structure.
+    ///
+    /// `render_time` and `script_time` are per-frame times in milliseconds;
+    /// see [`FirestormLLSDUtils::create_performance_stats_timed`] to pass
+    /// them as durations.
     pub fn create_performance_stats(
         fps: f64,
         bandwidth: f64,
@@ -90,6 +97,35 @@ impl FirestormLLSDUtils {
         stats
     }
 
+    /// Create performance statistics from per-frame render and script
+    /// durations, stored as milliseconds like
+    /// [`FirestormLLSDUtils::create_performance_stats`]. Read them back with
+    /// [`FirestormLLSDUtils::frame_time`].
+    pub fn create_performance_stats_timed(
+        fps: f64,
+        bandwidth: f64,
+        memory_usage: f64,
+        render_time: Duration,
+        script_time: Duration,
+        triangles: i32,
+    ) -> HashMap<String, LLSDValue> {
+        Self::create_performance_stats(
+            fps,
+            bandwidth,
+            memory_usage,
+            render_time.as_secs_f64() * 1000.0,
+            script_time.as_secs_f64() * 1000.0,
+            triangles,
+        )
+    }
+
+    /// Read a millisecond frame time field (`render_time` or `script_time`)
+    /// from performance statistics as a duration
+    pub fn frame_time(stats: &HashMap<String, LLSDValue>, field: &str) -> Option<Duration> {
+        let millis = stats.get(field)?.as_real()?;
+        LLSDUtils::llsd_to_duration(&LLSDValue::Real(millis / 1000.0))
+    }
+
     /// Create enhanced particle system data
     #[allow(clippy::too_many_arguments)]
     pub fn create_enhanced_particle_system(
@@ -208,6 +244,118 @@ impl FirestormLLSDUtils {
     pub fn deep_copy(data: &LLSDValue) -> LLSDValue {
         LLSDUtils::deep_clone(data)
     }
+
+    /// Validate that a map conforms to the `create_enhanced_particle_system` structure
+    #[cfg(feature = "secondlife")]
+    pub fn validate_particle_system(data: &LLSDValue) -> ValidationResult {
+        let rules = SLValidationRules::new()
+            .require_map()
+            .require_field("source_id", Some("uuid"))
+            .require_field("owner_key", Some("uuid"))
+            .require_field("pattern", Some("integer"))
+            .require_field("max_age", Some("real"))
+            .require_field("start_age", Some("real"))
+            .require_field("inner_angle", Some("real"))
+            .require_field("outer_angle", Some("real"))
+            .require_field("burst_rate", Some("real"))
+            .require_field("burst_part_count", Some("integer"))
+            .require_field("burst_speed_min", Some("real"))
+            .require_field("burst_speed_max", Some("real"))
+            .require_field("burst_radius", Some("real"))
+            .require_field("accel", Some("array"))
+            .require_field("texture_uuid", Some("uuid"))
+            .require_field("target_uuid", Some("uuid"))
+            .require_field("particle_flags", Some("integer"))
+            .require_field("start_color", Some("array"))
+            .require_field("end_color", Some("array"))
+            .require_field("start_scale", Some("array"))
+            .require_field("end_scale", Some("array"));
+
+        let mut result = validate_sl_structure(data, &rules);
+        if !result.is_valid() {
+            return result;
+        }
+
+        if let LLSDValue::Map(map) = data {
+            check_real_array(map, "accel", 3, &mut result);
+            check_real_array(map, "start_color", 4, &mut result);
+            check_real_array(map, "end_color", 4, &mut result);
+            check_real_array(map, "start_scale", 2, &mut result);
+            check_real_array(map, "end_scale", 2, &mut result);
+
+            for field in ["max_age", "start_age", "burst_rate", "burst_radius", "burst_speed_min", "burst_speed_max"] {
+                check_real_range(map, field, 0.0, f64::INFINITY, &mut result);
+            }
+            for field in ["inner_angle", "outer_angle"] {
+                check_real_range(map, field, 0.0, std::f64::consts::PI, &mut result);
+            }
+            if let Some(count) = map.get("burst_part_count").and_then(|v| v.as_integer()) {
+                if count < 0 {
+                    result.add_error(format!("Field burst_part_count must be non-negative, got {}", count));
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Validate that a map conforms to the `create_radar_data` structure
+    #[cfg(feature = "secondlife")]
+    pub fn validate_radar_data(data: &LLSDValue) -> ValidationResult {
+        let rules = SLValidationRules::new()
+            .require_map()
+            .require_field("agent_id", Some("uuid"))
+            .require_field("display_name", Some("string"))
+            .require_field("user_name", Some("string"))
+            .require_field("position", Some("array"))
+            .require_field("distance", Some("real"))
+            .require_field("is_typing", Some("boolean"))
+            .require_field("attachments", Some("array"));
+
+        let mut result = validate_sl_structure(data, &rules);
+        if !result.is_valid() {
+            return result;
+        }
+
+        if let LLSDValue::Map(map) = data {
+            check_real_array(map, "position", 3, &mut result);
+            check_real_range(map, "distance", 0.0, f64::INFINITY, &mut result);
+        }
+
+        result
+    }
+}
+
+/// Check that `field` is an array of exactly `len` numeric values
+#[cfg(feature = "secondlife")]
+fn check_real_array(map: &HashMap<String, LLSDValue>, field: &str, len: usize, result: &mut ValidationResult) {
+    match map.get(field) {
+        Some(LLSDValue::Array(items)) => {
+            if items.len() != len {
+                result.add_error(format!(
+                    "Field {} expected {} elements but got {}",
+                    field, len, items.len()
+                ));
+            } else if items.iter().any(|item| item.as_real().is_none()) {
+                result.add_error(format!("Field {} must contain only numbers", field));
+            }
+        }
+        Some(_) => result.add_error(format!("Field {} must be an array", field)),
+        None => {}
+    }
+}
+
+/// Check that a numeric `field` lies within `min..=max`
+#[cfg(feature = "secondlife")]
+fn check_real_range(map: &HashMap<String, LLSDValue>, field: &str, min: f64, max: f64, result: &mut ValidationResult) {
+    if let Some(value) = map.get(field).and_then(|v| v.as_real()) {
+        if !(min..=max).contains(&value) {
+            result.add_error(format!(
+                "Field {} value {} is outside the range {}..={}",
+                field, value, min, max
+            ));
+        }
+    }
 }
 
 /// RLV (Restrained Life Viewer) command structure
@@ -243,12 +391,60 @@ impl RLVCommand {
         llsd
     }
 
-    /// Convert RLV command to string representation
-    pub fn to_string(&self) -> String {
+    /// Parse a command in RLV string form, e.g. `@sit:ground=force`,
+    /// `@getoutfit=2222` or `@clear`. The option is everything between the
+    /// first `:` and the `=`, and the param keeps its leading `=`, matching
+    /// [`RLVCommand::new`]. The source ID is nil, as it isn't part of the
+    /// string.
+    pub fn parse(cmd: &str) -> LLSDResult<RLVCommand> {
+        let cmd = cmd.trim();
+        if !cmd.starts_with('@') {
+            return Err(LLSDError::validation_error(format!(
+                "RLV command must start with '@': {}", cmd
+            )));
+        }
+
+        let (head, param) = match cmd.find('=') {
+            Some(index) => cmd.split_at(index),
+            None => (cmd, ""),
+        };
+        let (behaviour, option) = head.split_once(':').unwrap_or((head, ""));
+        if behaviour.len() < 2 {
+            return Err(LLSDError::validation_error(format!(
+                "RLV command has no behaviour: {}", cmd
+            )));
+        }
+
+        Ok(Self::new(behaviour, option, param, Uuid::nil()))
+    }
+
+    /// Parse a relay message holding several commands separated by `,`,
+    /// e.g. `@detach=n,sit:ground=force,tplm=n`. The leading `@` applies to
+    /// every command. Each command gets its own result, so one malformed
+    /// command doesn't discard the rest.
+    pub fn parse_batch(msg: &str, source_id: Uuid) -> Vec<LLSDResult<RLVCommand>> {
+        let msg = msg.trim();
+        let Some(body) = msg.strip_prefix('@') else {
+            return vec![Self::parse(msg)];
+        };
+
+        body.split(',')
+            .map(|segment| {
+                let mut command = Self::parse(&format!("@{}", segment.trim()))?;
+                command.source_id = source_id;
+                Ok(command)
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for RLVCommand {
+    /// Format the command in RLV string form, e.g. `@sit:ground=force`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if self.option.is_empty() {
-            format!("{}{}", self.behaviour, self.param)
+            write!(f, "{}{}", self.behaviour, self.param)
         } else {
-            format!("{}:{}{}", self.behaviour, self.option, self.param)
+            write!(f, "{}:{}{}", self.behaviour, self.option, self.param)
         }
     }
 }
@@ -405,47 +601,188 @@ pub fn validate_fs_structure(llsd_data: &LLSDValue, rules: &FSValidationRules) -
 
 /// Thread-safe caching for performance
 pub struct FSLLSDCache {
-    cache: Arc<Mutex<HashMap<String, CacheEntry>>>,
-    ttl: Duration,
+    store: Arc<Mutex<CacheStore>>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 struct CacheEntry {
     data: LLSDValue,
     timestamp: Instant,
+    /// Tick of the store's access clock at the last put or hit
+    last_access: AtomicU64,
 }
 
-impl FSLLSDCache {
-    /// Create a new cache with TTL in milliseconds
-    pub fn new(ttl_ms: u64) -> Self {
+/// Result of looking up a key without removing it
+enum Lookup {
+    Hit(LLSDValue),
+    Expired,
+    Missing,
+}
+
+/// Snapshot of a cache's hit/miss counters, for tuning TTLs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FSCacheStats {
+    /// Lookups that found a live entry
+    pub hits: u64,
+    /// Lookups that found nothing, including those that found a stale entry
+    pub misses: u64,
+    /// Stale entries removed, by a lookup or by `cleanup`
+    pub expirations: u64,
+    /// Least recently used entries dropped to stay within capacity
+    pub evictions: u64,
+    /// Entries that have not yet expired
+    pub live_entries: usize,
+}
+
+/// Entries and expiry rules shared by [`FSLLSDCache`] and `FSLLSDCacheAsync`
+#[derive(Debug)]
+struct CacheStore {
+    entries: HashMap<String, CacheEntry>,
+    ttl: Duration,
+    max_entries: Option<usize>,
+    // Atomic so the async cache can count and track recency under its
+    // read lock
+    clock: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    expirations: AtomicU64,
+    evictions: AtomicU64,
+}
+
+impl CacheStore {
+    fn new(ttl_ms: u64, max_entries: Option<usize>) -> Self {
         Self {
-            cache: Arc::new(Mutex::new(HashMap::new())),
+            entries: HashMap::new(),
             ttl: Duration::from_millis(ttl_ms),
+            max_entries,
+            clock: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            expirations: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
         }
     }
 
-    /// Put data into the cache
-    pub fn put(&self, key: &str, data: LLSDValue) {
+    fn tick(&self) -> u64 {
+        self.clock.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    fn put(&mut self, key: &str, data: LLSDValue) {
+        if let Some(max_entries) = self.max_entries {
+            if max_entries == 0 {
+                return;
+            }
+            if !self.entries.contains_key(key) && self.entries.len() >= max_entries {
+                // Expired entries make room before any live one is evicted
+                self.cleanup();
+                if self.entries.len() >= max_entries {
+                    self.evict_least_recent();
+                }
+            }
+        }
+
         let entry = CacheEntry {
             data: LLSDUtils::deep_clone(&data),
             timestamp: Instant::now(),
+            last_access: AtomicU64::new(self.tick()),
+        };
+        self.entries.insert(key.to_string(), entry);
+    }
+
+    /// Drop the entry that was put or hit longest ago
+    fn evict_least_recent(&mut self) {
+        let oldest = self.entries
+            .iter()
+            .min_by_key(|(_, entry)| entry.last_access.load(Ordering::Relaxed))
+            .map(|(key, _)| key.clone());
+        if let Some(key) = oldest {
+            self.entries.remove(&key);
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn lookup(&self, key: &str) -> Lookup {
+        match self.entries.get(key) {
+            Some(entry) if entry.timestamp.elapsed() < self.ttl => {
+                entry.last_access.store(self.tick(), Ordering::Relaxed);
+                Lookup::Hit(LLSDUtils::deep_clone(&entry.data))
+            }
+            Some(_) => Lookup::Expired,
+            None => Lookup::Missing,
+        }
+    }
+
+    /// Look up `key` for a `get`, counting a hit or a miss
+    fn lookup_counted(&self, key: &str) -> Lookup {
+        let lookup = self.lookup(key);
+        let counter = match lookup {
+            Lookup::Hit(_) => &self.hits,
+            Lookup::Expired | Lookup::Missing => &self.misses,
         };
+        counter.fetch_add(1, Ordering::Relaxed);
+        lookup
+    }
+
+    /// Remove `key` if it has expired
+    fn evict_expired(&mut self, key: &str) {
+        if matches!(self.lookup(key), Lookup::Expired) {
+            self.entries.remove(key);
+            self.expirations.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn cleanup(&mut self) {
+        let now = Instant::now();
+        let ttl = self.ttl;
+        let before = self.entries.len();
+        self.entries.retain(|_, entry| now.duration_since(entry.timestamp) < ttl);
+        self.expirations.fetch_add((before - self.entries.len()) as u64, Ordering::Relaxed);
+    }
+
+    fn stats(&self) -> FSCacheStats {
+        FSCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            expirations: self.expirations.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            live_entries: self.entries.values().filter(|entry| entry.timestamp.elapsed() < self.ttl).count(),
+        }
+    }
+}
+
+impl FSLLSDCache {
+    /// Create a new cache with TTL in milliseconds
+    pub fn new(ttl_ms: u64) -> Self {
+        Self {
+            store: Arc::new(Mutex::new(CacheStore::new(ttl_ms, None))),
+        }
+    }
+
+    /// Create a cache holding at most `max_entries`; a `put` of a new key
+    /// into a full cache first removes expired entries, then if still full
+    /// evicts the least recently put or read entry. A capacity of 0 stores
+    /// nothing.
+    pub fn with_capacity(ttl_ms: u64, max_entries: usize) -> Self {
+        Self {
+            store: Arc::new(Mutex::new(CacheStore::new(ttl_ms, Some(max_entries)))),
+        }
+    }
 
-        if let Ok(mut cache) = self.cache.lock() {
-            cache.insert(key.to_string(), entry);
+    /// Put data into the cache
+    pub fn put(&self, key: &str, data: LLSDValue) {
+        if let Ok(mut store) = self.store.lock() {
+            store.put(key, data);
         }
     }
 
     /// Get data from the cache
     pub fn get(&self, key: &str) -> Option<LLSDValue> {
-        if let Ok(mut cache) = self.cache.lock() {
-            if let Some(entry) = cache.get(key) {
-                if entry.timestamp.elapsed() < self.ttl {
-                    return Some(LLSDUtils::deep_clone(&entry.data));
-                } else {
-                    // Remove expired entry
-                    cache.remove(key);
-                }
+        if let Ok(mut store) = self.store.lock() {
+            match store.lookup_counted(key) {
+                Lookup::Hit(data) => return Some(data),
+                // Remove expired entry
+                Lookup::Expired => store.evict_expired(key),
+                Lookup::Missing => {}
             }
         }
         None
@@ -453,15 +790,15 @@ impl FSLLSDCache {
 
     /// Clear all cached data
     pub fn clear(&self) {
-        if let Ok(mut cache) = self.cache.lock() {
-            cache.clear();
+        if let Ok(mut store) = self.store.lock() {
+            store.entries.clear();
         }
     }
 
     /// Get the current cache size
     pub fn size(&self) -> usize {
-        if let Ok(cache) = self.cache.lock() {
-            cache.len()
+        if let Ok(store) = self.store.lock() {
+            store.entries.len()
         } else {
             0
         }
@@ -469,18 +806,155 @@ impl FSLLSDCache {
 
     /// Remove expired entries
     pub fn cleanup(&self) {
-        if let Ok(mut cache) = self.cache.lock() {
-            let now = Instant::now();
-            cache.retain(|_, entry| now.duration_since(entry.timestamp) < self.ttl);
+        if let Ok(mut store) = self.store.lock() {
+            store.cleanup();
+        }
+    }
+
+    /// Hit, miss and expiration counts since the cache was created
+    pub fn stats(&self) -> FSCacheStats {
+        if let Ok(store) = self.store.lock() {
+            store.stats()
+        } else {
+            FSCacheStats::default()
         }
     }
 }
 
+/// [`FSLLSDCache`] for async code, guarded by a `tokio::sync::RwLock` so
+/// the lock is never held in a way that blocks the executor. Concurrent
+/// `get`s share a read lock. Clones share the same entries.
+#[cfg(feature = "async")]
+#[derive(Clone)]
+pub struct FSLLSDCacheAsync {
+    store: Arc<tokio::sync::RwLock<CacheStore>>,
+}
+
+#[cfg(feature = "async")]
+impl FSLLSDCacheAsync {
+    /// Create a new cache with TTL in milliseconds
+    pub fn new(ttl_ms: u64) -> Self {
+        Self {
+            store: Arc::new(tokio::sync::RwLock::new(CacheStore::new(ttl_ms, None))),
+        }
+    }
+
+    /// Create a cache holding at most `max_entries`, evicting the least
+    /// recently used entry; see [`FSLLSDCache::with_capacity`]
+    pub fn with_capacity(ttl_ms: u64, max_entries: usize) -> Self {
+        Self {
+            store: Arc::new(tokio::sync::RwLock::new(CacheStore::new(ttl_ms, Some(max_entries)))),
+        }
+    }
+
+    /// Put data into the cache
+    pub async fn put(&self, key: &str, data: LLSDValue) {
+        self.store.write().await.put(key, data);
+    }
+
+    /// Get data from the cache
+    pub async fn get(&self, key: &str) -> Option<LLSDValue> {
+        let lookup = self.store.read().await.lookup_counted(key);
+        match lookup {
+            Lookup::Hit(data) => Some(data),
+            Lookup::Expired => {
+                // Re-checked under the write lock: a put may have refreshed it
+                self.store.write().await.evict_expired(key);
+                None
+            }
+            Lookup::Missing => None,
+        }
+    }
+
+    /// Clear all cached data
+    pub async fn clear(&self) {
+        self.store.write().await.entries.clear();
+    }
+
+    /// Get the current cache size
+    pub async fn size(&self) -> usize {
+        self.store.read().await.entries.len()
+    }
+
+    /// Remove expired entries
+    pub async fn cleanup(&self) {
+        self.store.write().await.cleanup();
+    }
+
+    /// Hit, miss and expiration counts since the cache was created
+    pub async fn stats(&self) -> FSCacheStats {
+        self.store.read().await.stats()
+    }
+
+    /// Run [`cleanup`](Self::cleanup) every `interval` on the current tokio
+    /// runtime, so entries that are never read again are still reclaimed.
+    ///
+    /// The sweeper stops when the returned handle is stopped or dropped, or
+    /// once every clone of the cache is gone.
+    pub fn spawn_sweeper(&self, interval: Duration) -> CacheSweeper {
+        let store = Arc::downgrade(&self.store);
+        let task = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            // The first tick completes immediately
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                match store.upgrade() {
+                    Some(store) => store.write().await.cleanup(),
+                    None => break,
+                }
+            }
+        });
+        CacheSweeper { task }
+    }
+}
+
+/// Handle to a background sweeper started by
+/// [`FSLLSDCacheAsync::spawn_sweeper`]; dropping it stops the sweeper
+#[cfg(feature = "async")]
+pub struct CacheSweeper {
+    task: tokio::task::JoinHandle<()>,
+}
+
+#[cfg(feature = "async")]
+impl CacheSweeper {
+    /// Stop sweeping
+    pub fn stop(self) {
+        self.task.abort();
+    }
+
+    /// Whether the sweeper has stopped
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+}
+
+#[cfg(feature = "async")]
+impl Drop for CacheSweeper {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use uuid::uuid;
-    use std::thread;
+
+    /// Make a cached entry look `age` older than it is
+    fn backdate(store: &mut CacheStore, key: &str, age: Duration) {
+        let entry = store.entries.get_mut(key).unwrap();
+        entry.timestamp = entry.timestamp.checked_sub(age).unwrap();
+    }
+
+    /// Make every cached entry look older than the TTL
+    fn expire_all(store: &mut CacheStore) {
+        let ttl = store.ttl;
+        let keys: Vec<String> = store.entries.keys().cloned().collect();
+        for key in keys {
+            backdate(store, &key, ttl);
+        }
+    }
 
     #[test]
     fn test_create_radar_data() {
@@ -544,6 +1018,64 @@ mod tests {
         assert_eq!(command.to_string(), "@sit:ground=force");
     }
 
+    #[test]
+    fn test_rlv_command_parse() {
+        let parts = |c: &RLVCommand| (c.behaviour.clone(), c.option.clone(), c.param.clone());
+
+        // Force command
+        let sit = RLVCommand::parse("@sit:ground=force").unwrap();
+        assert_eq!(parts(&sit), ("@sit".into(), "ground".into(), "=force".into()));
+
+        // Query command replying on a channel
+        let query = RLVCommand::parse("@getoutfit=2222").unwrap();
+        assert_eq!(parts(&query), ("@getoutfit".into(), "".into(), "=2222".into()));
+
+        // Clear commands, with and without a param
+        let clear = RLVCommand::parse("@clear=n").unwrap();
+        assert_eq!(parts(&clear), ("@clear".into(), "".into(), "=n".into()));
+        let bare = RLVCommand::parse("@clear").unwrap();
+        assert_eq!(parts(&bare), ("@clear".into(), "".into(), "".into()));
+
+        let source_id = uuid!("550e8400-e29b-41d4-a716-446655440000");
+        for command in [
+            RLVCommand::new("@sit", "ground", "=force", source_id),
+            RLVCommand::new("@tploc", "", "=n", source_id),
+            RLVCommand::new("@notify", "2222;inv", "=add", source_id),
+            RLVCommand::new("@clear", "", "", source_id),
+        ] {
+            let parsed = RLVCommand::parse(&command.to_string()).unwrap();
+            assert_eq!(parts(&parsed), parts(&command));
+        }
+
+        assert!(RLVCommand::parse("sit:ground=force").is_err());
+        assert!(RLVCommand::parse("@=n").is_err());
+    }
+
+    #[test]
+    fn test_rlv_command_parse_batch() {
+        let source_id = uuid!("550e8400-e29b-41d4-a716-446655440000");
+        let results = RLVCommand::parse_batch("@detach=n,=force,tplm=n", source_id);
+
+        assert_eq!(results.len(), 3);
+        let first = results[0].as_ref().unwrap();
+        assert_eq!(first.to_string(), "@detach=n");
+        assert_eq!(first.source_id, source_id);
+        assert!(results[1].is_err());
+        let last = results[2].as_ref().unwrap();
+        assert_eq!(last.to_string(), "@tplm=n");
+        assert_eq!(last.source_id, source_id);
+
+        let commands: Vec<String> = RLVCommand::parse_batch("@detach=n,sit:ground=force,tplm=n", source_id)
+            .into_iter()
+            .map(|result| result.unwrap().to_string())
+            .collect();
+        assert_eq!(commands, vec!["@detach=n", "@sit:ground=force", "@tplm=n"]);
+
+        let missing_at = RLVCommand::parse_batch("detach=n,tplm=n", source_id);
+        assert_eq!(missing_at.len(), 1);
+        assert!(missing_at[0].is_err());
+    }
+
     #[test]
     fn test_version_compatibility() {
         assert!(FirestormLLSDUtils::is_compatible_version("6.5.0", "6.0.0"));
@@ -567,6 +1099,13 @@ mod tests {
         assert_eq!(stats["bandwidth"], LLSDValue::Real(500.0));
         assert_eq!(stats["triangles"], LLSDValue::Integer(150000));
         assert_eq!(stats["firestorm_version"], LLSDValue::String("6.0.0".to_string()));
+
+        let timed = FirestormLLSDUtils::create_performance_stats_timed(
+            60.0, 500.0, 1024.0, Duration::from_micros(16_670), Duration::from_micros(5_200), 150000
+        );
+        assert_eq!(FirestormLLSDUtils::frame_time(&timed, "render_time"), Some(Duration::from_micros(16_670)));
+        assert_eq!(FirestormLLSDUtils::frame_time(&timed, "script_time"), Some(Duration::from_micros(5_200)));
+        assert_eq!(FirestormLLSDUtils::frame_time(&timed, "fps_missing"), None);
     }
 
     #[test]
@@ -583,7 +1122,7 @@ mod tests {
         assert_eq!(cache.get("non_existent"), None);
 
         // Test expiration
-        thread::sleep(std::time::Duration::from_millis(150));
+        expire_all(&mut cache.store.lock().unwrap());
         assert_eq!(cache.get("test_key"), None);
         assert_eq!(cache.size(), 0); // Should be removed after access
 
@@ -595,6 +1134,154 @@ mod tests {
         assert_eq!(cache.size(), 0);
     }
 
+    #[test]
+    fn test_cache_stats() {
+        let cache = FSLLSDCache::new(100); // 100ms TTL
+        assert_eq!(cache.stats(), FSCacheStats::default());
+
+        cache.put("a", LLSDValue::Integer(1));
+        cache.put("b", LLSDValue::Integer(2));
+        cache.put("c", LLSDValue::Integer(3));
+        assert!(cache.get("a").is_some());
+        assert!(cache.get("a").is_some());
+        assert!(cache.get("missing").is_none());
+        assert_eq!(cache.stats(), FSCacheStats { hits: 2, misses: 1, expirations: 0, evictions: 0, live_entries: 3 });
+
+        expire_all(&mut cache.store.lock().unwrap());
+        cache.put("d", LLSDValue::Integer(4));
+        assert_eq!(cache.stats().live_entries, 1);
+
+        // A stale entry found by get is an expiration and a miss
+        assert!(cache.get("a").is_none());
+        assert!(cache.get("a").is_none());
+        // cleanup expires the remaining stale entries, b and c
+        cache.cleanup();
+        assert_eq!(cache.stats(), FSCacheStats { hits: 2, misses: 3, expirations: 3, evictions: 0, live_entries: 1 });
+        assert_eq!(cache.size(), 1);
+    }
+
+    #[test]
+    fn test_cache_lru_eviction() {
+        let cache = FSLLSDCache::with_capacity(60_000, 3);
+        for (i, key) in ["a", "b", "c"].iter().enumerate() {
+            cache.put(key, LLSDValue::Integer(i as i32));
+        }
+
+        // One over capacity evicts exactly the oldest entry
+        cache.put("d", LLSDValue::Integer(3));
+        assert_eq!(cache.size(), 3);
+        assert_eq!(cache.get("a"), None);
+        assert!(cache.get("b").is_some());
+        assert!(cache.get("c").is_some());
+        assert!(cache.get("d").is_some());
+
+        // Reading b makes c the least recently used
+        assert!(cache.get("b").is_some());
+        assert!(cache.get("d").is_some());
+        cache.put("e", LLSDValue::Integer(4));
+        assert_eq!(cache.get("c"), None);
+        assert!(cache.get("b").is_some());
+        assert!(cache.get("d").is_some());
+        assert!(cache.get("e").is_some());
+
+        // Replacing an existing key never evicts
+        cache.put("b", LLSDValue::Integer(10));
+        assert_eq!(cache.size(), 3);
+        assert_eq!(cache.stats().evictions, 2);
+    }
+
+    #[test]
+    fn test_cache_capacity_prefers_expired_entries() {
+        let cache = FSLLSDCache::with_capacity(60_000, 2);
+        cache.put("stale", LLSDValue::Integer(1));
+        cache.put("live", LLSDValue::Integer(2));
+        assert!(cache.get("stale").is_some());
+        backdate(&mut cache.store.lock().unwrap(), "stale", Duration::from_secs(60));
+
+        // The expired entry goes, although "live" was used less recently
+        cache.put("new", LLSDValue::Integer(3));
+        assert_eq!(cache.size(), 2);
+        assert!(cache.get("live").is_some());
+        assert!(cache.get("new").is_some());
+        let stats = cache.stats();
+        assert_eq!((stats.expirations, stats.evictions), (1, 0));
+
+        let empty = FSLLSDCache::with_capacity(60_000, 0);
+        empty.put("a", LLSDValue::Integer(1));
+        assert_eq!(empty.size(), 0);
+        assert_eq!(empty.get("a"), None);
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_async_cache_operations() {
+        let cache = FSLLSDCacheAsync::new(100); // 100ms TTL
+        cache.put("shared", LLSDValue::String("value".to_string())).await;
+
+        let readers: Vec<_> = (0..16)
+            .map(|_| {
+                let cache = cache.clone();
+                tokio::spawn(async move { cache.get("shared").await })
+            })
+            .collect();
+        for reader in readers {
+            assert_eq!(reader.await.unwrap(), Some(LLSDValue::String("value".to_string())));
+        }
+
+        let writers: Vec<_> = (0..8)
+            .map(|i| {
+                let cache = cache.clone();
+                tokio::spawn(async move { cache.put(&format!("key{}", i), LLSDValue::Integer(i)).await })
+            })
+            .collect();
+        for writer in writers {
+            writer.await.unwrap();
+        }
+        assert_eq!(cache.size().await, 9);
+        assert_eq!(cache.get("key3").await, Some(LLSDValue::Integer(3)));
+        assert_eq!(cache.get("missing").await, None);
+
+        // Expired entries are dropped on access and by cleanup
+        expire_all(&mut *cache.store.write().await);
+        assert_eq!(cache.get("shared").await, None);
+        assert_eq!(cache.size().await, 8);
+        cache.put("fresh", LLSDValue::Boolean(true)).await;
+        cache.cleanup().await;
+        assert_eq!(cache.size().await, 1);
+
+        cache.clear().await;
+        assert_eq!(cache.size().await, 0);
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test(start_paused = true)]
+    async fn test_async_cache_sweeper() {
+        let cache = FSLLSDCacheAsync::new(50); // 50ms TTL
+        for i in 0..10 {
+            cache.put(&format!("key{}", i), LLSDValue::Integer(i)).await;
+        }
+        expire_all(&mut *cache.store.write().await);
+
+        let sweeper = cache.spawn_sweeper(Duration::from_millis(20));
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        // Reclaimed without any get
+        assert_eq!(cache.size().await, 0);
+        assert!(!sweeper.is_finished());
+
+        // A stopped sweeper leaves expired entries alone
+        sweeper.stop();
+        cache.put("kept", LLSDValue::Boolean(true)).await;
+        expire_all(&mut *cache.store.write().await);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(cache.size().await, 1);
+
+        // The sweeper ends on its own once the cache is dropped
+        let sweeper = cache.spawn_sweeper(Duration::from_millis(10));
+        drop(cache);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(sweeper.is_finished());
+    }
+
     #[cfg(feature = "secondlife")]
     #[test]
     fn test_fs_validation() {
@@ -673,4 +1360,67 @@ mod tests {
             panic!("Expected start_color array");
         }
     }
-}
\ No newline at end of file
+
+    #[cfg(feature = "secondlife")]
+    #[test]
+    fn test_validate_particle_system() {
+        let particle_system = FirestormLLSDUtils::create_enhanced_particle_system(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            1,
+            5.0, 0.0,
+            0.1, 0.2,
+            1.0, 10,
+            1.0, 2.0, 5.0,
+            [0.0, 0.0, -9.8],
+            Uuid::new_v4(),
+            Uuid::nil(),
+            0x01,
+            [1.0, 1.0, 1.0, 1.0],
+            [0.0, 0.0, 0.0, 0.0],
+            [1.0, 1.0],
+            [0.5, 0.5],
+        );
+
+        let result = FirestormLLSDUtils::validate_particle_system(&LLSDValue::Map(particle_system.clone()));
+        assert!(result.is_valid(), "{:?}", result.errors());
+
+        // Two-element acceleration array
+        let mut bad_accel = particle_system.clone();
+        bad_accel.insert("accel".to_string(), LLSDValue::Array(vec![LLSDValue::Real(0.0), LLSDValue::Real(-9.8)]));
+        let result = FirestormLLSDUtils::validate_particle_system(&LLSDValue::Map(bad_accel));
+        assert!(!result.is_valid());
+        assert!(result.errors().iter().any(|e| e.contains("accel") && e.contains("expected 3")));
+
+        // Negative age and missing field
+        let mut bad_age = particle_system;
+        bad_age.insert("max_age".to_string(), LLSDValue::Real(-1.0));
+        let result = FirestormLLSDUtils::validate_particle_system(&LLSDValue::Map(bad_age.clone()));
+        assert!(result.errors().iter().any(|e| e.contains("max_age")));
+        bad_age.remove("end_scale");
+        let result = FirestormLLSDUtils::validate_particle_system(&LLSDValue::Map(bad_age));
+        assert!(result.errors().iter().any(|e| e.contains("end_scale")));
+    }
+
+    #[cfg(feature = "secondlife")]
+    #[test]
+    fn test_validate_radar_data() {
+        let mut radar_data = FirestormLLSDUtils::create_radar_data(
+            Uuid::new_v4(),
+            "Test User",
+            "testuser.resident",
+            [128.0, 128.0, 25.0],
+            15.5,
+            false,
+            Vec::new(),
+        );
+        assert!(FirestormLLSDUtils::validate_radar_data(&LLSDValue::Map(radar_data.clone())).is_valid());
+
+        radar_data.insert("distance".to_string(), LLSDValue::Real(-3.0));
+        radar_data.insert("position".to_string(), LLSDValue::Array(vec![LLSDValue::Real(1.0)]));
+        let result = FirestormLLSDUtils::validate_radar_data(&LLSDValue::Map(radar_data));
+        assert_eq!(result.errors().len(), 2);
+
+        assert!(!FirestormLLSDUtils::validate_radar_data(&LLSDValue::Integer(1)).is_valid());
+    }
+}
@@ -0,0 +1,167 @@
+/*!
+ * LLSD Value Generation - Rust Implementation
+ *
+ * `arbitrary::Arbitrary` support for fuzzers and property tests
+ * Copyright (C) 2024 Linden Lab
+ */
+
+use crate::types::LLSDValue;
+use arbitrary::{Arbitrary, Result, Unstructured};
+use chrono::{TimeZone, Utc};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Latest generated date, 2100-01-01T00:00:00Z
+const MAX_TIMESTAMP: i64 = 4_102_444_800;
+
+/// Bounds on the size of generated value trees
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArbitraryLimits {
+    /// Maximum container nesting; leaves are generated at this depth
+    pub max_depth: usize,
+    /// Maximum number of elements in a single array or map
+    pub max_container_len: usize,
+}
+
+impl Default for ArbitraryLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: 4,
+            max_container_len: 8,
+        }
+    }
+}
+
+impl ArbitraryLimits {
+    /// Limits one level further down the tree
+    fn nested(&self) -> Self {
+        Self {
+            max_depth: self.max_depth.saturating_sub(1),
+            ..*self
+        }
+    }
+}
+
+impl LLSDValue {
+    /// Generate a well-formed value within `limits`.
+    ///
+    /// Every variant can be produced. Reals are always finite and dates are
+    /// whole seconds between 1970 and 2100, so generated values round-trip
+    /// exactly through the binary format.
+    pub fn arbitrary_with(u: &mut Unstructured<'_>, limits: &ArbitraryLimits) -> Result<Self> {
+        let variants = if limits.max_depth == 0 { 10 } else { 12 };
+
+        Ok(match u.choose_index(variants)? {
+            0 => LLSDValue::Undefined,
+            1 => LLSDValue::Boolean(u.arbitrary()?),
+            2 => LLSDValue::Integer(u.arbitrary()?),
+            3 => LLSDValue::Integer64(u.arbitrary()?),
+            4 => {
+                let r: f64 = u.arbitrary()?;
+                LLSDValue::Real(if r.is_finite() { r } else { 0.0 })
+            }
+            5 => LLSDValue::String(u.arbitrary()?),
+            6 => LLSDValue::UUID(Uuid::from_bytes(u.arbitrary()?)),
+            7 => {
+                let seconds = u.int_in_range(0..=MAX_TIMESTAMP)?;
+                LLSDValue::Date(Utc.timestamp_opt(seconds, 0).single().unwrap_or_default())
+            }
+            8 => LLSDValue::URI(u.arbitrary()?),
+            9 => LLSDValue::Binary(u.arbitrary()?),
+            10 => {
+                let len = u.int_in_range(0..=limits.max_container_len)?;
+                let mut array = Vec::with_capacity(len);
+                for _ in 0..len {
+                    array.push(Self::arbitrary_with(u, &limits.nested())?);
+                }
+                LLSDValue::Array(array)
+            }
+            _ => {
+                let len = u.int_in_range(0..=limits.max_container_len)?;
+                let mut map = HashMap::with_capacity(len);
+                for _ in 0..len {
+                    let key: String = u.arbitrary()?;
+                    map.insert(key, Self::arbitrary_with(u, &limits.nested())?);
+                }
+                LLSDValue::Map(map)
+            }
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for LLSDValue {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Self::arbitrary_with(u, &ArbitraryLimits::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LLSDFactory;
+    use crate::types::LLSDDocument;
+
+    /// Deterministic pseudo-random input for `Unstructured`
+    fn noise(seed: u64, len: usize) -> Vec<u8> {
+        let mut state = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                (state >> 33) as u8
+            })
+            .collect()
+    }
+
+    fn depth(value: &LLSDValue) -> usize {
+        match value {
+            LLSDValue::Array(items) => 1 + items.iter().map(depth).max().unwrap_or(0),
+            LLSDValue::Map(entries) => 1 + entries.values().map(depth).max().unwrap_or(0),
+            _ => 0,
+        }
+    }
+
+    #[test]
+    fn test_respects_limits() {
+        let limits = ArbitraryLimits { max_depth: 2, max_container_len: 3 };
+        for seed in 0..200 {
+            let data = noise(seed, 4096);
+            let value = LLSDValue::arbitrary_with(&mut Unstructured::new(&data), &limits).unwrap();
+            assert!(depth(&value) <= 2);
+            let mut stack = vec![&value];
+            while let Some(v) = stack.pop() {
+                match v {
+                    LLSDValue::Array(items) => {
+                        assert!(items.len() <= 3);
+                        stack.extend(items);
+                    }
+                    LLSDValue::Map(entries) => {
+                        assert!(entries.len() <= 3);
+                        stack.extend(entries.values());
+                    }
+                    LLSDValue::Real(r) => assert!(r.is_finite()),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_generated_values_survive_all_formats() {
+        for seed in 0..200 {
+            let data = noise(seed, 4096);
+            let value = LLSDValue::arbitrary(&mut Unstructured::new(&data)).unwrap();
+            let doc = LLSDDocument::new(value.clone());
+
+            let binary = LLSDFactory::serialize_binary(&doc).unwrap();
+            assert_eq!(*LLSDFactory::parse_binary(&binary).unwrap().content(), value);
+
+            // XML and JSON are lossy for some values; they only must not panic
+            if let Ok(xml) = LLSDFactory::serialize_xml(&doc, false) {
+                let _ = LLSDFactory::parse_xml(&xml);
+            }
+            if let Ok(json) = LLSDFactory::serialize_json(&doc, false) {
+                let _ = LLSDFactory::parse_json(&json);
+            }
+        }
+    }
+}
@@ -5,17 +5,39 @@
  * Copyright (C) 2024 Linden Lab
  */
 
-use crate::types::{LLSDValue, LLSDDocument};
+use crate::types::{LLSDValue, LLSDDocument, LLSDType};
 use crate::error::{LLSDError, LLSDResult};
+use crate::utils::{BudgetMeter, LimitedWriter, ParseBudget};
+use serde::ser::{SerializeMap, SerializeSeq};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
 use serde_json::{Value, Map};
+use std::cell::RefCell;
 use std::collections::HashMap;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+
+/// URI schemes [`LLSDJsonParser`] recognizes by default
+pub const DEFAULT_URI_SCHEMES: &[&str] = &["http", "https", "ftp"];
 
 /// LLSD JSON parser
+///
+/// JSON has no URI type, so a `URI` serialized without type preservation is
+/// a plain string, and the parser decides from its text whether to read it
+/// back as `URI` or `String`. A string is a URI if it contains `://` or
+/// starts with one of the parser's URI schemes followed by `:`; see
+/// [`with_uri_schemes`](Self::with_uri_schemes). A URI such as
+/// `mailto:someone@example.com` therefore becomes a `String` unless
+/// `mailto` is added to the schemes.
 #[derive(Debug, Default)]
 pub struct LLSDJsonParser {
     strict_uuid_parsing: bool,
+    lenient: bool,
+    parse_nonfinite: bool,
+    uri_schemes: Option<Vec<String>>,
+    budget: ParseBudget,
 }
 
 impl LLSDJsonParser {
@@ -30,43 +52,122 @@ impl LLSDJsonParser {
         self
     }
 
+    /// Tolerate trailing commas, `//` and `/* */` comments, and single-quoted
+    /// strings, as found in hand-edited config files. Off by default.
+    pub fn with_lenient_json(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Read the strings `"NaN"`, `"Infinity"` and `"-Infinity"` as reals,
+    /// matching [`NonFinitePolicy::String`]. Off by default.
+    pub fn with_parse_nonfinite(mut self, parse: bool) -> Self {
+        self.parse_nonfinite = parse;
+        self
+    }
+
+    /// Read strings starting with `<scheme>:` as URIs for each of
+    /// `schemes`, compared case-insensitively, in place of
+    /// [`DEFAULT_URI_SCHEMES`]. Strings containing `://` are URIs
+    /// regardless.
+    ///
+    /// To add to the defaults, include them:
+    ///
+    /// ```
+    /// # use llsd::json::{LLSDJsonParser, DEFAULT_URI_SCHEMES};
+    /// # use llsd::LLSDValue;
+    /// let parser = LLSDJsonParser::new()
+    ///     .with_uri_schemes(DEFAULT_URI_SCHEMES.iter().copied().chain(["mailto"]));
+    /// let doc = parser.parse(r#""mailto:someone@example.com""#).unwrap();
+    /// assert!(matches!(doc.content(), LLSDValue::URI(_)));
+    /// ```
+    pub fn with_uri_schemes<I, S>(mut self, schemes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.uri_schemes = Some(schemes.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Abort with [`LLSDError::ParseAborted`] once parsing exceeds either
+    /// limit of `budget`. The budget covers the LLSD value being built; the
+    /// JSON text is tokenized up front, bounded only by the input's size.
+    pub fn with_parse_budget(mut self, budget: ParseBudget) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Whether `s` starts with `<scheme>:` for a configured URI scheme
+    fn has_uri_scheme(&self, s: &str) -> bool {
+        let Some((scheme, _)) = s.split_once(':') else {
+            return false;
+        };
+        match &self.uri_schemes {
+            Some(schemes) => schemes.iter().any(|known| known.eq_ignore_ascii_case(scheme)),
+            None => DEFAULT_URI_SCHEMES.iter().any(|known| known.eq_ignore_ascii_case(scheme)),
+        }
+    }
+
     /// Parse LLSD from JSON string
     pub fn parse(&self, json: &str) -> LLSDResult<LLSDDocument> {
-        let value: Value = serde_json::from_str(json)?;
-        let llsd_value = self.convert_json_value(&value)?;
+        if json.trim().is_empty() {
+            return Err(LLSDError::EmptyInput);
+        }
+
+        let value: Value = if self.lenient {
+            serde_json::from_str(&normalize_lenient_json(json)?)?
+        } else {
+            serde_json::from_str(json)?
+        };
+        let llsd_value = self.convert_json_value(&value, &mut BudgetMeter::start(self.budget))?;
         Ok(LLSDDocument::new(llsd_value))
     }
 
+    /// Type of the root value, so callers can route a payload without
+    /// decoding it. Maps and arrays are recognized from their first
+    /// character and the rest of the payload is not validated; a scalar
+    /// root is parsed to tell strings from the types written as strings.
+    pub fn probe_root_type(json: &str) -> LLSDResult<LLSDType> {
+        match json.trim_start().chars().next() {
+            None => Err(LLSDError::EmptyInput),
+            Some('{') => Ok(LLSDType::Map),
+            Some('[') => Ok(LLSDType::Array),
+            Some(_) => Ok(Self::new().parse(json)?.get_type()),
+        }
+    }
+
     /// Convert JSON value to LLSD value
-    fn convert_json_value(&self, value: &Value) -> LLSDResult<LLSDValue> {
+    fn convert_json_value(&self, value: &Value, meter: &mut BudgetMeter) -> LLSDResult<LLSDValue> {
+        meter.charge_value()?;
         match value {
             Value::Null => Ok(LLSDValue::Undefined),
             Value::Bool(b) => Ok(LLSDValue::Boolean(*b)),
             Value::Number(n) => {
                 if let Some(i) = n.as_i64() {
-                    if i >= i32::MIN as i64 && i <= i32::MAX as i64 {
-                        Ok(LLSDValue::Integer(i as i32))
-                    } else {
-                        Ok(LLSDValue::Real(i as f64))
-                    }
+                    Ok(LLSDValue::from_i64(i))
                 } else if let Some(f) = n.as_f64() {
                     Ok(LLSDValue::Real(f))
                 } else {
                     Err(LLSDError::custom("Invalid number format"))
                 }
             }
-            Value::String(s) => self.convert_json_string(s),
+            Value::String(s) => {
+                meter.charge(s.len())?;
+                self.convert_json_string(s)
+            }
             Value::Array(arr) => {
                 let mut llsd_array = Vec::with_capacity(arr.len());
                 for item in arr {
-                    llsd_array.push(self.convert_json_value(item)?);
+                    llsd_array.push(self.convert_json_value(item, meter)?);
                 }
                 Ok(LLSDValue::Array(llsd_array))
             }
             Value::Object(obj) => {
                 let mut llsd_map = HashMap::with_capacity(obj.len());
                 for (key, value) in obj {
-                    llsd_map.insert(key.clone(), self.convert_json_value(value)?);
+                    meter.charge(key.len())?;
+                    llsd_map.insert(key.clone(), self.convert_json_value(value, meter)?);
                 }
                 Ok(LLSDValue::Map(llsd_map))
             }
@@ -75,6 +176,15 @@ impl LLSDJsonParser {
 
     /// Convert JSON string to appropriate LLSD type
     fn convert_json_string(&self, s: &str) -> LLSDResult<LLSDValue> {
+        if self.parse_nonfinite {
+            match s {
+                "NaN" => return Ok(LLSDValue::Real(f64::NAN)),
+                "Infinity" => return Ok(LLSDValue::Real(f64::INFINITY)),
+                "-Infinity" => return Ok(LLSDValue::Real(f64::NEG_INFINITY)),
+                _ => {}
+            }
+        }
+
         // Try to parse as UUID
         if self.strict_uuid_parsing {
             if let Ok(uuid) = Uuid::parse_str(s) {
@@ -94,8 +204,8 @@ impl LLSDJsonParser {
             return Ok(LLSDValue::Date(date.with_timezone(&Utc)));
         }
 
-        // Try to parse as URI (heuristic: contains :// or starts with common schemes)
-        if s.contains("://") || s.starts_with("http:") || s.starts_with("https:") || s.starts_with("ftp:") {
+        // Try to parse as URI (heuristic: contains :// or starts with a known scheme)
+        if s.contains("://") || self.has_uri_scheme(s) {
             return Ok(LLSDValue::URI(s.to_string()));
         }
 
@@ -104,11 +214,108 @@ impl LLSDJsonParser {
     }
 }
 
+/// Rewrite lenient JSON into strict JSON: strip comments, drop trailing
+/// commas before `]`/`}`, and convert single-quoted strings to double-quoted.
+fn normalize_lenient_json(input: &str) -> LLSDResult<String> {
+    let mut out = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                out.push('"');
+                loop {
+                    match chars.next() {
+                        Some('\\') => {
+                            out.push('\\');
+                            if let Some(escaped) = chars.next() {
+                                out.push(escaped);
+                            }
+                        }
+                        Some('"') => {
+                            out.push('"');
+                            break;
+                        }
+                        Some(other) => out.push(other),
+                        None => return Err(LLSDError::custom("Unterminated string in lenient JSON")),
+                    }
+                }
+            }
+            '\'' => {
+                out.push('"');
+                loop {
+                    match chars.next() {
+                        Some('\\') => match chars.next() {
+                            Some('\'') => out.push('\''),
+                            Some(escaped) => {
+                                out.push('\\');
+                                out.push(escaped);
+                            }
+                            None => {}
+                        },
+                        Some('\'') => {
+                            out.push('"');
+                            break;
+                        }
+                        Some('"') => out.push_str("\\\""),
+                        Some(other) => out.push(other),
+                        None => return Err(LLSDError::custom("Unterminated string in lenient JSON")),
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        out.push('\n');
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = '\0';
+                loop {
+                    match chars.next() {
+                        Some('/') if prev == '*' => break,
+                        Some(next) => prev = next,
+                        None => return Err(LLSDError::custom("Unterminated comment in lenient JSON")),
+                    }
+                }
+                out.push(' ');
+            }
+            ']' | '}' => {
+                let trimmed = out.trim_end().len();
+                if out[..trimmed].ends_with(',') {
+                    out.truncate(trimmed - 1);
+                }
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+
+    Ok(out)
+}
+
+/// How the JSON serializer writes reals JSON cannot represent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NonFinitePolicy {
+    /// Fail serialization
+    #[default]
+    Error,
+    /// Write `null`
+    Null,
+    /// Write the strings `"NaN"`, `"Infinity"` and `"-Infinity"`
+    String,
+}
+
 /// LLSD JSON serializer
 #[derive(Debug, Default)]
 pub struct LLSDJsonSerializer {
     pretty_print: bool,
     preserve_types: bool,
+    nonfinite_policy: NonFinitePolicy,
+    max_output_size: Option<usize>,
 }
 
 impl LLSDJsonSerializer {
@@ -124,20 +331,46 @@ impl LLSDJsonSerializer {
     }
 
     /// Enable type preservation (add type hints for UUIDs, dates, etc.)
+    ///
+    /// Without it, UUIDs, dates and URIs are written as plain strings and
+    /// [`LLSDJsonParser`] infers their type from the text, so a URI whose
+    /// scheme the parser does not know reads back as a `String`.
     pub fn with_type_preservation(mut self, preserve: bool) -> Self {
         self.preserve_types = preserve;
         self
     }
 
+    /// Choose how NaN and infinite reals are written; see [`NonFinitePolicy`]
+    pub fn with_nonfinite_policy(mut self, policy: NonFinitePolicy) -> Self {
+        self.nonfinite_policy = policy;
+        self
+    }
+
+    /// Fail with [`LLSDError::OutputTooLarge`] as soon as the JSON would
+    /// grow past `bytes`. The document is written as it is walked, so an
+    /// oversized one is abandoned without being converted in full.
+    pub fn with_max_output_size(mut self, bytes: usize) -> Self {
+        self.max_output_size = Some(bytes);
+        self
+    }
+
     /// Serialize LLSD to JSON string
     pub fn serialize(&self, document: &LLSDDocument) -> LLSDResult<String> {
-        let json_value = self.convert_llsd_value(document.content())?;
-        
-        if self.pretty_print {
-            Ok(serde_json::to_string_pretty(&json_value)?)
+        let error = RefCell::new(None);
+        let json_value = JsonOutput { serializer: self, value: document.content(), error: &error };
+
+        let mut output = Vec::new();
+        let mut writer = LimitedWriter::new(&mut output, self.max_output_size);
+        let result = if self.pretty_print {
+            serde_json::to_writer_pretty(&mut writer, &json_value)
         } else {
-            Ok(serde_json::to_string(&json_value)?)
+            serde_json::to_writer(&mut writer, &json_value)
+        };
+        if let Err(err) = result {
+            return Err(error.into_inner().unwrap_or_else(|| writer.map_error(err.into())));
         }
+
+        String::from_utf8(output).map_err(LLSDError::from)
     }
 
     /// Convert LLSD value to JSON value
@@ -146,11 +379,24 @@ impl LLSDJsonSerializer {
             LLSDValue::Undefined => Ok(Value::Null),
             LLSDValue::Boolean(b) => Ok(Value::Bool(*b)),
             LLSDValue::Integer(i) => Ok(Value::Number((*i).into())),
+            LLSDValue::Integer64(i) => Ok(Value::Number((*i).into())),
             LLSDValue::Real(r) => {
                 if let Some(n) = serde_json::Number::from_f64(*r) {
-                    Ok(Value::Number(n))
-                } else {
-                    Err(LLSDError::custom("Invalid floating-point number"))
+                    return Ok(Value::Number(n));
+                }
+                match self.nonfinite_policy {
+                    NonFinitePolicy::Error => Err(LLSDError::custom("Invalid floating-point number")),
+                    NonFinitePolicy::Null => Ok(Value::Null),
+                    NonFinitePolicy::String => {
+                        let text = if r.is_nan() {
+                            "NaN"
+                        } else if *r > 0.0 {
+                            "Infinity"
+                        } else {
+                            "-Infinity"
+                        };
+                        Ok(Value::String(text.to_string()))
+                    }
                 }
             }
             LLSDValue::String(s) => Ok(Value::String(s.clone())),
@@ -187,7 +433,7 @@ impl LLSDJsonSerializer {
                 }
             }
             LLSDValue::Binary(b) => {
-                let base64_str = base64::encode(b);
+                let base64_str = BASE64.encode(b);
                 if self.preserve_types {
                     let mut obj = Map::new();
                     obj.insert("__type".to_string(), Value::String("binary".to_string()));
@@ -215,7 +461,75 @@ impl LLSDJsonSerializer {
     }
 }
 
+/// An LLSD value written straight to a serde_json serializer, converted
+/// as [`LLSDJsonSerializer::convert_llsd_value`] would, without building
+/// the whole JSON tree first. Map keys are written in sorted order, as a
+/// converted `serde_json::Map` would hold them.
+struct JsonOutput<'a> {
+    serializer: &'a LLSDJsonSerializer,
+    value: &'a LLSDValue,
+    /// Conversion error behind a failed write, kept to be returned as is
+    error: &'a RefCell<Option<LLSDError>>,
+}
+
+impl Serialize for JsonOutput<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let child = |value| JsonOutput { serializer: self.serializer, value, error: self.error };
+        match self.value {
+            LLSDValue::Array(items) => {
+                let mut seq = serializer.serialize_seq(Some(items.len()))?;
+                for item in items {
+                    seq.serialize_element(&child(item))?;
+                }
+                seq.end()
+            }
+            LLSDValue::Map(map) => {
+                let mut keys: Vec<&String> = map.keys().collect();
+                keys.sort();
+                let mut entries = serializer.serialize_map(Some(map.len()))?;
+                for key in keys {
+                    entries.serialize_entry(key, &child(&map[key]))?;
+                }
+                entries.end()
+            }
+            scalar => match self.serializer.convert_llsd_value(scalar) {
+                Ok(value) => value.serialize(serializer),
+                Err(err) => {
+                    let message = err.to_string();
+                    *self.error.borrow_mut() = Some(err);
+                    Err(serde::ser::Error::custom(message))
+                }
+            },
+        }
+    }
+}
+
+impl LLSDValue {
+    /// Convert any `Serialize` type into an LLSD value.
+    ///
+    /// The value is bridged through the JSON data model and converted the
+    /// same way `LLSDJsonParser` converts parsed JSON: integers that fit in
+    /// 32 bits become `Integer`, wider ones `Integer64`, other numbers
+    /// `Real`, and strings that look like UUIDs, dates or URIs take those
+    /// types. `None` becomes `Undefined`.
+    pub fn from_serialize<T: Serialize + ?Sized>(value: &T) -> LLSDResult<LLSDValue> {
+        let json = serde_json::to_value(value)?;
+        LLSDJsonParser::new().convert_json_value(&json, &mut BudgetMeter::start(ParseBudget::default()))
+    }
+
+    /// Convert this LLSD value into any `DeserializeOwned` type.
+    ///
+    /// UUIDs, dates and URIs are presented as strings and binary data as a
+    /// base64 string, matching `LLSDJsonSerializer`. Non-finite reals cannot
+    /// be represented and produce an error.
+    pub fn to_deserialize<T: DeserializeOwned>(&self) -> LLSDResult<T> {
+        let json = LLSDJsonSerializer::new().convert_llsd_value(self)?;
+        Ok(serde_json::from_value(json)?)
+    }
+}
+
 #[cfg(test)]
+#[allow(clippy::approx_constant)]
 mod tests {
     use super::*;
     use uuid::uuid;
@@ -345,4 +659,248 @@ mod tests {
         // This is expected behavior
         assert_eq!(parsed_doc.get_type(), doc.get_type());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_lenient_json() {
+        let lenient = r#"
+            // viewer settings
+            {
+                'name': 'Alice\'s "alt"',
+                "scores": [95, 87, 92.5,],   /* trailing comma */
+                'nested': { 'url': 'https://example.com/a,b', },
+            }
+        "#;
+        let strict = r#"{
+            "name": "Alice's \"alt\"",
+            "scores": [95, 87, 92.5],
+            "nested": { "url": "https://example.com/a,b" }
+        }"#;
+
+        let lenient_doc = LLSDJsonParser::new().with_lenient_json(true).parse(lenient).unwrap();
+        let strict_doc = LLSDJsonParser::new().parse(strict).unwrap();
+        assert_eq!(lenient_doc.content(), strict_doc.content());
+        assert_eq!(
+            lenient_doc.content().get_path("name"),
+            Some(&LLSDValue::String("Alice's \"alt\"".to_string()))
+        );
+
+        // Strict mode remains the default
+        assert!(LLSDJsonParser::new().parse(lenient).is_err());
+        assert!(LLSDJsonParser::new().with_lenient_json(true).parse("{'a': 1 /* open").is_err());
+    }
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Agent {
+        id: Uuid,
+        name: String,
+        title: Option<String>,
+    }
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Region {
+        name: String,
+        agents: i32,
+        handle: u64,
+        water_height: f64,
+        residents: Vec<Agent>,
+        owner: Option<Agent>,
+    }
+
+    #[test]
+    fn test_serde_bridge() {
+        let region = Region {
+            name: "Ahern".to_string(),
+            agents: 2,
+            handle: 1_099_511_628_032_000,
+            water_height: 20.0,
+            residents: vec![
+                Agent {
+                    id: uuid!("550e8400-e29b-41d4-a716-446655440000"),
+                    name: "Alice".to_string(),
+                    title: Some("Builder".to_string()),
+                },
+                Agent {
+                    id: uuid!("550e8400-e29b-41d4-a716-446655440001"),
+                    name: "Bob".to_string(),
+                    title: None,
+                },
+            ],
+            owner: None,
+        };
+
+        let value = LLSDValue::from_serialize(&region).unwrap();
+        assert_eq!(value.get_path("name"), Some(&LLSDValue::String("Ahern".to_string())));
+        assert_eq!(value.get_path("agents"), Some(&LLSDValue::Integer(2)));
+        assert_eq!(value.get_path("handle"), Some(&LLSDValue::Integer64(1_099_511_628_032_000)));
+        assert_eq!(value.get_path("water_height"), Some(&LLSDValue::Real(20.0)));
+        assert_eq!(value.get_path("owner"), Some(&LLSDValue::Undefined));
+        assert_eq!(value.get_path("residents.1.title"), Some(&LLSDValue::Undefined));
+        assert_eq!(
+            value.get_path("residents.0.id"),
+            Some(&LLSDValue::UUID(uuid!("550e8400-e29b-41d4-a716-446655440000")))
+        );
+
+        let back: Region = value.to_deserialize().unwrap();
+        assert_eq!(back, region);
+
+        // Shape mismatches surface as errors
+        assert!(LLSDValue::Integer(1).to_deserialize::<Region>().is_err());
+        assert!(LLSDValue::Real(f64::NAN).to_deserialize::<f64>().is_err());
+    }
+
+    #[test]
+    fn test_nonfinite_policies() {
+        let doc = LLSDDocument::new(LLSDValue::Array(vec![
+            LLSDValue::Real(f64::NAN),
+            LLSDValue::Real(f64::INFINITY),
+            LLSDValue::Real(f64::NEG_INFINITY),
+            LLSDValue::Real(1.5),
+        ]));
+
+        // Error stays the default
+        assert!(LLSDJsonSerializer::new().serialize(&doc).is_err());
+        assert!(LLSDJsonSerializer::new()
+            .with_nonfinite_policy(NonFinitePolicy::Error)
+            .serialize(&doc)
+            .is_err());
+
+        let null = LLSDJsonSerializer::new()
+            .with_nonfinite_policy(NonFinitePolicy::Null)
+            .serialize(&doc)
+            .unwrap();
+        assert_eq!(null, "[null,null,null,1.5]");
+
+        let string = LLSDJsonSerializer::new()
+            .with_nonfinite_policy(NonFinitePolicy::String)
+            .serialize(&doc)
+            .unwrap();
+        assert_eq!(string, r#"["NaN","Infinity","-Infinity",1.5]"#);
+
+        // Without the parser flag the strings stay strings
+        let plain = LLSDJsonParser::new().parse(&string).unwrap();
+        assert_eq!(plain.content().get_path("0"), Some(&LLSDValue::String("NaN".to_string())));
+
+        let parsed = LLSDJsonParser::new().with_parse_nonfinite(true).parse(&string).unwrap();
+        let reals: Vec<f64> = parsed
+            .content()
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_real().unwrap())
+            .collect();
+        assert!(reals[0].is_nan());
+        assert_eq!(&reals[1..], &[f64::INFINITY, f64::NEG_INFINITY, 1.5]);
+    }
+
+    #[test]
+    fn test_max_output_size() {
+        let items: Vec<LLSDValue> = (0..100_000)
+            .map(|i| LLSDValue::String(format!("item {}", i)))
+            .collect();
+        let document = LLSDDocument::new(LLSDValue::Array(items));
+
+        for serializer in [
+            LLSDJsonSerializer::new().with_max_output_size(1024),
+            LLSDJsonSerializer::new().with_pretty_print(true).with_max_output_size(1024),
+        ] {
+            assert!(matches!(
+                serializer.serialize(&document),
+                Err(LLSDError::OutputTooLarge { limit: 1024 })
+            ));
+        }
+
+        let small = LLSDDocument::new(LLSDValue::Integer(42));
+        let serializer = LLSDJsonSerializer::new().with_max_output_size(2);
+        assert_eq!(serializer.serialize(&small).unwrap(), "42");
+    }
+
+    #[test]
+    fn test_streamed_output_matches_converted_value() {
+        let mut inner = HashMap::new();
+        inner.insert("when".to_string(), LLSDValue::Date(Utc::now()));
+        inner.insert("blob".to_string(), LLSDValue::Binary(vec![0, 1, 2]));
+        inner.insert("empty".to_string(), LLSDValue::Array(Vec::new()));
+        let mut map = HashMap::new();
+        for key in ["zeta", "alpha", "mid"] {
+            map.insert(key.to_string(), LLSDValue::Map(inner.clone()));
+        }
+        map.insert("items".to_string(), LLSDValue::Array(vec![
+            LLSDValue::UUID(Uuid::new_v4()),
+            LLSDValue::Real(f64::NAN),
+            LLSDValue::Undefined,
+        ]));
+        let value = LLSDValue::Map(map);
+
+        for serializer in [
+            LLSDJsonSerializer::new().with_nonfinite_policy(NonFinitePolicy::Null),
+            LLSDJsonSerializer::new().with_pretty_print(true).with_nonfinite_policy(NonFinitePolicy::String),
+            LLSDJsonSerializer::new().with_type_preservation(true).with_nonfinite_policy(NonFinitePolicy::Null),
+        ] {
+            let converted = serializer.convert_llsd_value(&value).unwrap();
+            let expected = if serializer.pretty_print {
+                serde_json::to_string_pretty(&converted).unwrap()
+            } else {
+                serde_json::to_string(&converted).unwrap()
+            };
+            assert_eq!(serializer.serialize(&LLSDDocument::new(value.clone())).unwrap(), expected);
+        }
+
+        // Conversion errors surface unchanged from inside containers
+        let nan = LLSDDocument::new(LLSDValue::Array(vec![LLSDValue::Real(f64::NAN)]));
+        let err = LLSDJsonSerializer::new().serialize(&nan).unwrap_err();
+        assert!(matches!(err, LLSDError::CustomError { message } if message == "Invalid floating-point number"));
+    }
+
+    #[test]
+    fn test_uri_round_trip_typing() {
+        let serializer = LLSDJsonSerializer::new();
+        let round_trip = |parser: &LLSDJsonParser, value: LLSDValue| {
+            let json = serializer.serialize(&LLSDDocument::new(value)).unwrap();
+            parser.parse(&json).unwrap().content().clone()
+        };
+        let http = LLSDValue::URI("http://example.com/path".to_string());
+        let mailto = LLSDValue::URI("mailto:someone@example.com".to_string());
+        let plain = LLSDValue::String("note: not a link".to_string());
+
+        // Default schemes: http survives, mailto degrades to a string
+        let parser = LLSDJsonParser::new();
+        assert_eq!(round_trip(&parser, http.clone()), http);
+        assert_eq!(
+            round_trip(&parser, mailto.clone()),
+            LLSDValue::String("mailto:someone@example.com".to_string())
+        );
+        assert_eq!(round_trip(&parser, plain.clone()), plain);
+
+        let parser = LLSDJsonParser::new()
+            .with_uri_schemes(DEFAULT_URI_SCHEMES.iter().copied().chain(["mailto"]));
+        assert_eq!(round_trip(&parser, http.clone()), http);
+        assert_eq!(round_trip(&parser, mailto.clone()), mailto);
+        assert_eq!(round_trip(&parser, plain.clone()), plain);
+        assert!(matches!(
+            parser.parse(r#""MAILTO:someone@example.com""#).unwrap().content(),
+            LLSDValue::URI(_)
+        ));
+
+        // Replacing the list drops the defaults, except for "://"
+        let parser = LLSDJsonParser::new().with_uri_schemes(["urn"]);
+        assert!(matches!(parser.parse(r#""urn:isbn:0451450523""#).unwrap().content(), LLSDValue::URI(_)));
+        assert!(matches!(parser.parse(r#""http:relative""#).unwrap().content(), LLSDValue::String(_)));
+        assert_eq!(round_trip(&parser, http.clone()), http);
+    }
+
+    #[test]
+    fn test_parse_budget() {
+        // A few bytes of input per value, far more per parsed value
+        let nulls = format!("[{}null]", "0,".repeat(100_000));
+        let parser = LLSDJsonParser::new()
+            .with_parse_budget(ParseBudget { max_bytes: Some(64 * 1024), max_duration: None });
+        assert!(matches!(parser.parse(&nulls), Err(LLSDError::ParseAborted { .. })));
+
+        let parser = LLSDJsonParser::new().with_parse_budget(ParseBudget {
+            max_bytes: Some(64 * 1024),
+            max_duration: Some(std::time::Duration::from_secs(30)),
+        });
+        let doc = parser.parse(r#"{"name":"Alice","tags":["a","b"]}"#).unwrap();
+        assert_eq!(doc.content().get_path("tags.1"), Some(&LLSDValue::String("b".to_string())));
+    }
+}
@@ -11,8 +11,11 @@ pub mod types;
 pub mod xml;
 pub mod binary;
 pub mod json;
+pub mod notation;
 pub mod utils;
 pub mod error;
+pub mod schema;
+pub mod visitor;
 
 #[cfg(feature = "secondlife")]
 pub mod secondlife;
@@ -20,15 +23,27 @@ pub mod secondlife;
 #[cfg(feature = "firestorm")]
 pub mod firestorm;
 
+#[cfg(feature = "viewer")]
+pub mod viewer;
+
+#[cfg(feature = "arbitrary")]
+pub mod fuzzing;
+
+#[cfg(any(feature = "testing", feature = "proptest"))]
+pub mod testing;
+
 // Re-export core types
-pub use types::{LLSDValue, LLSDType, LLSDFormat, LLSDDocument};
+pub use types::{ArrayMerge, LLSDValue, LLSDType, LLSDFormat, LLSDDocument, LLSDEntries, LLSDKey, MergeStrategy, ScalarConflict, METADATA_BYTE_LENGTH, METADATA_MERGED_FROM, METADATA_SOURCE_FORMAT};
 pub use error::{LLSDError, LLSDResult};
-pub use utils::LLSDUtils;
+pub use utils::{DiffReason, LLSDDiff, LLSDUtils, NormalizeOptions, ParseBudget};
+pub use schema::{LLSDSchema, LLSDDocumentBuilder};
+pub use visitor::{LLSDVisitor, LLSDVisitorMut};
 
 // Re-export parsers and serializers
-pub use xml::{LLSDXmlParser, LLSDXmlSerializer};
-pub use binary::{LLSDBinaryParser, LLSDBinarySerializer};
-pub use json::{LLSDJsonParser, LLSDJsonSerializer};
+pub use xml::{LLSDXmlParser, LLSDXmlSerializer, LLSDXmlStreamParser, RealFormat};
+pub use binary::{ByteOrder, LLSDBinaryParser, LLSDBinarySerializer};
+pub use json::{LLSDJsonParser, LLSDJsonSerializer, NonFinitePolicy};
+pub use notation::{LLSDNotationParser, LLSDNotationSerializer};
 
 #[cfg(feature = "secondlife")]
 pub use secondlife::SecondLifeLLSDUtils;
@@ -36,26 +51,131 @@ pub use secondlife::SecondLifeLLSDUtils;
 #[cfg(feature = "firestorm")]
 pub use firestorm::FirestormLLSDUtils;
 
+#[cfg(feature = "arbitrary")]
+pub use fuzzing::ArbitraryLimits;
+
 /// Factory for creating LLSD parsers and serializers
+///
+/// Documents from the `parse_*` methods carry [`METADATA_SOURCE_FORMAT`]
+/// and [`METADATA_BYTE_LENGTH`] metadata; parsers used directly add none.
 pub struct LLSDFactory;
 
 impl LLSDFactory {
     /// Parse LLSD from XML string
     pub fn parse_xml(xml: &str) -> LLSDResult<LLSDDocument> {
         let parser = LLSDXmlParser::new();
-        parser.parse(xml)
+        Ok(Self::stamp(parser.parse(xml)?, LLSDFormat::XML, xml.len()))
     }
 
     /// Parse LLSD from binary data
     pub fn parse_binary(data: &[u8]) -> LLSDResult<LLSDDocument> {
         let parser = LLSDBinaryParser::new();
-        parser.parse(data)
+        Ok(Self::stamp(parser.parse(data)?, LLSDFormat::Binary, data.len()))
+    }
+
+    /// Parse LLSD from untrusted binary data, aborting with
+    /// [`LLSDError::ParseAborted`] once parsing exceeds either limit of
+    /// `budget`
+    pub fn parse_binary_with_budget(data: &[u8], budget: ParseBudget) -> LLSDResult<LLSDDocument> {
+        let parser = LLSDBinaryParser::new().with_parse_budget(budget);
+        Ok(Self::stamp(parser.parse(data)?, LLSDFormat::Binary, data.len()))
+    }
+
+    /// Parse untrusted binary LLSD incrementally from `reader` under
+    /// `budget`, as [`parse_binary_with_budget`](Self::parse_binary_with_budget)
+    /// does for a buffer. The recorded byte length is the number of bytes
+    /// read.
+    pub fn parse_binary_reader_with_budget<R: std::io::Read>(reader: R, budget: ParseBudget) -> LLSDResult<LLSDDocument> {
+        let parser = LLSDBinaryParser::new().with_parse_budget(budget);
+        let mut reader = utils::CountingReader::new(reader);
+        let document = parser.parse_reader(&mut reader)?;
+        Ok(Self::stamp(document, LLSDFormat::Binary, reader.count()))
     }
 
     /// Parse LLSD from JSON string
     pub fn parse_json(json: &str) -> LLSDResult<LLSDDocument> {
         let parser = LLSDJsonParser::new();
-        parser.parse(json)
+        Ok(Self::stamp(parser.parse(json)?, LLSDFormat::JSON, json.len()))
+    }
+
+    /// Parse LLSD from notation string
+    pub fn parse_notation(notation: &str) -> LLSDResult<LLSDDocument> {
+        Self::parse_notation_bytes(notation.as_bytes())
+    }
+
+    /// Parse notation bytes, which may hold raw binary values that are not
+    /// UTF-8
+    fn parse_notation_bytes(data: &[u8]) -> LLSDResult<LLSDDocument> {
+        let parser = LLSDNotationParser::new();
+        Ok(Self::stamp(parser.parse_bytes(data)?, LLSDFormat::Notation, data.len()))
+    }
+
+    /// Parse LLSD in a format chosen at runtime. Text formats must be UTF-8,
+    /// except notation.
+    pub fn parse(format: LLSDFormat, data: &[u8]) -> LLSDResult<LLSDDocument> {
+        match format {
+            LLSDFormat::XML => Self::parse_xml(Self::utf8(data)?),
+            LLSDFormat::JSON => Self::parse_json(Self::utf8(data)?),
+            LLSDFormat::Binary => Self::parse_binary(data),
+            LLSDFormat::Notation => Self::parse_notation_bytes(data),
+        }
+    }
+
+    /// Record where a parsed document came from
+    fn stamp(mut document: LLSDDocument, format: LLSDFormat, byte_length: usize) -> LLSDDocument {
+        document.set_metadata_entry(METADATA_SOURCE_FORMAT, LLSDValue::String(format.name().to_string()));
+        document.set_metadata_entry(METADATA_BYTE_LENGTH, LLSDValue::from_i64(byte_length as i64));
+        document
+    }
+
+    /// Parse LLSD of unknown format, returning the detected format with the
+    /// document.
+    ///
+    /// Detection follows [`LLSDFormat::detect`]: the binary magic, then a
+    /// `<?llsd/...?>` header, then XML, then JSON if the tokens in the first
+    /// KiB look like JSON, and notation otherwise. JSON is chosen from that
+    /// sniff without validating the payload, so malformed JSON-looking input
+    /// fails with a JSON parse error rather than falling back to notation. A
+    /// `<?llsd/binary?>` header may be followed by values without the magic
+    /// number.
+    pub fn parse_auto(data: &[u8]) -> LLSDResult<(LLSDFormat, LLSDDocument)> {
+        let trimmed = LLSDFormat::trim_preamble(data);
+        if trimmed.is_empty() {
+            return Err(LLSDError::EmptyInput);
+        }
+
+        let format = LLSDFormat::detect(data)
+            .ok_or_else(|| LLSDError::custom("Unable to detect LLSD format"))?;
+        let marker_body = LLSDFormat::split_marker(trimmed).map(|(_, body)| body);
+
+        let document = match format {
+            LLSDFormat::Binary => match marker_body {
+                Some(body) if !body.starts_with(&binary::LLSD_BINARY_MAGIC.to_be_bytes()) => {
+                    LLSDBinaryParser::new().without_magic_validation().parse(body)?
+                }
+                Some(body) => Self::parse_binary(body)?,
+                None => Self::parse_binary(data)?,
+            },
+            LLSDFormat::XML => Self::parse_xml(Self::utf8(trimmed)?)?,
+            LLSDFormat::JSON => Self::parse_json(Self::utf8(marker_body.unwrap_or(trimmed))?)?,
+            LLSDFormat::Notation => Self::parse_notation_bytes(marker_body.unwrap_or(trimmed))?,
+        };
+
+        // The whole payload, including any preamble or marker
+        Ok((format, Self::stamp(document, format, data.len())))
+    }
+
+    /// Parse LLSD using the codec named by an HTTP `Content-Type` header
+    pub fn parse_with_content_type(content_type: &str, data: &[u8]) -> LLSDResult<LLSDDocument> {
+        let format = LLSDFormat::from_content_type(content_type).ok_or_else(|| {
+            LLSDError::custom(format!("Unsupported LLSD content type: {}", content_type))
+        })?;
+        Self::parse(format, data)
+    }
+
+    /// Borrow text payload bytes as UTF-8
+    fn utf8(data: &[u8]) -> LLSDResult<&str> {
+        std::str::from_utf8(data).map_err(|e| LLSDError::custom(format!("Invalid UTF-8: {}", e)))
     }
 
     /// Serialize LLSD to XML string
@@ -76,6 +196,23 @@ impl LLSDFactory {
         serializer.serialize(document)
     }
 
+    /// Serialize LLSD to notation string
+    pub fn serialize_notation(document: &LLSDDocument, pretty: bool) -> LLSDResult<String> {
+        let serializer = LLSDNotationSerializer::new().with_pretty_print(pretty);
+        serializer.serialize(document)
+    }
+
+    /// Serialize LLSD in a format chosen at runtime. `pretty` is ignored
+    /// for binary.
+    pub fn serialize(document: &LLSDDocument, format: LLSDFormat, pretty: bool) -> LLSDResult<Vec<u8>> {
+        match format {
+            LLSDFormat::XML => Self::serialize_xml(document, pretty).map(String::into_bytes),
+            LLSDFormat::JSON => Self::serialize_json(document, pretty).map(String::into_bytes),
+            LLSDFormat::Binary => Self::serialize_binary(document),
+            LLSDFormat::Notation => Self::serialize_notation(document, pretty).map(String::into_bytes),
+        }
+    }
+
     /// Create an LLSD document with the given content
     pub fn create(content: LLSDValue) -> LLSDDocument {
         LLSDDocument::new(content)
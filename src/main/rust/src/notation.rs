@@ -0,0 +1,614 @@
+/*!
+ * LLSD Notation Parser and Serializer - Rust Implementation
+ *
+ * Compact text format used by the Second Life viewer, e.g.
+ * `{'name':'Alice','age':i30,'tags':['a','b']}`
+ * Copyright (C) 2024 Linden Lab
+ */
+
+use crate::types::{LLSDValue, LLSDDocument};
+use crate::error::{LLSDError, LLSDResult};
+use crate::utils::{BudgetMeter, ParseBudget};
+use std::collections::HashMap;
+use uuid::Uuid;
+use chrono::{DateTime, SecondsFormat, Utc};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+
+/// LLSD notation parser
+#[derive(Debug)]
+pub struct LLSDNotationParser {
+    max_depth: usize,
+    budget: ParseBudget,
+}
+
+impl Default for LLSDNotationParser {
+    fn default() -> Self {
+        Self { max_depth: 1000, budget: ParseBudget::default() }
+    }
+}
+
+impl LLSDNotationParser {
+    /// Create a new notation parser
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set maximum nesting depth to prevent stack overflow
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Abort with [`LLSDError::ParseAborted`] once parsing exceeds either
+    /// limit of `budget`
+    pub fn with_parse_budget(mut self, budget: ParseBudget) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Parse LLSD from notation text
+    pub fn parse(&self, notation: &str) -> LLSDResult<LLSDDocument> {
+        if notation.trim().is_empty() {
+            return Err(LLSDError::EmptyInput);
+        }
+
+        self.parse_bytes(notation.as_bytes())
+    }
+
+    /// Parse LLSD from notation bytes. Unlike [`parse`](Self::parse) this
+    /// accepts raw `b(n)"..."` binary values that aren't valid UTF-8.
+    pub fn parse_bytes(&self, data: &[u8]) -> LLSDResult<LLSDDocument> {
+        if data.trim_ascii().is_empty() {
+            return Err(LLSDError::EmptyInput);
+        }
+
+        let mut reader = NotationReader {
+            data,
+            pos: 0,
+            max_depth: self.max_depth,
+            meter: BudgetMeter::start(self.budget),
+        };
+        let value = reader.parse_value(0)?;
+        reader.skip_whitespace();
+        if reader.pos < data.len() {
+            return Err(reader.error("Trailing data after value"));
+        }
+
+        Ok(LLSDDocument::new(value))
+    }
+}
+
+/// Recursive descent over notation bytes
+struct NotationReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    max_depth: usize,
+    meter: BudgetMeter,
+}
+
+impl NotationReader<'_> {
+    fn error(&self, message: &str) -> LLSDError {
+        LLSDError::custom(format!("Notation parse error at byte {}: {}", self.pos, message))
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.data.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> LLSDResult<u8> {
+        let byte = self.peek().ok_or(LLSDError::UnexpectedEndOfData)?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    fn expect(&mut self, expected: u8) -> LLSDResult<()> {
+        if self.next()? != expected {
+            self.pos -= 1;
+            return Err(self.error(&format!("Expected '{}'", expected as char)));
+        }
+        Ok(())
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|b| b.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    /// Consume `word` if the input continues with it
+    fn eat(&mut self, word: &[u8]) -> bool {
+        if self.data[self.pos..].starts_with(word) {
+            self.pos += word.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Read the bare token of a number, stopping at a delimiter
+    fn token(&mut self) -> &str {
+        let start = self.pos;
+        while self.peek().is_some_and(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'-' | b'.')) {
+            self.pos += 1;
+        }
+        // Only ASCII was consumed
+        std::str::from_utf8(&self.data[start..self.pos]).unwrap_or_default()
+    }
+
+    fn parse_value(&mut self, depth: usize) -> LLSDResult<LLSDValue> {
+        if depth > self.max_depth {
+            return Err(self.error(&format!("Maximum depth {} exceeded", self.max_depth)));
+        }
+        self.meter.charge_value()?;
+
+        self.skip_whitespace();
+        let start = self.pos;
+        match self.next()? {
+            b'!' => Ok(LLSDValue::Undefined),
+            b'1' => Ok(LLSDValue::Boolean(true)),
+            b'0' => Ok(LLSDValue::Boolean(false)),
+            b't' | b'T' => {
+                let _ = self.eat(b"rue") || self.eat(b"RUE");
+                Ok(LLSDValue::Boolean(true))
+            }
+            b'f' | b'F' => {
+                let _ = self.eat(b"alse") || self.eat(b"ALSE");
+                Ok(LLSDValue::Boolean(false))
+            }
+            b'i' => {
+                let token = self.token().to_string();
+                let value: i64 = token.parse()
+                    .map_err(|_| LLSDError::custom(format!("Invalid integer: {}", token)))?;
+                Ok(LLSDValue::from_i64(value))
+            }
+            b'r' => {
+                let token = self.token().to_string();
+                let value: f64 = token.parse()
+                    .map_err(|_| LLSDError::custom(format!("Invalid real: {}", token)))?;
+                Ok(LLSDValue::Real(value))
+            }
+            b'u' => {
+                let end = (self.pos + 36).min(self.data.len());
+                let text = String::from_utf8_lossy(&self.data[self.pos..end]).into_owned();
+                let uuid = Uuid::parse_str(&text).map_err(|_| LLSDError::InvalidUuid { uuid: text })?;
+                self.pos = end;
+                Ok(LLSDValue::UUID(uuid))
+            }
+            b'\'' | b'"' | b's' => {
+                self.pos = start;
+                Ok(LLSDValue::String(self.parse_string()?))
+            }
+            b'l' => Ok(LLSDValue::URI(self.parse_quoted_string()?)),
+            b'd' => {
+                let text = self.parse_quoted_string()?;
+                let date = DateTime::parse_from_rfc3339(&text)
+                    .map_err(|_| LLSDError::InvalidDate { date: text.clone() })?
+                    .with_timezone(&Utc);
+                Ok(LLSDValue::Date(date))
+            }
+            b'b' => self.parse_binary(),
+            b'[' => self.parse_array(depth),
+            b'{' => self.parse_map(depth),
+            other => {
+                self.pos = start;
+                Err(self.error(&format!("Unexpected character '{}'", other as char)))
+            }
+        }
+    }
+
+    /// Parse a `'...'`, `"..."` or sized `s(n)"..."` string
+    fn parse_string(&mut self) -> LLSDResult<String> {
+        if self.peek() == Some(b's') {
+            self.pos += 1;
+            let raw = self.parse_sized()?;
+            return String::from_utf8(raw).map_err(LLSDError::from);
+        }
+        self.parse_quoted_string()
+    }
+
+    /// Parse a quoted string with backslash escapes
+    fn parse_quoted_string(&mut self) -> LLSDResult<String> {
+        let raw = self.parse_quoted()?;
+        String::from_utf8(raw).map_err(LLSDError::from)
+    }
+
+    fn parse_quoted(&mut self) -> LLSDResult<Vec<u8>> {
+        let quote = self.next()?;
+        if quote != b'\'' && quote != b'"' {
+            self.pos -= 1;
+            return Err(self.error("Expected quoted string"));
+        }
+
+        let mut out = Vec::new();
+        loop {
+            match self.next()? {
+                b'\\' => {
+                    let escaped = match self.next()? {
+                        b'a' => 0x07,
+                        b'b' => 0x08,
+                        b'f' => 0x0C,
+                        b'n' => b'\n',
+                        b'r' => b'\r',
+                        b't' => b'\t',
+                        b'v' => 0x0B,
+                        b'x' => {
+                            let end = (self.pos + 2).min(self.data.len());
+                            let hex = std::str::from_utf8(&self.data[self.pos..end]).unwrap_or_default();
+                            let byte = u8::from_str_radix(hex, 16)
+                                .map_err(|_| self.error("Invalid \\x escape"))?;
+                            self.pos = end;
+                            byte
+                        }
+                        other => other,
+                    };
+                    out.push(escaped);
+                }
+                byte if byte == quote => {
+                    self.meter.charge(out.len())?;
+                    return Ok(out);
+                }
+                byte => out.push(byte),
+            }
+        }
+    }
+
+    /// Parse `(n)"..."`: a length and exactly that many raw bytes
+    fn parse_sized(&mut self) -> LLSDResult<Vec<u8>> {
+        self.expect(b'(')?;
+        let token = self.token().to_string();
+        let len: usize = token.parse()
+            .map_err(|_| self.error(&format!("Invalid size: {}", token)))?;
+        self.expect(b')')?;
+
+        let quote = self.next()?;
+        if quote != b'\'' && quote != b'"' {
+            self.pos -= 1;
+            return Err(self.error("Expected quote after size"));
+        }
+        if self.data.len() - self.pos < len {
+            return Err(LLSDError::UnexpectedEndOfData);
+        }
+        self.meter.charge(len)?;
+        let raw = self.data[self.pos..self.pos + len].to_vec();
+        self.pos += len;
+        self.expect(quote)?;
+        Ok(raw)
+    }
+
+    /// Parse `b64"..."`, `b16"..."` or sized `b(n)"..."`
+    fn parse_binary(&mut self) -> LLSDResult<LLSDValue> {
+        if self.peek() == Some(b'(') {
+            return Ok(LLSDValue::Binary(self.parse_sized()?));
+        }
+        if self.eat(b"64") {
+            let text = self.parse_quoted_string()?;
+            let cleaned: String = text.chars().filter(|c| !c.is_ascii_whitespace()).collect();
+            return Ok(LLSDValue::Binary(BASE64.decode(cleaned)?));
+        }
+        if self.eat(b"16") {
+            let text = self.parse_quoted_string()?;
+            let digits: Vec<u8> = text.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+            if digits.len() % 2 == 1 {
+                return Err(self.error("Odd number of base16 digits"));
+            }
+            let bytes = digits
+                .chunks(2)
+                .map(|pair| {
+                    std::str::from_utf8(pair).ok().and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                })
+                .collect::<Option<Vec<u8>>>()
+                .ok_or_else(|| self.error("Invalid base16 digit"))?;
+            return Ok(LLSDValue::Binary(bytes));
+        }
+        Err(self.error("Expected b64, b16 or sized binary"))
+    }
+
+    fn parse_array(&mut self, depth: usize) -> LLSDResult<LLSDValue> {
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(LLSDValue::Array(items));
+        }
+
+        loop {
+            items.push(self.parse_value(depth + 1)?);
+            self.skip_whitespace();
+            match self.next()? {
+                b',' => continue,
+                b']' => return Ok(LLSDValue::Array(items)),
+                _ => {
+                    self.pos -= 1;
+                    return Err(self.error("Expected ',' or ']' in array"));
+                }
+            }
+        }
+    }
+
+    fn parse_map(&mut self, depth: usize) -> LLSDResult<LLSDValue> {
+        let mut map = HashMap::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(LLSDValue::Map(map));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key = self.parse_string()?;
+            self.skip_whitespace();
+            self.expect(b':')?;
+            let value = self.parse_value(depth + 1)?;
+            map.insert(key, value);
+
+            self.skip_whitespace();
+            match self.next()? {
+                b',' => continue,
+                b'}' => return Ok(LLSDValue::Map(map)),
+                _ => {
+                    self.pos -= 1;
+                    return Err(self.error("Expected ',' or '}' in map"));
+                }
+            }
+        }
+    }
+}
+
+/// LLSD notation serializer
+#[derive(Debug)]
+pub struct LLSDNotationSerializer {
+    pretty_print: bool,
+    indent_size: usize,
+    sorted_keys: bool,
+}
+
+impl Default for LLSDNotationSerializer {
+    fn default() -> Self {
+        Self {
+            pretty_print: false,
+            indent_size: 2,
+            sorted_keys: false,
+        }
+    }
+}
+
+impl LLSDNotationSerializer {
+    /// Create a new notation serializer
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Put each array item and map entry on its own indented line
+    pub fn with_pretty_print(mut self, pretty: bool) -> Self {
+        self.pretty_print = pretty;
+        self
+    }
+
+    /// Set indent size for pretty printing
+    pub fn with_indent_size(mut self, size: usize) -> Self {
+        self.indent_size = size;
+        self
+    }
+
+    /// Emit map keys in lexicographic order, so equal documents always
+    /// serialize to identical text
+    pub fn with_sorted_keys(mut self, sorted: bool) -> Self {
+        self.sorted_keys = sorted;
+        self
+    }
+
+    /// Serialize LLSD to notation string
+    pub fn serialize(&self, document: &LLSDDocument) -> LLSDResult<String> {
+        let mut output = String::new();
+        self.write_value(&mut output, document.content(), 0);
+        Ok(output)
+    }
+
+    fn write_value(&self, out: &mut String, value: &LLSDValue, depth: usize) {
+        match value {
+            LLSDValue::Undefined => out.push('!'),
+            LLSDValue::Boolean(b) => out.push_str(if *b { "true" } else { "false" }),
+            LLSDValue::Integer(i) => out.push_str(&format!("i{}", i)),
+            LLSDValue::Integer64(i) => out.push_str(&format!("i{}", i)),
+            LLSDValue::Real(r) => out.push_str(&format!("r{:?}", r)),
+            LLSDValue::String(s) => write_quoted(out, '\'', s),
+            LLSDValue::UUID(u) => out.push_str(&format!("u{}", u)),
+            LLSDValue::Date(d) => {
+                out.push('d');
+                write_quoted(out, '"', &d.to_rfc3339_opts(SecondsFormat::AutoSi, true));
+            }
+            LLSDValue::URI(u) => {
+                out.push('l');
+                write_quoted(out, '"', u);
+            }
+            LLSDValue::Binary(b) => {
+                out.push_str("b64");
+                write_quoted(out, '"', &BASE64.encode(b));
+            }
+            LLSDValue::Array(items) => {
+                out.push('[');
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        out.push(',');
+                    }
+                    self.write_indent(out, depth + 1);
+                    self.write_value(out, item, depth + 1);
+                }
+                if !items.is_empty() {
+                    self.write_indent(out, depth);
+                }
+                out.push(']');
+            }
+            LLSDValue::Map(map) => {
+                let mut entries: Vec<(&String, &LLSDValue)> = map.iter().collect();
+                if self.sorted_keys {
+                    entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+                }
+
+                out.push('{');
+                for (index, (key, val)) in entries.into_iter().enumerate() {
+                    if index > 0 {
+                        out.push(',');
+                    }
+                    self.write_indent(out, depth + 1);
+                    write_quoted(out, '\'', key);
+                    out.push(':');
+                    self.write_value(out, val, depth + 1);
+                }
+                if !map.is_empty() {
+                    self.write_indent(out, depth);
+                }
+                out.push('}');
+            }
+        }
+    }
+
+    /// Start a new line at `depth` when pretty printing
+    fn write_indent(&self, out: &mut String, depth: usize) {
+        if self.pretty_print {
+            out.push('\n');
+            out.push_str(&" ".repeat(depth * self.indent_size));
+        }
+    }
+}
+
+/// Write `text` between `quote`s, escaping the quote, backslashes and
+/// control characters
+fn write_quoted(out: &mut String, quote: char, text: &str) {
+    out.push(quote);
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c == quote => {
+                out.push('\\');
+                out.push(c);
+            }
+            c if c.is_ascii_control() => out.push_str(&format!("\\x{:02x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push(quote);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::uuid;
+
+    #[test]
+    fn test_parse_scalars() {
+        let parser = LLSDNotationParser::new();
+        let parse = |text: &str| parser.parse(text).unwrap().content().clone();
+
+        assert_eq!(parse("!"), LLSDValue::Undefined);
+        for text in ["1", "t", "T", "true", "TRUE"] {
+            assert_eq!(parse(text), LLSDValue::Boolean(true));
+        }
+        for text in ["0", "f", "F", "false", "FALSE"] {
+            assert_eq!(parse(text), LLSDValue::Boolean(false));
+        }
+        assert_eq!(parse("i-42"), LLSDValue::Integer(-42));
+        assert_eq!(parse("i5000000000"), LLSDValue::Integer64(5_000_000_000));
+        assert_eq!(parse("r1.5e3"), LLSDValue::Real(1500.0));
+        assert!(matches!(parse("rnan"), LLSDValue::Real(r) if r.is_nan()));
+        assert_eq!(
+            parse("u550e8400-e29b-41d4-a716-446655440000"),
+            LLSDValue::UUID(uuid!("550e8400-e29b-41d4-a716-446655440000"))
+        );
+        assert_eq!(parse("'it\\'s'"), LLSDValue::String("it's".to_string()));
+        assert_eq!(parse("\"tab\\there\\x21\""), LLSDValue::String("tab\there!".to_string()));
+        assert_eq!(parse("s(5)\"a'b\"c\""), LLSDValue::String("a'b\"c".to_string()));
+        assert_eq!(parse("l\"http://example.com/\""), LLSDValue::URI("http://example.com/".to_string()));
+        assert_eq!(
+            parse("d\"2021-01-01T00:00:00Z\""),
+            LLSDValue::Date(DateTime::parse_from_rfc3339("2021-01-01T00:00:00Z").unwrap().with_timezone(&Utc))
+        );
+        assert_eq!(parse("b64\"AAFC\""), LLSDValue::Binary(vec![0x00, 0x01, 0x42]));
+        assert_eq!(parse("b16\"00FF42\""), LLSDValue::Binary(vec![0x00, 0xFF, 0x42]));
+    }
+
+    #[test]
+    fn test_parse_containers() {
+        let text = "{ 'name' : 'Alice', \"age\":i30,\n 'tags':[ 'a' , 'b' ], 'none':{} , 'empty':[] }";
+        let doc = LLSDNotationParser::new().parse(text).unwrap();
+
+        assert_eq!(doc.content().get_path("name"), Some(&LLSDValue::String("Alice".to_string())));
+        assert_eq!(doc.content().get_path("age"), Some(&LLSDValue::Integer(30)));
+        assert_eq!(doc.content().get_path("tags.1"), Some(&LLSDValue::String("b".to_string())));
+        assert_eq!(doc.content().get_path("none"), Some(&LLSDValue::Map(HashMap::new())));
+        assert_eq!(doc.content().get_path("empty"), Some(&LLSDValue::Array(Vec::new())));
+    }
+
+    #[test]
+    fn test_parse_sized_binary_bytes() {
+        let mut data = b"[b(3)\"".to_vec();
+        data.extend_from_slice(&[0xFF, 0x00, b'"']);
+        data.extend_from_slice(b"\"]");
+
+        let doc = LLSDNotationParser::new().parse_bytes(&data).unwrap();
+        assert_eq!(doc.content(), &LLSDValue::Array(vec![LLSDValue::Binary(vec![0xFF, 0x00, b'"'])]));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let parser = LLSDNotationParser::new();
+
+        assert!(matches!(parser.parse("  \n"), Err(LLSDError::EmptyInput)));
+        assert!(matches!(parser.parse_bytes(b""), Err(LLSDError::EmptyInput)));
+        assert!(matches!(parser.parse_bytes(b"  \n"), Err(LLSDError::EmptyInput)));
+        assert!(matches!(parser.parse("'open"), Err(LLSDError::UnexpectedEndOfData)));
+        assert!(parser.parse("[i1 i2]").is_err());
+        assert!(parser.parse("{'a' i1}").is_err());
+        assert!(parser.parse("i1 i2").is_err());
+        assert!(parser.parse("ix").is_err());
+        assert!(parser.parse("unot-a-uuid").is_err());
+        assert!(parser.parse("s(10)\"short\"").is_err());
+        assert!(parser.parse("?").is_err());
+
+        let deep = "[".repeat(20) + &"]".repeat(20);
+        assert!(parser.parse(&deep).is_ok());
+        assert!(LLSDNotationParser::new().with_max_depth(10).parse(&deep).is_err());
+    }
+
+    #[test]
+    fn test_serialize() {
+        let mut map = HashMap::new();
+        map.insert("b".to_string(), LLSDValue::Array(vec![LLSDValue::Integer(1), LLSDValue::Boolean(true)]));
+        map.insert("a".to_string(), LLSDValue::String("it's\n".to_string()));
+        let document = LLSDDocument::new(LLSDValue::Map(map));
+
+        let compact = LLSDNotationSerializer::new().with_sorted_keys(true).serialize(&document).unwrap();
+        assert_eq!(compact, "{'a':'it\\'s\\n','b':[i1,true]}");
+
+        let pretty = LLSDNotationSerializer::new()
+            .with_sorted_keys(true)
+            .with_pretty_print(true)
+            .serialize(&document)
+            .unwrap();
+        assert_eq!(pretty, "{\n  'a':'it\\'s\\n',\n  'b':[\n    i1,\n    true\n  ]\n}");
+
+        for text in [compact, pretty] {
+            assert_eq!(LLSDNotationParser::new().parse(&text).unwrap().content(), document.content());
+        }
+    }
+
+    #[test]
+    fn test_parse_budget() {
+        // Two bytes of input per value, far more per parsed value
+        let undefs = format!("[{}!]", "!,".repeat(100_000));
+        let parser = LLSDNotationParser::new()
+            .with_parse_budget(ParseBudget { max_bytes: Some(64 * 1024), max_duration: None });
+        assert!(matches!(parser.parse(&undefs), Err(LLSDError::ParseAborted { .. })));
+
+        let parser = LLSDNotationParser::new().with_parse_budget(ParseBudget {
+            max_bytes: Some(64 * 1024),
+            max_duration: Some(std::time::Duration::from_secs(30)),
+        });
+        let doc = parser.parse("{'name':'Alice','tags':['a','b'],'data':b(3)\"abc\"}").unwrap();
+        assert_eq!(doc.content().get_path("tags.1"), Some(&LLSDValue::String("b".to_string())));
+    }
+}
@@ -0,0 +1,310 @@
+/*!
+ * LLSD Schema Validation - Rust Implementation
+ *
+ * Declarative structure checks and a validating document builder
+ * Copyright (C) 2024 Linden Lab
+ */
+
+use crate::types::{LLSDValue, LLSDType, LLSDDocument};
+use crate::error::{LLSDError, LLSDResult};
+use std::collections::HashMap;
+
+/// Expected type and presence of a single field, addressed by dotted path
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldSchema {
+    path: String,
+    field_type: Option<LLSDType>,
+    required: bool,
+}
+
+impl FieldSchema {
+    /// Dotted path of the field from the document root
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Expected type, if constrained
+    pub fn field_type(&self) -> Option<LLSDType> {
+        self.field_type
+    }
+
+    /// Whether the field must be present
+    pub fn is_required(&self) -> bool {
+        self.required
+    }
+}
+
+/// Schema describing the expected shape of an LLSD value
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LLSDSchema {
+    root_type: Option<LLSDType>,
+    fields: Vec<FieldSchema>,
+}
+
+impl LLSDSchema {
+    /// Create an empty schema that accepts any value
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require the root to be a map
+    pub fn require_map(mut self) -> Self {
+        self.root_type = Some(LLSDType::Map);
+        self
+    }
+
+    /// Require the root to be an array
+    pub fn require_array(mut self) -> Self {
+        self.root_type = Some(LLSDType::Array);
+        self
+    }
+
+    /// Require a field at `path`, optionally of a specific type
+    pub fn require_field(mut self, path: &str, field_type: Option<LLSDType>) -> Self {
+        self.fields.push(FieldSchema {
+            path: path.to_string(),
+            field_type,
+            required: true,
+        });
+        self
+    }
+
+    /// Allow a field at `path`, checking its type only when present
+    pub fn optional_field(mut self, path: &str, field_type: LLSDType) -> Self {
+        self.fields.push(FieldSchema {
+            path: path.to_string(),
+            field_type: Some(field_type),
+            required: false,
+        });
+        self
+    }
+
+    /// Field constraints in declaration order
+    pub fn fields(&self) -> &[FieldSchema] {
+        &self.fields
+    }
+
+    /// Collect every way `value` fails to conform; empty if it conforms
+    pub fn check(&self, value: &LLSDValue) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        if let Some(root_type) = self.root_type {
+            if value.get_type() != root_type {
+                errors.push(format!("Expected {:?} root but got {:?}", root_type, value.get_type()));
+                return errors;
+            }
+        }
+
+        for field in &self.fields {
+            match value.get_path(&field.path) {
+                None if field.required => errors.push(format!("Missing required field: {}", field.path)),
+                None => {}
+                Some(found) => {
+                    if let Some(expected) = field.field_type {
+                        if found.get_type() != expected {
+                            errors.push(format!(
+                                "Field {} expected {:?} but got {:?}",
+                                field.path,
+                                expected,
+                                found.get_type()
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+
+        errors
+    }
+
+    /// Copy `value` with each typed field converted to its schema type by
+    /// [`LLSDValue::coerce_to`], for input whose values all arrive as
+    /// strings.
+    ///
+    /// Fails with a single validation error listing every missing required
+    /// field and every field that cannot be converted. The root type is
+    /// checked, not converted.
+    pub fn coerce(&self, value: &LLSDValue) -> LLSDResult<LLSDValue> {
+        if let Some(root_type) = self.root_type {
+            if value.get_type() != root_type {
+                return Err(LLSDError::validation_error(format!(
+                    "Expected {:?} root but got {:?}",
+                    root_type,
+                    value.get_type()
+                )));
+            }
+        }
+
+        let mut coerced = value.clone();
+        let mut errors = Vec::new();
+        for field in &self.fields {
+            match (value.get_path(&field.path), field.field_type) {
+                (None, _) if field.required => errors.push(format!("Missing required field: {}", field.path)),
+                (Some(found), Some(expected)) => match found.coerce_to(expected) {
+                    Ok(converted) => {
+                        coerced.set_path(&field.path, converted);
+                    }
+                    Err(err) => errors.push(format!("Field {}: {}", field.path, err)),
+                },
+                _ => {}
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(coerced)
+        } else {
+            Err(LLSDError::validation_error(errors.join("; ")))
+        }
+    }
+
+    /// Validate `value`, reporting all problems in a single validation error
+    pub fn validate(&self, value: &LLSDValue) -> LLSDResult<()> {
+        let errors = self.check(value);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(LLSDError::validation_error(errors.join("; ")))
+        }
+    }
+}
+
+/// Builds an `LLSDDocument` by dotted path and validates it against a schema
+#[derive(Debug, Clone)]
+pub struct LLSDDocumentBuilder {
+    schema: LLSDSchema,
+    content: LLSDValue,
+    errors: Vec<String>,
+}
+
+impl LLSDDocumentBuilder {
+    /// Start building a map document checked against `schema`
+    pub fn new(schema: LLSDSchema) -> Self {
+        Self {
+            schema,
+            content: LLSDValue::Map(HashMap::new()),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Set the value at `path`, creating intermediate maps as needed
+    pub fn set_path<V: Into<LLSDValue>>(mut self, path: &str, value: V) -> Self {
+        if !self.content.set_path_create(path, value.into()) {
+            self.errors.push(format!("Cannot set path: {}", path));
+        }
+        self
+    }
+
+    /// Finish the document, failing if any path could not be set or the
+    /// content does not conform to the schema
+    pub fn build(self) -> LLSDResult<LLSDDocument> {
+        let mut errors = self.errors;
+        errors.extend(self.schema.check(&self.content));
+
+        if errors.is_empty() {
+            Ok(LLSDDocument::new(self.content))
+        } else {
+            Err(LLSDError::validation_error(errors.join("; ")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user_schema() -> LLSDSchema {
+        LLSDSchema::new()
+            .require_map()
+            .require_field("user.name", Some(LLSDType::String))
+            .require_field("user.age", Some(LLSDType::Integer))
+            .optional_field("user.premium", LLSDType::Boolean)
+    }
+
+    #[test]
+    fn test_builder_satisfies_schema() {
+        let document = LLSDDocumentBuilder::new(user_schema())
+            .set_path("user.name", "Alice")
+            .set_path("user.age", 30)
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            document.content().get_path("user.name"),
+            Some(&LLSDValue::String("Alice".to_string()))
+        );
+        assert_eq!(document.content().get_path("user.age"), Some(&LLSDValue::Integer(30)));
+    }
+
+    #[test]
+    fn test_builder_reports_missing_fields() {
+        let result = LLSDDocumentBuilder::new(user_schema())
+            .set_path("user.premium", "yes")
+            .build();
+
+        match result {
+            Err(LLSDError::ValidationError { message }) => {
+                assert!(message.contains("Missing required field: user.name"));
+                assert!(message.contains("Missing required field: user.age"));
+                assert!(message.contains("user.premium expected Boolean"));
+            }
+            other => panic!("Expected validation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_builder_reports_unsettable_path() {
+        let result = LLSDDocumentBuilder::new(LLSDSchema::new())
+            .set_path("name", "Alice")
+            .set_path("name.first", "Alice")
+            .build();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_schema_root_type() {
+        let schema = LLSDSchema::new().require_array();
+        assert!(schema.validate(&LLSDValue::Array(Vec::new())).is_ok());
+        assert_eq!(schema.check(&LLSDValue::Integer(1)).len(), 1);
+    }
+
+    #[test]
+    fn test_schema_coerce() {
+        let schema = LLSDSchema::new()
+            .require_map()
+            .require_field("age", Some(LLSDType::Integer))
+            .require_field("active", Some(LLSDType::Boolean))
+            .optional_field("score", LLSDType::Real);
+        let form = |entries: &[(&str, &str)]| {
+            LLSDValue::Map(
+                entries
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), LLSDValue::String(v.to_string())))
+                    .collect(),
+            )
+        };
+
+        let typed = schema.coerce(&form(&[("age", "30"), ("active", "true"), ("note", "kept")])).unwrap();
+        let mut expected = HashMap::new();
+        expected.insert("age".to_string(), LLSDValue::Integer(30));
+        expected.insert("active".to_string(), LLSDValue::Boolean(true));
+        expected.insert("note".to_string(), LLSDValue::String("kept".to_string()));
+        assert_eq!(typed, LLSDValue::Map(expected));
+        assert!(schema.validate(&typed).is_ok());
+
+        let typed = schema.coerce(&form(&[("age", " 7 "), ("active", "0"), ("score", "2.5")])).unwrap();
+        assert_eq!(typed.get_path("score"), Some(&LLSDValue::Real(2.5)));
+        assert_eq!(typed.get_path("active"), Some(&LLSDValue::Boolean(false)));
+
+        match schema.coerce(&form(&[("age", "thirty"), ("score", "high")])) {
+            Err(LLSDError::ValidationError { message }) => {
+                assert!(message.contains("Field age"), "{}", message);
+                assert!(message.contains("'thirty'"), "{}", message);
+                assert!(message.contains("Missing required field: active"), "{}", message);
+                assert!(message.contains("Field score"), "{}", message);
+            }
+            other => panic!("Expected validation error, got {:?}", other),
+        }
+
+        assert!(schema.coerce(&LLSDValue::Array(Vec::new())).is_err());
+    }
+}
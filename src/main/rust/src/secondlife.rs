@@ -5,15 +5,259 @@
  * Copyright (C) 2024 Linden Lab
  */
 
-use crate::types::LLSDValue;
 use crate::error::{LLSDError, LLSDResult};
+use crate::types::LLSDValue;
 use std::collections::HashMap;
+use std::net::Ipv4Addr;
 use uuid::Uuid;
-use chrono::{DateTime, Utc};
+use chrono::Utc;
+
+/// Permission bit allowing the object to be moved
+pub const PERM_MOVE: i32 = 0x0008_0000;
+/// Permission bit allowing the object to be modified
+pub const PERM_MODIFY: i32 = 0x0000_4000;
+/// Permission bit allowing the object to be copied
+pub const PERM_COPY: i32 = 0x0000_8000;
+/// Permission bit allowing the object to be given away or sold
+pub const PERM_TRANSFER: i32 = 0x0000_2000;
+/// All permission bits
+pub const PERM_ALL: i32 = 0x7FFF_FFFF;
+/// No permission bits
+pub const PERM_NONE: i32 = 0;
+
+/// Teleport flag set when the teleport was started by accepting a lure
+pub const TELEPORT_FLAGS_VIA_LURE: u32 = 1 << 2;
+
+/// Mask keys expected in an object permissions map
+pub const PERMISSION_MASK_KEYS: [&str; 5] = [
+    "base_mask",
+    "owner_mask",
+    "group_mask",
+    "everyone_mask",
+    "next_owner_mask",
+];
+
+/// The `login-flags` block of a login response
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LoginFlags {
+    pub stipend_since_login: bool,
+    pub ever_logged_in: bool,
+    pub gendered: bool,
+    pub daylight_savings: bool,
+}
+
+impl LoginFlags {
+    const FIELDS: [&'static str; 4] = ["stipend_since_login", "ever_logged_in", "gendered", "daylight_savings"];
+
+    fn values(&self) -> [bool; 4] {
+        [self.stipend_since_login, self.ever_logged_in, self.gendered, self.daylight_savings]
+    }
+
+    /// Encode as the login server does: a one-element array holding a map
+    /// of `"Y"`/`"N"` strings
+    pub fn to_llsd(&self) -> LLSDValue {
+        let block = Self::FIELDS
+            .iter()
+            .zip(self.values())
+            .map(|(field, value)| (field.to_string(), yes_no(value)))
+            .collect();
+        SecondLifeLLSDUtils::wrap_login_block(block)
+    }
+
+    /// Decode a `login-flags` block, wrapped in its array or not. Missing
+    /// flags are false; flags of the wrong type are an error.
+    pub fn from_llsd(value: &LLSDValue) -> LLSDResult<Self> {
+        let block = SecondLifeLLSDUtils::unwrap_login_block(value)
+            .ok_or_else(|| LLSDError::type_mismatch("login block".to_string(), value.type_name().to_string()))?;
+
+        let mut values = [false; 4];
+        for (field, value) in Self::FIELDS.iter().zip(values.iter_mut()) {
+            if let Some(flag) = block.get(*field) {
+                *value = parse_yes_no(flag).ok_or_else(|| {
+                    LLSDError::type_mismatch(format!("Y/N for {}", field), flag.type_name().to_string())
+                })?;
+            }
+        }
+
+        let [stipend_since_login, ever_logged_in, gendered, daylight_savings] = values;
+        Ok(Self { stipend_since_login, ever_logged_in, gendered, daylight_savings })
+    }
+}
+
+/// Login responses write booleans as `"Y"` or `"N"`
+fn yes_no(value: bool) -> LLSDValue {
+    LLSDValue::String(if value { "Y" } else { "N" }.to_string())
+}
+
+fn parse_yes_no(value: &LLSDValue) -> Option<bool> {
+    match value {
+        LLSDValue::String(s) if s.eq_ignore_ascii_case("y") => Some(true),
+        LLSDValue::String(s) if s.eq_ignore_ascii_case("n") => Some(false),
+        LLSDValue::Boolean(b) => Some(*b),
+        _ => None,
+    }
+}
+
+/// Read a `U32` field, sent as 4 bytes of big-endian binary or as an
+/// integer holding the same bits
+fn u32_field(block: &HashMap<String, LLSDValue>, field: &str) -> LLSDResult<u32> {
+    match required(block, field)? {
+        LLSDValue::Binary(bytes) => <[u8; 4]>::try_from(bytes.as_slice())
+            .map(u32::from_be_bytes)
+            .map_err(|_| LLSDError::type_mismatch(format!("4 bytes for {}", field), format!("{} bytes", bytes.len()))),
+        LLSDValue::Integer(bits) => Ok(*bits as u32),
+        LLSDValue::Integer64(value) => u32::try_from(*value)
+            .map_err(|_| LLSDError::type_mismatch(format!("u32 for {}", field), value.to_string())),
+        other => Err(LLSDError::type_mismatch(format!("u32 for {}", field), other.type_name().to_string())),
+    }
+}
+
+fn required<'a>(block: &'a HashMap<String, LLSDValue>, field: &str) -> LLSDResult<&'a LLSDValue> {
+    block.get(field).ok_or_else(|| LLSDError::missing_field(field))
+}
+
+/// Origin of a chat message (`CHAT_SOURCE_*` in the viewer)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChatSourceType {
+    System,
+    Agent,
+    Object,
+    Unknown,
+}
+
+impl ChatSourceType {
+    /// Integer code used on the wire
+    pub fn to_i32(self) -> i32 {
+        match self {
+            ChatSourceType::System => 0,
+            ChatSourceType::Agent => 1,
+            ChatSourceType::Object => 2,
+            ChatSourceType::Unknown => 3,
+        }
+    }
+
+    /// Convert a wire code, returning `None` for unrecognised values
+    pub fn from_i32(value: i32) -> Option<Self> {
+        match value {
+            0 => Some(ChatSourceType::System),
+            1 => Some(ChatSourceType::Agent),
+            2 => Some(ChatSourceType::Object),
+            3 => Some(ChatSourceType::Unknown),
+            _ => None,
+        }
+    }
+}
+
+/// Kind of chat message (`CHAT_TYPE_*` in the viewer)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChatType {
+    Whisper,
+    Normal,
+    Shout,
+    Start,
+    Stop,
+    DebugMsg,
+    Region,
+    Owner,
+    Direct,
+}
+
+impl ChatType {
+    /// Integer code used on the wire (3 is unused)
+    pub fn to_i32(self) -> i32 {
+        match self {
+            ChatType::Whisper => 0,
+            ChatType::Normal => 1,
+            ChatType::Shout => 2,
+            ChatType::Start => 4,
+            ChatType::Stop => 5,
+            ChatType::DebugMsg => 6,
+            ChatType::Region => 7,
+            ChatType::Owner => 8,
+            ChatType::Direct => 9,
+        }
+    }
+
+    /// Convert a wire code, returning `None` for unrecognised values
+    pub fn from_i32(value: i32) -> Option<Self> {
+        match value {
+            0 => Some(ChatType::Whisper),
+            1 => Some(ChatType::Normal),
+            2 => Some(ChatType::Shout),
+            4 => Some(ChatType::Start),
+            5 => Some(ChatType::Stop),
+            6 => Some(ChatType::DebugMsg),
+            7 => Some(ChatType::Region),
+            8 => Some(ChatType::Owner),
+            9 => Some(ChatType::Direct),
+            _ => None,
+        }
+    }
+}
 
 /// Second Life specific LLSD utilities
 pub struct SecondLifeLLSDUtils;
 
+/// The `Info` block of a `TeleportFinish` event queue message, from
+/// [`SecondLifeLLSDUtils::parse_teleport_finish`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TeleportFinish {
+    pub agent_id: Uuid,
+    pub location_id: u32,
+    pub sim_ip: Ipv4Addr,
+    pub sim_port: u16,
+    pub region_handle: u64,
+    /// Seed capability URL of the destination region
+    pub seed_capability: String,
+    /// Maturity rating of the destination region (`SIM_ACCESS_*`)
+    pub sim_access: i32,
+    pub teleport_flags: u32,
+}
+
+/// Contents of a `SimulatorFeatures` capability response, from
+/// [`SecondLifeLLSDUtils::parse_simulator_features`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SimulatorFeatures {
+    /// `MeshEnabled`
+    pub mesh_enabled: bool,
+    /// `PhysicsMaterialsSupported`
+    pub physics_materials_supported: bool,
+    /// `MaxAgentAttachments`
+    pub max_agent_attachments: Option<i32>,
+    /// `GridServices`, service names to their settings
+    pub grid_services: HashMap<String, LLSDValue>,
+    /// `OpenSimExtras`, sent only by OpenSimulator regions
+    pub open_sim_extras: Option<HashMap<String, LLSDValue>>,
+    /// Every other entry, and typed entries whose value had the wrong type
+    pub extras: HashMap<String, LLSDValue>,
+}
+
+impl SimulatorFeatures {
+    /// Whether the region is running OpenSimulator
+    pub fn is_opensim(&self) -> bool {
+        self.open_sim_extras.is_some()
+    }
+
+    /// `OpenSimExtras.GridName`
+    pub fn grid_name(&self) -> Option<&str> {
+        self.open_sim_extra("GridName").and_then(LLSDValue::as_string)
+    }
+
+    /// `OpenSimExtras.GridURL`
+    pub fn grid_url(&self) -> Option<&str> {
+        self.open_sim_extra("GridURL").and_then(|value| value.as_string().or_else(|| value.as_uri()))
+    }
+
+    /// `OpenSimExtras.ExportSupported`, false when absent
+    pub fn export_supported(&self) -> bool {
+        self.open_sim_extra("ExportSupported").and_then(LLSDValue::as_boolean).unwrap_or(false)
+    }
+
+    fn open_sim_extra(&self, key: &str) -> Option<&LLSDValue> {
+        self.open_sim_extras.as_ref()?.get(key)
+    }
+}
+
 impl SecondLifeLLSDUtils {
     /// Create a Second Life compatible LLSD response structure
     pub fn create_sl_response(success: bool, message: &str, data: Option<LLSDValue>) -> HashMap<String, LLSDValue> {
@@ -85,13 +329,54 @@ impl SecondLifeLLSDUtils {
         properties
     }
 
-    /// Create asset upload request
+    /// Check that a permissions map has every mask in [`PERMISSION_MASK_KEYS`] as an integer
+    pub fn validate_permissions(permissions: &HashMap<String, LLSDValue>) -> ValidationResult {
+        let mut result = ValidationResult::new();
+
+        for key in PERMISSION_MASK_KEYS {
+            match permissions.get(key) {
+                Some(LLSDValue::Integer(_)) => {}
+                Some(other) => result.add_error(format!(
+                    "Permission {} must be an integer, got {}",
+                    key,
+                    other.type_name()
+                )),
+                None => result.add_error(format!("Missing permission mask: {}", key)),
+            }
+        }
+
+        result
+    }
+
+    /// Fill in any absent permission masks with viewer defaults for a new object:
+    /// full base and owner rights, nothing for group and everyone, and
+    /// move/transfer for the next owner. Masks already present are kept.
+    pub fn with_default_permissions(mut permissions: HashMap<String, LLSDValue>) -> HashMap<String, LLSDValue> {
+        let defaults = [
+            ("base_mask", PERM_ALL),
+            ("owner_mask", PERM_ALL),
+            ("group_mask", PERM_NONE),
+            ("everyone_mask", PERM_NONE),
+            ("next_owner_mask", PERM_MOVE | PERM_TRANSFER),
+        ];
+
+        for (key, mask) in defaults {
+            permissions
+                .entry(key.to_string())
+                .or_insert(LLSDValue::Integer(mask));
+        }
+
+        permissions
+    }
+
+    /// Create asset upload request filed into the inventory folder `folder_id`
     pub fn create_asset_upload_request(
         asset_type: &str,
         name: &str,
         description: &str,
         data: Vec<u8>,
         expected_upload_cost: i32,
+        folder_id: Uuid,
     ) -> HashMap<String, LLSDValue> {
         let mut request = HashMap::new();
         
@@ -104,7 +389,7 @@ impl SecondLifeLLSDUtils {
         asset_resources.insert("asset_data".to_string(), LLSDValue::Binary(data));
         request.insert("asset_resources".to_string(), LLSDValue::Map(asset_resources));
         
-        request.insert("folder_id".to_string(), LLSDValue::UUID(Uuid::new_v4()));
+        request.insert("folder_id".to_string(), LLSDValue::UUID(folder_id));
         request.insert("inventory_type".to_string(), LLSDValue::Integer(Self::asset_type_to_inventory_type(asset_type)));
         request.insert("expected_upload_cost".to_string(), LLSDValue::Integer(expected_upload_cost));
         
@@ -177,7 +462,27 @@ impl SecondLifeLLSDUtils {
         chat
     }
 
+    /// Create chat message structure from typed source and chat kinds
+    pub fn create_chat_message_typed(
+        from_name: &str,
+        source_type: ChatSourceType,
+        chat_type: ChatType,
+        message: &str,
+        position: Option<[f64; 3]>,
+        owner_id: Option<Uuid>,
+    ) -> HashMap<String, LLSDValue> {
+        Self::create_chat_message(
+            from_name,
+            source_type.to_i32(),
+            chat_type.to_i32(),
+            message,
+            position,
+            owner_id,
+        )
+    }
+
     /// Create sim stats structure
+    #[allow(clippy::too_many_arguments)]
     pub fn create_sim_stats(
         region_id: Uuid,
         time_dilation: f64,
@@ -206,6 +511,321 @@ impl SecondLifeLLSDUtils {
         
         stats
     }
+
+    /// Build a region handle from the region's global position in meters.
+    /// The handle packs `x` into the high 32 bits and `y` into the low 32.
+    pub fn region_handle(global_x: u32, global_y: u32) -> u64 {
+        ((global_x as u64) << 32) | global_y as u64
+    }
+
+    /// Split a region handle back into its global `(x, y)` position in meters
+    pub fn region_handle_coords(handle: u64) -> (u32, u32) {
+        ((handle >> 32) as u32, handle as u32)
+    }
+
+    /// Encode a region handle the way the viewer does: LLSD has no unsigned
+    /// 64-bit type, so the handle is sent as 8 bytes of big-endian binary
+    pub fn region_handle_to_llsd(handle: u64) -> LLSDValue {
+        LLSDValue::Binary(handle.to_be_bytes().to_vec())
+    }
+
+    /// Decode a region handle written by [`region_handle_to_llsd`](Self::region_handle_to_llsd).
+    /// A non-negative `Integer64` is also accepted for senders that use one.
+    pub fn region_handle_from_llsd(value: &LLSDValue) -> Option<u64> {
+        match value {
+            LLSDValue::Binary(bytes) => <[u8; 8]>::try_from(bytes.as_slice()).ok().map(u64::from_be_bytes),
+            LLSDValue::Integer64(handle) => u64::try_from(*handle).ok(),
+            _ => None,
+        }
+    }
+
+    /// Wrap a login response block in the one-element array the login
+    /// server sends for `login-flags`, `global-textures`, `ui-config` and
+    /// similar entries
+    pub fn wrap_login_block(block: HashMap<String, LLSDValue>) -> LLSDValue {
+        LLSDValue::Array(vec![LLSDValue::Map(block)])
+    }
+
+    /// The map inside a login response block written by
+    /// [`wrap_login_block`](Self::wrap_login_block). A bare map is accepted
+    /// as-is; anything else, including an array that does not hold exactly
+    /// one map, gives `None`.
+    pub fn unwrap_login_block(value: &LLSDValue) -> Option<&HashMap<String, LLSDValue>> {
+        match value {
+            LLSDValue::Array(items) => match items.as_slice() {
+                [LLSDValue::Map(block)] => Some(block),
+                _ => None,
+            },
+            LLSDValue::Map(block) => Some(block),
+            _ => None,
+        }
+    }
+
+    /// Create the `global-textures` login block
+    pub fn create_global_textures(sun_texture_id: Uuid, moon_texture_id: Uuid, cloud_texture_id: Uuid) -> LLSDValue {
+        let mut textures = HashMap::new();
+        textures.insert("sun_texture_id".to_string(), LLSDValue::UUID(sun_texture_id));
+        textures.insert("moon_texture_id".to_string(), LLSDValue::UUID(moon_texture_id));
+        textures.insert("cloud_texture_id".to_string(), LLSDValue::UUID(cloud_texture_id));
+        Self::wrap_login_block(textures)
+    }
+
+    /// Create the `ui-config` login block
+    pub fn create_ui_config(allow_first_life: bool) -> LLSDValue {
+        let mut config = HashMap::new();
+        config.insert("allow_first_life".to_string(), yes_no(allow_first_life));
+        Self::wrap_login_block(config)
+    }
+
+    /// Parse a `TeleportFinish` event queue message: the whole event with
+    /// its `message` and `body`, or just the body.
+    ///
+    /// The `Info` and `AgentData` blocks may be one-map arrays, as the
+    /// simulator sends them, or bare maps. `AgentID` is read from `Info`,
+    /// or from `AgentData` if `Info` lacks it. `U32` fields and `SimIP`
+    /// are accepted as big-endian binary or as integers.
+    pub fn parse_teleport_finish(message: &LLSDValue) -> LLSDResult<TeleportFinish> {
+        let body = match message.as_map() {
+            Some(event) if event.contains_key("body") => &event["body"],
+            _ => message,
+        };
+        let body = body
+            .as_map()
+            .ok_or_else(|| LLSDError::type_mismatch("map".to_string(), body.type_name().to_string()))?;
+        let block = |name: &str| -> LLSDResult<&HashMap<String, LLSDValue>> {
+            let value = required(body, name)?;
+            Self::unwrap_login_block(value).ok_or_else(|| {
+                LLSDError::type_mismatch(format!("{} block", name), value.type_name().to_string())
+            })
+        };
+        let info = block("Info")?;
+
+        let agent_id = match info.get("AgentID") {
+            Some(id) => id,
+            None => required(block("AgentData")?, "AgentID")?,
+        };
+        let agent_id = agent_id
+            .as_uuid()
+            .ok_or_else(|| LLSDError::type_mismatch("uuid for AgentID".to_string(), agent_id.type_name().to_string()))?;
+
+        let sim_ip = match required(info, "SimIP")? {
+            LLSDValue::String(ip) => ip
+                .parse()
+                .map_err(|_| LLSDError::type_mismatch("IPv4 address for SimIP".to_string(), ip.clone()))?,
+            _ => Ipv4Addr::from(u32_field(info, "SimIP")?),
+        };
+
+        let sim_port = required(info, "SimPort")?;
+        let sim_port = sim_port
+            .as_integer()
+            .and_then(|port| u16::try_from(port).ok())
+            .ok_or_else(|| LLSDError::type_mismatch("port for SimPort".to_string(), sim_port.type_name().to_string()))?;
+
+        let region_handle = required(info, "RegionHandle")?;
+        let region_handle = Self::region_handle_from_llsd(region_handle).ok_or_else(|| {
+            LLSDError::type_mismatch("region handle".to_string(), region_handle.type_name().to_string())
+        })?;
+
+        let seed_capability = match required(info, "SeedCapability")? {
+            LLSDValue::URI(url) | LLSDValue::String(url) => url.clone(),
+            other => {
+                return Err(LLSDError::type_mismatch(
+                    "uri for SeedCapability".to_string(),
+                    other.type_name().to_string(),
+                ))
+            }
+        };
+
+        let sim_access = required(info, "SimAccess")?;
+        let sim_access = sim_access.as_integer().ok_or_else(|| {
+            LLSDError::type_mismatch("integer for SimAccess".to_string(), sim_access.type_name().to_string())
+        })?;
+
+        Ok(TeleportFinish {
+            agent_id,
+            location_id: u32_field(info, "LocationID")?,
+            sim_ip,
+            sim_port,
+            region_handle,
+            seed_capability,
+            sim_access,
+            teleport_flags: u32_field(info, "TeleportFlags")?,
+        })
+    }
+
+    /// Parse a `SimulatorFeatures` capability response. Nothing is dropped:
+    /// unrecognized entries, and recognized ones of the wrong type, are kept
+    /// in [`SimulatorFeatures::extras`]. A non-map gives the defaults.
+    pub fn parse_simulator_features(value: &LLSDValue) -> SimulatorFeatures {
+        let mut features = SimulatorFeatures::default();
+        let Some(map) = value.as_map() else {
+            return features;
+        };
+
+        for (key, entry) in map {
+            let typed = match (key.as_str(), entry) {
+                ("MeshEnabled", LLSDValue::Boolean(enabled)) => {
+                    features.mesh_enabled = *enabled;
+                    true
+                }
+                ("PhysicsMaterialsSupported", LLSDValue::Boolean(supported)) => {
+                    features.physics_materials_supported = *supported;
+                    true
+                }
+                ("MaxAgentAttachments", LLSDValue::Integer(max)) => {
+                    features.max_agent_attachments = Some(*max);
+                    true
+                }
+                ("GridServices", LLSDValue::Map(services)) => {
+                    features.grid_services = services.clone();
+                    true
+                }
+                ("OpenSimExtras", LLSDValue::Map(extras)) => {
+                    features.open_sim_extras = Some(extras.clone());
+                    true
+                }
+                _ => false,
+            };
+            if !typed {
+                features.extras.insert(key.clone(), entry.clone());
+            }
+        }
+        features
+    }
+
+    /// Create a teleport request for an agent to a position in a region
+    pub fn create_teleport_request(
+        agent_id: Uuid,
+        region_handle: u64,
+        position: [f64; 3],
+        look_at: [f64; 3],
+    ) -> LLSDResult<HashMap<String, LLSDValue>> {
+        let mut teleport = HashMap::new();
+
+        teleport.insert("agent_id".to_string(), LLSDValue::UUID(agent_id));
+        teleport.insert("region_handle".to_string(), Self::region_handle_to_llsd(region_handle));
+        teleport.insert("position".to_string(), LLSDValue::vector3(position[0], position[1], position[2])?);
+        teleport.insert("look_at".to_string(), LLSDValue::vector3(look_at[0], look_at[1], look_at[2])?);
+
+        Ok(teleport)
+    }
+
+    /// Create a teleport-lure-request structure, accepting the lure `lure_id`
+    /// offered to the agent. [`TELEPORT_FLAGS_VIA_LURE`] is always set.
+    pub fn create_teleport_lure_request(
+        agent_id: Uuid,
+        lure_id: Uuid,
+        region_handle: u64,
+        position: [f64; 3],
+        look_at: [f64; 3],
+        teleport_flags: u32,
+    ) -> LLSDResult<HashMap<String, LLSDValue>> {
+        let mut lure = Self::create_teleport_request(agent_id, region_handle, position, look_at)?;
+
+        lure.insert("lure_id".to_string(), LLSDValue::UUID(lure_id));
+        lure.insert(
+            "teleport_flags".to_string(),
+            LLSDValue::Integer((teleport_flags | TELEPORT_FLAGS_VIA_LURE) as i32),
+        );
+
+        Ok(lure)
+    }
+
+    /// Create a teleport-finish structure telling the viewer where it landed
+    /// and which seed capability to use in the destination region
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_teleport_finish(
+        agent_id: Uuid,
+        region_handle: u64,
+        position: [f64; 3],
+        look_at: [f64; 3],
+        seed_capability: &str,
+        sim_access: i32,
+        teleport_flags: u32,
+    ) -> LLSDResult<HashMap<String, LLSDValue>> {
+        let mut finish = Self::create_teleport_request(agent_id, region_handle, position, look_at)?;
+
+        finish.insert("seed_capability".to_string(), LLSDValue::URI(seed_capability.to_string()));
+        finish.insert("sim_access".to_string(), LLSDValue::Integer(sim_access));
+        finish.insert("teleport_flags".to_string(), LLSDValue::Integer(teleport_flags as i32));
+
+        Ok(finish)
+    }
+
+    /// Check a teleport-lure-request built by [`create_teleport_lure_request`](Self::create_teleport_lure_request)
+    pub fn validate_teleport_lure_request(lure: &HashMap<String, LLSDValue>) -> ValidationResult {
+        let mut result = ValidationResult::new();
+        validate_teleport_fields(lure, &mut result);
+
+        match lure.get("lure_id") {
+            Some(LLSDValue::UUID(_)) => {}
+            Some(other) => result.add_error(format!("lure_id must be a uuid, got {}", other.type_name())),
+            None => result.add_error("Missing required field: lure_id".to_string()),
+        }
+
+        match lure.get("teleport_flags") {
+            Some(LLSDValue::Integer(flags)) if (*flags as u32) & TELEPORT_FLAGS_VIA_LURE == 0 => {
+                result.add_warning("teleport_flags does not include TELEPORT_FLAGS_VIA_LURE".to_string())
+            }
+            Some(LLSDValue::Integer(_)) => {}
+            Some(other) => result.add_error(format!(
+                "teleport_flags must be an integer, got {}",
+                other.type_name()
+            )),
+            None => result.add_error("Missing required field: teleport_flags".to_string()),
+        }
+
+        result
+    }
+
+    /// Check a teleport-finish structure built by [`create_teleport_finish`](Self::create_teleport_finish)
+    pub fn validate_teleport_finish(finish: &HashMap<String, LLSDValue>) -> ValidationResult {
+        let mut result = ValidationResult::new();
+        validate_teleport_fields(finish, &mut result);
+
+        match finish.get("seed_capability") {
+            Some(LLSDValue::URI(uri)) | Some(LLSDValue::String(uri)) if !uri.is_empty() => {}
+            Some(_) => result.add_error("seed_capability must be a non-empty uri".to_string()),
+            None => result.add_error("Missing required field: seed_capability".to_string()),
+        }
+
+        for field in ["sim_access", "teleport_flags"] {
+            match finish.get(field) {
+                Some(LLSDValue::Integer(_)) => {}
+                Some(other) => result.add_error(format!("{} must be an integer, got {}", field, other.type_name())),
+                None => result.add_error(format!("Missing required field: {}", field)),
+            }
+        }
+
+        result
+    }
+}
+
+/// Check the fields shared by teleport structures
+fn validate_teleport_fields(teleport: &HashMap<String, LLSDValue>, result: &mut ValidationResult) {
+    match teleport.get("agent_id") {
+        Some(LLSDValue::UUID(id)) if SecondLifeLLSDUtils::is_valid_sl_uuid(id) => {}
+        Some(LLSDValue::UUID(_)) => result.add_error("agent_id must not be the null UUID".to_string()),
+        Some(other) => result.add_error(format!("agent_id must be a uuid, got {}", other.type_name())),
+        None => result.add_error("Missing required field: agent_id".to_string()),
+    }
+
+    match teleport.get("region_handle") {
+        Some(handle) if SecondLifeLLSDUtils::region_handle_from_llsd(handle).is_some() => {}
+        Some(other) => result.add_error(format!(
+            "region_handle must be 8 bytes of binary, got {}",
+            other.type_name()
+        )),
+        None => result.add_error("Missing required field: region_handle".to_string()),
+    }
+
+    for field in ["position", "look_at"] {
+        match teleport.get(field) {
+            Some(value) if value.as_vector3().is_some_and(|v| v.iter().all(|c| c.is_finite())) => {}
+            Some(_) => result.add_error(format!("{} must be an array of 3 finite numbers", field)),
+            None => result.add_error(format!("Missing required field: {}", field)),
+        }
+    }
 }
 
 /// Validation rules for Second Life LLSD structures
@@ -327,19 +947,7 @@ pub fn validate_sl_structure(llsd_data: &LLSDValue, rules: &SLValidationRules) -
         // Check field types
         for (field, expected_type) in &rules.field_types {
             if let Some(value) = map.get(field) {
-                let actual_type = match value {
-                    LLSDValue::Undefined => "undefined",
-                    LLSDValue::Boolean(_) => "boolean",
-                    LLSDValue::Integer(_) => "integer",
-                    LLSDValue::Real(_) => "real",
-                    LLSDValue::String(_) => "string",
-                    LLSDValue::UUID(_) => "uuid",
-                    LLSDValue::Date(_) => "date",
-                    LLSDValue::URI(_) => "uri",
-                    LLSDValue::Binary(_) => "binary",
-                    LLSDValue::Map(_) => "map",
-                    LLSDValue::Array(_) => "array",
-                };
+                let actual_type = value.type_name();
 
                 if actual_type != expected_type {
                     result.add_warning(format!(
@@ -400,6 +1008,47 @@ mod tests {
         assert_eq!(appearance["cof_version"], LLSDValue::Integer(1));
     }
 
+    #[test]
+    fn test_permission_validation() {
+        let complete: HashMap<String, LLSDValue> = PERMISSION_MASK_KEYS
+            .iter()
+            .map(|key| (key.to_string(), LLSDValue::Integer(PERM_ALL)))
+            .collect();
+        assert!(SecondLifeLLSDUtils::validate_permissions(&complete).is_valid());
+
+        let mut missing = complete.clone();
+        missing.remove("next_owner_mask");
+        let result = SecondLifeLLSDUtils::validate_permissions(&missing);
+        assert_eq!(result.errors().len(), 1);
+        assert!(result.errors()[0].contains("next_owner_mask"));
+
+        let mut wrong_type = complete.clone();
+        wrong_type.insert("group_mask".to_string(), LLSDValue::String("all".to_string()));
+        let result = SecondLifeLLSDUtils::validate_permissions(&wrong_type);
+        assert_eq!(result.errors(), ["Permission group_mask must be an integer, got string"]);
+
+        // Defaults fill only what is absent
+        let filled = SecondLifeLLSDUtils::with_default_permissions(missing);
+        assert!(SecondLifeLLSDUtils::validate_permissions(&filled).is_valid());
+        assert_eq!(filled["next_owner_mask"], LLSDValue::Integer(PERM_MOVE | PERM_TRANSFER));
+        assert_eq!(filled["base_mask"], LLSDValue::Integer(PERM_ALL));
+
+        let properties = SecondLifeLLSDUtils::create_object_properties(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::nil(),
+            "Box",
+            "",
+            SecondLifeLLSDUtils::with_default_permissions(HashMap::new()),
+        );
+        if let LLSDValue::Map(permissions) = &properties["permissions"] {
+            assert!(SecondLifeLLSDUtils::validate_permissions(permissions).is_valid());
+            assert_eq!(permissions["group_mask"], LLSDValue::Integer(PERM_NONE));
+        } else {
+            panic!("Expected permissions map");
+        }
+    }
+
     #[test]
     fn test_asset_type_conversion() {
         let upload_request = SecondLifeLLSDUtils::create_asset_upload_request(
@@ -408,6 +1057,7 @@ mod tests {
             "A test texture",
             vec![1, 2, 3, 4],
             10,
+            Uuid::new_v4(),
         );
 
         assert_eq!(upload_request["asset_type"], LLSDValue::String("texture".to_string()));
@@ -415,6 +1065,42 @@ mod tests {
         assert_eq!(upload_request["expected_upload_cost"], LLSDValue::Integer(10));
     }
 
+    #[test]
+    fn test_asset_upload_request_is_deterministic() {
+        let folder_id = uuid!("6ba7b810-9dad-11d1-80b4-00c04fd430c8");
+        let build = || {
+            SecondLifeLLSDUtils::create_asset_upload_request(
+                "sound",
+                "Chime",
+                "Door chime",
+                vec![0xCA, 0xFE],
+                10,
+                folder_id,
+            )
+        };
+
+        let mut resources = HashMap::new();
+        resources.insert("asset_data".to_string(), LLSDValue::Binary(vec![0xCA, 0xFE]));
+        let expected: HashMap<String, LLSDValue> = [
+            ("asset_type", LLSDValue::String("sound".to_string())),
+            ("name", LLSDValue::String("Chime".to_string())),
+            ("description", LLSDValue::String("Door chime".to_string())),
+            ("asset_resources", LLSDValue::Map(resources)),
+            ("folder_id", LLSDValue::UUID(folder_id)),
+            ("inventory_type", LLSDValue::Integer(1)),
+            ("expected_upload_cost", LLSDValue::Integer(10)),
+            ("everyone_mask", LLSDValue::Integer(0)),
+            ("group_mask", LLSDValue::Integer(0)),
+            ("next_owner_mask", LLSDValue::Integer(PERM_MOVE | PERM_TRANSFER)),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .collect();
+
+        assert_eq!(build(), expected);
+        assert_eq!(build(), build());
+    }
+
     #[test]
     fn test_chat_message_creation() {
         let chat = SecondLifeLLSDUtils::create_chat_message(
@@ -442,6 +1128,73 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_vector_helpers_match_chat_position() {
+        let chat = SecondLifeLLSDUtils::create_chat_message(
+            "TestUser",
+            1,
+            0,
+            "Hello World",
+            Some([128.0, 128.0, 25.0]),
+            None,
+        );
+        assert_eq!(chat["position"], LLSDValue::vector3(128.0, 128.0, 25.0).unwrap());
+
+        let manual4 = LLSDValue::Array(vec![
+            LLSDValue::Real(0.0),
+            LLSDValue::Real(0.0),
+            LLSDValue::Real(0.0),
+            LLSDValue::Real(1.0),
+        ]);
+        assert_eq!(LLSDValue::vector4(0.0, 0.0, 0.0, 1.0).unwrap(), manual4);
+        assert_eq!(LLSDValue::rotation(0.0, 0.0, 0.0, 1.0).unwrap(), manual4);
+        assert_eq!(LLSDValue::color(0.0, 0.0, 0.0, 1.0).unwrap(), manual4);
+    }
+
+    #[test]
+    fn test_chat_enum_codes() {
+        let sources = [
+            (ChatSourceType::System, 0),
+            (ChatSourceType::Agent, 1),
+            (ChatSourceType::Object, 2),
+            (ChatSourceType::Unknown, 3),
+        ];
+        for (source, code) in sources {
+            assert_eq!(source.to_i32(), code);
+            assert_eq!(ChatSourceType::from_i32(code), Some(source));
+        }
+        assert_eq!(ChatSourceType::from_i32(4), None);
+
+        let types = [
+            (ChatType::Whisper, 0),
+            (ChatType::Normal, 1),
+            (ChatType::Shout, 2),
+            (ChatType::Start, 4),
+            (ChatType::Stop, 5),
+            (ChatType::DebugMsg, 6),
+            (ChatType::Region, 7),
+            (ChatType::Owner, 8),
+            (ChatType::Direct, 9),
+        ];
+        for (chat_type, code) in types {
+            assert_eq!(chat_type.to_i32(), code);
+            assert_eq!(ChatType::from_i32(code), Some(chat_type));
+        }
+        assert_eq!(ChatType::from_i32(3), None);
+        assert_eq!(ChatType::from_i32(-1), None);
+
+        let chat = SecondLifeLLSDUtils::create_chat_message_typed(
+            "TestUser",
+            ChatSourceType::Object,
+            ChatType::Shout,
+            "Hello",
+            None,
+            None,
+        );
+        assert_eq!(chat["source_type"], LLSDValue::Integer(2));
+        assert_eq!(chat["chat_type"], LLSDValue::Integer(2));
+    }
+
     #[test]
     fn test_validation_rules() {
         let rules = SLValidationRules::new()
@@ -511,4 +1264,269 @@ mod tests {
         assert_eq!(stats["child_agents"], LLSDValue::Integer(5));
         assert!(matches!(stats["timestamp"], LLSDValue::Real(_)));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_teleport_lure_request() {
+        use crate::binary::{LLSDBinaryParser, LLSDBinarySerializer};
+        use crate::types::LLSDDocument;
+        use crate::xml::{LLSDXmlParser, LLSDXmlSerializer};
+
+        let agent_id = uuid!("550e8400-e29b-41d4-a716-446655440000");
+        let lure_id = uuid!("6ba7b810-9dad-11d1-80b4-00c04fd430c8");
+        // Region at grid (1000, 1001), well above 2^32 once packed
+        let handle = SecondLifeLLSDUtils::region_handle(256_000, 256_256);
+        assert_eq!(handle, (256_000u64 << 32) | 256_256);
+        assert_eq!(SecondLifeLLSDUtils::region_handle_coords(handle), (256_000, 256_256));
+
+        let lure = SecondLifeLLSDUtils::create_teleport_lure_request(
+            agent_id,
+            lure_id,
+            handle,
+            [128.0, 64.5, 22.25],
+            [1.0, 0.0, 0.0],
+            0,
+        )
+        .unwrap();
+        assert!(SecondLifeLLSDUtils::validate_teleport_lure_request(&lure).is_valid());
+        assert_eq!(lure["teleport_flags"], LLSDValue::Integer(TELEPORT_FLAGS_VIA_LURE as i32));
+
+        let document = LLSDDocument::new(LLSDValue::Map(lure));
+        let binary = LLSDBinarySerializer::new().serialize(&document).unwrap();
+        let xml = LLSDXmlSerializer::new().serialize(&document).unwrap();
+        let parsed = [
+            LLSDBinaryParser::new().parse(&binary).unwrap(),
+            LLSDXmlParser::new().parse(&xml).unwrap(),
+        ];
+
+        for document in &parsed {
+            let LLSDValue::Map(lure) = document.content() else {
+                panic!("Expected lure map");
+            };
+            assert!(SecondLifeLLSDUtils::validate_teleport_lure_request(lure).is_valid());
+            assert_eq!(lure["position"], LLSDValue::vector3(128.0, 64.5, 22.25).unwrap());
+            assert_eq!(lure["look_at"], LLSDValue::vector3(1.0, 0.0, 0.0).unwrap());
+            assert_eq!(SecondLifeLLSDUtils::region_handle_from_llsd(&lure["region_handle"]), Some(handle));
+            assert_eq!(lure["lure_id"], LLSDValue::UUID(lure_id));
+        }
+    }
+
+    #[test]
+    fn test_teleport_finish_validation() {
+        let agent_id = uuid!("550e8400-e29b-41d4-a716-446655440000");
+        let handle = SecondLifeLLSDUtils::region_handle(256_000, 256_256);
+
+        let mut finish = SecondLifeLLSDUtils::create_teleport_finish(
+            agent_id,
+            handle,
+            [10.0, 20.0, 30.0],
+            [0.0, 1.0, 0.0],
+            "https://sim.example.com:12043/cap/seed",
+            13,
+            TELEPORT_FLAGS_VIA_LURE,
+        )
+        .unwrap();
+        assert!(SecondLifeLLSDUtils::validate_teleport_finish(&finish).is_valid());
+        assert_eq!(
+            SecondLifeLLSDUtils::region_handle_from_llsd(&LLSDValue::Integer64(handle as i64)),
+            Some(handle)
+        );
+
+        finish.insert("region_handle".to_string(), LLSDValue::Binary(vec![0; 4]));
+        finish.insert("look_at".to_string(), LLSDValue::Array(vec![LLSDValue::Real(1.0)]));
+        finish.insert("agent_id".to_string(), LLSDValue::UUID(Uuid::nil()));
+        finish.remove("seed_capability");
+        let result = SecondLifeLLSDUtils::validate_teleport_finish(&finish);
+        assert_eq!(result.errors().len(), 4);
+
+        let bad_position = [f64::NAN, 0.0, 0.0];
+        assert!(SecondLifeLLSDUtils::create_teleport_request(agent_id, handle, bad_position, [1.0, 0.0, 0.0]).is_err());
+
+        let mut lure = SecondLifeLLSDUtils::create_teleport_lure_request(
+            agent_id,
+            Uuid::nil(),
+            handle,
+            [0.0; 3],
+            [1.0, 0.0, 0.0],
+            0,
+        )
+        .unwrap();
+        lure.insert("teleport_flags".to_string(), LLSDValue::Integer(0));
+        let result = SecondLifeLLSDUtils::validate_teleport_lure_request(&lure);
+        assert!(result.is_valid());
+        assert_eq!(result.warnings().len(), 1);
+    }
+
+    #[test]
+    fn test_login_blocks() {
+        let flags = LoginFlags { ever_logged_in: true, daylight_savings: true, ..Default::default() };
+        let value = flags.to_llsd();
+
+        // The login server's [{...}] shape
+        let items = value.as_array().unwrap();
+        assert_eq!(items.len(), 1);
+        let block = items[0].as_map().unwrap();
+        assert_eq!(block["ever_logged_in"], LLSDValue::String("Y".to_string()));
+        assert_eq!(block["stipend_since_login"], LLSDValue::String("N".to_string()));
+        assert_eq!(block.len(), 4);
+
+        assert_eq!(LoginFlags::from_llsd(&value).unwrap(), flags);
+        assert_eq!(LoginFlags::from_llsd(&items[0]).unwrap(), flags);
+        assert_eq!(
+            SecondLifeLLSDUtils::unwrap_login_block(&value),
+            Some(block)
+        );
+
+        let mut partial = HashMap::new();
+        partial.insert("gendered".to_string(), LLSDValue::String("Y".to_string()));
+        let parsed = LoginFlags::from_llsd(&SecondLifeLLSDUtils::wrap_login_block(partial)).unwrap();
+        assert_eq!(parsed, LoginFlags { gendered: true, ..Default::default() });
+
+        let mut bad = HashMap::new();
+        bad.insert("gendered".to_string(), LLSDValue::Integer(1));
+        assert!(LoginFlags::from_llsd(&SecondLifeLLSDUtils::wrap_login_block(bad)).is_err());
+        assert!(LoginFlags::from_llsd(&LLSDValue::Array(vec![])).is_err());
+        assert!(SecondLifeLLSDUtils::unwrap_login_block(&LLSDValue::Array(vec![
+            LLSDValue::Map(HashMap::new()),
+            LLSDValue::Map(HashMap::new()),
+        ]))
+        .is_none());
+
+        let sun = uuid!("cce0f112-878f-4586-a2e2-a8f104bba271");
+        let textures = SecondLifeLLSDUtils::create_global_textures(sun, Uuid::nil(), Uuid::nil());
+        let block = SecondLifeLLSDUtils::unwrap_login_block(&textures).unwrap();
+        assert_eq!(block["sun_texture_id"], LLSDValue::UUID(sun));
+
+        let ui = SecondLifeLLSDUtils::create_ui_config(true);
+        assert_eq!(
+            SecondLifeLLSDUtils::unwrap_login_block(&ui).unwrap()["allow_first_life"],
+            LLSDValue::String("Y".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_simulator_features() {
+        let sl = LLSDValue::Map({
+            let mut map = HashMap::new();
+            map.insert("MeshEnabled".to_string(), LLSDValue::Boolean(true));
+            map.insert("PhysicsMaterialsSupported".to_string(), LLSDValue::Boolean(true));
+            map.insert("MaxAgentAttachments".to_string(), LLSDValue::Integer(38));
+            map.insert("AnimatedObjects".to_string(), LLSDValue::Map({
+                let mut animated = HashMap::new();
+                animated.insert("MaxAgentAnimatedObjectAttachments".to_string(), LLSDValue::Integer(3));
+                animated
+            }));
+            map.insert("GridServices".to_string(), LLSDValue::Map({
+                let mut services = HashMap::new();
+                services.insert("search".to_string(), LLSDValue::URI("https://search.secondlife.com/".to_string()));
+                services
+            }));
+            map
+        });
+        let features = SecondLifeLLSDUtils::parse_simulator_features(&sl);
+        assert!(features.mesh_enabled);
+        assert!(features.physics_materials_supported);
+        assert_eq!(features.max_agent_attachments, Some(38));
+        assert!(features.grid_services.contains_key("search"));
+        assert!(!features.is_opensim());
+        assert_eq!(features.grid_name(), None);
+        assert!(!features.export_supported());
+        assert_eq!(features.extras.len(), 1);
+        assert!(features.extras["AnimatedObjects"].as_map().is_some());
+
+        let opensim = LLSDValue::Map({
+            let mut map = HashMap::new();
+            map.insert("MeshEnabled".to_string(), LLSDValue::Boolean(false));
+            // Wrong type, kept rather than dropped
+            map.insert("MaxAgentAttachments".to_string(), LLSDValue::String("38".to_string()));
+            map.insert("OpenSimExtras".to_string(), LLSDValue::Map({
+                let mut extras = HashMap::new();
+                extras.insert("GridName".to_string(), LLSDValue::String("OSGrid".to_string()));
+                extras.insert("GridURL".to_string(), LLSDValue::String("http://login.osgrid.org/".to_string()));
+                extras.insert("ExportSupported".to_string(), LLSDValue::Boolean(true));
+                extras.insert("whisper-range".to_string(), LLSDValue::Integer(10));
+                extras
+            }));
+            map
+        });
+        let features = SecondLifeLLSDUtils::parse_simulator_features(&opensim);
+        assert!(!features.mesh_enabled);
+        assert!(!features.physics_materials_supported);
+        assert_eq!(features.max_agent_attachments, None);
+        assert_eq!(features.extras["MaxAgentAttachments"], LLSDValue::String("38".to_string()));
+        assert!(features.grid_services.is_empty());
+        assert!(features.is_opensim());
+        assert_eq!(features.grid_name(), Some("OSGrid"));
+        assert_eq!(features.grid_url(), Some("http://login.osgrid.org/"));
+        assert!(features.export_supported());
+        assert_eq!(features.open_sim_extras.as_ref().unwrap()["whisper-range"], LLSDValue::Integer(10));
+
+        assert_eq!(SecondLifeLLSDUtils::parse_simulator_features(&LLSDValue::Integer(1)), SimulatorFeatures::default());
+    }
+
+    #[test]
+    fn test_parse_teleport_finish() {
+        let agent_id = uuid!("550e8400-e29b-41d4-a716-446655440000");
+        let handle = SecondLifeLLSDUtils::region_handle(256_000, 256_256);
+        let seed = "https://sim10.agni.lindenlab.com:12043/cap/2f1c0e4b-1a5e-4d6f-9b3c-0a4c9e2d7f11";
+
+        let mut info = HashMap::new();
+        info.insert("AgentID".to_string(), LLSDValue::UUID(agent_id));
+        info.insert("LocationID".to_string(), LLSDValue::Binary(4u32.to_be_bytes().to_vec()));
+        info.insert("SimIP".to_string(), LLSDValue::Binary(vec![216, 82, 8, 40]));
+        info.insert("SimPort".to_string(), LLSDValue::Integer(13005));
+        info.insert("RegionHandle".to_string(), SecondLifeLLSDUtils::region_handle_to_llsd(handle));
+        info.insert("SeedCapability".to_string(), LLSDValue::URI(seed.to_string()));
+        info.insert("SimAccess".to_string(), LLSDValue::Integer(13));
+        info.insert("TeleportFlags".to_string(), LLSDValue::Binary(TELEPORT_FLAGS_VIA_LURE.to_be_bytes().to_vec()));
+        let mut body = HashMap::new();
+        body.insert("Info".to_string(), LLSDValue::Array(vec![LLSDValue::Map(info.clone())]));
+        let mut event = HashMap::new();
+        event.insert("message".to_string(), LLSDValue::String("TeleportFinish".to_string()));
+        event.insert("body".to_string(), LLSDValue::Map(body.clone()));
+
+        let finish = SecondLifeLLSDUtils::parse_teleport_finish(&LLSDValue::Map(event)).unwrap();
+        assert_eq!(finish.region_handle, handle);
+        assert_eq!(SecondLifeLLSDUtils::region_handle_coords(finish.region_handle), (256_000, 256_256));
+        assert_eq!(finish.seed_capability, seed);
+        assert_eq!(finish.agent_id, agent_id);
+        assert_eq!(finish.location_id, 4);
+        assert_eq!(finish.sim_ip, Ipv4Addr::new(216, 82, 8, 40));
+        assert_eq!(finish.sim_port, 13005);
+        assert_eq!(finish.sim_access, 13);
+        assert_eq!(finish.teleport_flags, TELEPORT_FLAGS_VIA_LURE);
+
+        // A bare body with bare blocks, the agent in AgentData and
+        // integer-encoded fields
+        info.remove("AgentID");
+        info.insert("SimIP".to_string(), LLSDValue::String("216.82.8.40".to_string()));
+        info.insert("TeleportFlags".to_string(), LLSDValue::Integer(TELEPORT_FLAGS_VIA_LURE as i32));
+        info.insert("RegionHandle".to_string(), LLSDValue::Integer64(handle as i64));
+        let mut agent_data = HashMap::new();
+        agent_data.insert("AgentID".to_string(), LLSDValue::UUID(agent_id));
+        let mut bare = HashMap::new();
+        bare.insert("Info".to_string(), LLSDValue::Map(info.clone()));
+        bare.insert("AgentData".to_string(), LLSDValue::Array(vec![LLSDValue::Map(agent_data)]));
+        assert_eq!(SecondLifeLLSDUtils::parse_teleport_finish(&LLSDValue::Map(bare)).unwrap(), finish);
+
+        // Missing and mistyped fields are errors
+        let mut missing = body.clone();
+        missing.insert("Info".to_string(), LLSDValue::Map({
+            let mut info = info.clone();
+            info.insert("AgentID".to_string(), LLSDValue::UUID(agent_id));
+            info.remove("SeedCapability");
+            info
+        }));
+        match SecondLifeLLSDUtils::parse_teleport_finish(&LLSDValue::Map(missing)) {
+            Err(LLSDError::MissingField { field }) => assert_eq!(field, "SeedCapability"),
+            other => panic!("Expected a missing field, got {:?}", other),
+        }
+        info.insert("AgentID".to_string(), LLSDValue::UUID(agent_id));
+        info.insert("RegionHandle".to_string(), LLSDValue::Binary(vec![0; 4]));
+        body.insert("Info".to_string(), LLSDValue::Map(info));
+        assert!(matches!(
+            SecondLifeLLSDUtils::parse_teleport_finish(&LLSDValue::Map(body)),
+            Err(LLSDError::TypeMismatch { .. })
+        ));
+        assert!(SecondLifeLLSDUtils::parse_teleport_finish(&LLSDValue::Integer(1)).is_err());
+    }
+}
@@ -0,0 +1,155 @@
+/*!
+ * LLSD Test Value Generators - Rust Implementation
+ *
+ * Random `LLSDValue` trees for property tests of code that handles LLSD
+ * Copyright (C) 2024 Linden Lab
+ */
+
+use crate::types::LLSDValue;
+use chrono::{TimeZone, Utc};
+use uuid::Uuid;
+
+#[cfg(feature = "testing")]
+use rand::{Rng, RngExt};
+#[cfg(feature = "testing")]
+use std::collections::HashMap;
+
+#[cfg(feature = "proptest")]
+use proptest::prelude::*;
+
+/// Latest generated date, 2100-01-01T00:00:00Z
+const MAX_TIMESTAMP: i64 = 4_102_444_800;
+/// Maximum number of elements in a generated array or map
+const MAX_CONTAINER_LEN: usize = 8;
+/// Maximum length of generated strings, in characters
+const MAX_STRING_LEN: usize = 16;
+/// Maximum length of generated binary data
+const MAX_BINARY_LEN: usize = 64;
+
+/// Generate a random well-formed value, nesting containers at most
+/// `depth_budget` levels deep.
+///
+/// Every variant can be produced. Containers hold at most 8 elements,
+/// reals are always finite and dates are whole seconds between 1970 and
+/// 2100, so generated values round-trip exactly through the binary format.
+#[cfg(feature = "testing")]
+pub fn arbitrary_value<R: Rng + ?Sized>(rng: &mut R, depth_budget: usize) -> LLSDValue {
+    let variants = if depth_budget == 0 { 10 } else { 12 };
+
+    match rng.random_range(0..variants) {
+        0 => LLSDValue::Undefined,
+        1 => LLSDValue::Boolean(rng.random()),
+        2 => LLSDValue::Integer(rng.random()),
+        3 => LLSDValue::Integer64(rng.random()),
+        4 => {
+            let r = f64::from_bits(rng.random());
+            LLSDValue::Real(if r.is_finite() { r } else { 0.0 })
+        }
+        5 => LLSDValue::String(random_string(rng)),
+        6 => LLSDValue::UUID(Uuid::from_bytes(rng.random())),
+        7 => {
+            let seconds = rng.random_range(0..=MAX_TIMESTAMP);
+            LLSDValue::Date(Utc.timestamp_opt(seconds, 0).single().unwrap_or_default())
+        }
+        8 => LLSDValue::URI(random_string(rng)),
+        9 => {
+            let mut bytes = vec![0u8; rng.random_range(0..=MAX_BINARY_LEN)];
+            rng.fill(&mut bytes[..]);
+            LLSDValue::Binary(bytes)
+        }
+        10 => {
+            let len = rng.random_range(0..=MAX_CONTAINER_LEN);
+            LLSDValue::Array((0..len).map(|_| arbitrary_value(rng, depth_budget - 1)).collect())
+        }
+        _ => {
+            let len = rng.random_range(0..=MAX_CONTAINER_LEN);
+            let mut map = HashMap::with_capacity(len);
+            for _ in 0..len {
+                map.insert(random_string(rng), arbitrary_value(rng, depth_budget - 1));
+            }
+            LLSDValue::Map(map)
+        }
+    }
+}
+
+#[cfg(feature = "testing")]
+fn random_string<R: Rng + ?Sized>(rng: &mut R) -> String {
+    let len = rng.random_range(0..=MAX_STRING_LEN);
+    (0..len).map(|_| rng.random::<char>()).collect()
+}
+
+/// Proptest strategy for values nesting containers at most `depth` levels
+/// deep, with the same bounds as [`arbitrary_value`]. Shrinks toward
+/// scalars and smaller containers.
+#[cfg(feature = "proptest")]
+pub fn value_strategy(depth: u32) -> impl Strategy<Value = LLSDValue> {
+    let text = || prop::collection::vec(any::<char>(), 0..=MAX_STRING_LEN).prop_map(String::from_iter);
+    let leaf = prop_oneof![
+        Just(LLSDValue::Undefined),
+        any::<bool>().prop_map(LLSDValue::Boolean),
+        any::<i32>().prop_map(LLSDValue::Integer),
+        any::<i64>().prop_map(LLSDValue::Integer64),
+        (prop::num::f64::POSITIVE | prop::num::f64::NEGATIVE | prop::num::f64::ZERO).prop_map(LLSDValue::Real),
+        text().prop_map(LLSDValue::String),
+        any::<[u8; 16]>().prop_map(|b| LLSDValue::UUID(Uuid::from_bytes(b))),
+        (0..=MAX_TIMESTAMP).prop_map(|t| LLSDValue::Date(Utc.timestamp_opt(t, 0).single().unwrap_or_default())),
+        text().prop_map(LLSDValue::URI),
+        prop::collection::vec(any::<u8>(), 0..=MAX_BINARY_LEN).prop_map(LLSDValue::Binary),
+    ];
+
+    leaf.prop_recursive(depth, 64, MAX_CONTAINER_LEN as u32, move |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..=MAX_CONTAINER_LEN).prop_map(LLSDValue::Array),
+            prop::collection::hash_map(text(), inner, 0..=MAX_CONTAINER_LEN).prop_map(LLSDValue::Map),
+        ]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::LLSDDocument;
+    use crate::LLSDFactory;
+
+    fn depth(value: &LLSDValue) -> usize {
+        match value {
+            LLSDValue::Array(items) => 1 + items.iter().map(depth).max().unwrap_or(0),
+            LLSDValue::Map(entries) => 1 + entries.values().map(depth).max().unwrap_or(0),
+            _ => 0,
+        }
+    }
+
+    fn assert_binary_round_trip(value: &LLSDValue) {
+        let document = LLSDDocument::new(value.clone());
+        let data = LLSDFactory::serialize_binary(&document).unwrap();
+        assert_eq!(LLSDFactory::parse_binary(&data).unwrap().content(), value);
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn test_arbitrary_value_binary_round_trip() {
+        use rand::SeedableRng;
+
+        let mut rng = rand::rngs::SmallRng::seed_from_u64(0x11_5d);
+        let mut seen = std::collections::HashSet::new();
+        for _ in 0..500 {
+            let value = arbitrary_value(&mut rng, 3);
+            assert!(depth(&value) <= 3);
+            seen.insert(value.get_type());
+            assert_binary_round_trip(&value);
+        }
+        // Every variant
+        assert_eq!(seen.len(), 12, "{:?}", seen);
+
+        assert!(!matches!(arbitrary_value(&mut rng, 0), LLSDValue::Array(_) | LLSDValue::Map(_)));
+    }
+
+    #[cfg(feature = "proptest")]
+    proptest! {
+        #[test]
+        fn value_strategy_binary_round_trip(value in value_strategy(3)) {
+            prop_assert!(depth(&value) <= 3);
+            assert_binary_round_trip(&value);
+        }
+    }
+}
@@ -6,10 +6,10 @@
  */
 
 use std::collections::HashMap;
-use std::borrow::Borrow;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, FixedOffset, Utc};
+use crate::error::{LLSDError, LLSDResult};
 
 /// LLSD data types enumeration
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -17,6 +17,7 @@ pub enum LLSDType {
     Unknown,
     Boolean,
     Integer,
+    Integer64,
     Real,
     String,
     UUID,
@@ -27,6 +28,50 @@ pub enum LLSDType {
     Array,
 }
 
+impl LLSDType {
+    /// Element name for this type in LLSD XML. `Integer64` shares
+    /// `<integer>` with `Integer`.
+    pub fn xml_tag(&self) -> &'static str {
+        match self {
+            LLSDType::Unknown => "undef",
+            LLSDType::Boolean => "boolean",
+            LLSDType::Integer | LLSDType::Integer64 => "integer",
+            LLSDType::Real => "real",
+            LLSDType::String => "string",
+            LLSDType::UUID => "uuid",
+            LLSDType::Date => "date",
+            LLSDType::URI => "uri",
+            LLSDType::Binary => "binary",
+            LLSDType::Map => "map",
+            LLSDType::Array => "array",
+        }
+    }
+
+    /// Type of an LLSD XML value element, or `None` for anything else
+    /// (including the structural `<llsd>` and `<key>`). `<integer>` maps to
+    /// `Integer`; the parser widens values that don't fit.
+    pub fn from_xml_tag(tag: &str) -> Option<LLSDType> {
+        match tag {
+            "undef" => Some(LLSDType::Unknown),
+            "boolean" => Some(LLSDType::Boolean),
+            "integer" => Some(LLSDType::Integer),
+            "real" => Some(LLSDType::Real),
+            "string" => Some(LLSDType::String),
+            "uuid" => Some(LLSDType::UUID),
+            "date" => Some(LLSDType::Date),
+            "uri" => Some(LLSDType::URI),
+            "binary" => Some(LLSDType::Binary),
+            "map" => Some(LLSDType::Map),
+            "array" => Some(LLSDType::Array),
+            _ => None,
+        }
+    }
+}
+
+/// Bytes of a text payload [`LLSDFormat::detect`] reads to tell JSON
+/// from notation
+const DETECT_PREFIX_LEN: usize = 1024;
+
 /// LLSD serialization formats
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum LLSDFormat {
@@ -36,16 +81,198 @@ pub enum LLSDFormat {
     Notation,
 }
 
+impl LLSDFormat {
+    /// Short lowercase name: `"xml"`, `"json"`, `"binary"` or `"notation"`
+    pub fn name(&self) -> &'static str {
+        match self {
+            LLSDFormat::XML => "xml",
+            LLSDFormat::JSON => "json",
+            LLSDFormat::Binary => "binary",
+            LLSDFormat::Notation => "notation",
+        }
+    }
+
+    /// MIME type used for this format in CAPS requests and responses
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            LLSDFormat::XML => "application/llsd+xml",
+            LLSDFormat::JSON => "application/llsd+json",
+            LLSDFormat::Binary => "application/llsd+binary",
+            LLSDFormat::Notation => "application/llsd+notation",
+        }
+    }
+
+    /// Map a `Content-Type` header value to a format, ignoring parameters
+    /// such as `charset` and letter case
+    pub fn from_content_type(content_type: &str) -> Option<LLSDFormat> {
+        let mime = content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+        match mime.as_str() {
+            "application/llsd+xml" => Some(LLSDFormat::XML),
+            "application/llsd+json" => Some(LLSDFormat::JSON),
+            "application/llsd+binary" => Some(LLSDFormat::Binary),
+            "application/llsd+notation" => Some(LLSDFormat::Notation),
+            _ => None,
+        }
+    }
+
+    /// Guess the format of a serialized payload from its header.
+    ///
+    /// Skips a UTF-8 byte order mark and leading whitespace, then looks for
+    /// the binary magic, a `<?llsd/...?>` marker, an XML tag, JSON, or a
+    /// notation value. JSON and notation are recognized from the tokens in
+    /// the first KiB, so the payload need not be complete or valid. Returns
+    /// `None` when nothing matches.
+    pub fn detect(data: &[u8]) -> Option<LLSDFormat> {
+        use crate::binary::{LLSD_BINARY_MAGIC, LLSD_EXTENDED_MAGIC};
+
+        let data = data.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(data);
+        if data.starts_with(&LLSD_BINARY_MAGIC.to_be_bytes())
+            || data.starts_with(&LLSD_EXTENDED_MAGIC.to_be_bytes())
+        {
+            return Some(LLSDFormat::Binary);
+        }
+
+        let data = Self::trim_preamble(data);
+        if data.is_empty() {
+            return None;
+        }
+
+        if let Some((marker, _)) = Self::split_marker(data) {
+            return match marker.as_str() {
+                "binary" => Some(LLSDFormat::Binary),
+                "notation" => Some(LLSDFormat::Notation),
+                "xml" => Some(LLSDFormat::XML),
+                "json" => Some(LLSDFormat::JSON),
+                _ => None,
+            };
+        }
+        if data[0] == b'<' {
+            return Some(LLSDFormat::XML);
+        }
+
+        // Text formats are told apart by their first tokens, so a large
+        // payload is not read in full and a truncated one is still detected
+        let truncated = data.len() > DETECT_PREFIX_LEN;
+        let prefix = &data[..data.len().min(DETECT_PREFIX_LEN)];
+        let text = match std::str::from_utf8(prefix) {
+            Ok(text) => text,
+            // A character split by the cut is not an encoding error
+            Err(e) if truncated && e.error_len().is_none() => {
+                std::str::from_utf8(&prefix[..e.valid_up_to()]).ok()?
+            }
+            Err(_) => return None,
+        };
+        if Self::looks_like_json(text, truncated) {
+            return Some(LLSDFormat::JSON);
+        }
+        if Self::looks_like_notation(text) {
+            return Some(LLSDFormat::Notation);
+        }
+        None
+    }
+
+    /// Skip a UTF-8 byte order mark and leading whitespace
+    pub(crate) fn trim_preamble(data: &[u8]) -> &[u8] {
+        let data = data.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(data);
+        let start = data.iter().position(|b| !b.is_ascii_whitespace()).unwrap_or(data.len());
+        &data[start..]
+    }
+
+    /// Split a `<?llsd/binary?>` style header into its lowercased format
+    /// name and the payload after it (and its line break), if present
+    pub(crate) fn split_marker(data: &[u8]) -> Option<(String, &[u8])> {
+        let rest = data.strip_prefix(b"<?")?;
+        let end = rest.windows(2).position(|w| w == b"?>")?;
+        let marker = std::str::from_utf8(&rest[..end]).ok()?.trim().to_ascii_lowercase();
+        let name = marker.strip_prefix("llsd/")?.trim().to_string();
+
+        let body = &rest[end + 2..];
+        let body = body.strip_prefix(b"\r").unwrap_or(body);
+        let body = body.strip_prefix(b"\n").unwrap_or(body);
+        Some((name, body))
+    }
+
+    /// Whether `text` is made only of JSON tokens. A string or number may run
+    /// past the end of `text`, and so may a literal when the payload was cut
+    /// at `text`'s end, so truncated documents such as `[1, 2` still count.
+    fn looks_like_json(text: &str, truncated: bool) -> bool {
+        let bytes = text.as_bytes();
+        let mut pos = 0;
+        while pos < bytes.len() {
+            match bytes[pos] {
+                b'{' | b'}' | b'[' | b']' | b',' | b':' => pos += 1,
+                c if c.is_ascii_whitespace() => pos += 1,
+                b'"' => {
+                    pos += 1;
+                    while pos < bytes.len() && bytes[pos] != b'"' {
+                        pos += if bytes[pos] == b'\\' { 2 } else { 1 };
+                    }
+                    pos += 1;
+                }
+                b'-' | b'0'..=b'9' => {
+                    pos += 1;
+                    while pos < bytes.len() && matches!(bytes[pos], b'0'..=b'9' | b'.' | b'e' | b'E' | b'+' | b'-') {
+                        pos += 1;
+                    }
+                }
+                b'a'..=b'z' => {
+                    let start = pos;
+                    while pos < bytes.len() && bytes[pos].is_ascii_alphabetic() {
+                        pos += 1;
+                    }
+                    let word = &text[start..pos];
+                    let cut = truncated && pos == bytes.len();
+                    let literal = |l: &&str| *l == word || (cut && l.starts_with(word));
+                    if !["true", "false", "null"].iter().any(literal) {
+                        return false;
+                    }
+                }
+                _ => return false,
+            }
+        }
+        true
+    }
+
+    /// Check whether text opens with a notation value prefix
+    fn looks_like_notation(text: &str) -> bool {
+        let mut chars = text.chars();
+        let first = match chars.next() {
+            Some(c) => c,
+            None => return false,
+        };
+        let second = chars.next();
+
+        match first {
+            '{' | '[' | '!' | '\'' | '"' => true,
+            '0' | '1' => second.is_none(),
+            't' | 'T' => matches!(text, "t" | "T" | "true" | "TRUE"),
+            'f' | 'F' => matches!(text, "f" | "F" | "false" | "FALSE"),
+            'i' => second.is_some_and(|c| c.is_ascii_digit() || c == '-'),
+            'r' => second.is_some_and(|c| c.is_ascii_digit() || matches!(c, '-' | '.' | 'n')),
+            'u' => second.is_some_and(|c| c.is_ascii_hexdigit()),
+            's' => second == Some('('),
+            'b' => matches!(second, Some('(' | '1' | '6')),
+            'l' | 'd' => second == Some('"'),
+            _ => false,
+        }
+    }
+}
+
 /// LLSD Value enumeration representing all possible LLSD data types
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum LLSDValue {
     /// Undefined/null value
+    #[default]
     Undefined,
     /// Boolean value
     Boolean(bool),
     /// Integer value (32-bit signed)
     Integer(i32),
+    /// Integer value (64-bit signed), for counters that overflow `Integer`.
+    /// XML and JSON write it as a plain integer, so values that fit in 32
+    /// bits come back from those formats as `Integer`.
+    Integer64(i64),
     /// Real/float value (64-bit)
     Real(f64),
     /// String value
@@ -65,12 +292,81 @@ pub enum LLSDValue {
 }
 
 impl LLSDValue {
+    /// Create a 3-component vector (position, scale, velocity) as an array of reals
+    pub fn vector3(x: f64, y: f64, z: f64) -> LLSDResult<Self> {
+        Self::real_array("vector3", &[x, y, z])
+    }
+
+    /// Create a 4-component vector as an array of reals
+    pub fn vector4(x: f64, y: f64, z: f64, w: f64) -> LLSDResult<Self> {
+        Self::real_array("vector4", &[x, y, z, w])
+    }
+
+    /// Create a rotation quaternion as an array of reals in `[x, y, z, w]` order
+    pub fn rotation(x: f64, y: f64, z: f64, w: f64) -> LLSDResult<Self> {
+        Self::real_array("rotation", &[x, y, z, w])
+    }
+
+    /// Create an RGBA color as an array of reals, each component in `0.0..=1.0`
+    pub fn color(r: f64, g: f64, b: f64, a: f64) -> LLSDResult<Self> {
+        let components = [r, g, b, a];
+        if let Some(c) = components.iter().find(|c| !(0.0..=1.0).contains(*c)) {
+            return Err(LLSDError::validation_error(format!(
+                "color component {} is outside the range 0.0..=1.0",
+                c
+            )));
+        }
+        Self::real_array("color", &components)
+    }
+
+    /// Read a 3-component vector written by [`vector3`](Self::vector3).
+    /// Integer components are accepted and converted to reals.
+    pub fn as_vector3(&self) -> Option<[f64; 3]> {
+        self.as_reals()
+    }
+
+    /// Read a 4-component vector written by [`vector4`](Self::vector4)
+    pub fn as_vector4(&self) -> Option<[f64; 4]> {
+        self.as_reals()
+    }
+
+    /// Read a rotation quaternion in `[x, y, z, w]` order, as written by
+    /// [`rotation`](Self::rotation)
+    pub fn as_quaternion(&self) -> Option<[f64; 4]> {
+        self.as_reals()
+    }
+
+    /// Read an array of exactly `N` numbers
+    fn as_reals<const N: usize>(&self) -> Option<[f64; N]> {
+        let items = self.as_array()?;
+        if items.len() != N {
+            return None;
+        }
+        let mut components = [0.0; N];
+        for (slot, item) in components.iter_mut().zip(items) {
+            *slot = item.as_real()?;
+        }
+        Some(components)
+    }
+
+    /// Build an array of reals, rejecting NaN and infinite components
+    fn real_array(kind: &str, components: &[f64]) -> LLSDResult<Self> {
+        if let Some(c) = components.iter().find(|c| !c.is_finite()) {
+            return Err(LLSDError::validation_error(format!(
+                "{} component {} is not finite",
+                kind, c
+            )));
+        }
+        Ok(LLSDValue::Array(components.iter().map(|c| LLSDValue::Real(*c)).collect()))
+    }
+
     /// Get the type of this LLSD value
     pub fn get_type(&self) -> LLSDType {
         match self {
             LLSDValue::Undefined => LLSDType::Unknown,
             LLSDValue::Boolean(_) => LLSDType::Boolean,
             LLSDValue::Integer(_) => LLSDType::Integer,
+            LLSDValue::Integer64(_) => LLSDType::Integer64,
             LLSDValue::Real(_) => LLSDType::Real,
             LLSDValue::String(_) => LLSDType::String,
             LLSDValue::UUID(_) => LLSDType::UUID,
@@ -82,6 +378,25 @@ impl LLSDValue {
         }
     }
 
+    /// Lowercase name of this value's type, as used by validators and in
+    /// error messages. Both integer widths are `"integer"`, matching the
+    /// XML element name.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            LLSDValue::Undefined => "undefined",
+            LLSDValue::Boolean(_) => "boolean",
+            LLSDValue::Integer(_) | LLSDValue::Integer64(_) => "integer",
+            LLSDValue::Real(_) => "real",
+            LLSDValue::String(_) => "string",
+            LLSDValue::UUID(_) => "uuid",
+            LLSDValue::Date(_) => "date",
+            LLSDValue::URI(_) => "uri",
+            LLSDValue::Binary(_) => "binary",
+            LLSDValue::Map(_) => "map",
+            LLSDValue::Array(_) => "array",
+        }
+    }
+
     /// Check if this value is undefined
     pub fn is_undefined(&self) -> bool {
         matches!(self, LLSDValue::Undefined)
@@ -95,19 +410,40 @@ impl LLSDValue {
         }
     }
 
-    /// Try to get this value as an integer
+    /// Try to get this value as an integer, narrowing a 64-bit integer
+    /// only if it fits
     pub fn as_integer(&self) -> Option<i32> {
         match self {
             LLSDValue::Integer(i) => Some(*i),
+            LLSDValue::Integer64(i) => i32::try_from(*i).ok(),
             _ => None,
         }
     }
 
+    /// Try to get this value as a 64-bit integer
+    pub fn as_integer64(&self) -> Option<i64> {
+        match self {
+            LLSDValue::Integer(i) => Some(*i as i64),
+            LLSDValue::Integer64(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    /// Build the narrowest integer value that holds `value`: `Integer` when
+    /// it fits in 32 bits, otherwise `Integer64`
+    pub fn from_i64(value: i64) -> Self {
+        match i32::try_from(value) {
+            Ok(i) => LLSDValue::Integer(i),
+            Err(_) => LLSDValue::Integer64(value),
+        }
+    }
+
     /// Try to get this value as a real number
     pub fn as_real(&self) -> Option<f64> {
         match self {
             LLSDValue::Real(r) => Some(*r),
             LLSDValue::Integer(i) => Some(*i as f64),
+            LLSDValue::Integer64(i) => Some(*i as f64),
             _ => None,
         }
     }
@@ -185,6 +521,75 @@ impl LLSDValue {
         }
     }
 
+    /// Convert this value to `target`, as a form or query string loader
+    /// would: text is parsed as the target type, numbers convert between
+    /// widths when the value fits, and booleans are 0 or 1.
+    ///
+    /// Strings become booleans from `true`/`false`/`1`/`0` in any case, and
+    /// scalars become strings through their usual text form. Containers and
+    /// `Undefined` only convert to their own type. Anything else is
+    /// [`LLSDError::TypeMismatch`].
+    pub fn coerce_to(&self, target: LLSDType) -> LLSDResult<LLSDValue> {
+        if self.get_type() == target {
+            return Ok(self.clone());
+        }
+
+        let text = self.as_string().map(str::trim);
+        let coerced = match target {
+            LLSDType::Boolean => match self {
+                LLSDValue::Integer(_) | LLSDValue::Integer64(_) => self.as_integer64().map(|i| LLSDValue::Boolean(i != 0)),
+                LLSDValue::Real(r) => Some(LLSDValue::Boolean(*r != 0.0)),
+                _ => text.and_then(|t| match t.to_ascii_lowercase().as_str() {
+                    "true" | "1" => Some(LLSDValue::Boolean(true)),
+                    "false" | "0" => Some(LLSDValue::Boolean(false)),
+                    _ => None,
+                }),
+            },
+            LLSDType::Integer | LLSDType::Integer64 => {
+                let wide = match self {
+                    LLSDValue::Boolean(b) => Some(*b as i64),
+                    LLSDValue::Real(r) if r.is_finite() && r.fract() == 0.0 && r.abs() < i64::MAX as f64 => Some(*r as i64),
+                    _ => self.as_integer64().or_else(|| text.and_then(|t| t.parse().ok())),
+                };
+                match target {
+                    LLSDType::Integer => wide.and_then(|i| i32::try_from(i).ok()).map(LLSDValue::Integer),
+                    _ => wide.map(LLSDValue::Integer64),
+                }
+            }
+            LLSDType::Real => match self {
+                LLSDValue::Boolean(b) => Some(LLSDValue::Real(if *b { 1.0 } else { 0.0 })),
+                _ => self.as_real().or_else(|| text.and_then(|t| t.parse().ok())).map(LLSDValue::Real),
+            },
+            LLSDType::String => match self {
+                LLSDValue::Boolean(b) => Some(LLSDValue::String(b.to_string())),
+                LLSDValue::Integer(i) => Some(LLSDValue::String(i.to_string())),
+                LLSDValue::Integer64(i) => Some(LLSDValue::String(i.to_string())),
+                LLSDValue::Real(r) => Some(LLSDValue::String(r.to_string())),
+                LLSDValue::UUID(u) => Some(LLSDValue::String(u.to_string())),
+                LLSDValue::Date(d) => Some(LLSDValue::String(d.to_rfc3339())),
+                LLSDValue::URI(u) => Some(LLSDValue::String(u.clone())),
+                _ => None,
+            },
+            LLSDType::UUID => text.and_then(|t| Uuid::parse_str(t).ok()).map(LLSDValue::UUID),
+            LLSDType::Date => text
+                .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+                .map(|d| LLSDValue::Date(d.with_timezone(&Utc))),
+            LLSDType::URI => match self {
+                LLSDValue::String(s) => Some(LLSDValue::URI(s.clone())),
+                _ => None,
+            },
+            LLSDType::Binary | LLSDType::Map | LLSDType::Array | LLSDType::Unknown => None,
+        };
+
+        coerced.ok_or_else(|| {
+            let actual = match text {
+                Some(t) => format!("{} '{}'", self.type_name(), t),
+                None => self.type_name().to_string(),
+            };
+            LLSDError::type_mismatch(format!("value coercible to {:?}", target), actual)
+        })
+    }
+
     /// Get a nested value using dot notation path
     pub fn get_path(&self, path: &str) -> Option<&LLSDValue> {
         let parts: Vec<&str> = path.split('.').collect();
@@ -206,6 +611,75 @@ impl LLSDValue {
         Some(current)
     }
 
+    /// Like [`get_path`](Self::get_path), but explains a failed lookup.
+    ///
+    /// A missing map key or out-of-range index is
+    /// [`LLSDError::PathNotFound`] with the path up to the failing segment.
+    /// Indexing into a scalar, or into an array with a non-numeric segment,
+    /// is [`LLSDError::TypeMismatch`] naming the segment.
+    pub fn try_get_path(&self, path: &str) -> LLSDResult<&LLSDValue> {
+        let mut current = self;
+        let mut parent: Option<&str> = None;
+        let mut consumed = 0;
+
+        for part in path.split('.') {
+            consumed += part.len();
+            let prefix = &path[..consumed];
+            consumed += 1;
+
+            current = match current {
+                LLSDValue::Map(map) => map.get(part).ok_or_else(|| LLSDError::path_not_found(prefix))?,
+                LLSDValue::Array(arr) => {
+                    let index: usize = part.parse().map_err(|_| {
+                        LLSDError::type_mismatch("array index".to_string(), format!("'{}' in '{}'", part, prefix))
+                    })?;
+                    arr.get(index).ok_or_else(|| LLSDError::path_not_found(prefix))?
+                }
+                scalar => {
+                    let expected = match parent {
+                        Some(segment) => format!("container at segment '{}'", segment),
+                        None => "container at the root".to_string(),
+                    };
+                    return Err(LLSDError::type_mismatch(expected, scalar.type_name().to_string()));
+                }
+            };
+            parent = Some(part);
+        }
+
+        Ok(current)
+    }
+
+    /// Extract the field `key` of a map as `T`.
+    ///
+    /// ```
+    /// # use llsd::LLSDValue;
+    /// # use std::collections::HashMap;
+    /// let person = LLSDValue::Map(HashMap::from([("age".to_string(), LLSDValue::Integer(42))]));
+    /// let age: i32 = person.get_map_field("age")?;
+    /// assert_eq!(age, 42);
+    /// # Ok::<(), llsd::LLSDError>(())
+    /// ```
+    ///
+    /// An absent key is [`LLSDError::MissingField`]. A field of the wrong
+    /// type, or a value that is not a map, is [`LLSDError::TypeMismatch`]
+    /// naming the field.
+    pub fn get_map_field<'a, T>(&'a self, key: &str) -> LLSDResult<T>
+    where
+        T: TryFrom<&'a LLSDValue, Error = LLSDError>,
+    {
+        let map = self.as_map().ok_or_else(|| {
+            LLSDError::type_mismatch(format!("map holding field '{}'", key), self.type_name().to_string())
+        })?;
+        let value = map.get(key).ok_or_else(|| LLSDError::missing_field(key))?;
+
+        T::try_from(value).map_err(|err| match err {
+            LLSDError::TypeMismatch { expected, actual } => {
+                LLSDError::type_mismatch(format!("{} for field '{}'", expected, key), actual)
+            }
+            other => other,
+        })
+    }
+
     /// Set a nested value using dot notation path
     pub fn set_path(&mut self, path: &str, value: LLSDValue) -> bool {
         let parts: Vec<&str> = path.split('.').collect();
@@ -262,11 +736,164 @@ impl LLSDValue {
             _ => false,
         }
     }
+
+    /// Set a nested value using dot notation path, creating missing
+    /// intermediate maps along the way.
+    ///
+    /// Arrays are never extended: a numeric segment must name an existing
+    /// element, and missing nodes are only created for non-numeric keys.
+    /// On failure the value is left unchanged.
+    pub fn set_path_create(&mut self, path: &str, value: LLSDValue) -> bool {
+        let parts: Vec<&str> = path.split('.').collect();
+        if !self.can_create_path(&parts) {
+            return false;
+        }
+
+        let (last_part, parents) = match parts.split_last() {
+            Some(split) => split,
+            None => return false,
+        };
+
+        let mut current = self;
+        for part in parents {
+            current = match current {
+                LLSDValue::Map(map) => map
+                    .entry(part.to_string())
+                    .or_insert_with(|| LLSDValue::Map(HashMap::new())),
+                LLSDValue::Array(arr) => match part.parse::<usize>().ok().and_then(|i| arr.get_mut(i)) {
+                    Some(next) => next,
+                    None => return false,
+                },
+                _ => return false,
+            };
+        }
+
+        current.set_path(last_part, value)
+    }
+
+    /// Check that `set_path_create` can succeed without mutating anything
+    fn can_create_path(&self, parts: &[&str]) -> bool {
+        let mut current = self;
+        for (depth, part) in parts.iter().enumerate() {
+            let is_last = depth + 1 == parts.len();
+            match current {
+                LLSDValue::Map(map) => match map.get(*part) {
+                    Some(next) if !is_last => current = next,
+                    Some(_) => return true,
+                    None => {
+                        // Everything below here is created, and only maps
+                        // can be created
+                        let created = if is_last { &[][..] } else { &parts[depth..] };
+                        return created.iter().all(|p| p.parse::<usize>().is_err());
+                    }
+                },
+                LLSDValue::Array(arr) => match part.parse::<usize>().ok().and_then(|i| arr.get(i)) {
+                    Some(next) if !is_last => current = next,
+                    Some(_) => return true,
+                    None => return false,
+                },
+                _ => return false,
+            }
+        }
+        false
+    }
+
+    /// Iterate over the direct children of a map or array with their keys,
+    /// so one loop handles either container. Scalars yield nothing.
+    ///
+    /// Array elements come in order; map entries in unspecified order.
+    pub fn entries(&self) -> LLSDEntries<'_> {
+        let inner = match self {
+            LLSDValue::Map(map) => EntriesInner::Map(map.iter()),
+            LLSDValue::Array(arr) => EntriesInner::Array(arr.iter().enumerate()),
+            _ => EntriesInner::Empty,
+        };
+        LLSDEntries { inner }
+    }
+
+    /// Iterate mutably over every leaf (non-container) value in the structure
+    ///
+    /// Empty maps and arrays are containers, not leaves, so they are never yielded.
+    pub fn leaves_mut(&mut self) -> LeavesMut<'_> {
+        LeavesMut { stack: vec![self] }
+    }
+
+    /// Apply a function to every leaf value in place
+    pub fn for_each_leaf_mut<F: FnMut(&mut LLSDValue)>(&mut self, f: F) {
+        self.leaves_mut().for_each(f);
+    }
 }
 
-impl Default for LLSDValue {
-    fn default() -> Self {
-        LLSDValue::Undefined
+/// Key of a container entry yielded by [`LLSDValue::entries`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LLSDKey<'a> {
+    /// Key of a map entry
+    Str(&'a str),
+    /// Index of an array element
+    Index(usize),
+}
+
+/// Formats as the key or index, as used in a [`get_path`](LLSDValue::get_path) segment
+impl std::fmt::Display for LLSDKey<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LLSDKey::Str(key) => f.write_str(key),
+            LLSDKey::Index(index) => write!(f, "{}", index),
+        }
+    }
+}
+
+/// Iterator over the entries of a map or array, see [`LLSDValue::entries`]
+pub struct LLSDEntries<'a> {
+    inner: EntriesInner<'a>,
+}
+
+enum EntriesInner<'a> {
+    Map(std::collections::hash_map::Iter<'a, String, LLSDValue>),
+    Array(std::iter::Enumerate<std::slice::Iter<'a, LLSDValue>>),
+    Empty,
+}
+
+impl<'a> Iterator for LLSDEntries<'a> {
+    type Item = (LLSDKey<'a>, &'a LLSDValue);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.inner {
+            EntriesInner::Map(iter) => iter.next().map(|(key, value)| (LLSDKey::Str(key), value)),
+            EntriesInner::Array(iter) => iter.next().map(|(index, value)| (LLSDKey::Index(index), value)),
+            EntriesInner::Empty => None,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.inner {
+            EntriesInner::Map(iter) => iter.size_hint(),
+            EntriesInner::Array(iter) => iter.size_hint(),
+            EntriesInner::Empty => (0, Some(0)),
+        }
+    }
+}
+
+impl ExactSizeIterator for LLSDEntries<'_> {}
+
+/// Iterator over mutable references to the leaves of an LLSD structure
+pub struct LeavesMut<'a> {
+    stack: Vec<&'a mut LLSDValue>,
+}
+
+impl<'a> Iterator for LeavesMut<'a> {
+    type Item = &'a mut LLSDValue;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(value) = self.stack.pop() {
+            match value {
+                LLSDValue::Map(map) => self.stack.extend(map.values_mut()),
+                // Push in reverse so array elements are yielded in order
+                LLSDValue::Array(arr) => self.stack.extend(arr.iter_mut().rev()),
+                leaf => return Some(leaf),
+            }
+        }
+        None
     }
 }
 
@@ -282,6 +909,12 @@ impl From<i32> for LLSDValue {
     }
 }
 
+impl From<i64> for LLSDValue {
+    fn from(value: i64) -> Self {
+        LLSDValue::Integer64(value)
+    }
+}
+
 impl From<f64> for LLSDValue {
     fn from(value: f64) -> Self {
         LLSDValue::Real(value)
@@ -330,23 +963,213 @@ impl From<Vec<LLSDValue>> for LLSDValue {
     }
 }
 
+macro_rules! impl_try_from_value {
+    ($target:ty, $accessor:ident, $expected:literal) => {
+        impl<'a> TryFrom<&'a LLSDValue> for $target {
+            type Error = LLSDError;
+
+            fn try_from(value: &'a LLSDValue) -> LLSDResult<Self> {
+                value
+                    .$accessor()
+                    .map(Into::into)
+                    .ok_or_else(|| LLSDError::type_mismatch($expected.to_string(), value.type_name().to_string()))
+            }
+        }
+    };
+}
+
+impl_try_from_value!(bool, as_boolean, "boolean");
+impl_try_from_value!(i32, as_integer, "32-bit integer");
+impl_try_from_value!(i64, as_integer64, "integer");
+impl_try_from_value!(f64, as_real, "real");
+impl_try_from_value!(&'a str, as_string, "string");
+impl_try_from_value!(String, as_string, "string");
+impl_try_from_value!(Uuid, as_uuid, "uuid");
+impl_try_from_value!(DateTime<Utc>, as_date, "date");
+impl_try_from_value!(&'a [u8], as_binary, "binary");
+impl_try_from_value!(Vec<u8>, as_binary, "binary");
+
+/// Which side [`LLSDUtils::merge_with`](crate::LLSDUtils::merge_with) keeps when two
+/// values conflict
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScalarConflict {
+    /// Take the overlay value, like [`LLSDUtils::merge_maps`](crate::LLSDUtils::merge_maps)
+    #[default]
+    OverlayWins,
+    /// Keep the base value
+    BaseWins,
+}
+
+/// How [`LLSDUtils::merge_with`](crate::LLSDUtils::merge_with) combines two arrays
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ArrayMerge {
+    /// Treat the arrays as single values in conflict, settled by
+    /// [`MergeStrategy::on_scalar_conflict`]
+    #[default]
+    Replace,
+    /// Append every overlay item to the base array
+    Concat,
+    /// Append overlay items not already in the base array
+    Union,
+}
+
+/// Conflict handling for [`LLSDUtils::merge_with`](crate::LLSDUtils::merge_with). The
+/// default matches [`LLSDUtils::merge_maps`](crate::LLSDUtils::merge_maps).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MergeStrategy {
+    pub on_scalar_conflict: ScalarConflict,
+    pub on_array: ArrayMerge,
+}
+
+/// Merge `overlay` into `base` as [`LLSDUtils::merge_with`](crate::LLSDUtils::merge_with)
+/// describes, adding the dot notation path of each `base` value that was
+/// overwritten to `replaced`. `path` is the path of `base`.
+pub(crate) fn merge_values<'a>(
+    base: &mut LLSDValue,
+    overlay: &'a LLSDValue,
+    strategy: MergeStrategy,
+    path: &mut Vec<&'a str>,
+    replaced: &mut Vec<String>,
+) {
+    match (&mut *base, overlay) {
+        (LLSDValue::Map(base_map), LLSDValue::Map(overlay_map)) => {
+            for (key, value) in overlay_map {
+                match base_map.get_mut(key) {
+                    Some(existing) => {
+                        path.push(key);
+                        merge_values(existing, value, strategy, path, replaced);
+                        path.pop();
+                    }
+                    None => {
+                        base_map.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (LLSDValue::Array(base_items), LLSDValue::Array(overlay_items))
+            if strategy.on_array != ArrayMerge::Replace =>
+        {
+            for item in overlay_items {
+                if strategy.on_array == ArrayMerge::Concat || !base_items.contains(item) {
+                    base_items.push(item.clone());
+                }
+            }
+        }
+        _ => {
+            if strategy.on_scalar_conflict == ScalarConflict::OverlayWins {
+                *base = overlay.clone();
+                replaced.push(path.join("."));
+            }
+        }
+    }
+}
+
+/// Metadata key for the format a document was parsed from, see
+/// [`LLSDFormat::name`]
+pub const METADATA_SOURCE_FORMAT: &str = "source_format";
+/// Metadata key for the size in bytes of the payload a document was parsed
+/// from
+pub const METADATA_BYTE_LENGTH: &str = "byte_length";
+/// Metadata key for the array of metadata maps of the documents merged into
+/// this one by [`LLSDDocument::merge`], oldest first
+pub const METADATA_MERGED_FROM: &str = "merged_from";
+
 /// LLSD Document container
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+///
+/// Equality compares only the content; use [`deep_eq`](Self::deep_eq) to
+/// include the recorded date offsets and [`metadata`](Self::metadata).
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LLSDDocument {
     content: LLSDValue,
+    /// Original UTC offsets of dates, keyed by dot notation path
+    #[serde(skip)]
+    date_offsets: HashMap<String, FixedOffset>,
+    /// Out-of-band annotations, never serialized as LLSD
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    metadata: HashMap<String, LLSDValue>,
+}
+
+impl PartialEq for LLSDDocument {
+    fn eq(&self, other: &Self) -> bool {
+        self.content == other.content
+    }
 }
 
 impl LLSDDocument {
     /// Create a new LLSD document with the given content
     pub fn new(content: LLSDValue) -> Self {
-        Self { content }
+        Self {
+            content,
+            date_offsets: HashMap::new(),
+            metadata: HashMap::new(),
+        }
+    }
+
+    /// Replace the document's metadata
+    pub fn with_metadata(mut self, metadata: HashMap<String, LLSDValue>) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Annotations kept beside the content, such as the
+    /// [`METADATA_SOURCE_FORMAT`] and [`METADATA_BYTE_LENGTH`] that
+    /// [`LLSDFactory`](crate::LLSDFactory) parse methods record. Serializers
+    /// ignore them.
+    pub fn metadata(&self) -> &HashMap<String, LLSDValue> {
+        &self.metadata
+    }
+
+    /// Set one metadata entry, replacing any previous value
+    pub fn set_metadata_entry(&mut self, key: impl Into<String>, value: LLSDValue) {
+        self.metadata.insert(key.into(), value);
+    }
+
+    /// Equality including recorded date offsets and metadata
+    pub fn deep_eq(&self, other: &Self) -> bool {
+        self == other && self.date_offsets == other.date_offsets && self.metadata == other.metadata
+    }
+
+    /// Merge `other`'s content into this document with
+    /// [`LLSDUtils::merge_with`](crate::LLSDUtils::merge_with), appending
+    /// `other`'s metadata to [`METADATA_MERGED_FROM`]. `other`'s own merge
+    /// history stays nested in its entry.
+    ///
+    /// Date offsets `other` recorded are kept for the dates it contributed,
+    /// and this document's offsets are dropped for values `other` replaced.
+    pub fn merge(&mut self, other: &LLSDDocument, strategy: MergeStrategy) {
+        let mut replaced = Vec::new();
+        merge_values(&mut self.content, &other.content, strategy, &mut Vec::new(), &mut replaced);
+        self.date_offsets.retain(|path, _| {
+            !replaced.iter().any(|prefix| {
+                prefix.is_empty()
+                    || path == prefix
+                    || path.strip_prefix(prefix.as_str()).is_some_and(|rest| rest.starts_with('.'))
+            })
+        });
+
+        for (path, offset) in &other.date_offsets {
+            if Self::value_at(&self.content, path) == Self::value_at(&other.content, path) {
+                self.date_offsets.insert(path.clone(), *offset);
+            }
+        }
+
+        let breadcrumb = LLSDValue::Map(other.metadata.clone());
+        match self.metadata.get_mut(METADATA_MERGED_FROM) {
+            Some(LLSDValue::Array(merged_from)) => merged_from.push(breadcrumb),
+            _ => {
+                self.metadata.insert(METADATA_MERGED_FROM.to_string(), LLSDValue::Array(vec![breadcrumb]));
+            }
+        }
+    }
+
+    /// Value at a dot notation path, with the empty path for the root
+    fn value_at<'a>(content: &'a LLSDValue, path: &str) -> Option<&'a LLSDValue> {
+        if path.is_empty() { Some(content) } else { content.get_path(path) }
     }
 
     /// Create a new empty LLSD document
     pub fn empty() -> Self {
-        Self {
-            content: LLSDValue::Undefined,
-        }
+        Self::new(LLSDValue::Undefined)
     }
 
     /// Get the content of the document
@@ -354,14 +1177,46 @@ impl LLSDDocument {
         &self.content
     }
 
-    /// Get mutable access to the content of the document
+    /// Get mutable access to the content of the document. Recorded date
+    /// offsets are cleared, as edits can move or replace the dates they
+    /// describe; record them again with [`set_date_offset`](Self::set_date_offset).
     pub fn content_mut(&mut self) -> &mut LLSDValue {
+        self.date_offsets.clear();
         &mut self.content
     }
 
-    /// Set the content of the document
+    /// Set the content of the document. Recorded date offsets are cleared.
     pub fn set_content(&mut self, content: LLSDValue) {
         self.content = content;
+        self.date_offsets.clear();
+    }
+
+    /// Offset a date was originally written with, if one was recorded.
+    ///
+    /// Dates are always stored as UTC; offsets are kept beside the content
+    /// by [`LLSDXmlParser::with_preserve_offset`](crate::LLSDXmlParser::with_preserve_offset)
+    /// so serializers can write the date back as it arrived. The root value
+    /// has the empty path.
+    pub fn date_offset(&self, path: &str) -> Option<FixedOffset> {
+        self.date_offsets.get(path).copied()
+    }
+
+    /// Record the offset the date at `path` should be written with
+    pub fn set_date_offset(&mut self, path: impl Into<String>, offset: FixedOffset) {
+        self.date_offsets.insert(path.into(), offset);
+    }
+
+    /// All recorded date offsets, keyed by path
+    pub fn date_offsets(&self) -> &HashMap<String, FixedOffset> {
+        &self.date_offsets
+    }
+
+    /// The date at `path` in its recorded offset, or in UTC if none was
+    /// recorded
+    pub fn date_with_offset(&self, path: &str) -> Option<DateTime<FixedOffset>> {
+        let date = Self::value_at(&self.content, path)?.as_date()?;
+        let offset = self.date_offset(path).unwrap_or_else(|| FixedOffset::east_opt(0).unwrap());
+        Some(date.with_timezone(&offset))
     }
 
     /// Get the type of the root content
@@ -5,15 +5,345 @@
  * Copyright (C) 2024 Linden Lab
  */
 
-use crate::types::{LLSDValue, LLSDType};
+use crate::types::{merge_values, LLSDType, LLSDValue};
+pub use crate::types::{ArrayMerge, MergeStrategy, ScalarConflict};
+use crate::visitor::LLSDVisitor;
 use crate::error::{LLSDError, LLSDResult};
 use uuid::Uuid;
+use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::time::{Duration, Instant};
 use chrono::{DateTime, Utc};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
 
 /// Utility functions for working with LLSD data
 pub struct LLSDUtils;
 
+/// Steps [`LLSDUtils::normalize`] applies. The default applies none;
+/// [`NormalizeOptions::all`] applies every one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct NormalizeOptions {
+    /// Remove map entries whose value is `Undefined`
+    pub drop_undefined: bool,
+    /// Write whole-number reals and small 64-bit integers as the narrowest
+    /// integer type that holds them exactly
+    pub canonical_numbers: bool,
+    /// Remove map entries whose value is an empty map or array, including
+    /// ones left empty by the other steps
+    pub trim_empty: bool,
+}
+
+impl NormalizeOptions {
+    /// Every normalization step
+    pub fn all() -> Self {
+        Self { drop_undefined: true, canonical_numbers: true, trim_empty: true }
+    }
+}
+
+/// Why two values differ at a path reported by [`LLSDUtils::diff`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffReason {
+    /// The values have different LLSD types
+    TypeMismatch,
+    /// The values have the same type but different contents
+    ValueMismatch,
+    /// The right value has an entry the left one lacks
+    MissingInLeft,
+    /// The left value has an entry the right one lacks
+    MissingInRight,
+}
+
+/// One difference between two values, at a dot notation path (empty for
+/// the root)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LLSDDiff {
+    pub path: String,
+    pub reason: DiffReason,
+}
+
+impl std::fmt::Display for LLSDDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let path = if self.path.is_empty() { "<root>" } else { &self.path };
+        write!(f, "{}: {:?}", path, self.reason)
+    }
+}
+
+/// Writer that refuses to grow past an optional byte limit; serializers
+/// use it to stop as soon as the output is too large
+pub(crate) struct LimitedWriter<W> {
+    inner: W,
+    written: usize,
+    limit: Option<usize>,
+    exceeded: bool,
+}
+
+impl<W: std::io::Write> LimitedWriter<W> {
+    pub(crate) fn new(inner: W, limit: Option<usize>) -> Self {
+        Self { inner, written: 0, limit, exceeded: false }
+    }
+
+    /// Replace `err` with [`LLSDError::OutputTooLarge`] if it was caused
+    /// by the limit
+    pub(crate) fn map_error(&self, err: LLSDError) -> LLSDError {
+        match self.limit {
+            Some(limit) if self.exceeded => LLSDError::OutputTooLarge { limit },
+            _ => err,
+        }
+    }
+}
+
+impl<W: std::io::Write> std::io::Write for LimitedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if let Some(limit) = self.limit {
+            if self.written + buf.len() > limit {
+                self.exceeded = true;
+                return Err(std::io::Error::other("output size limit exceeded"));
+            }
+        }
+        let n = self.inner.write(buf)?;
+        self.written += n;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Reader that counts the bytes read through it
+pub(crate) struct CountingReader<R> {
+    inner: R,
+    count: usize,
+}
+
+impl<R: std::io::Read> CountingReader<R> {
+    pub(crate) fn new(inner: R) -> Self {
+        Self { inner, count: 0 }
+    }
+
+    /// Bytes read so far
+    pub(crate) fn count(&self) -> usize {
+        self.count
+    }
+}
+
+impl<R: std::io::Read> std::io::Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count += n;
+        Ok(n)
+    }
+}
+
+/// Resource limits for one parse of untrusted input, on top of the depth
+/// and element limits. Limits left as `None` are not enforced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ParseBudget {
+    /// Most bytes the parsed value may occupy: the inline size of every
+    /// value plus the contents of its strings, map keys and binary data
+    pub max_bytes: Option<usize>,
+    /// Most wall-clock time the parse may take
+    pub max_duration: Option<Duration>,
+}
+
+/// Number of values parsed between time budget checks
+pub(crate) const BUDGET_CHECK_INTERVAL: usize = 64;
+
+/// Progress of one parse against its [`ParseBudget`]
+pub(crate) struct BudgetMeter {
+    budget: ParseBudget,
+    deadline: Option<Instant>,
+    bytes: usize,
+    values_until_check: usize,
+}
+
+impl BudgetMeter {
+    /// Start the clock on `budget`
+    pub(crate) fn start(budget: ParseBudget) -> Self {
+        Self {
+            budget,
+            deadline: budget.max_duration.map(|duration| Instant::now() + duration),
+            bytes: 0,
+            values_until_check: 0,
+        }
+    }
+
+    /// Account for one more parsed value, checking the time budget every
+    /// [`BUDGET_CHECK_INTERVAL`] values
+    pub(crate) fn charge_value(&mut self) -> LLSDResult<()> {
+        if self.values_until_check == 0 {
+            self.check_time()?;
+            self.values_until_check = BUDGET_CHECK_INTERVAL;
+        }
+        self.values_until_check -= 1;
+        self.charge(std::mem::size_of::<LLSDValue>())
+    }
+
+    /// Account for `bytes` more of the parsed value
+    pub(crate) fn charge(&mut self, bytes: usize) -> LLSDResult<()> {
+        self.bytes = self.bytes.saturating_add(bytes);
+        match self.budget.max_bytes {
+            Some(limit) if self.bytes > limit => Err(LLSDError::ParseAborted {
+                reason: format!("parse budget exceeded: value needs more than {} bytes", limit),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Fail once the time budget is spent
+    pub(crate) fn check_time(&self) -> LLSDResult<()> {
+        match self.deadline {
+            Some(deadline) if Instant::now() >= deadline => Err(LLSDError::ParseAborted {
+                reason: format!("time budget of {:?} exceeded", self.budget.max_duration.unwrap_or_default()),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Type a config string as integer, real, boolean, UUID or string
+fn infer_scalar(raw: &str) -> LLSDValue {
+    if let Ok(i) = raw.parse::<i64>() {
+        return LLSDValue::from_i64(i);
+    }
+    if let Ok(r) = raw.parse::<f64>() {
+        // "inf" and "nan" are more likely words than numbers here
+        if r.is_finite() {
+            return LLSDValue::Real(r);
+        }
+    }
+    if raw.eq_ignore_ascii_case("true") {
+        return LLSDValue::Boolean(true);
+    }
+    if raw.eq_ignore_ascii_case("false") {
+        return LLSDValue::Boolean(false);
+    }
+    // Hyphenated form only, so hex digests stay strings
+    if raw.len() == 36 {
+        if let Ok(uuid) = Uuid::parse_str(raw) {
+            return LLSDValue::UUID(uuid);
+        }
+    }
+    LLSDValue::String(raw.to_string())
+}
+
+/// Percent-encode everything but RFC 3986 unreserved characters
+fn percent_encode(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    out
+}
+
+/// Decode `%XX` escapes and `+`; malformed escapes are kept as written
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' => {
+                let byte = text
+                    .get(i + 1..i + 3)
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                if let Some(byte) = byte {
+                    out.push(byte);
+                    i += 3;
+                    continue;
+                }
+                out.push(b'%');
+            }
+            other => out.push(other),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// A container [`LLSDUtils::deep_clone_safe`] is part way through copying
+enum CloneFrame<'a> {
+    Array {
+        items: std::slice::Iter<'a, LLSDValue>,
+        copy: Vec<LLSDValue>,
+    },
+    Map {
+        entries: std::collections::hash_map::Iter<'a, String, LLSDValue>,
+        key: Option<&'a str>,
+        copy: HashMap<String, LLSDValue>,
+    },
+}
+
+impl<'a> CloneFrame<'a> {
+    /// Store the copy of the child last returned by `next_child`
+    fn push(&mut self, child: LLSDValue) {
+        match self {
+            CloneFrame::Array { copy, .. } => copy.push(child),
+            CloneFrame::Map { key, copy, .. } => {
+                if let Some(key) = key.take() {
+                    copy.insert(key.to_string(), child);
+                }
+            }
+        }
+    }
+
+    fn next_child(&mut self) -> Option<&'a LLSDValue> {
+        match self {
+            CloneFrame::Array { items, .. } => items.next(),
+            CloneFrame::Map { entries, key, .. } => entries.next().map(|(k, v)| {
+                *key = Some(k.as_str());
+                v
+            }),
+        }
+    }
+
+    fn finish(self) -> LLSDValue {
+        match self {
+            CloneFrame::Array { copy, .. } => LLSDValue::Array(copy),
+            CloneFrame::Map { copy, .. } => LLSDValue::Map(copy),
+        }
+    }
+}
+
+/// Order two values for [`LLSDUtils::sort_array_by_path`]
+fn compare_values(a: &LLSDValue, b: &LLSDValue) -> Ordering {
+    fn rank(value: &LLSDValue) -> u8 {
+        match value {
+            LLSDValue::Undefined => 0,
+            LLSDValue::Boolean(_) => 1,
+            LLSDValue::Integer(_) | LLSDValue::Integer64(_) | LLSDValue::Real(_) => 2,
+            LLSDValue::String(_) | LLSDValue::URI(_) => 3,
+            LLSDValue::UUID(_) => 4,
+            LLSDValue::Date(_) => 5,
+            LLSDValue::Binary(_) => 6,
+            LLSDValue::Map(_) => 7,
+            LLSDValue::Array(_) => 8,
+        }
+    }
+
+    // Exact for 64-bit integers that would lose precision as reals
+    if let (Some(x), Some(y)) = (a.as_integer64(), b.as_integer64()) {
+        return x.cmp(&y);
+    }
+    match (a, b) {
+        (LLSDValue::Boolean(x), LLSDValue::Boolean(y)) => x.cmp(y),
+        (LLSDValue::UUID(x), LLSDValue::UUID(y)) => x.cmp(y),
+        (LLSDValue::Date(x), LLSDValue::Date(y)) => x.cmp(y),
+        (LLSDValue::Binary(x), LLSDValue::Binary(y)) => x.cmp(y),
+        _ => match (a.as_real(), b.as_real(), a.as_string(), b.as_string()) {
+            (Some(x), Some(y), _, _) => x.total_cmp(&y),
+            (_, _, Some(x), Some(y)) => x.cmp(y),
+            _ => rank(a).cmp(&rank(b)),
+        },
+    }
+}
+
 impl LLSDUtils {
     /// Generate a random UUID
     pub fn generate_uuid() -> Uuid {
@@ -30,6 +360,50 @@ impl LLSDUtils {
         value.clone() // Rust's Clone trait already does deep cloning
     }
 
+    /// Deep clone an LLSD value without recursion, so arbitrarily deep
+    /// structures from untrusted input cannot overflow the stack.
+    ///
+    /// The result equals [`deep_clone`](Self::deep_clone)'s. Note that
+    /// dropping or comparing such a structure still recurses.
+    pub fn deep_clone_safe(value: &LLSDValue) -> LLSDValue {
+        let mut stack: Vec<CloneFrame> = Vec::new();
+        let mut current = value;
+        loop {
+            let mut copied = match current {
+                LLSDValue::Array(items) => {
+                    stack.push(CloneFrame::Array { items: items.iter(), copy: Vec::with_capacity(items.len()) });
+                    None
+                }
+                LLSDValue::Map(map) => {
+                    stack.push(CloneFrame::Map {
+                        entries: map.iter(),
+                        key: None,
+                        copy: HashMap::with_capacity_and_hasher(map.len(), map.hasher().clone()),
+                    });
+                    None
+                }
+                scalar => Some(scalar.clone()),
+            };
+
+            // Hand finished copies up until some container has a child left
+            loop {
+                let Some(frame) = stack.last_mut() else {
+                    return copied.expect("root value copied");
+                };
+                if let Some(child) = copied.take() {
+                    frame.push(child);
+                }
+                match frame.next_child() {
+                    Some(child) => {
+                        current = child;
+                        break;
+                    }
+                    None => copied = stack.pop().map(CloneFrame::finish),
+                }
+            }
+        }
+    }
+
     /// Get a nested value using dot notation path with a default value
     pub fn get_value<'a>(root: &'a LLSDValue, path: &str, default: &'a LLSDValue) -> &'a LLSDValue {
         root.get_path(path).unwrap_or(default)
@@ -44,10 +418,26 @@ impl LLSDUtils {
         }
     }
 
+    /// Get a string or URI value at a path as an owned string; `None` if the
+    /// path is missing or holds another type
+    pub fn get_string_opt(root: &LLSDValue, path: &str) -> Option<String> {
+        Self::get_str(root, path).map(str::to_string)
+    }
+
+    /// Borrow a string or URI value at a path without allocating.
+    /// Prefer this over [`get_string`](Self::get_string) when reading many values.
+    pub fn get_str<'a>(root: &'a LLSDValue, path: &str) -> Option<&'a str> {
+        match root.get_path(path) {
+            Some(LLSDValue::String(s)) | Some(LLSDValue::URI(s)) => Some(s.as_str()),
+            _ => None,
+        }
+    }
+
     /// Safely get an integer value from a path
     pub fn get_integer(root: &LLSDValue, path: &str, default: i32) -> i32 {
         match root.get_path(path) {
             Some(LLSDValue::Integer(i)) => *i,
+            Some(LLSDValue::Integer64(i)) => i32::try_from(*i).unwrap_or(default),
             Some(LLSDValue::Real(r)) => *r as i32,
             _ => default,
         }
@@ -58,6 +448,7 @@ impl LLSDUtils {
         match root.get_path(path) {
             Some(LLSDValue::Real(r)) => *r,
             Some(LLSDValue::Integer(i)) => *i as f64,
+            Some(LLSDValue::Integer64(i)) => *i as f64,
             _ => default,
         }
     }
@@ -102,6 +493,68 @@ impl LLSDUtils {
         }
     }
 
+    /// List the differences between two values.
+    ///
+    /// Maps and arrays are compared entry by entry, so a single changed leaf
+    /// deep in a tree is reported as one entry at its path. Map keys are
+    /// visited in sorted order. NaN reals compare equal to each other.
+    pub fn diff(a: &LLSDValue, b: &LLSDValue) -> Vec<LLSDDiff> {
+        let mut diffs = Vec::new();
+        Self::diff_into(a, b, &mut String::new(), &mut diffs);
+        diffs
+    }
+
+    fn diff_into(a: &LLSDValue, b: &LLSDValue, path: &mut String, diffs: &mut Vec<LLSDDiff>) {
+        match (a, b) {
+            (LLSDValue::Map(left), LLSDValue::Map(right)) => {
+                let mut keys: Vec<&String> = left.keys().collect();
+                keys.extend(right.keys().filter(|key| !left.contains_key(*key)));
+                keys.sort_unstable();
+                for key in keys {
+                    Self::diff_entry(left.get(key), right.get(key), key, path, diffs);
+                }
+            }
+            (LLSDValue::Array(left), LLSDValue::Array(right)) => {
+                for index in 0..left.len().max(right.len()) {
+                    Self::diff_entry(left.get(index), right.get(index), &index.to_string(), path, diffs);
+                }
+            }
+            _ => {
+                let reason = if a.get_type() != b.get_type() {
+                    Some(DiffReason::TypeMismatch)
+                } else if a != b && !matches!((a, b), (LLSDValue::Real(l), LLSDValue::Real(r)) if l.is_nan() && r.is_nan()) {
+                    Some(DiffReason::ValueMismatch)
+                } else {
+                    None
+                };
+                if let Some(reason) = reason {
+                    diffs.push(LLSDDiff { path: path.clone(), reason });
+                }
+            }
+        }
+    }
+
+    /// Compare one map entry or array element, extending `path` by `segment`
+    fn diff_entry(
+        left: Option<&LLSDValue>,
+        right: Option<&LLSDValue>,
+        segment: &str,
+        path: &mut String,
+        diffs: &mut Vec<LLSDDiff>,
+    ) {
+        let len = path.len();
+        if !path.is_empty() {
+            path.push('.');
+        }
+        path.push_str(segment);
+        match (left, right) {
+            (Some(l), Some(r)) => Self::diff_into(l, r, path, diffs),
+            (Some(_), None) => diffs.push(LLSDDiff { path: path.clone(), reason: DiffReason::MissingInRight }),
+            (None, _) => diffs.push(LLSDDiff { path: path.clone(), reason: DiffReason::MissingInLeft }),
+        }
+        path.truncate(len);
+    }
+
     /// Check if two LLSD values are equal with floating-point tolerance
     pub fn equals_with_tolerance(a: &LLSDValue, b: &LLSDValue, tolerance: f64) -> bool {
         match (a, b) {
@@ -118,6 +571,148 @@ impl LLSDUtils {
         }
     }
 
+    /// Check whether `value` matches a partial `template`.
+    ///
+    /// Every key in a template map must be present in the value's map and
+    /// match recursively; extra keys in the value are ignored. A template
+    /// array matches an array with at least as many elements whose leading
+    /// elements match in order. Template scalars must be equal.
+    pub fn matches_template(value: &LLSDValue, template: &LLSDValue) -> bool {
+        match (value, template) {
+            (LLSDValue::Map(map), LLSDValue::Map(template_map)) => {
+                template_map.iter().all(|(key, expected)| {
+                    map.get(key).is_some_and(|actual| Self::matches_template(actual, expected))
+                })
+            }
+            (LLSDValue::Array(arr), LLSDValue::Array(template_arr)) => {
+                template_arr.len() <= arr.len()
+                    && arr.iter().zip(template_arr).all(|(actual, expected)| {
+                        Self::matches_template(actual, expected)
+                    })
+            }
+            _ => value == template,
+        }
+    }
+
+    /// Check that `roundtripped` is what a JSON round trip of `original`
+    /// may legitimately produce.
+    ///
+    /// JSON has no UUID, date, URI or binary types, and does not keep the
+    /// distinction between whole reals and integers, so these are treated
+    /// as equal:
+    /// - a UUID, date or URI and a string holding its text form
+    /// - binary data and a string holding its base64 encoding
+    /// - a whole real and the equal integer, and integers of either width
+    ///
+    /// Maps and arrays must match in shape; everything else must be equal.
+    pub fn equals_after_json(original: &LLSDValue, roundtripped: &LLSDValue) -> bool {
+        match (original, roundtripped) {
+            (LLSDValue::Map(a), LLSDValue::Map(b)) => {
+                a.len() == b.len()
+                    && a.iter().all(|(key, value)| {
+                        b.get(key).is_some_and(|other| Self::equals_after_json(value, other))
+                    })
+            }
+            (LLSDValue::Array(a), LLSDValue::Array(b)) => {
+                a.len() == b.len()
+                    && a.iter().zip(b).all(|(value, other)| Self::equals_after_json(value, other))
+            }
+            _ if original == roundtripped => true,
+            (LLSDValue::Date(d), LLSDValue::String(s)) | (LLSDValue::String(s), LLSDValue::Date(d)) => {
+                DateTime::parse_from_rfc3339(s).is_ok_and(|parsed| parsed == *d)
+            }
+            (LLSDValue::UUID(u), LLSDValue::String(s)) | (LLSDValue::String(s), LLSDValue::UUID(u)) => {
+                Uuid::parse_str(s).is_ok_and(|parsed| parsed == *u)
+            }
+            _ => match (Self::json_number(original), Self::json_number(roundtripped)) {
+                (Some(a), Some(b)) => a == b,
+                _ => matches!(
+                    (Self::json_text(original), Self::json_text(roundtripped)),
+                    (Some(a), Some(b)) if a == b
+                ),
+            },
+        }
+    }
+
+    /// Numeric value of an integer or whole real, as JSON sees it
+    fn json_number(value: &LLSDValue) -> Option<i64> {
+        match value {
+            LLSDValue::Integer(i) => Some(*i as i64),
+            LLSDValue::Integer64(i) => Some(*i),
+            LLSDValue::Real(r) if r.fract() == 0.0 && r.abs() < i64::MAX as f64 => Some(*r as i64),
+            _ => None,
+        }
+    }
+
+    /// Text a string-like value is written as in JSON
+    fn json_text(value: &LLSDValue) -> Option<String> {
+        match value {
+            LLSDValue::String(s) | LLSDValue::URI(s) => Some(s.clone()),
+            LLSDValue::UUID(u) => Some(u.to_string()),
+            LLSDValue::Date(d) => Some(d.to_rfc3339()),
+            LLSDValue::Binary(b) => Some(BASE64.encode(b)),
+            _ => None,
+        }
+    }
+
+    /// Sort an array of maps by the value at `path` within each element.
+    /// Numbers compare by value whatever their width; other values compare
+    /// within their own type, and values of different types group by type.
+    /// Elements without the path go last in either direction, and the sort
+    /// is stable. Does nothing if `value` is not an array.
+    pub fn sort_array_by_path(value: &mut LLSDValue, path: &str, ascending: bool) {
+        if let LLSDValue::Array(items) = value {
+            items.sort_by(|a, b| match (a.get_path(path), b.get_path(path)) {
+                (Some(x), Some(y)) if ascending => compare_values(x, y),
+                (Some(x), Some(y)) => compare_values(y, x),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            });
+        }
+    }
+
+    /// Canonical copy of `value` for comparison and hashing, so documents
+    /// that differ only in the ways `options` selects compare equal.
+    ///
+    /// Map key order needs no step of its own, as maps compare without
+    /// regard to order; for ordered output see
+    /// [`LLSDXmlSerializer::with_sorted_keys`](crate::LLSDXmlSerializer::with_sorted_keys).
+    /// Array elements are normalized but never removed, so indices are kept.
+    pub fn normalize(value: &LLSDValue, options: NormalizeOptions) -> LLSDValue {
+        match value {
+            LLSDValue::Map(map) => LLSDValue::Map(
+                map.iter()
+                    .map(|(key, child)| (key, Self::normalize(child, options)))
+                    .filter(|(_, child)| !(options.drop_undefined && child.is_undefined()))
+                    .filter(|(_, child)| !(options.trim_empty && Self::is_empty_container(child)))
+                    .map(|(key, child)| (key.clone(), child))
+                    .collect(),
+            ),
+            LLSDValue::Array(items) => {
+                LLSDValue::Array(items.iter().map(|item| Self::normalize(item, options)).collect())
+            }
+            LLSDValue::Real(r) if options.canonical_numbers => {
+                // Exactly representable integers only
+                if r.fract() == 0.0 && r.abs() <= (1u64 << 53) as f64 {
+                    LLSDValue::from_i64(*r as i64)
+                } else {
+                    value.clone()
+                }
+            }
+            LLSDValue::Integer64(i) if options.canonical_numbers => LLSDValue::from_i64(*i),
+            _ => value.clone(),
+        }
+    }
+
+    fn is_empty_container(value: &LLSDValue) -> bool {
+        match value {
+            LLSDValue::Map(map) => map.is_empty(),
+            LLSDValue::Array(items) => items.is_empty(),
+            _ => false,
+        }
+    }
+
     /// Merge two LLSD maps recursively
     pub fn merge_maps(base: &mut HashMap<String, LLSDValue>, overlay: &HashMap<String, LLSDValue>) {
         for (key, value) in overlay {
@@ -132,6 +727,43 @@ impl LLSDUtils {
         }
     }
 
+    /// Insert each entry of `defaults` that `value`'s map lacks. Present
+    /// keys are never overwritten, even when `Undefined`; a non-map
+    /// `value` is left alone.
+    pub fn apply_defaults(value: &mut LLSDValue, defaults: &HashMap<String, LLSDValue>) {
+        if let LLSDValue::Map(map) = value {
+            for (key, default) in defaults {
+                map.entry(key.clone()).or_insert_with(|| default.clone());
+            }
+        }
+    }
+
+    /// Like [`apply_defaults`](Self::apply_defaults), but where both the
+    /// value and the default of a present key are maps, fill in that
+    /// nested map's missing keys too, at any depth
+    pub fn apply_defaults_deep(value: &mut LLSDValue, defaults: &HashMap<String, LLSDValue>) {
+        let LLSDValue::Map(map) = value else {
+            return;
+        };
+        for (key, default) in defaults {
+            match (map.get_mut(key), default) {
+                (Some(existing), LLSDValue::Map(nested)) => Self::apply_defaults_deep(existing, nested),
+                (Some(_), _) => {}
+                (None, _) => {
+                    map.insert(key.clone(), default.clone());
+                }
+            }
+        }
+    }
+
+    /// Merge `overlay` into `base` at any level. Maps merge key by key and
+    /// arrays as `strategy.on_array` says; any other differing pair,
+    /// including values of different types, is a conflict settled by
+    /// `strategy.on_scalar_conflict`.
+    pub fn merge_with(base: &mut LLSDValue, overlay: &LLSDValue, strategy: MergeStrategy) {
+        merge_values(base, overlay, strategy, &mut Vec::new(), &mut Vec::new());
+    }
+
     /// Filter an LLSD map by keeping only specified keys
     pub fn filter_map(map: &HashMap<String, LLSDValue>, keep_keys: &[&str]) -> HashMap<String, LLSDValue> {
         map.iter()
@@ -145,6 +777,26 @@ impl LLSDUtils {
             .collect()
     }
 
+    /// Keep only `keys` from a map, or from each map in an array, like a
+    /// SQL `SELECT`. Array elements that are not maps, and any other value,
+    /// are returned unchanged.
+    pub fn project(value: &LLSDValue, keys: &[&str]) -> LLSDValue {
+        match value {
+            LLSDValue::Map(map) => LLSDValue::Map(Self::filter_map(map, keys)),
+            LLSDValue::Array(items) => LLSDValue::Array(
+                items.iter().map(|item| Self::project_row(item, keys)).collect(),
+            ),
+            other => other.clone(),
+        }
+    }
+
+    fn project_row(item: &LLSDValue, keys: &[&str]) -> LLSDValue {
+        match item {
+            LLSDValue::Map(map) => LLSDValue::Map(Self::filter_map(map, keys)),
+            other => other.clone(),
+        }
+    }
+
     /// Remove null/undefined values from an LLSD map
     pub fn remove_nulls(map: &mut HashMap<String, LLSDValue>) {
         map.retain(|_, v| !v.is_undefined());
@@ -161,9 +813,10 @@ impl LLSDUtils {
     pub fn to_debug_string(value: &LLSDValue, indent: usize) -> String {
         let indent_str = " ".repeat(indent);
         match value {
-            LLSDValue::Undefined => "undefined".to_string(),
+            LLSDValue::Undefined => value.type_name().to_string(),
             LLSDValue::Boolean(b) => b.to_string(),
             LLSDValue::Integer(i) => i.to_string(),
+            LLSDValue::Integer64(i) => i.to_string(),
             LLSDValue::Real(r) => r.to_string(),
             LLSDValue::String(s) => format!("\"{}\"", s),
             LLSDValue::UUID(u) => u.to_string(),
@@ -206,27 +859,419 @@ impl LLSDUtils {
         }
     }
 
-    /// Count the total number of elements in an LLSD structure
+    /// Zip parallel columns into an array of row maps.
+    ///
+    /// Row `i` holds element `i` of every column. Ragged columns are allowed:
+    /// the result has as many rows as the longest column, and a shorter
+    /// column simply has no entry in rows past its end.
+    pub fn zip_columns(columns: HashMap<String, &[LLSDValue]>) -> LLSDValue {
+        let rows = columns.values().map(|column| column.len()).max().unwrap_or(0);
+
+        LLSDValue::Array(
+            (0..rows)
+                .map(|i| {
+                    LLSDValue::Map(
+                        columns
+                            .iter()
+                            .filter_map(|(key, column)| column.get(i).map(|v| (key.clone(), v.clone())))
+                            .collect(),
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    /// Split an array of row maps into parallel columns, the inverse of
+    /// [`LLSDUtils::zip_columns`].
+    ///
+    /// Every column has one entry per row; a row lacking a key (or that is
+    /// not a map) contributes `Undefined` so columns stay aligned. A
+    /// non-array value yields no columns.
+    pub fn unzip_rows(array: &LLSDValue) -> HashMap<String, Vec<LLSDValue>> {
+        let rows = match array {
+            LLSDValue::Array(rows) => rows,
+            _ => return HashMap::new(),
+        };
+
+        let mut columns: HashMap<String, Vec<LLSDValue>> = HashMap::new();
+        for row in rows {
+            if let LLSDValue::Map(map) = row {
+                for key in map.keys() {
+                    columns.entry(key.clone()).or_default();
+                }
+            }
+        }
+
+        for row in rows {
+            for (key, column) in columns.iter_mut() {
+                let value = match row {
+                    LLSDValue::Map(map) => map.get(key).cloned().unwrap_or_default(),
+                    _ => LLSDValue::Undefined,
+                };
+                column.push(value);
+            }
+        }
+
+        columns
+    }
+
+    /// Render a value as an aligned text table for quick inspection.
+    ///
+    /// A flat map (values are scalars or arrays of scalars) becomes a
+    /// key/value table with keys sorted; an array of flat maps becomes one
+    /// row per element with a column per key. Anything else falls back to
+    /// [`LLSDUtils::to_debug_string`].
+    pub fn to_table(value: &LLSDValue) -> String {
+        match value {
+            LLSDValue::Map(map) if Self::is_flat_map(map) => {
+                let mut keys: Vec<&String> = map.keys().collect();
+                keys.sort();
+                let rows = keys
+                    .into_iter()
+                    .map(|key| vec![key.clone(), Self::table_cell(&map[key])])
+                    .collect();
+                Self::format_table(vec!["key".to_string(), "value".to_string()], rows)
+            }
+            LLSDValue::Array(arr)
+                if !arr.is_empty()
+                    && arr.iter().all(|v| matches!(v, LLSDValue::Map(m) if Self::is_flat_map(m))) =>
+            {
+                let mut columns: Vec<&String> = arr
+                    .iter()
+                    .filter_map(|v| v.as_map())
+                    .flat_map(|m| m.keys())
+                    .collect();
+                columns.sort();
+                columns.dedup();
+
+                let rows = arr
+                    .iter()
+                    .filter_map(|v| v.as_map())
+                    .enumerate()
+                    .map(|(index, row)| {
+                        let mut cells = vec![index.to_string()];
+                        cells.extend(columns.iter().map(|column| {
+                            row.get(*column).map(Self::table_cell).unwrap_or_default()
+                        }));
+                        cells
+                    })
+                    .collect();
+
+                let mut header = vec!["#".to_string()];
+                header.extend(columns.into_iter().cloned());
+                Self::format_table(header, rows)
+            }
+            _ => Self::to_debug_string(value, 0),
+        }
+    }
+
+    /// A map is flat if no value is a map or an array containing containers
+    fn is_flat_map(map: &HashMap<String, LLSDValue>) -> bool {
+        map.values().all(|v| match v {
+            LLSDValue::Map(_) => false,
+            LLSDValue::Array(items) => items
+                .iter()
+                .all(|item| !matches!(item, LLSDValue::Map(_) | LLSDValue::Array(_))),
+            _ => true,
+        })
+    }
+
+    /// Render a scalar (or array of scalars) as a single table cell
+    fn table_cell(value: &LLSDValue) -> String {
+        match value {
+            LLSDValue::String(s) | LLSDValue::URI(s) => s.clone(),
+            LLSDValue::Array(items) => format!(
+                "[{}]",
+                items.iter().map(Self::table_cell).collect::<Vec<_>>().join(", ")
+            ),
+            other => Self::to_debug_string(other, 0),
+        }
+    }
+
+    /// Lay out rows under a header with each column padded to its widest cell
+    fn format_table(header: Vec<String>, rows: Vec<Vec<String>>) -> String {
+        let mut widths: Vec<usize> = header.iter().map(|h| h.chars().count()).collect();
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let format_row = |cells: &[String]| {
+            let line = cells
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                .collect::<Vec<_>>()
+                .join("  ");
+            line.trim_end().to_string()
+        };
+
+        let mut lines = vec![format_row(&header)];
+        lines.push(widths.iter().map(|w| "-".repeat(*w)).collect::<Vec<_>>().join("  "));
+        lines.extend(rows.iter().map(|row| format_row(row)));
+        lines.join("\n")
+    }
+
+    /// Heuristically detect binary data that arrived as a string.
+    ///
+    /// Returns true when the text contains U+FFFD replacement characters or
+    /// NUL (the usual traces of a lossy UTF-8 conversion), or when more than
+    /// 10% of its characters are control characters other than tab, newline
+    /// and carriage return. Plain ASCII and ordinary Unicode text never match.
+    pub fn looks_binary(s: &str) -> bool {
+        let mut total = 0usize;
+        let mut control = 0usize;
+        for c in s.chars() {
+            if c == '\u{FFFD}' || c == '\0' {
+                return true;
+            }
+            if c.is_control() && !matches!(c, '\t' | '\n' | '\r') {
+                control += 1;
+            }
+            total += 1;
+        }
+        control * 10 > total
+    }
+
+    /// Encode a 64-bit integer (region handle, 64-bit ID) losslessly.
+    ///
+    /// Reference LLSD integers are 32-bit, so the value is stored as an
+    /// 8-byte big-endian `Binary` that other implementations can carry.
+    /// Read it back with [`LLSDUtils::llsd_to_i64`]. Within this crate,
+    /// [`LLSDValue::Integer64`] is the direct alternative.
+    pub fn i64_to_llsd(value: i64) -> LLSDValue {
+        LLSDValue::Binary(value.to_be_bytes().to_vec())
+    }
+
+    /// Decode a 64-bit integer written by [`LLSDUtils::i64_to_llsd`].
+    ///
+    /// Accepts the 8-byte big-endian `Binary` form, its base64 string form
+    /// (what untyped JSON round trips produce), and plain integers.
+    pub fn llsd_to_i64(value: &LLSDValue) -> Option<i64> {
+        let bytes = match value {
+            LLSDValue::Integer(i) => return Some(*i as i64),
+            LLSDValue::Integer64(i) => return Some(*i),
+            LLSDValue::Binary(b) => b.clone(),
+            LLSDValue::String(s) => BASE64.decode(s).ok()?,
+            _ => return None,
+        };
+        let bytes: [u8; 8] = bytes.try_into().ok()?;
+        Some(i64::from_be_bytes(bytes))
+    }
+
+    /// Convert a duration to LLSD.
+    ///
+    /// By convention durations are stored as `Real` seconds, so sub-second
+    /// precision survives every format.
+    pub fn duration_to_llsd(duration: Duration) -> LLSDValue {
+        LLSDValue::Real(duration.as_secs_f64())
+    }
+
+    /// Read a duration stored as `Real` or integer seconds.
+    ///
+    /// Returns `None` for other types and for negative or non-finite values.
+    pub fn llsd_to_duration(value: &LLSDValue) -> Option<Duration> {
+        let seconds = match value {
+            LLSDValue::Real(r) => *r,
+            LLSDValue::Integer(i) => *i as f64,
+            LLSDValue::Integer64(i) => *i as f64,
+            _ => return None,
+        };
+        Duration::try_from_secs_f64(seconds).ok()
+    }
+
+    /// Build a map from flat key/value pairs such as environment variables
+    /// or config entries.
+    ///
+    /// Dotted keys become nested maps (`RENDER.SHADOWS` is `SHADOWS` inside
+    /// `RENDER`). Values are inferred in order as integer, real, boolean
+    /// (`true`/`false`, any case), UUID, and otherwise kept as strings.
+    /// Pairs are applied in order; a later key replaces an earlier value at
+    /// the same path, including a scalar that it needs to nest under.
+    pub fn from_kv<I: IntoIterator<Item = (String, String)>>(pairs: I) -> LLSDValue {
+        let mut root = HashMap::new();
+        for (key, raw) in pairs {
+            let mut parts: Vec<&str> = key.split('.').collect();
+            let last = parts.pop().unwrap_or_default();
+
+            let mut map = &mut root;
+            for part in parts {
+                let slot = map
+                    .entry(part.to_string())
+                    .or_insert_with(|| LLSDValue::Map(HashMap::new()));
+                if !matches!(slot, LLSDValue::Map(_)) {
+                    *slot = LLSDValue::Map(HashMap::new());
+                }
+                map = match slot {
+                    LLSDValue::Map(inner) => inner,
+                    _ => unreachable!("slot was just made a map"),
+                };
+            }
+            map.insert(last.to_string(), infer_scalar(&raw));
+        }
+        LLSDValue::Map(root)
+    }
+
+    /// Flatten a map of scalars into a URL query string, `k=v&...`, for
+    /// caps that take their parameters that way.
+    ///
+    /// Keys and values are percent-encoded and keys are sorted, so the
+    /// output is deterministic. Scalars are written as text: binary as
+    /// base64, dates as RFC 3339, undefined as an empty value. Nested
+    /// arrays and maps are an error.
+    pub fn to_query_string(value: &LLSDValue) -> LLSDResult<String> {
+        let map = value
+            .as_map()
+            .ok_or_else(|| LLSDError::type_mismatch("map", value.type_name()))?;
+        let mut entries: Vec<(&String, &LLSDValue)> = map.iter().collect();
+        entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+
+        let mut pairs = Vec::with_capacity(entries.len());
+        for (key, value) in entries {
+            let text = match value {
+                LLSDValue::Undefined => String::new(),
+                LLSDValue::Boolean(b) => b.to_string(),
+                LLSDValue::Integer(i) => i.to_string(),
+                LLSDValue::Integer64(i) => i.to_string(),
+                LLSDValue::Real(r) => r.to_string(),
+                LLSDValue::String(s) | LLSDValue::URI(s) => s.clone(),
+                LLSDValue::UUID(u) => u.to_string(),
+                LLSDValue::Date(d) => d.to_rfc3339(),
+                LLSDValue::Binary(b) => BASE64.encode(b),
+                LLSDValue::Map(_) | LLSDValue::Array(_) => {
+                    return Err(LLSDError::type_mismatch(
+                        format!("scalar for query parameter '{}'", key),
+                        value.type_name().to_string(),
+                    ));
+                }
+            };
+            pairs.push(format!("{}={}", percent_encode(key), percent_encode(&text)));
+        }
+        Ok(pairs.join("&"))
+    }
+
+    /// Parse a URL query string into a flat map.
+    ///
+    /// Keys and values are percent-decoded, with `+` read as a space.
+    /// Values are typed like [`LLSDUtils::from_kv`]; a later key replaces
+    /// an earlier one and a key without `=` gets an empty string.
+    pub fn from_query_string(query: &str) -> LLSDValue {
+        let query = query.strip_prefix('?').unwrap_or(query);
+        let mut map = HashMap::new();
+        for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+            let (key, raw) = pair.split_once('=').unwrap_or((pair, ""));
+            let raw = percent_decode(raw);
+            let value = if raw.is_empty() { LLSDValue::String(raw) } else { infer_scalar(&raw) };
+            map.insert(percent_decode(key), value);
+        }
+        LLSDValue::Map(map)
+    }
+
+    /// Render a value as pretty JSON that is safe to write to logs.
+    ///
+    /// Binary data is summarized as `"<N bytes>"`, UUIDs keep only their
+    /// first 8 and last 4 hex digits, and non-finite reals become strings.
+    /// Output longer than `max_len` bytes is cut and ends with
+    /// `...[truncated]`, so it is either valid JSON or visibly truncated.
+    pub fn to_log_json(value: &LLSDValue, max_len: usize) -> String {
+        const TRUNCATED: &str = "...[truncated]";
+
+        let json = serde_json::to_string_pretty(&Self::log_json_value(value))
+            .unwrap_or_else(|_| "null".to_string());
+        if json.len() <= max_len {
+            return json;
+        }
+
+        let mut cut = max_len.saturating_sub(TRUNCATED.len());
+        while !json.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        let mut output = json[..cut].to_string();
+        output.push_str(&TRUNCATED[..max_len.min(TRUNCATED.len())]);
+        output
+    }
+
+    /// Redacted JSON form of a value for [`LLSDUtils::to_log_json`]
+    fn log_json_value(value: &LLSDValue) -> serde_json::Value {
+        use serde_json::Value;
+
+        match value {
+            LLSDValue::Undefined => Value::Null,
+            LLSDValue::Boolean(b) => Value::Bool(*b),
+            LLSDValue::Integer(i) => Value::from(*i),
+            LLSDValue::Integer64(i) => Value::from(*i),
+            LLSDValue::Real(r) => serde_json::Number::from_f64(*r)
+                .map(Value::Number)
+                .unwrap_or_else(|| Value::String(r.to_string())),
+            LLSDValue::String(s) | LLSDValue::URI(s) => Value::String(s.clone()),
+            LLSDValue::UUID(u) => {
+                let text = u.to_string();
+                Value::String(format!("{}-****-****-****-********{}", &text[..8], &text[32..]))
+            }
+            LLSDValue::Date(d) => Value::String(d.to_rfc3339()),
+            LLSDValue::Binary(b) => Value::String(format!("<{} bytes>", b.len())),
+            LLSDValue::Map(map) => Value::Object(
+                map.iter().map(|(k, v)| (k.clone(), Self::log_json_value(v))).collect(),
+            ),
+            LLSDValue::Array(arr) => Value::Array(arr.iter().map(Self::log_json_value).collect()),
+        }
+    }
+
+    /// Count the total number of elements in an LLSD structure: every
+    /// scalar plus every map and array, the root included. Map keys are not
+    /// counted.
     pub fn count_elements(value: &LLSDValue) -> usize {
         match value {
             LLSDValue::Map(map) => {
-                1 + map.values().map(|v| Self::count_elements(v)).sum::<usize>()
+                1 + map.values().map(Self::count_elements).sum::<usize>()
             }
             LLSDValue::Array(arr) => {
-                1 + arr.iter().map(|v| Self::count_elements(v)).sum::<usize>()
+                1 + arr.iter().map(Self::count_elements).sum::<usize>()
             }
             _ => 1,
         }
     }
 
+    /// Count how many values of each type occur anywhere in an LLSD structure,
+    /// containers included. The counts add up to [`count_elements`](Self::count_elements).
+    pub fn type_histogram(value: &LLSDValue) -> HashMap<LLSDType, usize> {
+        struct Types(HashMap<LLSDType, usize>);
+        impl LLSDVisitor for Types {
+            fn visit_value(&mut self, _path: &str, value: &LLSDValue) {
+                *self.0.entry(value.get_type()).or_insert(0) += 1;
+            }
+        }
+
+        let mut types = Types(HashMap::new());
+        value.walk(&mut types);
+        types.0
+    }
+
+    /// Count how often each map key name appears at any depth
+    pub fn key_frequency(value: &LLSDValue) -> HashMap<String, usize> {
+        struct Keys(HashMap<String, usize>);
+        impl LLSDVisitor for Keys {
+            fn visit_map_enter(&mut self, _path: &str, map: &HashMap<String, LLSDValue>) {
+                for key in map.keys() {
+                    *self.0.entry(key.clone()).or_insert(0) += 1;
+                }
+            }
+        }
+
+        let mut keys = Keys(HashMap::new());
+        value.walk(&mut keys);
+        keys.0
+    }
+
     /// Get the maximum depth of an LLSD structure
     pub fn max_depth(value: &LLSDValue) -> usize {
         match value {
             LLSDValue::Map(map) => {
-                1 + map.values().map(|v| Self::max_depth(v)).max().unwrap_or(0)
+                1 + map.values().map(Self::max_depth).max().unwrap_or(0)
             }
             LLSDValue::Array(arr) => {
-                1 + arr.iter().map(|v| Self::max_depth(v)).max().unwrap_or(0)
+                1 + arr.iter().map(Self::max_depth).max().unwrap_or(0)
             }
             _ => 1,
         }
@@ -6,25 +6,37 @@
  * Rust implementation Copyright (C) 2024
  */
 
-use std::collections::HashMap;
+//! Comprehensive cache management system for Second Life viewer (Rust implementation).
+//!
+//! Features:
+//! - Safe concurrent operations with Rust's ownership system
+//! - Async operations with Tokio for high performance
+//! - Memory-safe cache management with zero-cost abstractions
+//! - Type-safe configuration with enums and structs
+
+use std::collections::{HashMap, VecDeque};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
-use tokio::sync::Mutex;
-use tokio::time::{interval, Instant};
+use tokio::sync::{broadcast, watch, Mutex};
+use tokio::task::JoinHandle;
+use tokio::time::{interval_at, Instant};
 use serde::{Deserialize, Serialize};
-
-/// Comprehensive cache management system for Second Life viewer (Rust implementation).
-/// 
-/// Features:
-/// - Safe concurrent operations with Rust's ownership system
-/// - Async operations with Tokio for high performance
-/// - Memory-safe cache management with zero-cost abstractions
-/// - Type-safe configuration with enums and structs
+use crate::error::{LLSDError, LLSDResult};
+use crate::types::LLSDValue;
 
 pub const MAX_CACHE_SIZE: u64 = 200 * 1024 * 1024 * 1024; // 200GB
 pub const DEFAULT_CACHE_SIZE: u64 = 10 * 1024 * 1024 * 1024; // 10GB
+pub const MANIFEST_VERSION: i32 = 1;
+
+/// Default period of the background maintenance cleanup
+pub const DEFAULT_CLEANUP_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Number of recent evictions remembered for thrash detection
+const THRASH_WINDOW: usize = 256;
+/// Evictions needed before the thrash ratio is trusted enough to report
+const THRASH_MIN_EVICTIONS: usize = 16;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum StorageLocation {
@@ -93,6 +105,43 @@ impl CacheType {
         }
     }
 
+    /// Percentage of the total cache size recommended for this type.
+    /// The shares of all types sum to 100.
+    pub fn share_percent(&self) -> u64 {
+        match self {
+            CacheType::Texture => 60,
+            CacheType::Sound => 15,
+            CacheType::Mesh => 15,
+            CacheType::Animation => 2,
+            CacheType::Clothing => 2,
+            CacheType::Object => 3,
+            CacheType::Inventory => 2,
+            CacheType::Temporary => 1,
+        }
+    }
+
+    /// Recommended size cap for this type given a total cache size in bytes
+    pub fn recommended_limit(&self, total: u64) -> u64 {
+        (total as u128 * self.share_percent() as u128 / 100) as u64
+    }
+
+    /// Recommended maximum age for entries of this type, or `None` if
+    /// entries are only evicted for space
+    pub fn recommended_ttl(&self) -> Option<Duration> {
+        match self {
+            CacheType::Temporary => Some(Duration::from_secs(24 * 60 * 60)), // 24 hours
+            _ => None,
+        }
+    }
+
+    /// Cache type stored under `folder_name`
+    pub fn from_folder_name(folder_name: &str) -> Option<CacheType> {
+        Self::all_types()
+            .iter()
+            .copied()
+            .find(|cache_type| cache_type.folder_name() == folder_name)
+    }
+
     pub fn all_types() -> &'static [CacheType] {
         &[
             CacheType::Texture,
@@ -115,6 +164,10 @@ pub struct CacheEntry {
     pub creation_time: u64,
     pub last_access_time: u64,
     pub access_count: u64,
+    /// MD5 of the stored data, recorded by [`CacheManager::store`]. Entries
+    /// indexed from disk at startup have none.
+    #[serde(default)]
+    pub content_hash: Option<String>,
 }
 
 impl CacheEntry {
@@ -131,6 +184,7 @@ impl CacheEntry {
             creation_time: now,
             last_access_time: now,
             access_count: 0,
+            content_hash: None,
         }
     }
 
@@ -171,6 +225,9 @@ pub struct CacheStatistics {
     pub type_limits: HashMap<CacheType, u64>,
     pub storage_location: StorageLocation,
     pub base_path: String,
+    /// Bytes not written because identical data was already cached
+    #[serde(default)]
+    pub dedup_savings: u64,
 }
 
 impl CacheStatistics {
@@ -230,6 +287,7 @@ impl std::fmt::Display for CacheStatistics {
                 self.total_requests(), self.total_hits, self.total_misses)?;
         writeln!(f, "  Writes: {}", self.total_writes)?;
         writeln!(f, "  Cleanups: {}", self.total_cleanups)?;
+        writeln!(f, "  Dedup Savings: {}", format_bytes(self.dedup_savings))?;
         writeln!(f, "  Storage: {}", self.storage_location.display_name())?;
         writeln!(f, "  Path: {}", self.base_path)?;
         writeln!(f)?;
@@ -250,11 +308,187 @@ impl std::fmt::Display for CacheStatistics {
     }
 }
 
+/// Notifications from a [`CacheManager`], see [`CacheManager::subscribe`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum CacheEvent {
+    /// The thrash ratio rose to the threshold; the cache is likely too small
+    /// for its working set
+    ThrashDetected(f64),
+    /// The thrash ratio fell back below the threshold
+    ThrashResolved(f64),
+}
+
+/// Keys recently evicted for space, each marked once it is requested again
+#[derive(Debug, Default)]
+struct ThrashTracker {
+    evicted: VecDeque<(String, bool)>,
+    thrashing: bool,
+}
+
+impl ThrashTracker {
+    fn record_eviction(&mut self, key: &str) {
+        if self.evicted.len() == THRASH_WINDOW {
+            self.evicted.pop_front();
+        }
+        self.evicted.push_back((key.to_string(), false));
+    }
+
+    fn record_miss(&mut self, key: &str) {
+        if let Some((_, missed)) = self.evicted.iter_mut().rev().find(|(evicted, _)| evicted == key) {
+            *missed = true;
+        }
+    }
+
+    fn ratio(&self) -> f64 {
+        if self.evicted.is_empty() {
+            return 0.0;
+        }
+        let missed = self.evicted.iter().filter(|(_, missed)| *missed).count();
+        missed as f64 / self.evicted.len() as f64
+    }
+}
+
+/// Result of checking a cache manifest against the files on disk
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ManifestReport {
+    /// Keys whose file is present with the expected size and checksum
+    pub present: Vec<String>,
+    /// Keys with no backing file
+    pub missing: Vec<String>,
+    /// Keys whose file exists but differs from the manifest
+    pub mismatched: Vec<String>,
+}
+
+impl ManifestReport {
+    /// Whether every manifest entry is present and intact
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty() && self.mismatched.is_empty()
+    }
+}
+
+/// Result of [`CacheManager::preload`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CachePreloadReport {
+    /// Keys found on disk and indexed
+    pub found: usize,
+    /// Keys with no backing file
+    pub missing: usize,
+}
+
+/// A content-addressed entry: its cache type and content hash
+type ContentKey = (CacheType, String);
+
+/// Handles on the state [`CacheManager::remove`] updates, shared with the
+/// background maintenance task so expired entries are removed the same way
+#[derive(Clone)]
+struct SharedEntries {
+    cache_directories: Arc<RwLock<HashMap<CacheType, PathBuf>>>,
+    statistics: Arc<RwLock<CacheStatistics>>,
+    cache_index: Arc<Mutex<HashMap<String, CacheEntry>>>,
+    type_sizes: Arc<RwLock<HashMap<CacheType, u64>>>,
+    content_refs: Arc<Mutex<HashMap<ContentKey, usize>>>,
+    content_files: Arc<Mutex<HashMap<String, Vec<PathBuf>>>>,
+}
+
+/// What removing a cache item did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Removal {
+    /// The file was deleted, freeing this many bytes
+    Removed(u64),
+    /// Only a reference was dropped; other references keep the file
+    Dereferenced,
+    /// Nothing was stored under the key
+    Missing,
+}
+
+impl SharedEntries {
+    /// Drop a reference to the item, deleting its file and updating the
+    /// index, sizes and statistics once no reference remains
+    async fn remove(
+        &self,
+        cache_type: CacheType,
+        key: &str,
+    ) -> Result<Removal, Box<dyn std::error::Error + Send + Sync>> {
+        {
+            let mut refs = self.content_refs.lock().await;
+            let ref_key = (cache_type, key.to_string());
+            if let Some(count) = refs.get_mut(&ref_key) {
+                *count -= 1;
+                if *count > 0 {
+                    return Ok(Removal::Dereferenced);
+                }
+                refs.remove(&ref_key);
+            }
+        }
+
+        self.unlink(cache_type, key).await
+    }
+
+    /// Delete the item whatever references it still has, as eviction and
+    /// expiry do
+    async fn evict(
+        &self,
+        cache_type: CacheType,
+        key: &str,
+    ) -> Result<Removal, Box<dyn std::error::Error + Send + Sync>> {
+        self.content_refs.lock().await.remove(&(cache_type, key.to_string()));
+        self.unlink(cache_type, key).await
+    }
+
+    /// Delete the item's file and drop it from the index, sizes and
+    /// statistics
+    async fn unlink(
+        &self,
+        cache_type: CacheType,
+        key: &str,
+    ) -> Result<Removal, Box<dyn std::error::Error + Send + Sync>> {
+        let cache_file = CacheManager::cache_file_path(&self.cache_directories.read().unwrap(), cache_type, key);
+
+        if !cache_file.exists() {
+            return Ok(Removal::Missing);
+        }
+
+        let file_size = fs::metadata(&cache_file)?.len();
+        fs::remove_file(&cache_file)?;
+        CacheManager::untrack_content_file(&mut *self.content_files.lock().await, &cache_file);
+
+        // Update cache tracking
+        {
+            let mut index = self.cache_index.lock().await;
+            index.remove(key);
+        }
+
+        {
+            let mut type_sizes = self.type_sizes.write().unwrap();
+            if let Some(type_size) = type_sizes.get_mut(&cache_type) {
+                *type_size = type_size.saturating_sub(file_size);
+            }
+        }
+
+        {
+            let mut stats = self.statistics.write().unwrap();
+            stats.total_size = stats.total_size.saturating_sub(file_size);
+            if let Some(type_size) = stats.type_sizes.get_mut(&cache_type) {
+                *type_size = type_size.saturating_sub(file_size);
+            }
+        }
+
+        log::debug!(
+            "Removed {:?} item: {} ({})",
+            cache_type,
+            key,
+            format_bytes(file_size)
+        );
+
+        Ok(Removal::Removed(file_size))
+    }
+}
+
 pub struct CacheManager {
     storage_location: StorageLocation,
     max_cache_size: u64,
     base_cache_directory: PathBuf,
-    cache_directories: HashMap<CacheType, PathBuf>,
+    cache_directories: Arc<RwLock<HashMap<CacheType, PathBuf>>>,
     
     // Statistics with thread-safe access
     statistics: Arc<RwLock<CacheStatistics>>,
@@ -265,6 +499,34 @@ pub struct CacheManager {
     // Type limits and sizes
     type_limits: HashMap<CacheType, u64>,
     type_sizes: Arc<RwLock<HashMap<CacheType, u64>>>,
+
+    // Maximum entry age per type; types without one only expire for space
+    type_ttls: Arc<RwLock<HashMap<CacheType, Duration>>>,
+
+    // Reference counts for content-addressed entries, keyed by cache type
+    // and content hash
+    content_refs: Arc<Mutex<HashMap<ContentKey, usize>>>,
+
+    // Held by store_content_addressed for a key while it checks, stores and
+    // counts it, so concurrent stores of the same data write it once
+    content_locks: Mutex<HashMap<ContentKey, Arc<Mutex<()>>>>,
+
+    // Files holding each content hash written this session, for dedup
+    content_files: Arc<Mutex<HashMap<String, Vec<PathBuf>>>>,
+
+    // Whether retrieve verifies data against the stored MD5
+    integrity_checks: bool,
+
+    // Recent evictions and the ratio at which thrashing is reported
+    thrash: Arc<RwLock<ThrashTracker>>,
+    thrash_threshold: f64,
+    event_sender: broadcast::Sender<CacheEvent>,
+
+    // Period of the background maintenance cleanup; `None` pauses it
+    cleanup_interval: watch::Sender<Option<Duration>>,
+
+    // Background maintenance task, taken by `shutdown`
+    maintenance_task: std::sync::Mutex<Option<JoinHandle<()>>>,
 }
 
 impl CacheManager {
@@ -272,8 +534,18 @@ impl CacheManager {
         storage_location: StorageLocation,
         max_cache_size: u64,
     ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
-        let max_size = max_cache_size.min(MAX_CACHE_SIZE);
         let base_cache_directory = Self::get_base_cache_directory(storage_location)?;
+        Self::with_base_directory(storage_location, base_cache_directory, max_cache_size).await
+    }
+
+    /// Create a cache manager rooted at an explicit directory instead of the
+    /// default path for `storage_location`
+    pub async fn with_base_directory(
+        storage_location: StorageLocation,
+        base_cache_directory: PathBuf,
+        max_cache_size: u64,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let max_size = max_cache_size.min(MAX_CACHE_SIZE);
         
         // Create cache directories
         fs::create_dir_all(&base_cache_directory)?;
@@ -294,11 +566,16 @@ impl CacheManager {
             type_sizes.insert(*cache_type, 0);
         }
 
+        let type_ttls = CacheType::all_types()
+            .iter()
+            .filter_map(|cache_type| cache_type.recommended_ttl().map(|ttl| (*cache_type, ttl)))
+            .collect();
+
         let mut manager = Self {
             storage_location,
             max_cache_size: max_size,
             base_cache_directory: base_cache_directory.clone(),
-            cache_directories,
+            cache_directories: Arc::new(RwLock::new(cache_directories)),
             statistics: Arc::new(RwLock::new(CacheStatistics {
                 total_size: 0,
                 max_size,
@@ -310,17 +587,29 @@ impl CacheManager {
                 type_limits: type_limits.clone(),
                 storage_location,
                 base_path: base_cache_directory.to_string_lossy().to_string(),
+                dedup_savings: 0,
             })),
             cache_index: Arc::new(Mutex::new(HashMap::new())),
             type_limits,
             type_sizes: Arc::new(RwLock::new(type_sizes)),
+            type_ttls: Arc::new(RwLock::new(type_ttls)),
+            content_refs: Arc::new(Mutex::new(HashMap::new())),
+            content_locks: Mutex::new(HashMap::new()),
+            content_files: Arc::new(Mutex::new(HashMap::new())),
+            integrity_checks: false,
+            thrash: Arc::new(RwLock::new(ThrashTracker::default())),
+            thrash_threshold: 0.5,
+            event_sender: broadcast::channel(16).0,
+            cleanup_interval: watch::channel(Some(DEFAULT_CLEANUP_INTERVAL)).0,
+            maintenance_task: std::sync::Mutex::new(None),
         };
 
         // Load existing cache index
         manager.load_cache_index().await?;
         
         // Start periodic cleanup
-        manager.start_periodic_cleanup().await;
+        let task = manager.start_periodic_cleanup();
+        *manager.maintenance_task.get_mut().unwrap() = Some(task);
 
         log::info!(
             "Rust Cache manager initialized with {} storage, max size: {}",
@@ -356,22 +645,18 @@ impl CacheManager {
     }
 
     fn initialize_default_limits(max_size: u64) -> HashMap<CacheType, u64> {
-        let mut limits = HashMap::new();
-        
-        // Distribute cache space across types (percentages)
-        limits.insert(CacheType::Texture, max_size * 60 / 100);  // 60%
-        limits.insert(CacheType::Sound, max_size * 15 / 100);    // 15%
-        limits.insert(CacheType::Mesh, max_size * 15 / 100);     // 15%
-        limits.insert(CacheType::Animation, max_size * 2 / 100); // 2%
-        limits.insert(CacheType::Clothing, max_size * 2 / 100);  // 2%
-        limits.insert(CacheType::Object, max_size * 3 / 100);    // 3%
-        limits.insert(CacheType::Inventory, max_size * 2 / 100); // 2%
-        limits.insert(CacheType::Temporary, max_size * 1 / 100); // 1%
-        
-        limits
+        CacheType::all_types()
+            .iter()
+            .map(|cache_type| (*cache_type, cache_type.recommended_limit(max_size)))
+            .collect()
     }
 
-    /// Store data in cache with Rust's async/await
+    /// Store data in cache with Rust's async/await.
+    ///
+    /// Data identical to something already stored this session under
+    /// another key is hard-linked to the existing file rather than written
+    /// again; see [`dedup_savings`](Self::dedup_savings). Where linking
+    /// fails (for example across file systems) the data is written as usual.
     pub async fn store(
         &self,
         cache_type: CacheType,
@@ -399,11 +684,12 @@ impl CacheManager {
             fs::create_dir_all(parent)?;
         }
 
-        // Write data to file
-        fs::write(&cache_file, data)?;
+        let content_hash = format!("{:x}", md5::compute(&data));
+        let linked = self.write_deduplicated(&cache_file, &content_hash, &data).await?;
 
         // Create cache entry
-        let entry = CacheEntry::new(key.clone(), cache_type, data_size);
+        let mut entry = CacheEntry::new(key.clone(), cache_type, data_size);
+        entry.content_hash = Some(content_hash);
 
         // Update cache tracking
         {
@@ -420,6 +706,9 @@ impl CacheManager {
             let mut stats = self.statistics.write().unwrap();
             stats.total_size += data_size;
             stats.total_writes += 1;
+            if linked {
+                stats.dedup_savings += data_size;
+            }
             if let Some(type_size) = stats.type_sizes.get_mut(&cache_type) {
                 *type_size += data_size;
             }
@@ -435,6 +724,130 @@ impl CacheManager {
         Ok(true)
     }
 
+    /// Write `data` to `cache_file`, or link it to an existing file with the
+    /// same content. Returns whether the file was linked to another key's.
+    ///
+    /// A hash match alone is not trusted: the bytes are compared before
+    /// linking, so a colliding digest never serves another key's data.
+    async fn write_deduplicated(
+        &self,
+        cache_file: &Path,
+        content_hash: &str,
+        data: &[u8],
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let mut content_files = self.content_files.lock().await;
+        let holders = content_files.get(content_hash).cloned().unwrap_or_default();
+
+        // The same data stored again under the same key: nothing to write
+        if holders.iter().any(|path| path == cache_file)
+            && fs::read(cache_file).is_ok_and(|existing| existing == data)
+        {
+            return Ok(false);
+        }
+
+        // Never write through an old link: that would change every key
+        // sharing the file
+        match fs::remove_file(cache_file) {
+            Ok(()) => Self::untrack_content_file(&mut content_files, cache_file),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        let linked = holders.iter().any(|existing| {
+            existing != cache_file
+                && fs::read(existing).is_ok_and(|existing| existing == data)
+                && fs::hard_link(existing, cache_file).is_ok()
+        });
+        if !linked {
+            fs::write(cache_file, data)?;
+        }
+
+        content_files
+            .entry(content_hash.to_string())
+            .or_default()
+            .push(cache_file.to_path_buf());
+        Ok(linked)
+    }
+
+    /// Forget `path` as a holder of its content, dropping hashes that no
+    /// file holds any more
+    fn untrack_content_file(content_files: &mut HashMap<String, Vec<PathBuf>>, path: &Path) {
+        content_files.retain(|_, paths| {
+            paths.retain(|held| held != path);
+            !paths.is_empty()
+        });
+    }
+
+    /// Total bytes [`store`](Self::store) has avoided writing by linking to
+    /// identical data already in the cache
+    pub fn dedup_savings(&self) -> u64 {
+        self.statistics.read().unwrap().dedup_savings
+    }
+
+    /// Store data under its content hash so identical data shares one file.
+    ///
+    /// Returns the hash key. Storing data that is already present only adds
+    /// a reference; [`remove`](Self::remove) deletes the file once the last
+    /// reference is dropped. Reference counts live for the lifetime of the
+    /// manager; a matching file left from an earlier session is adopted.
+    pub async fn store_content_addressed(
+        &self,
+        cache_type: CacheType,
+        data: Vec<u8>,
+    ) -> LLSDResult<String> {
+        let key = format!("{:x}", md5::compute(&data));
+        let ref_key = (cache_type, key.clone());
+
+        // Only stores of the same data wait on each other; the store below
+        // may evict, which takes the reference count lock itself
+        let key_lock = Arc::clone(self.content_locks.lock().await.entry(ref_key.clone()).or_default());
+        let held = key_lock.lock().await;
+        let result = self.add_content_reference(cache_type, &key, data).await;
+        drop(held);
+
+        let mut locks = self.content_locks.lock().await;
+        if Arc::strong_count(&key_lock) == 2 {
+            locks.remove(&ref_key);
+        }
+
+        result.map(|()| key)
+    }
+
+    /// Count a reference to content-addressed data, storing it first if no
+    /// reference exists yet
+    async fn add_content_reference(&self, cache_type: CacheType, key: &str, data: Vec<u8>) -> LLSDResult<()> {
+        if let Some(count) = self.content_refs.lock().await.get_mut(&(cache_type, key.to_string())) {
+            *count += 1;
+            return Ok(());
+        }
+
+        if !self.exists(cache_type, key).await {
+            let stored = self
+                .store(cache_type, key.to_string(), data)
+                .await
+                .map_err(|e| LLSDError::custom(e.to_string()))?;
+            if !stored {
+                return Err(LLSDError::custom(format!(
+                    "Data too large for cache type {:?}",
+                    cache_type
+                )));
+            }
+        }
+
+        *self.content_refs.lock().await.entry((cache_type, key.to_string())).or_insert(0) += 1;
+        Ok(())
+    }
+
+    /// Number of live references to a content-addressed entry
+    pub async fn content_ref_count(&self, cache_type: CacheType, key: &str) -> usize {
+        self.content_refs
+            .lock()
+            .await
+            .get(&(cache_type, key.to_string()))
+            .copied()
+            .unwrap_or(0)
+    }
+
     /// Retrieve data from cache with async operations
     pub async fn retrieve(
         &self,
@@ -444,22 +857,36 @@ impl CacheManager {
         let cache_file = self.get_cache_file_path(cache_type, key);
 
         if !cache_file.exists() {
-            let mut stats = self.statistics.write().unwrap();
-            stats.total_misses += 1;
+            {
+                let mut stats = self.statistics.write().unwrap();
+                stats.total_misses += 1;
+            }
+            self.thrash.write().unwrap().record_miss(key);
+            self.check_thrash();
             return Ok(None);
         }
 
         // Update access time
-        {
+        let expected_hash = {
             let mut index = self.cache_index.lock().await;
-            if let Some(entry) = index.get_mut(key) {
+            index.get_mut(key).and_then(|entry| {
                 entry.update_access_time();
-            }
-        }
+                entry.content_hash.clone()
+            })
+        };
 
         // Read data from file
         let data = fs::read(&cache_file)?;
 
+        if let Some(expected) = expected_hash.filter(|_| self.integrity_checks) {
+            if format!("{:x}", md5::compute(&data)) != expected {
+                log::warn!("Evicting corrupted {:?} item: {}", cache_type, key);
+                // Drop the file even if other references to it remain
+                self.shared_entries().evict(cache_type, key).await?;
+                return Err(LLSDError::ChecksumMismatch { key: key.to_string() }.into());
+            }
+        }
+
         {
             let mut stats = self.statistics.write().unwrap();
             stats.total_hits += 1;
@@ -475,56 +902,249 @@ impl CacheManager {
         Ok(Some(data))
     }
 
-    /// Check if item exists in cache
-    pub async fn exists(&self, cache_type: CacheType, key: &str) -> bool {
-        let cache_file = self.get_cache_file_path(cache_type, key);
-        cache_file.exists()
-    }
+    /// Retrieve data by its MD5 digest, whichever key stored it.
+    ///
+    /// Files written this session are found through the dedup hash index,
+    /// others through the checksums in the cache index. Data that no longer
+    /// matches `hash` is not returned.
+    pub async fn retrieve_by_hash(&self, hash: &[u8]) -> Option<Vec<u8>> {
+        let content_hash: String = hash.iter().map(|b| format!("{:02x}", b)).collect();
+
+        let indexed = self
+            .content_files
+            .lock()
+            .await
+            .get(&content_hash)
+            .and_then(|paths| paths.iter().find(|path| path.exists()).cloned());
+        let path = match indexed {
+            Some(path) => Some(path),
+            None => self
+                .cache_index
+                .lock()
+                .await
+                .values()
+                .find(|entry| entry.content_hash.as_deref() == Some(content_hash.as_str()))
+                .map(|entry| self.get_cache_file_path(entry.cache_type, &entry.key)),
+        };
 
-    /// Remove item from cache
-    pub async fn remove(
-        &self,
-        cache_type: CacheType,
-        key: &str,
-    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
-        let cache_file = self.get_cache_file_path(cache_type, key);
+        let data = path
+            .and_then(|path| fs::read(path).ok())
+            .filter(|data| format!("{:x}", md5::compute(data)) == content_hash);
 
-        if !cache_file.exists() {
-            return Ok(false);
+        let mut stats = self.statistics.write().unwrap();
+        match data {
+            Some(_) => stats.total_hits += 1,
+            None => stats.total_misses += 1,
         }
+        data
+    }
 
-        let file_size = fs::metadata(&cache_file)?.len();
-        fs::remove_file(&cache_file)?;
-
-        // Update cache tracking
-        {
-            let mut index = self.cache_index.lock().await;
-            index.remove(key);
+    /// Describe every indexed entry as an LLSD manifest: key, cache type,
+    /// size and MD5 checksum, without the data itself.
+    ///
+    /// Serialize the manifest with any LLSD format and pass it to
+    /// [`import_manifest`](Self::import_manifest) on another machine to see
+    /// which entries arrived intact. Entries are sorted by key.
+    ///
+    /// Indexed entries whose file has gone are left out of `entries` and
+    /// listed by key under `missing`.
+    pub async fn export_manifest(&self) -> LLSDResult<LLSDValue> {
+        let mut entries: Vec<CacheEntry> = self.cache_index.lock().await.values().cloned().collect();
+        entries.sort_by(|a, b| a.key.cmp(&b.key));
+
+        let mut items = Vec::with_capacity(entries.len());
+        let mut missing = Vec::new();
+        for entry in entries {
+            let data = match tokio::fs::read(self.get_cache_file_path(entry.cache_type, &entry.key)).await {
+                Ok(data) => data,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    missing.push(LLSDValue::String(entry.key));
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+            let mut item = HashMap::new();
+            item.insert("key".to_string(), LLSDValue::String(entry.key));
+            item.insert(
+                "type".to_string(),
+                LLSDValue::String(entry.cache_type.folder_name().to_string()),
+            );
+            item.insert("size".to_string(), LLSDValue::from_i64(data.len() as i64));
+            item.insert(
+                "md5".to_string(),
+                LLSDValue::String(format!("{:x}", md5::compute(&data))),
+            );
+            items.push(LLSDValue::Map(item));
         }
 
-        {
-            let mut type_sizes = self.type_sizes.write().unwrap();
-            if let Some(type_size) = type_sizes.get_mut(&cache_type) {
-                *type_size = type_size.saturating_sub(file_size);
+        let mut manifest = HashMap::new();
+        manifest.insert("version".to_string(), LLSDValue::Integer(MANIFEST_VERSION));
+        manifest.insert("entries".to_string(), LLSDValue::Array(items));
+        manifest.insert("missing".to_string(), LLSDValue::Array(missing));
+        Ok(LLSDValue::Map(manifest))
+    }
+
+    /// Compare a manifest from [`export_manifest`](Self::export_manifest)
+    /// with the files in this cache. Nothing is modified.
+    pub async fn import_manifest(&self, manifest: &LLSDValue) -> LLSDResult<ManifestReport> {
+        let version = manifest
+            .get_path("version")
+            .and_then(|v| v.as_integer())
+            .ok_or_else(|| LLSDError::custom("Manifest has no version"))?;
+        if version != MANIFEST_VERSION {
+            return Err(LLSDError::custom(format!(
+                "Unsupported manifest version: {}",
+                version
+            )));
+        }
+        let entries = manifest
+            .get_path("entries")
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| LLSDError::custom("Manifest has no entries"))?;
+
+        let mut report = ManifestReport::default();
+        for item in entries {
+            let field = |name: &str| {
+                item.get_path(name)
+                    .and_then(|v| v.as_string())
+                    .ok_or_else(|| LLSDError::MissingField { field: name.to_string() })
+            };
+            let key = field("key")?;
+            let folder = field("type")?;
+            let checksum = field("md5")?;
+            let size = item
+                .get_path("size")
+                .and_then(|v| v.as_integer64())
+                .ok_or_else(|| LLSDError::MissingField { field: "size".to_string() })?;
+            let cache_type = CacheType::from_folder_name(folder)
+                .ok_or_else(|| LLSDError::custom(format!("Unknown cache type: {}", folder)))?;
+
+            let path = self.get_cache_file_path(cache_type, key);
+            match tokio::fs::read(&path).await {
+                Ok(data) => {
+                    if data.len() as i64 == size && format!("{:x}", md5::compute(&data)) == checksum {
+                        report.present.push(key.to_string());
+                    } else {
+                        report.mismatched.push(key.to_string());
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    report.missing.push(key.to_string());
+                }
+                Err(e) => return Err(e.into()),
             }
         }
 
-        {
+        Ok(report)
+    }
+
+    /// Index the given entries from the files on disk, without walking the
+    /// whole cache directory.
+    ///
+    /// Each entry that exists is recorded with its current file size, and
+    /// its modification time as both creation and last access time. Entries
+    /// with no file are counted as missing and left out of the index.
+    pub async fn preload(&self, manifest: &[(CacheType, String)]) -> CachePreloadReport {
+        let mut report = CachePreloadReport::default();
+        let mut index = self.cache_index.lock().await;
+
+        for (cache_type, key) in manifest {
+            let metadata = match fs::metadata(self.get_cache_file_path(*cache_type, key)) {
+                Ok(metadata) if metadata.is_file() => metadata,
+                _ => {
+                    report.missing += 1;
+                    continue;
+                }
+            };
+
+            let mut entry = CacheEntry::new(key.clone(), *cache_type, metadata.len());
+            if let Some(modified) = metadata
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            {
+                entry.creation_time = modified.as_millis() as u64;
+                entry.last_access_time = entry.creation_time;
+            }
+
+            // Swap out the size the index held for the key, which may have
+            // been counted at another size or under another type
+            let previous = index.insert(key.clone(), entry);
+            let mut type_sizes = self.type_sizes.write().unwrap();
             let mut stats = self.statistics.write().unwrap();
-            stats.total_size = stats.total_size.saturating_sub(file_size);
-            if let Some(type_size) = stats.type_sizes.get_mut(&cache_type) {
-                *type_size = type_size.saturating_sub(file_size);
+            if let Some(previous) = previous {
+                for sizes in [&mut *type_sizes, &mut stats.type_sizes] {
+                    if let Some(type_size) = sizes.get_mut(&previous.cache_type) {
+                        *type_size = type_size.saturating_sub(previous.size);
+                    }
+                }
+                stats.total_size = stats.total_size.saturating_sub(previous.size);
+            }
+            for sizes in [&mut *type_sizes, &mut stats.type_sizes] {
+                *sizes.entry(*cache_type).or_insert(0) += metadata.len();
             }
+            stats.total_size += metadata.len();
+
+            report.found += 1;
         }
 
-        log::debug!(
-            "Removed {:?} item: {} ({})",
-            cache_type,
-            key,
-            format_bytes(file_size)
-        );
+        report
+    }
 
-        Ok(true)
+    /// Check if item exists in cache.
+    ///
+    /// This checks the filesystem and is authoritative; see
+    /// [`contains_key`](Self::contains_key) for a cheaper index lookup.
+    pub async fn exists(&self, cache_type: CacheType, key: &str) -> bool {
+        let cache_file = self.get_cache_file_path(cache_type, key);
+        cache_file.exists()
+    }
+
+    /// Whether the in-memory index has an entry for the item. O(1) and
+    /// without filesystem access, so suited to fast negative checks, but a
+    /// file deleted outside the manager still reads as present; use
+    /// [`exists`](Self::exists) when that matters.
+    pub async fn contains_key(&self, cache_type: CacheType, key: &str) -> bool {
+        self.cache_index
+            .lock()
+            .await
+            .get(key)
+            .is_some_and(|entry| entry.cache_type == cache_type)
+    }
+
+    /// Index record for an item: size, creation and last access times, and
+    /// access count. The file itself is not read.
+    pub async fn entry_metadata(&self, cache_type: CacheType, key: &str) -> Option<CacheEntry> {
+        self.cache_index
+            .lock()
+            .await
+            .get(key)
+            .filter(|entry| entry.cache_type == cache_type)
+            .cloned()
+    }
+
+    /// Keys of every indexed item of `cache_type`, sorted
+    pub async fn list_keys(&self, cache_type: CacheType) -> Vec<String> {
+        let mut keys: Vec<String> = self
+            .cache_index
+            .lock()
+            .await
+            .values()
+            .filter(|entry| entry.cache_type == cache_type)
+            .map(|entry| entry.key.clone())
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    /// Remove item from cache
+    pub async fn remove(
+        &self,
+        cache_type: CacheType,
+        key: &str,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let removal = self.shared_entries().remove(cache_type, key).await?;
+        Ok(removal != Removal::Missing)
     }
 
     /// Clear all cache for a specific type
@@ -532,7 +1152,7 @@ impl CacheManager {
         &self,
         cache_type: CacheType,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let type_dir = self.cache_directories.get(&cache_type)
+        let type_dir = self.cache_directories.read().unwrap().get(&cache_type).cloned()
             .ok_or("Cache directory not found")?;
 
         if !type_dir.exists() {
@@ -555,9 +1175,14 @@ impl CacheManager {
 
         // Remove directory contents
         if type_dir.exists() {
-            fs::remove_dir_all(type_dir)?;
-            fs::create_dir_all(type_dir)?;
+            fs::remove_dir_all(&type_dir)?;
+            fs::create_dir_all(&type_dir)?;
         }
+        self.content_files.lock().await.retain(|_, paths| {
+            paths.retain(|path| !path.starts_with(&type_dir));
+            !paths.is_empty()
+        });
+        self.content_refs.lock().await.retain(|(ref_type, _), _| *ref_type != cache_type);
 
         // Update cache tracking
         {
@@ -598,7 +1223,15 @@ impl CacheManager {
     }
 
     fn get_cache_file_path(&self, cache_type: CacheType, key: &str) -> PathBuf {
-        let type_dir = self.cache_directories.get(&cache_type)
+        Self::cache_file_path(&self.cache_directories.read().unwrap(), cache_type, key)
+    }
+
+    fn cache_file_path(
+        cache_directories: &HashMap<CacheType, PathBuf>,
+        cache_type: CacheType,
+        key: &str,
+    ) -> PathBuf {
+        let type_dir = cache_directories.get(&cache_type)
             .expect("Cache directory should exist");
         
         // Create subdirectories based on key hash for better file system performance
@@ -649,14 +1282,17 @@ impl CacheManager {
         // Sort by last access time (oldest first)
         type_entries.sort_by_key(|entry| entry.last_access_time);
 
+        let entries = self.shared_entries();
         let mut freed_space = 0u64;
         for entry in type_entries {
             if freed_space >= space_needed {
                 break;
             }
 
-            if self.remove(entry.cache_type, &entry.key).await? {
-                freed_space += entry.size;
+            // Referenced entries go too: dropping one reference frees nothing
+            if let Removal::Removed(size) = entries.evict(entry.cache_type, &entry.key).await? {
+                freed_space += size;
+                self.record_eviction(&entry.key);
             }
         }
 
@@ -694,14 +1330,17 @@ impl CacheManager {
         // Sort by last access time (oldest first)
         all_entries.sort_by_key(|entry| entry.last_access_time);
 
+        let entries = self.shared_entries();
         let mut freed_space = 0u64;
         for entry in all_entries {
             if freed_space >= space_needed {
                 break;
             }
 
-            if self.remove(entry.cache_type, &entry.key).await? {
-                freed_space += entry.size;
+            // Referenced entries go too: dropping one reference frees nothing
+            if let Removal::Removed(size) = entries.evict(entry.cache_type, &entry.key).await? {
+                freed_space += size;
+                self.record_eviction(&entry.key);
             }
         }
 
@@ -716,7 +1355,7 @@ impl CacheManager {
         let mut type_sizes = HashMap::new();
 
         for cache_type in CacheType::all_types() {
-            let type_dir = self.cache_directories.get(cache_type)
+            let type_dir = self.cache_directories.read().unwrap().get(cache_type).cloned()
                 .ok_or("Cache directory not found")?;
 
             if !type_dir.exists() {
@@ -762,53 +1401,108 @@ impl CacheManager {
         Ok(())
     }
 
-    async fn start_periodic_cleanup(&self) {
-        let statistics = Arc::clone(&self.statistics);
-        let cache_index = Arc::clone(&self.cache_index);
-        
+    /// Handles on the state shared by the removal path
+    fn shared_entries(&self) -> SharedEntries {
+        SharedEntries {
+            cache_directories: Arc::clone(&self.cache_directories),
+            statistics: Arc::clone(&self.statistics),
+            cache_index: Arc::clone(&self.cache_index),
+            type_sizes: Arc::clone(&self.type_sizes),
+            content_refs: Arc::clone(&self.content_refs),
+            content_files: Arc::clone(&self.content_files),
+        }
+    }
+
+    fn start_periodic_cleanup(&self) -> JoinHandle<()> {
+        let entries = self.shared_entries();
+        let type_ttls = Arc::clone(&self.type_ttls);
+        let mut period = self.cleanup_interval.subscribe();
+
         tokio::spawn(async move {
-            let mut interval = interval(Duration::from_secs(5 * 60)); // 5 minutes
-            
+            // The first pass runs a full interval after startup; after a
+            // reconfiguration the countdown restarts
+            let start = |every: Duration| interval_at(Instant::now() + every, every);
+            let mut ticker = period.borrow_and_update().map(start);
+
             loop {
-                interval.tick().await;
-                
-                // Perform maintenance cleanup
-                Self::perform_maintenance_cleanup(&statistics, &cache_index).await;
+                let tick = async {
+                    match ticker.as_mut() {
+                        Some(ticker) => {
+                            ticker.tick().await;
+                        }
+                        None => std::future::pending().await,
+                    }
+                };
+
+                tokio::select! {
+                    _ = tick => {
+                        // Perform maintenance cleanup
+                        if let Err(e) = Self::perform_maintenance_cleanup(&entries, &type_ttls).await {
+                            log::warn!("Maintenance cleanup failed: {}", e);
+                        }
+                    }
+                    changed = period.changed() => {
+                        // The manager was dropped
+                        if changed.is_err() {
+                            break;
+                        }
+                        ticker = period.borrow_and_update().map(start);
+                    }
+                }
             }
-        });
+        })
     }
 
+    /// Remove every entry older than its type's TTL, deleting its file.
+    /// Returns the number of entries removed.
     async fn perform_maintenance_cleanup(
-        statistics: &Arc<RwLock<CacheStatistics>>,
-        cache_index: &Arc<Mutex<HashMap<String, CacheEntry>>>,
-    ) {
+        entries: &SharedEntries,
+        type_ttls: &Arc<RwLock<HashMap<CacheType, Duration>>>,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
         log::debug!("Performing maintenance cleanup");
 
-        // Remove expired temporary cache entries
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_millis() as u64;
-        let temp_cache_expiry = 24 * 60 * 60 * 1000; // 24 hours
+        let expired = {
+            let index = entries.cache_index.lock().await;
+            Self::expired_entries(&index, &type_ttls.read().unwrap())
+        };
 
-        let mut expired_keys = Vec::new();
-        {
-            let index = cache_index.lock().await;
-            for (key, entry) in index.iter() {
-                if entry.cache_type == CacheType::Temporary &&
-                   now - entry.creation_time > temp_cache_expiry {
-                    expired_keys.push(key.clone());
-                }
+        let mut removed = 0;
+        for (cache_type, key) in expired {
+            if let Removal::Removed(_) = entries.evict(cache_type, &key).await? {
+                removed += 1;
             }
         }
 
-        // Remove expired entries
-        if !expired_keys.is_empty() {
-            let mut index = cache_index.lock().await;
-            for key in expired_keys {
-                index.remove(&key);
-            }
+        if removed > 0 {
+            log::debug!("Maintenance removed {} expired items", removed);
         }
+        Ok(removed)
+    }
+
+    /// Entries older than the TTL configured for their type
+    fn expired_entries(
+        index: &HashMap<String, CacheEntry>,
+        type_ttls: &HashMap<CacheType, Duration>,
+    ) -> Vec<(CacheType, String)> {
+        index
+            .values()
+            .filter(|entry| {
+                type_ttls
+                    .get(&entry.cache_type)
+                    .is_some_and(|ttl| entry.age() > *ttl)
+            })
+            .map(|entry| (entry.cache_type, entry.key.clone()))
+            .collect()
+    }
+
+    /// Remove every entry older than the TTL configured for its type,
+    /// deleting its file. Returns the number of entries removed.
+    ///
+    /// This runs the same expiry check as the periodic background cleanup,
+    /// but on demand. A content-addressed entry expires in one pass, however
+    /// many references it has.
+    pub async fn perform_maintenance(&self) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        Self::perform_maintenance_cleanup(&self.shared_entries(), &self.type_ttls).await
     }
 
     // Configuration methods
@@ -827,7 +1521,7 @@ impl CacheManager {
             for cache_type in CacheType::all_types() {
                 let type_dir = self.base_cache_directory.join(cache_type.folder_name());
                 fs::create_dir_all(&type_dir)?;
-                self.cache_directories.insert(*cache_type, type_dir);
+                self.cache_directories.write().unwrap().insert(*cache_type, type_dir);
             }
         }
         
@@ -855,6 +1549,104 @@ impl CacheManager {
         }
     }
 
+    /// Expire entries of `cache_type` once they are older than `ttl`, or
+    /// never if `None`.
+    ///
+    /// By default only [`CacheType::Temporary`] expires, after 24 hours; see
+    /// [`CacheType::recommended_ttl`].
+    pub fn set_type_ttl(&mut self, cache_type: CacheType, ttl: Option<Duration>) {
+        let mut type_ttls = self.type_ttls.write().unwrap();
+        match ttl {
+            Some(ttl) => type_ttls.insert(cache_type, ttl),
+            None => type_ttls.remove(&cache_type),
+        };
+    }
+
+    /// TTL configured for `cache_type`, if its entries expire
+    pub fn type_ttl(&self, cache_type: CacheType) -> Option<Duration> {
+        self.type_ttls.read().unwrap().get(&cache_type).copied()
+    }
+
+    /// Run the background maintenance cleanup every `every` rather than
+    /// every [`DEFAULT_CLEANUP_INTERVAL`]
+    pub fn with_cleanup_interval(mut self, every: Duration) -> Self {
+        self.set_cleanup_interval(Some(every));
+        self
+    }
+
+    /// Change how often the background maintenance cleanup runs, or stop
+    /// it with `None`. The running loop picks up the change at once and
+    /// waits a full new interval before its next pass.
+    pub fn set_cleanup_interval(&mut self, every: Option<Duration>) {
+        self.cleanup_interval.send_replace(every);
+    }
+
+    /// Period of the background maintenance cleanup, if it is enabled
+    pub fn cleanup_interval(&self) -> Option<Duration> {
+        *self.cleanup_interval.borrow()
+    }
+
+    /// Verify data read by [`retrieve`](Self::retrieve) against the MD5
+    /// recorded when it was stored. A mismatch evicts the entry and returns
+    /// [`LLSDError::ChecksumMismatch`]. Entries indexed from disk at startup
+    /// have no checksum and are returned unchecked. Off by default.
+    pub fn with_integrity_checks(mut self, enabled: bool) -> Self {
+        self.integrity_checks = enabled;
+        self
+    }
+
+    /// Report [`CacheEvent::ThrashDetected`] once the thrash ratio reaches
+    /// `threshold` (0.5 by default)
+    pub fn set_thrash_threshold(&mut self, threshold: f64) {
+        self.thrash_threshold = threshold;
+    }
+
+    /// Share of recent evictions whose key was requested again while still
+    /// remembered, from 0.0 to 1.0.
+    ///
+    /// The last 256 evictions for space are tracked. A high ratio means the
+    /// cache keeps evicting data it is about to need, so it is too small for
+    /// its working set.
+    pub fn thrash_ratio(&self) -> f64 {
+        self.thrash.read().unwrap().ratio()
+    }
+
+    /// Subscribe to [`CacheEvent`]s
+    pub fn subscribe(&self) -> broadcast::Receiver<CacheEvent> {
+        self.event_sender.subscribe()
+    }
+
+    fn record_eviction(&self, key: &str) {
+        self.thrash.write().unwrap().record_eviction(key);
+        self.check_thrash();
+    }
+
+    /// Send an event when the thrash ratio crosses the threshold
+    fn check_thrash(&self) {
+        let event = {
+            let mut thrash = self.thrash.write().unwrap();
+            if thrash.evicted.len() < THRASH_MIN_EVICTIONS {
+                return;
+            }
+            let ratio = thrash.ratio();
+            let thrashing = ratio >= self.thrash_threshold;
+            if thrashing == thrash.thrashing {
+                return;
+            }
+            thrash.thrashing = thrashing;
+            if thrashing {
+                CacheEvent::ThrashDetected(ratio)
+            } else {
+                CacheEvent::ThrashResolved(ratio)
+            }
+        };
+
+        if let CacheEvent::ThrashDetected(ratio) = event {
+            log::warn!("Cache is thrashing: {:.0}% of recent evictions were requested again", ratio * 100.0);
+        }
+        let _ = self.event_sender.send(event);
+    }
+
     /// Get current cache statistics
     pub fn get_statistics(&self) -> CacheStatistics {
         self.statistics.read().unwrap().clone()
@@ -877,12 +1669,18 @@ impl CacheManager {
         stats.available_space()
     }
 
-    /// Shutdown cache manager
+    /// Shutdown cache manager, stopping its background maintenance task.
+    /// Calling it again does nothing.
     pub async fn shutdown(&self) {
         log::info!("Shutting down Rust cache manager");
-        
-        // Cache cleanup is automatic due to Rust's RAII
-        
+
+        let task = self.maintenance_task.lock().unwrap().take();
+        if let Some(task) = task {
+            task.abort();
+            // Cancelled unless the task had already stopped
+            let _ = task.await;
+        }
+
         log::info!("Rust cache manager shutdown complete");
     }
 }
@@ -907,11 +1705,591 @@ pub fn format_bytes(bytes: u64) -> String {
     format!("{:.1} {}", size, UNITS[unit_index])
 }
 
-// Add required dependencies to Cargo.toml:
-// [dependencies]
-// tokio = { version = "1", features = ["full"] }
-// serde = { version = "1.0", features = ["derive"] }
-// log = "0.4"
-// dirs = "4.0"
-// walkdir = "2"
-// md5 = "0.7"
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Cache manager in a fresh temporary directory
+    async fn temp_manager() -> (CacheManager, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("llsd-cache-test-{}", uuid::Uuid::new_v4()));
+        let manager = CacheManager::with_base_directory(StorageLocation::SystemTemp, dir.clone(), DEFAULT_CACHE_SIZE)
+            .await
+            .unwrap();
+        (manager, dir)
+    }
+
+    /// Make an indexed entry look `age` older than it is
+    async fn backdate(manager: &CacheManager, key: &str, age: Duration) {
+        let mut index = manager.cache_index.lock().await;
+        let entry = index.get_mut(key).unwrap();
+        entry.creation_time -= age.as_millis() as u64;
+    }
+
+    #[tokio::test]
+    async fn test_type_ttl_expires_entries() {
+        let (mut manager, dir) = temp_manager().await;
+        assert_eq!(manager.type_ttl(CacheType::Temporary), Some(Duration::from_secs(24 * 60 * 60)));
+        assert_eq!(manager.type_ttl(CacheType::Texture), None);
+
+        manager.set_type_ttl(CacheType::Texture, Some(Duration::from_millis(1)));
+        manager.store(CacheType::Texture, "short-lived".to_string(), b"data".to_vec()).await.unwrap();
+        manager.store(CacheType::Sound, "kept".to_string(), b"data".to_vec()).await.unwrap();
+
+        backdate(&manager, "short-lived", Duration::from_secs(1)).await;
+        backdate(&manager, "kept", Duration::from_secs(1)).await;
+        assert_eq!(manager.perform_maintenance().await.unwrap(), 1);
+        assert!(!manager.exists(CacheType::Texture, "short-lived").await);
+        assert!(manager.exists(CacheType::Sound, "kept").await);
+        assert_eq!(manager.get_statistics().type_size(CacheType::Texture), 0);
+
+        manager.set_type_ttl(CacheType::Texture, None);
+        assert_eq!(manager.type_ttl(CacheType::Texture), None);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_cleanup_interval_expires_entries() {
+        let (manager, dir) = temp_manager().await;
+        assert_eq!(manager.cleanup_interval(), Some(DEFAULT_CLEANUP_INTERVAL));
+        let mut manager = manager.with_cleanup_interval(Duration::from_secs(10));
+        assert_eq!(manager.cleanup_interval(), Some(Duration::from_secs(10)));
+
+        manager.store(CacheType::Temporary, "scratch".to_string(), b"data".to_vec()).await.unwrap();
+        backdate(&manager, "scratch", Duration::from_secs(48 * 60 * 60)).await;
+
+        // Nothing runs before the first interval has passed
+        tokio::time::sleep(Duration::from_secs(9)).await;
+        assert!(manager.contains_key(CacheType::Temporary, "scratch").await);
+
+        // The background pass removes the file and its accounting too
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        assert!(!manager.contains_key(CacheType::Temporary, "scratch").await);
+        assert!(!manager.exists(CacheType::Temporary, "scratch").await);
+        assert_eq!(manager.get_statistics().type_size(CacheType::Temporary), 0);
+        assert_eq!(manager.get_statistics().total_size, 0);
+
+        // Disabled, the loop leaves expired entries alone
+        manager.set_cleanup_interval(None);
+        assert_eq!(manager.cleanup_interval(), None);
+        manager.store(CacheType::Temporary, "kept".to_string(), b"data".to_vec()).await.unwrap();
+        backdate(&manager, "kept", Duration::from_secs(48 * 60 * 60)).await;
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        assert!(manager.contains_key(CacheType::Temporary, "kept").await);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_stops_maintenance_task() {
+        let (manager, dir) = temp_manager().await;
+        let manager = manager.with_cleanup_interval(Duration::from_millis(1));
+
+        // The task holds its own handle on the index while it runs
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(Arc::strong_count(&manager.cache_index), 2);
+
+        manager.shutdown().await;
+        assert!(manager.maintenance_task.lock().unwrap().is_none());
+        assert_eq!(Arc::strong_count(&manager.cache_index), 1);
+
+        // Nothing left to expire entries in the background
+        let mut manager = manager;
+        manager.set_type_ttl(CacheType::Temporary, Some(Duration::from_millis(1)));
+        manager.store(CacheType::Temporary, "scratch".to_string(), b"data".to_vec()).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(manager.contains_key(CacheType::Temporary, "scratch").await);
+
+        manager.shutdown().await;
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_retrieve_by_hash() {
+        let (manager, dir) = temp_manager().await;
+        let data = b"mesh asset bytes".to_vec();
+        manager.store(CacheType::Mesh, "mesh-a".to_string(), data.clone()).await.unwrap();
+
+        let hash = md5::compute(&data).0;
+        assert_eq!(manager.retrieve_by_hash(&hash).await, Some(data.clone()));
+        assert_eq!(manager.retrieve_by_hash(&md5::compute(b"other").0).await, None);
+
+        // Found through the cache index once the session's hash index
+        // forgets the file
+        manager.content_files.lock().await.clear();
+        assert_eq!(manager.retrieve_by_hash(&hash).await, Some(data.clone()));
+
+        // Overwritten under the same key, the old hash finds nothing
+        manager.store(CacheType::Mesh, "mesh-a".to_string(), b"replaced".to_vec()).await.unwrap();
+        assert_eq!(manager.retrieve_by_hash(&hash).await, None);
+        assert_eq!(
+            manager.retrieve_by_hash(&md5::compute(b"replaced").0).await,
+            Some(b"replaced".to_vec())
+        );
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_preload_from_manifest() {
+        let (manager, dir) = temp_manager().await;
+        manager.store(CacheType::Texture, "tex-a".to_string(), vec![1; 100]).await.unwrap();
+        manager.store(CacheType::Texture, "tex-b".to_string(), vec![2; 200]).await.unwrap();
+        manager.store(CacheType::Sound, "snd-a".to_string(), vec![3; 50]).await.unwrap();
+        drop(manager);
+
+        let fresh = CacheManager::with_base_directory(StorageLocation::SystemTemp, dir.clone(), DEFAULT_CACHE_SIZE)
+            .await
+            .unwrap();
+        let manifest = vec![
+            (CacheType::Texture, "tex-a".to_string()),
+            (CacheType::Texture, "tex-b".to_string()),
+            (CacheType::Sound, "snd-a".to_string()),
+            (CacheType::Sound, "snd-gone".to_string()),
+            (CacheType::Mesh, "tex-a".to_string()),
+        ];
+        let report = fresh.preload(&manifest).await;
+        assert_eq!(report, CachePreloadReport { found: 3, missing: 2 });
+
+        // Entries already indexed at startup are not counted twice
+        let stats = fresh.get_statistics();
+        assert_eq!(stats.type_size(CacheType::Texture), 300);
+        assert_eq!(stats.total_size, 350);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_preload_replaces_previous_size() {
+        let (manager, dir) = temp_manager().await;
+        manager.store(CacheType::Texture, "asset".to_string(), vec![1; 300]).await.unwrap();
+        manager.store(CacheType::Sound, "clip".to_string(), vec![2; 100]).await.unwrap();
+
+        // The texture file shrinks behind the manager's back
+        fs::write(manager.get_cache_file_path(CacheType::Texture, "asset"), vec![1; 120]).unwrap();
+        // And the same key turns up as a mesh
+        let mesh_file = manager.get_cache_file_path(CacheType::Mesh, "clip");
+        fs::create_dir_all(mesh_file.parent().unwrap()).unwrap();
+        fs::write(mesh_file, vec![3; 40]).unwrap();
+
+        let manifest = vec![
+            (CacheType::Texture, "asset".to_string()),
+            (CacheType::Mesh, "clip".to_string()),
+        ];
+        assert_eq!(manager.preload(&manifest).await, CachePreloadReport { found: 2, missing: 0 });
+
+        let stats = manager.get_statistics();
+        assert_eq!(stats.type_size(CacheType::Texture), 120);
+        assert_eq!(stats.type_size(CacheType::Sound), 0);
+        assert_eq!(stats.type_size(CacheType::Mesh), 40);
+        assert_eq!(stats.total_size, 160);
+        let type_sizes = manager.get_cache_type_sizes();
+        assert_eq!(type_sizes[&CacheType::Sound], 0);
+        assert_eq!(type_sizes[&CacheType::Mesh], 40);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_entry_metadata_and_list_keys() {
+        let (manager, dir) = temp_manager().await;
+        manager.store(CacheType::Texture, "tex-b".to_string(), vec![1; 64]).await.unwrap();
+        manager.store(CacheType::Texture, "tex-a".to_string(), vec![2; 32]).await.unwrap();
+        manager.store(CacheType::Sound, "snd".to_string(), vec![3; 16]).await.unwrap();
+
+        let entry = manager.entry_metadata(CacheType::Texture, "tex-b").await.unwrap();
+        assert_eq!(entry.key, "tex-b");
+        assert_eq!(entry.cache_type, CacheType::Texture);
+        assert_eq!(entry.size, 64);
+        assert_eq!(entry.access_count, 0);
+        assert!(entry.creation_time > 0);
+        assert_eq!(entry.last_access_time, entry.creation_time);
+
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        manager.retrieve(CacheType::Texture, "tex-b").await.unwrap().unwrap();
+        manager.retrieve(CacheType::Texture, "tex-b").await.unwrap().unwrap();
+        let accessed = manager.entry_metadata(CacheType::Texture, "tex-b").await.unwrap();
+        assert_eq!(accessed.access_count, 2);
+        assert!(accessed.last_access_time > entry.last_access_time);
+        assert_eq!(accessed.creation_time, entry.creation_time);
+
+        assert!(manager.entry_metadata(CacheType::Sound, "tex-b").await.is_none());
+        assert!(manager.entry_metadata(CacheType::Texture, "missing").await.is_none());
+
+        assert_eq!(manager.list_keys(CacheType::Texture).await, vec!["tex-a", "tex-b"]);
+        assert_eq!(manager.list_keys(CacheType::Sound).await, vec!["snd"]);
+        assert!(manager.list_keys(CacheType::Mesh).await.is_empty());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_integrity_check_evicts_corrupted_entry() {
+        let (manager, dir) = temp_manager().await;
+        let manager = manager.with_integrity_checks(true);
+        manager.store(CacheType::Texture, "tex".to_string(), vec![7; 256]).await.unwrap();
+        assert_eq!(manager.retrieve(CacheType::Texture, "tex").await.unwrap(), Some(vec![7; 256]));
+
+        // Truncated write
+        fs::write(manager.get_cache_file_path(CacheType::Texture, "tex"), [7; 100]).unwrap();
+
+        let err = manager.retrieve(CacheType::Texture, "tex").await.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<LLSDError>(),
+            Some(LLSDError::ChecksumMismatch { key }) if key == "tex"
+        ));
+        assert!(!manager.exists(CacheType::Texture, "tex").await);
+        assert!(manager.entry_metadata(CacheType::Texture, "tex").await.is_none());
+        assert_eq!(manager.retrieve(CacheType::Texture, "tex").await.unwrap(), None);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_contains_key_reflects_index() {
+        let (manager, dir) = temp_manager().await;
+        assert!(!manager.contains_key(CacheType::Texture, "tex").await);
+
+        manager.store(CacheType::Texture, "tex".to_string(), vec![1; 16]).await.unwrap();
+        assert!(manager.contains_key(CacheType::Texture, "tex").await);
+        assert!(!manager.contains_key(CacheType::Sound, "tex").await);
+
+        // Deleted behind the manager's back: only the disk check notices
+        fs::remove_file(manager.get_cache_file_path(CacheType::Texture, "tex")).unwrap();
+        assert!(manager.contains_key(CacheType::Texture, "tex").await);
+        assert!(!manager.exists(CacheType::Texture, "tex").await);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    /// Retrieve keys `0..keys` in order, storing each one that misses
+    async fn read_through(manager: &CacheManager, keys: usize, rounds: usize) {
+        for _ in 0..rounds {
+            for i in 0..keys {
+                let key = format!("texture-{}", i);
+                if manager.retrieve(CacheType::Texture, &key).await.unwrap().is_none() {
+                    let data = vec![i as u8; 1000];
+                    assert!(manager.store(CacheType::Texture, key, data).await.unwrap());
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_thrash_ratio_for_oversized_working_set() {
+        let dir = std::env::temp_dir().join(format!("llsd-cache-test-{}", uuid::Uuid::new_v4()));
+        // Textures get 60%: room for six 1000 byte items
+        let manager = CacheManager::with_base_directory(StorageLocation::SystemTemp, dir.clone(), 10_000)
+            .await
+            .unwrap();
+        let mut events = manager.subscribe();
+
+        // A working set that fits, then one twice the cache's size
+        read_through(&manager, 4, 5).await;
+        assert_eq!(manager.thrash_ratio(), 0.0);
+        assert!(events.try_recv().is_err());
+
+        read_through(&manager, 12, 5).await;
+        let ratio = manager.thrash_ratio();
+        assert!(ratio > 0.5, "thrash ratio {}", ratio);
+        assert!(matches!(events.try_recv(), Ok(CacheEvent::ThrashDetected(r)) if r >= 0.5));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    fn file_count(dir: &std::path::Path) -> usize {
+        walkdir::WalkDir::new(dir)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().is_file())
+            .count()
+    }
+
+    #[tokio::test]
+    async fn test_content_addressed_storage() {
+        let (manager, dir) = temp_manager().await;
+        let texture_dir = dir.join(CacheType::Texture.folder_name());
+
+        let first = manager
+            .store_content_addressed(CacheType::Texture, b"texture bytes".to_vec())
+            .await
+            .unwrap();
+        let second = manager
+            .store_content_addressed(CacheType::Texture, b"texture bytes".to_vec())
+            .await
+            .unwrap();
+
+        assert_eq!(first, second);
+        assert_eq!(file_count(&texture_dir), 1);
+        assert_eq!(manager.content_ref_count(CacheType::Texture, &first).await, 2);
+
+        // The file survives until the last reference is removed
+        assert!(manager.remove(CacheType::Texture, &first).await.unwrap());
+        assert_eq!(manager.content_ref_count(CacheType::Texture, &first).await, 1);
+        assert!(manager.exists(CacheType::Texture, &first).await);
+
+        assert!(manager.remove(CacheType::Texture, &first).await.unwrap());
+        assert_eq!(manager.content_ref_count(CacheType::Texture, &first).await, 0);
+        assert!(!manager.exists(CacheType::Texture, &first).await);
+        assert_eq!(file_count(&texture_dir), 0);
+
+        // The same bytes under another type get their own file and count
+        let texture = manager
+            .store_content_addressed(CacheType::Texture, b"shared bytes".to_vec())
+            .await
+            .unwrap();
+        let sound = manager
+            .store_content_addressed(CacheType::Sound, b"shared bytes".to_vec())
+            .await
+            .unwrap();
+        assert_eq!(texture, sound);
+        assert_eq!(manager.content_ref_count(CacheType::Texture, &texture).await, 1);
+        assert_eq!(manager.content_ref_count(CacheType::Sound, &sound).await, 1);
+        assert_eq!(
+            manager.retrieve(CacheType::Sound, &sound).await.unwrap(),
+            Some(b"shared bytes".to_vec())
+        );
+
+        // Clearing a type drops its counts but not another type's
+        manager.clear_cache(CacheType::Texture).await.unwrap();
+        assert_eq!(manager.content_ref_count(CacheType::Texture, &texture).await, 0);
+        assert_eq!(manager.content_ref_count(CacheType::Sound, &sound).await, 1);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_content_addressed_stores() {
+        let (manager, dir) = temp_manager().await;
+        let manager = Arc::new(manager);
+        let start = Arc::new(tokio::sync::Barrier::new(8));
+
+        let stores: Vec<_> = (0..8)
+            .map(|_| {
+                let manager = Arc::clone(&manager);
+                let start = Arc::clone(&start);
+                tokio::spawn(async move {
+                    start.wait().await;
+                    manager
+                        .store_content_addressed(CacheType::Texture, b"texture bytes".to_vec())
+                        .await
+                        .unwrap()
+                })
+            })
+            .collect();
+        let mut keys = Vec::new();
+        for store in stores {
+            keys.push(store.await.unwrap());
+        }
+
+        // One file, tracked once, with a reference per store
+        keys.dedup();
+        assert_eq!(keys.len(), 1);
+        assert_eq!(manager.content_ref_count(CacheType::Texture, &keys[0]).await, 8);
+        assert_eq!(manager.get_statistics().type_size(CacheType::Texture), 13);
+        assert_eq!(manager.get_cache_type_sizes()[&CacheType::Texture], 13);
+        assert!(manager.content_locks.lock().await.is_empty());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_eviction_drops_referenced_entries() {
+        let dir = std::env::temp_dir().join(format!("llsd-cache-test-{}", uuid::Uuid::new_v4()));
+        // Textures get 60%: room for six 1000 byte items
+        let manager = CacheManager::with_base_directory(StorageLocation::SystemTemp, dir.clone(), 10_000)
+            .await
+            .unwrap();
+
+        let shared = manager
+            .store_content_addressed(CacheType::Texture, vec![0xAB; 1000])
+            .await
+            .unwrap();
+        manager
+            .store_content_addressed(CacheType::Texture, vec![0xAB; 1000])
+            .await
+            .unwrap();
+        manager.cache_index.lock().await.get_mut(&shared).unwrap().last_access_time = 0;
+
+        // Evicting the shared entry must free its bytes, not one reference
+        for i in 0..6u8 {
+            manager.store(CacheType::Texture, format!("texture-{}", i), vec![i; 1000]).await.unwrap();
+        }
+        assert!(!manager.exists(CacheType::Texture, &shared).await);
+        assert_eq!(manager.content_ref_count(CacheType::Texture, &shared).await, 0);
+        assert_eq!(manager.get_statistics().type_size(CacheType::Texture), 6000);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_expiry_drops_referenced_entries() {
+        let (mut manager, dir) = temp_manager().await;
+        manager.set_type_ttl(CacheType::Texture, Some(Duration::from_millis(1)));
+
+        let mut key = String::new();
+        for _ in 0..3 {
+            key = manager
+                .store_content_addressed(CacheType::Texture, b"texture bytes".to_vec())
+                .await
+                .unwrap();
+        }
+        backdate(&manager, &key, Duration::from_secs(1)).await;
+
+        assert_eq!(manager.perform_maintenance().await.unwrap(), 1);
+        assert!(!manager.exists(CacheType::Texture, &key).await);
+        assert_eq!(manager.content_ref_count(CacheType::Texture, &key).await, 0);
+        assert_eq!(manager.get_statistics().type_size(CacheType::Texture), 0);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_store_deduplicates_identical_data() {
+        let (manager, dir) = temp_manager().await;
+        let texture_dir = dir.join(CacheType::Texture.folder_name());
+        let data = b"shared texture bytes".to_vec();
+
+        assert!(manager.store(CacheType::Texture, "first".to_string(), data.clone()).await.unwrap());
+        assert_eq!(manager.dedup_savings(), 0);
+        assert!(manager.store(CacheType::Texture, "second".to_string(), data.clone()).await.unwrap());
+        assert_eq!(manager.dedup_savings(), data.len() as u64);
+
+        // Both keys resolve to one physical file
+        let first = fs::metadata(manager.get_cache_file_path(CacheType::Texture, "first")).unwrap();
+        let second = fs::metadata(manager.get_cache_file_path(CacheType::Texture, "second")).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            assert_eq!((first.dev(), first.ino()), (second.dev(), second.ino()));
+            assert_eq!(first.nlink(), 2);
+        }
+        assert_eq!(first.len(), second.len());
+
+        let hashes: Vec<Option<String>> = {
+            let index = manager.cache_index.lock().await;
+            ["first", "second"].iter().map(|k| index[*k].content_hash.clone()).collect()
+        };
+        assert_eq!(hashes[0], Some(format!("{:x}", md5::compute(&data))));
+        assert_eq!(hashes[0], hashes[1]);
+
+        // Rewriting one key must not change the other
+        assert!(manager.store(CacheType::Texture, "second".to_string(), b"new".to_vec()).await.unwrap());
+        assert_eq!(manager.retrieve(CacheType::Texture, "first").await.unwrap(), Some(data.clone()));
+        assert_eq!(manager.retrieve(CacheType::Texture, "second").await.unwrap(), Some(b"new".to_vec()));
+
+        // Storing the same data under the same key again saves nothing
+        assert!(manager.store(CacheType::Texture, "first".to_string(), data.clone()).await.unwrap());
+        assert_eq!(manager.dedup_savings(), data.len() as u64);
+
+        assert!(manager.remove(CacheType::Texture, "first").await.unwrap());
+        assert!(manager.store(CacheType::Texture, "third".to_string(), data.clone()).await.unwrap());
+        assert_eq!(manager.dedup_savings(), data.len() as u64);
+        assert_eq!(file_count(&texture_dir), 2);
+
+        // Removing the first holder of some data leaves the others to link to
+        assert!(manager.store(CacheType::Texture, "fourth".to_string(), data.clone()).await.unwrap());
+        assert_eq!(manager.dedup_savings(), 2 * data.len() as u64);
+        assert!(manager.remove(CacheType::Texture, "third").await.unwrap());
+        assert!(manager.store(CacheType::Texture, "fifth".to_string(), data.clone()).await.unwrap());
+        assert_eq!(manager.dedup_savings(), 3 * data.len() as u64);
+        assert_eq!(file_count(&texture_dir), 3);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_store_does_not_link_on_hash_alone() {
+        let (manager, dir) = temp_manager().await;
+        let data = b"genuine bytes".to_vec();
+
+        // A file with other bytes claiming the same digest, as a collision would
+        assert!(manager.store(CacheType::Texture, "impostor".to_string(), b"forged".to_vec()).await.unwrap());
+        let impostor = manager.get_cache_file_path(CacheType::Texture, "impostor");
+        manager
+            .content_files
+            .lock()
+            .await
+            .insert(format!("{:x}", md5::compute(&data)), vec![impostor]);
+
+        assert!(manager.store(CacheType::Texture, "genuine".to_string(), data.clone()).await.unwrap());
+        assert_eq!(manager.dedup_savings(), 0);
+        assert_eq!(manager.retrieve(CacheType::Texture, "genuine").await.unwrap(), Some(data));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_manifest_reports_missing_entries() {
+        let (source, source_dir) = temp_manager().await;
+        for (key, data) in [("alpha", &b"first"[..]), ("beta", b"second"), ("gamma", b"third")] {
+            assert!(source.store(CacheType::Texture, key.to_string(), data.to_vec()).await.unwrap());
+        }
+        assert!(source.store(CacheType::Sound, "delta".to_string(), b"fourth".to_vec()).await.unwrap());
+
+        // The manifest travels as LLSD
+        let manifest = source.export_manifest().await.unwrap();
+        let xml = crate::LLSDFactory::serialize_xml(&crate::LLSDDocument::new(manifest), false).unwrap();
+        let manifest = crate::LLSDFactory::parse_xml(&xml).unwrap().content().clone();
+        assert_eq!(manifest.get_path("entries").and_then(|e| e.as_array()).map(|e| e.len()), Some(4));
+        assert_eq!(manifest.get_path("missing"), Some(&LLSDValue::Array(Vec::new())));
+
+        fs::remove_file(source.get_cache_file_path(CacheType::Texture, "beta")).unwrap();
+        fs::write(source.get_cache_file_path(CacheType::Sound, "delta"), b"changed").unwrap();
+
+        let report = source.import_manifest(&manifest).await.unwrap();
+        assert_eq!(report.present, vec!["alpha".to_string(), "gamma".to_string()]);
+        assert_eq!(report.missing, vec!["beta".to_string()]);
+        assert_eq!(report.mismatched, vec!["delta".to_string()]);
+        assert!(!report.is_complete());
+
+        // An empty cache is missing everything
+        let (target, target_dir) = temp_manager().await;
+        let report = target.import_manifest(&manifest).await.unwrap();
+        assert_eq!(report.missing.len(), 4);
+        assert!(target.import_manifest(&LLSDValue::Integer(1)).await.is_err());
+
+        // Files that vanish from the source are listed rather than failing the export
+        let manifest = source.export_manifest().await.unwrap();
+        let keys = |name: &str| -> Vec<String> {
+            let items = manifest.get_path(name).and_then(|v| v.as_array()).unwrap();
+            items
+                .iter()
+                .map(|item| item.get_path("key").unwrap_or(item).as_string().unwrap().to_string())
+                .collect()
+        };
+        assert_eq!(keys("entries"), vec!["alpha", "delta", "gamma"]);
+        assert_eq!(keys("missing"), vec!["beta"]);
+
+        let _ = fs::remove_dir_all(&source_dir);
+        let _ = fs::remove_dir_all(&target_dir);
+    }
+
+    #[test]
+    fn test_recommended_limits_cover_total() {
+        let percent: u64 = CacheType::all_types().iter().map(|t| t.share_percent()).sum();
+        assert_eq!(percent, 100);
+
+        for total in [DEFAULT_CACHE_SIZE, MAX_CACHE_SIZE, 1_000_003, 0] {
+            let sum: u64 = CacheType::all_types()
+                .iter()
+                .map(|t| t.recommended_limit(total))
+                .sum();
+            // Integer division may drop at most one byte per type
+            assert!(sum <= total);
+            assert!(total - sum < CacheType::all_types().len() as u64);
+        }
+
+        assert_eq!(CacheType::Texture.recommended_limit(1000), 600);
+        assert_eq!(CacheType::Temporary.recommended_limit(1000), 10);
+    }
+
+    #[test]
+    fn test_recommended_ttl() {
+        assert_eq!(
+            CacheType::Temporary.recommended_ttl(),
+            Some(Duration::from_secs(24 * 60 * 60))
+        );
+        assert_eq!(CacheType::Texture.recommended_ttl(), None);
+    }
+}
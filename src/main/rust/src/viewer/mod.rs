@@ -0,0 +1,9 @@
+/*!
+ * Viewer Subsystems - Rust Implementation
+ *
+ * Cache management and rendering controls modelled on the Second Life viewer
+ * Copyright (C) 2024 Linden Lab
+ */
+
+pub mod cache;
+pub mod rendering;
@@ -6,20 +6,22 @@
  * Rust implementation Copyright (C) 2024
  */
 
-use std::collections::HashMap;
+//! Advanced rendering system with fine-grained controls (Rust implementation).
+//!
+//! Features:
+//! - Zero-cost abstractions with Rust's type system
+//! - Safe concurrent rendering with ownership guarantees
+//! - Memory-safe OpenGL/Vulkan integration
+//! - High-performance async event system with Tokio
+
+use std::collections::{HashMap, VecDeque};
 use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant};
 use tokio::sync::{broadcast, RwLock as AsyncRwLock};
 use tokio::time::{interval, sleep};
 use serde::{Deserialize, Serialize};
-
-/// Advanced rendering system with fine-grained controls (Rust implementation).
-/// 
-/// Features:
-/// - Zero-cost abstractions with Rust's type system
-/// - Safe concurrent rendering with ownership guarantees
-/// - Memory-safe OpenGL/Vulkan integration
-/// - High-performance async event system with Tokio
+use crate::error::{LLSDError, LLSDResult};
+use crate::types::LLSDValue;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TextureQuality {
@@ -241,6 +243,19 @@ impl Default for ParticleSettings {
     }
 }
 
+/// Every settings group of an [`AdvancedRenderingSystem`] at one moment
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RenderingSnapshot {
+    pub quality: QualitySettings,
+    pub performance: PerformanceSettings,
+    pub effects: EffectsSettings,
+    pub textures: TextureSettings,
+    pub shadows: ShadowSettings,
+    pub meshes: MeshSettings,
+    pub avatars: AvatarSettings,
+    pub particles: ParticleSettings,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RenderStatistics {
     pub rendering_enabled: bool,
@@ -268,14 +283,24 @@ impl Default for RenderStatistics {
     }
 }
 
+/// Number of per-second FPS samples averaged by default
+pub const DEFAULT_FPS_WINDOW: usize = 60;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct PerformanceMetrics {
     pub current_fps: u32,
+    /// Mean of the last `fps_window` FPS samples
     pub average_fps: f32,
     pub frame_time: f32,
     pub cpu_usage: f32,
     pub memory_usage: u64,
     pub battery_level: Option<f32>,
+    /// Number of samples in the moving average window, read again for each
+    /// sample so a change applies to a running render loop
+    pub fps_window: usize,
+    /// Number of samples currently contributing to `average_fps`
+    pub fps_sample_count: usize,
 }
 
 impl Default for PerformanceMetrics {
@@ -287,7 +312,41 @@ impl Default for PerformanceMetrics {
             cpu_usage: 0.0,
             memory_usage: 0,
             battery_level: None,
+            fps_window: DEFAULT_FPS_WINDOW,
+            fps_sample_count: 0,
+        }
+    }
+}
+
+impl PerformanceMetrics {
+    /// Create metrics averaging over the last `window` FPS samples
+    pub fn with_fps_window(window: usize) -> Self {
+        Self {
+            fps_window: window.max(1),
+            ..Self::default()
+        }
+    }
+}
+
+/// Moving average of the render loop's per-second FPS samples
+#[derive(Debug, Default)]
+struct FpsAverage {
+    samples: VecDeque<u32>,
+}
+
+impl FpsAverage {
+    /// Record a sample and publish it, the mean of the last
+    /// `metrics.fps_window` samples and the sample count to `metrics`
+    fn record(&mut self, fps: u32, metrics: &mut PerformanceMetrics) {
+        self.samples.push_back(fps);
+        while self.samples.len() > metrics.fps_window.max(1) {
+            self.samples.pop_front();
         }
+
+        let sum: u64 = self.samples.iter().map(|&s| s as u64).sum();
+        metrics.current_fps = fps;
+        metrics.average_fps = sum as f32 / self.samples.len() as f32;
+        metrics.fps_sample_count = self.samples.len();
     }
 }
 
@@ -339,7 +398,6 @@ pub struct AdvancedRenderingSystem {
     // Frame timing
     last_frame_time: Arc<RwLock<Instant>>,
     frame_count: Arc<RwLock<u64>>,
-    fps_update_time: Arc<RwLock<Instant>>,
 }
 
 #[derive(Debug, Clone)]
@@ -376,7 +434,6 @@ impl AdvancedRenderingSystem {
             stored_settings: Arc::new(RwLock::new(None)),
             last_frame_time: Arc::new(RwLock::new(Instant::now())),
             frame_count: Arc::new(RwLock::new(0)),
-            fps_update_time: Arc::new(RwLock::new(Instant::now())),
         };
 
         system.apply_balanced_preset().await;
@@ -708,7 +765,7 @@ impl AdvancedRenderingSystem {
                 effects_enabled: effects.effects_enabled,
                 shadows_enabled: shadow.shadows_enabled,
                 max_particles: particle.max_particles,
-                texture_quality: texture.texture_quality.clone(),
+                texture_quality: texture.texture_quality,
                 lod_bias: mesh.lod_bias,
                 max_avatars: avatar.max_visible_avatars,
             }
@@ -817,7 +874,6 @@ impl AdvancedRenderingSystem {
             let performance_metrics = Arc::clone(&self.performance_metrics);
             let frame_count = Arc::clone(&self.frame_count);
             let last_frame_time = Arc::clone(&self.last_frame_time);
-            let fps_update_time = Arc::clone(&self.fps_update_time);
 
             let handle = tokio::spawn(async move {
                 Self::render_loop(
@@ -827,7 +883,6 @@ impl AdvancedRenderingSystem {
                     performance_metrics,
                     frame_count,
                     last_frame_time,
-                    fps_update_time,
                 ).await;
             });
 
@@ -849,11 +904,11 @@ impl AdvancedRenderingSystem {
         performance_metrics: Arc<RwLock<PerformanceMetrics>>,
         frame_count: Arc<RwLock<u64>>,
         last_frame_time: Arc<RwLock<Instant>>,
-        fps_update_time: Arc<RwLock<Instant>>,
     ) {
         let mut frame_interval = interval(Duration::from_millis(16)); // ~60 FPS
         let mut fps_counter = 0u32;
         let mut fps_start = Instant::now();
+        let mut fps_average = FpsAverage::default();
 
         loop {
             frame_interval.tick().await;
@@ -884,8 +939,7 @@ impl AdvancedRenderingSystem {
             if current_time.duration_since(fps_start) >= Duration::from_secs(1) {
                 {
                     let mut metrics = performance_metrics.write().unwrap();
-                    metrics.current_fps = fps_counter;
-                    metrics.average_fps = (metrics.average_fps + fps_counter as f32) / 2.0;
+                    fps_average.record(fps_counter, &mut metrics);
                     metrics.frame_time = frame_time;
                 }
 
@@ -1009,6 +1063,12 @@ impl AdvancedRenderingSystem {
         self.performance_metrics.read().unwrap().clone()
     }
 
+    /// Average FPS over the last `window` samples from the next sample on,
+    /// including in a render loop that is already running
+    pub fn set_fps_window(&self, window: usize) {
+        self.performance_metrics.write().unwrap().fps_window = window.max(1);
+    }
+
     pub fn get_render_statistics(&self) -> RenderStatistics {
         self.render_statistics.read().unwrap().clone()
     }
@@ -1050,6 +1110,33 @@ impl AdvancedRenderingSystem {
         settings
     }
 
+    pub async fn snapshot(&self) -> RenderingSnapshot {
+        RenderingSnapshot {
+            quality: self.quality_settings.read().await.clone(),
+            performance: self.performance_settings.read().await.clone(),
+            effects: self.effects_settings.read().await.clone(),
+            textures: self.texture_settings.read().await.clone(),
+            shadows: self.shadow_settings.read().await.clone(),
+            meshes: self.mesh_settings.read().await.clone(),
+            avatars: self.avatar_settings.read().await.clone(),
+            particles: self.particle_settings.read().await.clone(),
+        }
+    }
+
+    pub async fn apply_snapshot(&self, snapshot: RenderingSnapshot) {
+        *self.quality_settings.write().await = snapshot.quality;
+        *self.performance_settings.write().await = snapshot.performance;
+        *self.effects_settings.write().await = snapshot.effects;
+        *self.texture_settings.write().await = snapshot.textures;
+        *self.shadow_settings.write().await = snapshot.shadows;
+        *self.mesh_settings.write().await = snapshot.meshes;
+        *self.avatar_settings.write().await = snapshot.avatars;
+        *self.particle_settings.write().await = snapshot.particles;
+
+        log::info!("Applied rendering settings snapshot");
+        let _ = self.event_sender.send(RenderEvent::SettingsImported);
+    }
+
     pub async fn import_settings(&self, settings: HashMap<String, serde_json::Value>) {
         if let Some(quality) = settings.get("quality") {
             if let Ok(quality_settings) = serde_json::from_value::<QualitySettings>(quality.clone()) {
@@ -1099,9 +1186,272 @@ impl AdvancedRenderingSystem {
     }
 }
 
-// Add required dependencies to Cargo.toml:
-// [dependencies]
-// tokio = { version = "1", features = ["full"] }
-// serde = { version = "1.0", features = ["derive"] }
-// serde_json = "1.0"
-// log = "0.4"
\ No newline at end of file
+// Bridge to the viewer's settings.xml, where each control is a map of
+// Comment, Persist, Type and Value. Booleans are stored as integers.
+
+/// Convert a snapshot to the viewer's `settings.xml` controls.
+///
+/// Only settings with a viewer equivalent are written. `RenderShadowDetail`
+/// has three levels, so Low and Medium shadows both become 1 and High and
+/// Ultra both become 2. `RenderVolumeLODFactor` is `2^lod_bias`, mapping the
+/// presets' -2..=2 bias onto the viewer's 0.25..=4 factor.
+pub fn settings_to_sl_llsd(snapshot: &RenderingSnapshot) -> LLSDValue {
+    let shadow_detail = match (snapshot.shadows.shadows_enabled, snapshot.shadows.shadow_quality) {
+        (false, _) | (_, ShadowQuality::Disabled) => 0,
+        (true, ShadowQuality::Low | ShadowQuality::Medium) => 1,
+        (true, ShadowQuality::High | ShadowQuality::Ultra) => 2,
+    };
+    let flag = |enabled: bool| LLSDValue::Integer(enabled as i32);
+
+    let controls = [
+        ("RenderFarClip", "F32", "Distance of far clip plane from camera (meters)",
+            LLSDValue::Real(snapshot.quality.max_draw_distance as f64)),
+        ("RenderVSyncEnable", "Boolean", "Update frames in sync with the display refresh",
+            flag(snapshot.performance.vsync)),
+        ("RenderGlow", "Boolean", "Render bloom post effect",
+            flag(snapshot.effects.bloom)),
+        ("RenderDepthOfField", "Boolean", "Render depth of field post effect",
+            flag(snapshot.effects.depth_of_field)),
+        ("RenderScreenSpaceReflections", "Boolean", "Render screen space reflections",
+            flag(snapshot.effects.screen_space_reflections)),
+        ("RenderAnisotropic", "Boolean", "Render textures using anisotropic filtering",
+            flag(snapshot.textures.anisotropic_filtering)),
+        ("RenderShadowDetail", "S32", "Detail of shadows (0 = none, 1 = sun/moon, 2 = sun/moon + projectors)",
+            LLSDValue::Integer(shadow_detail)),
+        ("RenderVolumeLODFactor", "F32", "Controls level of detail of primitives (multiplier for current screen area when calculated level of detail)",
+            LLSDValue::Real(2f64.powf(snapshot.meshes.lod_bias as f64))),
+        ("RenderAvatarMaxNonImpostors", "U32", "Maximum number of avatars to fully render",
+            LLSDValue::from_i64(snapshot.avatars.max_visible_avatars as i64)),
+        ("RenderUseImpostors", "Boolean", "Render distant avatars as impostors",
+            flag(snapshot.avatars.avatar_impostors)),
+        ("RenderMaxPartCount", "S32", "Maximum number of particles to display on screen",
+            LLSDValue::from_i64(snapshot.particles.max_particles as i64)),
+    ];
+
+    let settings = controls
+        .into_iter()
+        .map(|(name, type_name, comment, value)| {
+            let mut control = HashMap::new();
+            control.insert("Comment".to_string(), LLSDValue::String(comment.to_string()));
+            control.insert("Persist".to_string(), LLSDValue::Integer(1));
+            control.insert("Type".to_string(), LLSDValue::String(type_name.to_string()));
+            control.insert("Value".to_string(), value);
+            (name.to_string(), LLSDValue::Map(control))
+        })
+        .collect();
+    LLSDValue::Map(settings)
+}
+
+/// Read a snapshot from the viewer's `settings.xml` controls, the inverse of
+/// [`settings_to_sl_llsd`].
+///
+/// Controls may be full `Value` records or bare values. Missing controls
+/// keep their defaults and unknown ones are ignored; a control of the wrong
+/// type is an error. `RenderShadowDetail` 1 reads as Medium, 2 as High.
+pub fn settings_from_sl_llsd(settings: &LLSDValue) -> LLSDResult<RenderingSnapshot> {
+    let controls = settings
+        .as_map()
+        .ok_or_else(|| LLSDError::type_mismatch("map", settings.type_name()))?;
+    let mut snapshot = RenderingSnapshot::default();
+
+    if let Some(far_clip) = sl_real(controls, "RenderFarClip")? {
+        snapshot.quality.max_draw_distance = sl_u32(far_clip.round() as i64, "RenderFarClip")?;
+    }
+    if let Some(vsync) = sl_bool(controls, "RenderVSyncEnable")? {
+        snapshot.performance.vsync = vsync;
+    }
+    if let Some(bloom) = sl_bool(controls, "RenderGlow")? {
+        snapshot.effects.bloom = bloom;
+    }
+    if let Some(depth_of_field) = sl_bool(controls, "RenderDepthOfField")? {
+        snapshot.effects.depth_of_field = depth_of_field;
+    }
+    if let Some(reflections) = sl_bool(controls, "RenderScreenSpaceReflections")? {
+        snapshot.effects.screen_space_reflections = reflections;
+    }
+    if let Some(anisotropic) = sl_bool(controls, "RenderAnisotropic")? {
+        snapshot.textures.anisotropic_filtering = anisotropic;
+    }
+    if let Some(detail) = sl_integer(controls, "RenderShadowDetail")? {
+        let (enabled, quality) = match detail {
+            0 => (false, ShadowQuality::Disabled),
+            1 => (true, ShadowQuality::Medium),
+            2 => (true, ShadowQuality::High),
+            _ => {
+                return Err(LLSDError::validation_error(format!(
+                    "RenderShadowDetail must be 0, 1 or 2, got {}", detail
+                )));
+            }
+        };
+        snapshot.shadows.shadows_enabled = enabled;
+        snapshot.shadows.shadow_quality = quality;
+    }
+    if let Some(factor) = sl_real(controls, "RenderVolumeLODFactor")? {
+        if factor <= 0.0 {
+            return Err(LLSDError::validation_error(format!(
+                "RenderVolumeLODFactor must be positive, got {}", factor
+            )));
+        }
+        snapshot.meshes.lod_bias = factor.log2() as f32;
+    }
+    if let Some(avatars) = sl_integer(controls, "RenderAvatarMaxNonImpostors")? {
+        snapshot.avatars.max_visible_avatars = sl_u32(avatars, "RenderAvatarMaxNonImpostors")?;
+    }
+    if let Some(impostors) = sl_bool(controls, "RenderUseImpostors")? {
+        snapshot.avatars.avatar_impostors = impostors;
+    }
+    if let Some(particles) = sl_integer(controls, "RenderMaxPartCount")? {
+        snapshot.particles.max_particles = sl_u32(particles, "RenderMaxPartCount")?;
+    }
+
+    Ok(snapshot)
+}
+
+/// The value of control `name`, unwrapping a `Value` record
+fn sl_value<'a>(controls: &'a HashMap<String, LLSDValue>, name: &str) -> Option<&'a LLSDValue> {
+    let control = controls.get(name)?;
+    match control {
+        LLSDValue::Map(record) => record.get("Value"),
+        bare => Some(bare),
+    }
+}
+
+fn sl_real(controls: &HashMap<String, LLSDValue>, name: &str) -> LLSDResult<Option<f64>> {
+    sl_value(controls, name)
+        .map(|value| value.as_real().ok_or_else(|| sl_type_error(name, "real", value)))
+        .transpose()
+}
+
+fn sl_integer(controls: &HashMap<String, LLSDValue>, name: &str) -> LLSDResult<Option<i64>> {
+    sl_value(controls, name)
+        .map(|value| value.as_integer64().ok_or_else(|| sl_type_error(name, "integer", value)))
+        .transpose()
+}
+
+fn sl_bool(controls: &HashMap<String, LLSDValue>, name: &str) -> LLSDResult<Option<bool>> {
+    sl_value(controls, name)
+        .map(|value| match value {
+            LLSDValue::Boolean(b) => Ok(*b),
+            other => other.as_integer64().map(|i| i != 0).ok_or_else(|| sl_type_error(name, "boolean", other)),
+        })
+        .transpose()
+}
+
+fn sl_u32(value: i64, name: &str) -> LLSDResult<u32> {
+    u32::try_from(value)
+        .map_err(|_| LLSDError::validation_error(format!("{} is out of range: {}", name, value)))
+}
+
+fn sl_type_error(name: &str, expected: &str, value: &LLSDValue) -> LLSDError {
+    LLSDError::type_mismatch(format!("{} for {}", expected, name), value.type_name().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_average_fps_is_windowed_mean() {
+        let samples = [30, 60, 90, 45, 15, 60];
+
+        let mut metrics = PerformanceMetrics::default();
+        let mut average = FpsAverage::default();
+        for fps in samples {
+            average.record(fps, &mut metrics);
+        }
+        let mean = samples.iter().sum::<u32>() as f32 / samples.len() as f32;
+        assert!((metrics.average_fps - mean).abs() < 1e-4);
+        assert_eq!(metrics.current_fps, 60);
+        assert_eq!(metrics.fps_sample_count, samples.len());
+
+        // Only the last three samples count once the window is full
+        let mut windowed = PerformanceMetrics::with_fps_window(3);
+        let mut average = FpsAverage::default();
+        for fps in samples {
+            average.record(fps, &mut windowed);
+        }
+        assert!((windowed.average_fps - 40.0).abs() < 1e-4);
+        assert_eq!(windowed.fps_sample_count, 3);
+
+        // A window changed between samples applies from the next one
+        windowed.fps_window = 2;
+        average.record(30, &mut windowed);
+        assert!((windowed.average_fps - 45.0).abs() < 1e-4);
+        assert_eq!(windowed.fps_sample_count, 2);
+    }
+
+    #[test]
+    fn test_settings_to_sl_llsd() {
+        let mut snapshot = RenderingSnapshot::default();
+        snapshot.quality.max_draw_distance = 512;
+        snapshot.shadows.shadow_quality = ShadowQuality::Ultra;
+        snapshot.meshes.lod_bias = 1.0;
+        snapshot.particles.max_particles = 4096;
+        snapshot.avatars.avatar_impostors = false;
+
+        let settings = settings_to_sl_llsd(&snapshot);
+        let value = |name: &str| settings.get_path(&format!("{}.Value", name)).cloned();
+        let type_name = |name: &str| settings.get_path(&format!("{}.Type", name)).and_then(|t| t.as_string().map(str::to_string));
+
+        assert_eq!(value("RenderFarClip"), Some(LLSDValue::Real(512.0)));
+        assert_eq!(value("RenderShadowDetail"), Some(LLSDValue::Integer(2)));
+        assert_eq!(value("RenderVolumeLODFactor"), Some(LLSDValue::Real(2.0)));
+        assert_eq!(value("RenderMaxPartCount"), Some(LLSDValue::Integer(4096)));
+        assert_eq!(value("RenderAvatarMaxNonImpostors"), Some(LLSDValue::Integer(30)));
+        assert_eq!(value("RenderUseImpostors"), Some(LLSDValue::Integer(0)));
+        assert_eq!(value("RenderVSyncEnable"), Some(LLSDValue::Integer(1)));
+        assert_eq!(type_name("RenderShadowDetail").as_deref(), Some("S32"));
+        assert_eq!(type_name("RenderUseImpostors").as_deref(), Some("Boolean"));
+        assert_eq!(settings.get_path("RenderFarClip.Persist"), Some(&LLSDValue::Integer(1)));
+
+        snapshot.shadows.shadows_enabled = false;
+        let settings = settings_to_sl_llsd(&snapshot);
+        assert_eq!(settings.get_path("RenderShadowDetail.Value"), Some(&LLSDValue::Integer(0)));
+    }
+
+    #[test]
+    fn test_settings_sl_llsd_round_trip() {
+        let mut snapshot = RenderingSnapshot::default();
+        snapshot.quality.max_draw_distance = 96;
+        snapshot.performance.vsync = false;
+        snapshot.effects.depth_of_field = true;
+        snapshot.textures.anisotropic_filtering = false;
+        snapshot.shadows.shadow_quality = ShadowQuality::High;
+        snapshot.meshes.lod_bias = -2.0;
+        snapshot.avatars.max_visible_avatars = 12;
+        snapshot.particles.max_particles = 500;
+
+        // Through settings.xml text, as the viewer would store it
+        let document = crate::LLSDDocument::new(settings_to_sl_llsd(&snapshot));
+        let xml = crate::LLSDFactory::serialize_xml(&document, true).unwrap();
+        let parsed = crate::LLSDFactory::parse_xml(&xml).unwrap();
+        let restored = settings_from_sl_llsd(parsed.content()).unwrap();
+
+        assert_eq!(restored.quality.max_draw_distance, 96);
+        assert!(!restored.performance.vsync);
+        assert!(restored.effects.depth_of_field);
+        assert!(!restored.textures.anisotropic_filtering);
+        assert!(restored.shadows.shadows_enabled);
+        assert_eq!(restored.shadows.shadow_quality, ShadowQuality::High);
+        assert_eq!(restored.meshes.lod_bias, -2.0);
+        assert_eq!(restored.avatars.max_visible_avatars, 12);
+        assert_eq!(restored.particles.max_particles, 500);
+        assert_eq!(settings_to_sl_llsd(&restored), settings_to_sl_llsd(&snapshot));
+
+        // Bare values work too, and missing controls keep their defaults
+        let mut bare = HashMap::new();
+        bare.insert("RenderShadowDetail".to_string(), LLSDValue::Integer(0));
+        bare.insert("RenderUseImpostors".to_string(), LLSDValue::Boolean(false));
+        let restored = settings_from_sl_llsd(&LLSDValue::Map(bare)).unwrap();
+        assert!(!restored.shadows.shadows_enabled);
+        assert!(!restored.avatars.avatar_impostors);
+        assert_eq!(restored.particles.max_particles, ParticleSettings::default().max_particles);
+
+        let mut wrong = HashMap::new();
+        wrong.insert("RenderMaxPartCount".to_string(), LLSDValue::String("lots".to_string()));
+        assert!(settings_from_sl_llsd(&LLSDValue::Map(wrong)).is_err());
+        let mut out_of_range = HashMap::new();
+        out_of_range.insert("RenderShadowDetail".to_string(), LLSDValue::Integer(3));
+        assert!(settings_from_sl_llsd(&LLSDValue::Map(out_of_range)).is_err());
+    }
+}
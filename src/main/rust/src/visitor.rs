@@ -0,0 +1,146 @@
+/*!
+ * LLSD Visitor - Rust Implementation
+ *
+ * Depth-first traversal of LLSD values with dot notation paths
+ * Copyright (C) 2024 Linden Lab
+ */
+
+use crate::types::LLSDValue;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Callbacks for [`LLSDValue::walk`]. Every method defaults to doing nothing,
+/// so a visitor only implements what it needs.
+///
+/// `path` is the dot notation path of the value, as accepted by
+/// [`LLSDValue::get_path`]; the root is the empty path. Map entries are
+/// visited in the map's iteration order.
+#[allow(unused_variables)]
+pub trait LLSDVisitor {
+    /// Called for every value before its type-specific method
+    fn visit_value(&mut self, path: &str, value: &LLSDValue) {}
+    fn visit_undefined(&mut self, path: &str) {}
+    fn visit_boolean(&mut self, path: &str, value: bool) {}
+    fn visit_integer(&mut self, path: &str, value: i32) {}
+    fn visit_integer64(&mut self, path: &str, value: i64) {}
+    fn visit_real(&mut self, path: &str, value: f64) {}
+    fn visit_string(&mut self, path: &str, value: &str) {}
+    fn visit_uuid(&mut self, path: &str, value: &Uuid) {}
+    fn visit_date(&mut self, path: &str, value: &DateTime<Utc>) {}
+    fn visit_uri(&mut self, path: &str, value: &str) {}
+    fn visit_binary(&mut self, path: &str, value: &[u8]) {}
+    /// Called before a map's entries are visited
+    fn visit_map_enter(&mut self, path: &str, map: &HashMap<String, LLSDValue>) {}
+    /// Called after a map's entries are visited
+    fn visit_map_exit(&mut self, path: &str) {}
+    /// Called before an array's items are visited
+    fn visit_array_enter(&mut self, path: &str, array: &[LLSDValue]) {}
+    /// Called after an array's items are visited
+    fn visit_array_exit(&mut self, path: &str) {}
+}
+
+/// Callbacks for [`LLSDValue::walk_mut`], which may modify values in place.
+///
+/// Container enter methods run before the children are walked, so entries
+/// added or removed there are reflected in the traversal.
+#[allow(unused_variables)]
+pub trait LLSDVisitorMut {
+    /// Called for every value before its type-specific method
+    fn visit_value(&mut self, path: &str, value: &mut LLSDValue) {}
+    fn visit_undefined(&mut self, path: &str) {}
+    fn visit_boolean(&mut self, path: &str, value: &mut bool) {}
+    fn visit_integer(&mut self, path: &str, value: &mut i32) {}
+    fn visit_integer64(&mut self, path: &str, value: &mut i64) {}
+    fn visit_real(&mut self, path: &str, value: &mut f64) {}
+    fn visit_string(&mut self, path: &str, value: &mut String) {}
+    fn visit_uuid(&mut self, path: &str, value: &mut Uuid) {}
+    fn visit_date(&mut self, path: &str, value: &mut DateTime<Utc>) {}
+    fn visit_uri(&mut self, path: &str, value: &mut String) {}
+    fn visit_binary(&mut self, path: &str, value: &mut Vec<u8>) {}
+    fn visit_map_enter(&mut self, path: &str, map: &mut HashMap<String, LLSDValue>) {}
+    fn visit_map_exit(&mut self, path: &str) {}
+    fn visit_array_enter(&mut self, path: &str, array: &mut Vec<LLSDValue>) {}
+    fn visit_array_exit(&mut self, path: &str) {}
+}
+
+/// Path of a child `segment` under `path`
+fn child_path(path: &str, segment: &str) -> String {
+    if path.is_empty() {
+        segment.to_string()
+    } else {
+        format!("{}.{}", path, segment)
+    }
+}
+
+impl LLSDValue {
+    /// Walk this value depth-first, calling `visitor` for every value
+    pub fn walk<V: LLSDVisitor + ?Sized>(&self, visitor: &mut V) {
+        self.walk_at("", visitor);
+    }
+
+    fn walk_at<V: LLSDVisitor + ?Sized>(&self, path: &str, visitor: &mut V) {
+        visitor.visit_value(path, self);
+        match self {
+            LLSDValue::Undefined => visitor.visit_undefined(path),
+            LLSDValue::Boolean(b) => visitor.visit_boolean(path, *b),
+            LLSDValue::Integer(i) => visitor.visit_integer(path, *i),
+            LLSDValue::Integer64(i) => visitor.visit_integer64(path, *i),
+            LLSDValue::Real(r) => visitor.visit_real(path, *r),
+            LLSDValue::String(s) => visitor.visit_string(path, s),
+            LLSDValue::UUID(u) => visitor.visit_uuid(path, u),
+            LLSDValue::Date(d) => visitor.visit_date(path, d),
+            LLSDValue::URI(u) => visitor.visit_uri(path, u),
+            LLSDValue::Binary(b) => visitor.visit_binary(path, b),
+            LLSDValue::Map(map) => {
+                visitor.visit_map_enter(path, map);
+                for (key, value) in map {
+                    value.walk_at(&child_path(path, key), visitor);
+                }
+                visitor.visit_map_exit(path);
+            }
+            LLSDValue::Array(array) => {
+                visitor.visit_array_enter(path, array);
+                for (index, value) in array.iter().enumerate() {
+                    value.walk_at(&child_path(path, &index.to_string()), visitor);
+                }
+                visitor.visit_array_exit(path);
+            }
+        }
+    }
+
+    /// Walk this value depth-first, letting `visitor` modify values in place
+    pub fn walk_mut<V: LLSDVisitorMut + ?Sized>(&mut self, visitor: &mut V) {
+        self.walk_mut_at("", visitor);
+    }
+
+    fn walk_mut_at<V: LLSDVisitorMut + ?Sized>(&mut self, path: &str, visitor: &mut V) {
+        visitor.visit_value(path, self);
+        match self {
+            LLSDValue::Undefined => visitor.visit_undefined(path),
+            LLSDValue::Boolean(b) => visitor.visit_boolean(path, b),
+            LLSDValue::Integer(i) => visitor.visit_integer(path, i),
+            LLSDValue::Integer64(i) => visitor.visit_integer64(path, i),
+            LLSDValue::Real(r) => visitor.visit_real(path, r),
+            LLSDValue::String(s) => visitor.visit_string(path, s),
+            LLSDValue::UUID(u) => visitor.visit_uuid(path, u),
+            LLSDValue::Date(d) => visitor.visit_date(path, d),
+            LLSDValue::URI(u) => visitor.visit_uri(path, u),
+            LLSDValue::Binary(b) => visitor.visit_binary(path, b),
+            LLSDValue::Map(map) => {
+                visitor.visit_map_enter(path, map);
+                for (key, value) in map.iter_mut() {
+                    value.walk_mut_at(&child_path(path, key), visitor);
+                }
+                visitor.visit_map_exit(path);
+            }
+            LLSDValue::Array(array) => {
+                visitor.visit_array_enter(path, array);
+                for (index, value) in array.iter_mut().enumerate() {
+                    value.walk_mut_at(&child_path(path, &index.to_string()), visitor);
+                }
+                visitor.visit_array_exit(path);
+            }
+        }
+    }
+}
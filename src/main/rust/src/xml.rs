@@ -5,19 +5,66 @@
  * Copyright (C) 2024 Linden Lab
  */
 
-use crate::types::{LLSDValue, LLSDDocument};
+use crate::types::{LLSDDocument, LLSDType, LLSDValue};
 use crate::error::{LLSDError, LLSDResult};
+use crate::utils::{BudgetMeter, LimitedWriter, LLSDUtils, ParseBudget};
 use quick_xml::events::{Event, BytesEnd, BytesStart, BytesText};
 use quick_xml::{Reader, Writer};
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::io::Cursor;
 use uuid::Uuid;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, FixedOffset, Utc};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
 
 /// LLSD XML parser
 #[derive(Debug, Default)]
 pub struct LLSDXmlParser {
     validate_structure: bool,
+    binary_string_recovery: bool,
+    preserve_offset: bool,
+    lenient_maps: bool,
+    budget: ParseBudget,
+}
+
+/// Date offsets recorded while parsing with `preserve_offset`
+#[derive(Debug, Default)]
+struct OffsetRecorder {
+    enabled: bool,
+    path: Vec<String>,
+    offsets: HashMap<String, FixedOffset>,
+}
+
+/// State of a single [`LLSDXmlParser::parse`] call
+struct ParseContext {
+    offsets: OffsetRecorder,
+    lenient_maps: bool,
+    warnings: Vec<String>,
+    meter: BudgetMeter,
+}
+
+impl OffsetRecorder {
+    fn enter(&mut self, segment: impl ToString) {
+        if self.enabled {
+            self.path.push(segment.to_string());
+        }
+    }
+
+    fn leave(&mut self) {
+        if self.enabled {
+            self.path.pop();
+        }
+    }
+
+    fn record(&mut self, content: &str) {
+        if !self.enabled {
+            return;
+        }
+        if let Ok(date) = DateTime::parse_from_rfc3339(content.trim()) {
+            self.offsets.insert(self.path.join("."), *date.offset());
+        }
+    }
 }
 
 impl LLSDXmlParser {
@@ -32,22 +79,81 @@ impl LLSDXmlParser {
         self
     }
 
+    /// Return `<string>` content that [`LLSDUtils::looks_binary`] flags as
+    /// `Binary` holding the text's UTF-8 bytes. Bytes already replaced by
+    /// U+FFFD upstream cannot be restored; this only preserves what arrived.
+    pub fn with_binary_string_recovery(mut self, recover: bool) -> Self {
+        self.binary_string_recovery = recover;
+        self
+    }
+
+    /// Record the UTC offset each `<date>` was written with, so it can be
+    /// written back unchanged. Values are still `Date` in UTC; offsets are
+    /// available through [`LLSDDocument::date_offset`].
+    pub fn with_preserve_offset(mut self, preserve: bool) -> Self {
+        self.preserve_offset = preserve;
+        self
+    }
+
+    /// Recover maps written by older tools: a `<key>` that follows its value
+    /// is paired with it, and a repeated `<key>` replaces the pending one.
+    /// Use [`parse_lenient`](Self::parse_lenient) to have each repair
+    /// reported.
+    pub fn with_lenient_maps(mut self, lenient: bool) -> Self {
+        self.lenient_maps = lenient;
+        self
+    }
+
+    /// Abort with [`LLSDError::ParseAborted`] once parsing exceeds either
+    /// limit of `budget`
+    pub fn with_parse_budget(mut self, budget: ParseBudget) -> Self {
+        self.budget = budget;
+        self
+    }
+
     /// Parse LLSD from XML string
     pub fn parse(&self, xml: &str) -> LLSDResult<LLSDDocument> {
+        let mut context = self.context(self.lenient_maps);
+        self.parse_in(xml, &mut context)
+    }
+
+    /// Parse with lenient maps (see [`with_lenient_maps`](Self::with_lenient_maps))
+    /// enabled for this call, returning a warning for each repair made.
+    ///
+    /// Warnings come back with the document rather than through the parser,
+    /// so one parser can be shared across threads and each caller sees only
+    /// its own parse's warnings.
+    pub fn parse_lenient(&self, xml: &str) -> LLSDResult<(LLSDDocument, Vec<String>)> {
+        let mut context = self.context(true);
+        let document = self.parse_in(xml, &mut context)?;
+        Ok((document, context.warnings))
+    }
+
+    fn context(&self, lenient_maps: bool) -> ParseContext {
+        ParseContext {
+            offsets: OffsetRecorder::default(),
+            lenient_maps,
+            warnings: Vec::new(),
+            meter: BudgetMeter::start(self.budget),
+        }
+    }
+
+    fn parse_in(&self, xml: &str, context: &mut ParseContext) -> LLSDResult<LLSDDocument> {
+        if xml.trim().is_empty() {
+            return Err(LLSDError::EmptyInput);
+        }
+
         let mut reader = Reader::from_str(xml);
         reader.trim_text(true);
         
-        let mut buf = Vec::new();
         let mut found_llsd_root = false;
         
         // Find the LLSD root element
         loop {
             match reader.read_event() {
-                Ok(Event::Start(ref e)) => {
-                    if e.name().as_ref() == b"llsd" {
-                        found_llsd_root = true;
-                        break;
-                    }
+                Ok(Event::Start(ref e)) if e.name().as_ref() == b"llsd" => {
+                    found_llsd_root = true;
+                    break;
                 }
                 Ok(Event::Eof) => break,
                 Err(e) => return Err(LLSDError::from(e)),
@@ -60,28 +166,77 @@ impl LLSDXmlParser {
         }
 
         // Parse the first child element
-        let value = self.parse_element(&mut reader, &mut buf)?;
-        Ok(LLSDDocument::new(value))
+        context.offsets.enabled = self.preserve_offset;
+        let value = self.parse_element(&mut reader, context)?;
+        let mut document = LLSDDocument::new(value);
+        for (path, offset) in context.offsets.offsets.drain() {
+            document.set_date_offset(path, offset);
+        }
+        Ok(document)
     }
 
-    /// Parse an individual XML element
-    fn parse_element(&self, reader: &mut Reader<&[u8]>, buf: &mut Vec<u8>) -> LLSDResult<LLSDValue> {
+    /// Type of the root value, read from the first element inside `<llsd>`
+    /// so callers can route a payload without decoding it. The rest of
+    /// the payload is not validated, and an `<integer>` probes as
+    /// `Integer` even if its value needs `Integer64`.
+    pub fn probe_root_type(xml: &str) -> LLSDResult<LLSDType> {
+        if xml.trim().is_empty() {
+            return Err(LLSDError::EmptyInput);
+        }
+
+        let mut reader = Reader::from_str(xml);
+        reader.trim_text(true);
+        let mut in_root = false;
         loop {
+            match reader.read_event() {
+                Ok(Event::Start(ref e)) if !in_root => {
+                    in_root = e.name().as_ref() == b"llsd";
+                }
+                Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) if in_root => {
+                    let tag_name = String::from_utf8_lossy(e.name().as_ref()).to_string();
+                    return LLSDType::from_xml_tag(&tag_name)
+                        .ok_or_else(|| LLSDError::custom(format!("Unknown LLSD element: {}", tag_name)));
+                }
+                Ok(Event::End(_)) if in_root => return Ok(LLSDType::Unknown),
+                Ok(Event::Eof) if in_root => return Err(unterminated("llsd")),
+                Ok(Event::Eof) => return Err(LLSDError::custom("Missing <llsd> root element")),
+                Err(e) => return Err(LLSDError::from(e)),
+                _ => {}
+            }
+        }
+    }
+
+    /// Parse the value inside `<llsd>` and its closing tag. An empty
+    /// `<llsd>` holds `Undefined`.
+    fn parse_element(&self, reader: &mut Reader<&[u8]>, context: &mut ParseContext) -> LLSDResult<LLSDValue> {
+        let value = loop {
             match reader.read_event() {
                 Ok(Event::Start(ref e)) => {
                     let tag_name = String::from_utf8_lossy(e.name().as_ref()).to_string();
-                    return self.parse_typed_element(&tag_name, reader, buf);
+                    break self.parse_typed_element(&tag_name, reader, context)?;
                 }
                 Ok(Event::Empty(ref e)) => {
                     let tag_name = String::from_utf8_lossy(e.name().as_ref()).to_string();
-                    return self.parse_empty_element(&tag_name);
-                }
-                Ok(Event::End(_)) => {
-                    return Ok(LLSDValue::Undefined);
+                    context.meter.charge_value()?;
+                    break self.parse_empty_element(&tag_name)?;
                 }
-                Ok(Event::Eof) => {
-                    return Ok(LLSDValue::Undefined);
+                Ok(Event::End(_)) => return Ok(LLSDValue::Undefined),
+                Ok(Event::Eof) => return Err(unterminated("llsd")),
+                Err(e) => return Err(LLSDError::from(e)),
+                _ => {}
+            }
+        };
+
+        loop {
+            match reader.read_event() {
+                Ok(Event::End(_)) => return Ok(value),
+                Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) => {
+                    return Err(LLSDError::custom(format!(
+                        "Unexpected <{}> after the root value; <llsd> holds one value",
+                        String::from_utf8_lossy(e.name().as_ref())
+                    )));
                 }
+                Ok(Event::Eof) => return Err(unterminated("llsd")),
                 Err(e) => return Err(LLSDError::from(e)),
                 _ => {}
             }
@@ -89,76 +244,90 @@ impl LLSDXmlParser {
     }
 
     /// Parse a typed XML element with content
-    fn parse_typed_element(&self, tag_name: &str, reader: &mut Reader<&[u8]>, buf: &mut Vec<u8>) -> LLSDResult<LLSDValue> {
-        match tag_name {
-            "undef" => {
-                self.skip_to_end(reader, buf, "undef")?;
+    fn parse_typed_element(
+        &self,
+        tag_name: &str,
+        reader: &mut Reader<&[u8]>,
+        context: &mut ParseContext,
+    ) -> LLSDResult<LLSDValue> {
+        context.meter.charge_value()?;
+        match LLSDType::from_xml_tag(tag_name) {
+            Some(LLSDType::Unknown) => {
+                self.skip_to_end(reader, tag_name)?;
                 Ok(LLSDValue::Undefined)
             }
-            "boolean" => {
-                let content = self.read_text_content(reader, buf)?;
+            Some(LLSDType::Array) => self.parse_array(reader, context),
+            Some(LLSDType::Map) => self.parse_map(reader, context),
+            llsd_type => {
+                let content = self.read_text_content(reader, tag_name)?;
+                context.meter.charge(content.len())?;
+                if llsd_type == Some(LLSDType::Date) {
+                    context.offsets.record(&content);
+                }
+                self.parse_scalar(tag_name, content)
+            }
+        }
+    }
+
+    /// Convert the text content of a scalar element to a value
+    fn parse_scalar(&self, tag_name: &str, content: String) -> LLSDResult<LLSDValue> {
+        match LLSDType::from_xml_tag(tag_name) {
+            Some(LLSDType::Boolean) => {
                 let value = content.trim().to_lowercase();
                 Ok(LLSDValue::Boolean(value == "1" || value == "true"))
             }
-            "integer" => {
-                let content = self.read_text_content(reader, buf)?;
-                let value: i32 = content.trim().parse()
+            Some(LLSDType::Integer) => {
+                let value: i64 = content.trim().parse()
                     .map_err(|_| LLSDError::custom(format!("Invalid integer: {}", content)))?;
-                Ok(LLSDValue::Integer(value))
+                Ok(LLSDValue::from_i64(value))
             }
-            "real" => {
-                let content = self.read_text_content(reader, buf)?;
+            Some(LLSDType::Real) => {
                 let value: f64 = content.trim().parse()
                     .map_err(|_| LLSDError::custom(format!("Invalid real: {}", content)))?;
                 Ok(LLSDValue::Real(value))
             }
-            "string" => {
-                let content = self.read_text_content(reader, buf)?;
-                Ok(LLSDValue::String(content))
+            Some(LLSDType::String) => {
+                if self.binary_string_recovery && LLSDUtils::looks_binary(&content) {
+                    Ok(LLSDValue::Binary(content.into_bytes()))
+                } else {
+                    Ok(LLSDValue::String(content))
+                }
             }
-            "uuid" => {
-                let content = self.read_text_content(reader, buf)?;
+            Some(LLSDType::UUID) => {
                 let uuid = Uuid::parse_str(content.trim())
                     .map_err(|_| LLSDError::InvalidUuid { uuid: content })?;
                 Ok(LLSDValue::UUID(uuid))
             }
-            "date" => {
-                let content = self.read_text_content(reader, buf)?;
+            Some(LLSDType::Date) => {
                 let date = DateTime::parse_from_rfc3339(content.trim())
                     .map_err(|_| LLSDError::InvalidDate { date: content.clone() })?
                     .with_timezone(&Utc);
                 Ok(LLSDValue::Date(date))
             }
-            "uri" => {
-                let content = self.read_text_content(reader, buf)?;
-                Ok(LLSDValue::URI(content))
-            }
-            "binary" => {
-                let content = self.read_text_content(reader, buf)?;
-                let bytes = base64::decode(content.trim())?;
+            Some(LLSDType::URI) => Ok(LLSDValue::URI(content)),
+            Some(LLSDType::Binary) => {
+                let bytes = BASE64.decode(content.trim())?;
                 Ok(LLSDValue::Binary(bytes))
             }
-            "array" => self.parse_array(reader, buf),
-            "map" => self.parse_map(reader, buf),
             _ => Err(LLSDError::custom(format!("Unknown LLSD element: {}", tag_name)))
         }
     }
 
     /// Parse empty XML elements
     fn parse_empty_element(&self, tag_name: &str) -> LLSDResult<LLSDValue> {
-        match tag_name {
-            "undef" => Ok(LLSDValue::Undefined),
-            "string" => Ok(LLSDValue::String(String::new())),
-            "binary" => Ok(LLSDValue::Binary(Vec::new())),
-            "array" => Ok(LLSDValue::Array(Vec::new())),
-            "map" => Ok(LLSDValue::Map(HashMap::new())),
-            "uuid" => Ok(LLSDValue::UUID(Uuid::nil())),
+        match LLSDType::from_xml_tag(tag_name) {
+            Some(LLSDType::Unknown) => Ok(LLSDValue::Undefined),
+            Some(LLSDType::String) => Ok(LLSDValue::String(String::new())),
+            Some(LLSDType::Binary) => Ok(LLSDValue::Binary(Vec::new())),
+            Some(LLSDType::Array) => Ok(LLSDValue::Array(Vec::new())),
+            Some(LLSDType::Map) => Ok(LLSDValue::Map(HashMap::new())),
+            Some(LLSDType::UUID) => Ok(LLSDValue::UUID(Uuid::nil())),
             _ => Err(LLSDError::custom(format!("Cannot have empty element: {}", tag_name)))
         }
     }
 
     /// Read text content from an element
-    fn read_text_content(&self, reader: &mut Reader<&[u8]>, buf: &mut Vec<u8>) -> LLSDResult<String> {
+    fn read_text_content(&self, reader: &mut Reader<&[u8]>, tag: &str) -> LLSDResult<String> {
         let mut content = String::new();
         
         loop {
@@ -167,10 +336,17 @@ impl LLSDXmlParser {
                     content.push_str(&e.unescape().unwrap_or_default());
                 }
                 Ok(Event::CData(ref e)) => {
-                    content.push_str(&String::from_utf8_lossy(&e));
+                    content.push_str(&String::from_utf8_lossy(e));
+                }
+                Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) => {
+                    return Err(LLSDError::custom(format!(
+                        "Unexpected <{}> inside <{}>",
+                        String::from_utf8_lossy(e.name().as_ref()),
+                        tag
+                    )));
                 }
                 Ok(Event::End(_)) => break,
-                Ok(Event::Eof) => break,
+                Ok(Event::Eof) => return Err(unterminated(tag)),
                 Err(e) => return Err(LLSDError::from(e)),
                 _ => {}
             }
@@ -180,20 +356,25 @@ impl LLSDXmlParser {
     }
 
     /// Parse an array element
-    fn parse_array(&self, reader: &mut Reader<&[u8]>, buf: &mut Vec<u8>) -> LLSDResult<LLSDValue> {
+    fn parse_array(&self, reader: &mut Reader<&[u8]>, context: &mut ParseContext) -> LLSDResult<LLSDValue> {
         let mut array = Vec::new();
         
         loop {
             match reader.read_event() {
-                Ok(Event::Start(_)) | Ok(Event::Empty(_)) => {
-                    // Step back one event to re-parse the element
-                    // Skip to end of this element
-                    // TODO: Implement proper position tracking
-                    let element = self.parse_element(reader, buf)?;
-                    array.push(element);
+                Ok(Event::Start(ref e)) => {
+                    let tag_name = String::from_utf8_lossy(e.name().as_ref()).to_string();
+                    context.offsets.enter(array.len());
+                    let value = self.parse_typed_element(&tag_name, reader, context);
+                    context.offsets.leave();
+                    array.push(value?);
                 }
-                Ok(Event::End(ref e)) if e.name().as_ref() == b"array" => break,
-                Ok(Event::Eof) => break,
+                Ok(Event::Empty(ref e)) => {
+                    let tag_name = String::from_utf8_lossy(e.name().as_ref()).to_string();
+                    context.meter.charge_value()?;
+                    array.push(self.parse_empty_element(&tag_name)?);
+                }
+                Ok(Event::End(ref e)) if e.name().as_ref() == LLSDType::Array.xml_tag().as_bytes() => break,
+                Ok(Event::Eof) => return Err(unterminated(LLSDType::Array.xml_tag())),
                 Err(e) => return Err(LLSDError::from(e)),
                 _ => {}
             }
@@ -203,48 +384,561 @@ impl LLSDXmlParser {
     }
 
     /// Parse a map element
-    fn parse_map(&self, reader: &mut Reader<&[u8]>, buf: &mut Vec<u8>) -> LLSDResult<LLSDValue> {
+    fn parse_map(&self, reader: &mut Reader<&[u8]>, context: &mut ParseContext) -> LLSDResult<LLSDValue> {
         let mut map = HashMap::new();
         let mut current_key: Option<String> = None;
+        // Lenient mode: a value seen before its key
+        let mut orphan: Option<LLSDValue> = None;
         
         loop {
             match reader.read_event() {
                 Ok(Event::Start(ref e)) => {
                     let tag_name = String::from_utf8_lossy(e.name().as_ref()).to_string();
                     if tag_name == "key" {
-                        current_key = Some(self.read_text_content(reader, buf)?);
+                        let key = self.read_text_content(reader, &tag_name)?;
+                        context.meter.charge(key.len())?;
+                        self.set_key(&mut map, &mut current_key, &mut orphan, key, context)?;
                     } else if let Some(key) = current_key.take() {
-                        let value = self.parse_typed_element(&tag_name, reader, buf)?;
-                        map.insert(key, value);
+                        context.offsets.enter(&key);
+                        let value = self.parse_typed_element(&tag_name, reader, context);
+                        context.offsets.leave();
+                        map.insert(key, value?);
+                    } else if context.lenient_maps {
+                        let value = self.parse_typed_element(&tag_name, reader, context)?;
+                        self.set_orphan(&mut orphan, value, context);
                     } else {
-                        return Err(LLSDError::custom("Map value without key"));
+                        return Err(value_without_key(&tag_name));
                     }
                 }
                 Ok(Event::Empty(ref e)) => {
                     let tag_name = String::from_utf8_lossy(e.name().as_ref()).to_string();
-                    if let Some(key) = current_key.take() {
+                    if tag_name == "key" {
+                        self.set_key(&mut map, &mut current_key, &mut orphan, String::new(), context)?;
+                    } else if let Some(key) = current_key.take() {
+                        context.meter.charge_value()?;
                         let value = self.parse_empty_element(&tag_name)?;
                         map.insert(key, value);
-                    } else if tag_name != "key" {
-                        return Err(LLSDError::custom("Empty map value without key"));
+                    } else if context.lenient_maps {
+                        context.meter.charge_value()?;
+                        let value = self.parse_empty_element(&tag_name)?;
+                        self.set_orphan(&mut orphan, value, context);
+                    } else {
+                        return Err(value_without_key(&tag_name));
                     }
                 }
-                Ok(Event::End(ref e)) if e.name().as_ref() == b"map" => break,
-                Ok(Event::Eof) => break,
+                Ok(Event::End(ref e)) if e.name().as_ref() == LLSDType::Map.xml_tag().as_bytes() => break,
+                Ok(Event::Eof) => return Err(unterminated(LLSDType::Map.xml_tag())),
                 Err(e) => return Err(LLSDError::from(e)),
                 _ => {}
             }
         }
+
+        if let Some(key) = current_key {
+            if !context.lenient_maps {
+                return Err(key_without_value(&key));
+            }
+            context.warnings.push(format!("Map key '{}' has no value", key));
+        }
+        if context.lenient_maps && orphan.is_some() {
+            context.warnings.push("Dropped map value without key".to_string());
+        }
         
         Ok(LLSDValue::Map(map))
     }
 
-    /// Skip to the end of an element
-    fn skip_to_end(&self, reader: &mut Reader<&[u8]>, buf: &mut Vec<u8>, tag: &str) -> LLSDResult<()> {
-        // For now, just continue reading until we find the matching end tag
-        // This is a simplified implementation
+    /// Handle a `<key>` in a map, pairing it with a value that came first
+    /// when lenient maps are enabled
+    fn set_key(
+        &self,
+        map: &mut HashMap<String, LLSDValue>,
+        current_key: &mut Option<String>,
+        orphan: &mut Option<LLSDValue>,
+        key: String,
+        context: &mut ParseContext,
+    ) -> LLSDResult<()> {
+        if !context.lenient_maps {
+            if let Some(previous) = current_key.replace(key) {
+                return Err(key_without_value(&previous));
+            }
+            return Ok(());
+        }
+        if let Some(value) = orphan.take() {
+            context.warnings.push(format!("Map key '{}' follows its value", key));
+            map.insert(key, value);
+        } else if let Some(previous) = current_key.replace(key) {
+            context.warnings.push(format!(
+                "Duplicate map key: '{}' replaced by '{}'",
+                previous,
+                current_key.as_deref().unwrap_or_default()
+            ));
+        }
+        Ok(())
+    }
+
+    fn set_orphan(&self, orphan: &mut Option<LLSDValue>, value: LLSDValue, context: &mut ParseContext) {
+        if orphan.replace(value).is_some() {
+            context.warnings.push("Dropped map value without key".to_string());
+        }
+    }
+
+    /// Skip to the end of an element, including any nested content
+    fn skip_to_end(&self, reader: &mut Reader<&[u8]>, tag: &str) -> LLSDResult<()> {
+        let mut depth = 0usize;
+        
+        loop {
+            match reader.read_event() {
+                Ok(Event::Start(_)) => depth += 1,
+                Ok(Event::End(ref e)) => {
+                    if depth == 0 {
+                        if e.name().as_ref() == tag.as_bytes() {
+                            return Ok(());
+                        }
+                        return Err(LLSDError::custom(format!(
+                            "Mismatched end tag inside <{}>",
+                            tag
+                        )));
+                    }
+                    depth -= 1;
+                }
+                Ok(Event::Eof) => return Err(unterminated(tag)),
+                Err(e) => return Err(LLSDError::from(e)),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn unterminated(tag: &str) -> LLSDError {
+    LLSDError::custom(format!("Unterminated <{}> element: input ended before </{}>", tag, tag))
+}
+
+fn value_without_key(tag: &str) -> LLSDError {
+    LLSDError::custom(format!("Map value <{}> without a preceding <key>", tag))
+}
+
+fn key_without_value(key: &str) -> LLSDError {
+    LLSDError::custom(format!("Map key '{}' has no value", key))
+}
+
+/// An element being assembled by [`LLSDXmlStreamParser`]
+#[derive(Debug)]
+enum StreamFrame {
+    Array(Vec<LLSDValue>),
+    Map {
+        map: HashMap<String, LLSDValue>,
+        key: Option<String>,
+    },
+    Key(String),
+    Scalar { tag: String, text: String },
+    /// Everything inside `<undef>` is ignored; counts nested open tags
+    Undef(usize),
+}
+
+/// Push parser for LLSD XML that arrives in chunks.
+///
+/// Bytes passed to [`feed`](Self::feed) are buffered only until the markup
+/// they belong to is complete, so tags and text may be split at any byte.
+/// Values are assembled as their closing tags arrive.
+///
+/// ```
+/// use llsd::LLSDXmlStreamParser;
+///
+/// let mut parser = LLSDXmlStreamParser::new();
+/// parser.feed(b"<llsd><arr");
+/// parser.feed(b"ay><integer>4");
+/// parser.feed(b"2</integer></array></llsd>");
+/// assert!(parser.is_complete());
+/// let document = parser.finish().unwrap();
+/// ```
+#[derive(Debug, Default)]
+pub struct LLSDXmlStreamParser {
+    parser: LLSDXmlParser,
+    pending: Vec<u8>,
+    stack: Vec<StreamFrame>,
+    in_root: bool,
+    root: Option<LLSDValue>,
+    complete: bool,
+    received_data: bool,
+    error: Option<LLSDError>,
+}
+
+impl LLSDXmlStreamParser {
+    /// Create a stream parser with default element conversion
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a stream parser that converts elements like `parser`
+    pub fn with_parser(parser: LLSDXmlParser) -> Self {
+        Self {
+            parser,
+            ..Self::default()
+        }
+    }
+
+    /// Consume the next chunk of the document. Errors are kept and
+    /// reported by [`finish`](Self::finish); later chunks are then ignored.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        if self.error.is_some() || self.complete {
+            return;
+        }
+        self.received_data |= bytes.iter().any(|b| !b.is_ascii_whitespace());
+        self.pending.extend_from_slice(bytes);
+
+        if let Err(e) = self.drain() {
+            self.error = Some(e);
+            self.pending.clear();
+        }
+    }
+
+    /// Whether the closing `</llsd>` tag has been seen
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// Return the parsed document, failing if the input was malformed or
+    /// ended before the document closed
+    pub fn finish(self) -> LLSDResult<LLSDDocument> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        if !self.received_data {
+            return Err(LLSDError::EmptyInput);
+        }
+        if !self.complete {
+            return Err(LLSDError::UnexpectedEndOfData);
+        }
+        Ok(LLSDDocument::new(self.root.unwrap_or_default()))
+    }
+
+    /// Process every complete token in the buffer, keeping any partial one
+    fn drain(&mut self) -> LLSDResult<()> {
+        let mut consumed = 0;
+
+        while consumed < self.pending.len() && !self.complete {
+            let rest = &self.pending[consumed..];
+            let len = if rest[0] == b'<' {
+                match Self::markup_len(rest) {
+                    Some(len) => len,
+                    None => break,
+                }
+            } else {
+                // Text runs to the next tag, which may not have arrived yet
+                match rest.iter().position(|&b| b == b'<') {
+                    Some(len) => len,
+                    None => break,
+                }
+            };
+
+            let token = self.pending[consumed..consumed + len].to_vec();
+            consumed += len;
+            if token[0] == b'<' {
+                self.handle_markup(&token)?;
+            } else {
+                self.handle_text(&token)?;
+            }
+        }
+
+        self.pending.drain(..consumed);
         Ok(())
     }
+
+    /// Length of the markup at the start of `data`, or `None` if incomplete
+    fn markup_len(data: &[u8]) -> Option<usize> {
+        let find = |terminator: &[u8], from: usize| {
+            data.get(from..)?
+                .windows(terminator.len())
+                .position(|w| w == terminator)
+                .map(|i| from + i + terminator.len())
+        };
+
+        const COMMENT: &[u8] = b"<!--";
+        const CDATA: &[u8] = b"<![CDATA[";
+        let is_prefix = |marker: &[u8]| data.len() < marker.len() && marker.starts_with(data);
+        if is_prefix(COMMENT) || is_prefix(CDATA) {
+            return None;
+        }
+
+        if data.starts_with(COMMENT) {
+            find(b"-->", COMMENT.len())
+        } else if data.starts_with(CDATA) {
+            find(b"]]>", CDATA.len())
+        } else if data.starts_with(b"<?") {
+            find(b"?>", 2)
+        } else {
+            // A '>' inside a quoted attribute value does not end the tag
+            let mut quote = None;
+            for (i, &b) in data.iter().enumerate().skip(1) {
+                match (quote, b) {
+                    (None, b'"' | b'\'') => quote = Some(b),
+                    (Some(q), _) if q == b => quote = None,
+                    (None, b'>') => return Some(i + 1),
+                    _ => {}
+                }
+            }
+            None
+        }
+    }
+
+    /// Handle text between tags
+    fn handle_text(&mut self, raw: &[u8]) -> LLSDResult<()> {
+        let text = std::str::from_utf8(raw)
+            .map_err(|e| LLSDError::custom(format!("Invalid UTF-8 in XML text: {}", e)))?;
+        let text = quick_xml::escape::unescape(text)
+            .map_err(|e| LLSDError::custom(format!("Invalid XML escape: {}", e)))?;
+        self.append_text(&text)
+    }
+
+    /// Append character data to the element being read
+    fn append_text(&mut self, text: &str) -> LLSDResult<()> {
+        match self.stack.last_mut() {
+            Some(StreamFrame::Scalar { text: content, .. }) | Some(StreamFrame::Key(content)) => {
+                content.push_str(text);
+                Ok(())
+            }
+            Some(StreamFrame::Undef(_)) => Ok(()),
+            _ if text.trim().is_empty() => Ok(()),
+            _ => Err(LLSDError::custom(format!("Unexpected text in LLSD XML: {}", text.trim()))),
+        }
+    }
+
+    /// Handle a complete tag, comment, CDATA section or declaration
+    fn handle_markup(&mut self, token: &[u8]) -> LLSDResult<()> {
+        // Each token is parsed alone, so nesting is checked here instead
+        let mut reader = Reader::from_reader(token);
+        reader.check_end_names(false);
+        match reader.read_event()? {
+            Event::Start(e) => {
+                let tag = String::from_utf8_lossy(e.name().as_ref()).to_string();
+                self.open(tag)
+            }
+            Event::Empty(e) => {
+                let tag = String::from_utf8_lossy(e.name().as_ref()).to_string();
+                self.empty(&tag)
+            }
+            Event::End(e) => {
+                let tag = String::from_utf8_lossy(e.name().as_ref()).to_string();
+                self.close(&tag)
+            }
+            Event::CData(e) => self.append_text(&String::from_utf8_lossy(&e)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Handle an opening tag
+    fn open(&mut self, tag: String) -> LLSDResult<()> {
+        if let Some(StreamFrame::Undef(depth)) = self.stack.last_mut() {
+            *depth += 1;
+            return Ok(());
+        }
+        if !self.in_root {
+            return if tag == "llsd" {
+                self.in_root = true;
+                Ok(())
+            } else {
+                Err(LLSDError::custom("Missing <llsd> root element"))
+            };
+        }
+
+        if tag == "key" {
+            return match self.stack.last() {
+                Some(StreamFrame::Map { .. }) => {
+                    self.stack.push(StreamFrame::Key(String::new()));
+                    Ok(())
+                }
+                _ => Err(LLSDError::custom("<key> outside of <map>")),
+            };
+        }
+
+        let frame = match LLSDType::from_xml_tag(&tag) {
+            Some(LLSDType::Array) => StreamFrame::Array(Vec::new()),
+            Some(LLSDType::Map) => StreamFrame::Map { map: HashMap::new(), key: None },
+            Some(LLSDType::Unknown) => StreamFrame::Undef(0),
+            _ => StreamFrame::Scalar { tag, text: String::new() },
+        };
+        self.stack.push(frame);
+        Ok(())
+    }
+
+    /// Handle a self-closing tag
+    fn empty(&mut self, tag: &str) -> LLSDResult<()> {
+        if let Some(StreamFrame::Undef(_)) = self.stack.last() {
+            return Ok(());
+        }
+        if !self.in_root {
+            return if tag == "llsd" {
+                self.complete = true;
+                Ok(())
+            } else {
+                Err(LLSDError::custom("Missing <llsd> root element"))
+            };
+        }
+        if tag == "key" {
+            return self.set_key(String::new());
+        }
+        let value = self.parser.parse_empty_element(tag)?;
+        self.deliver(value)
+    }
+
+    /// Handle a closing tag
+    fn close(&mut self, tag: &str) -> LLSDResult<()> {
+        if let Some(StreamFrame::Undef(depth)) = self.stack.last_mut() {
+            if *depth > 0 {
+                *depth -= 1;
+                return Ok(());
+            }
+        }
+
+        let frame = match self.stack.pop() {
+            Some(frame) => frame,
+            None if tag == "llsd" && self.in_root => {
+                self.complete = true;
+                return Ok(());
+            }
+            None => return Err(LLSDError::custom(format!("Unexpected closing tag: </{}>", tag))),
+        };
+
+        let value = match frame {
+            StreamFrame::Array(items) => LLSDValue::Array(items),
+            StreamFrame::Map { map, .. } => LLSDValue::Map(map),
+            StreamFrame::Undef(_) => LLSDValue::Undefined,
+            StreamFrame::Key(key) => {
+                if tag != "key" {
+                    return Err(LLSDError::custom(format!("Mismatched closing tag: </{}>", tag)));
+                }
+                return self.set_key(key.trim().to_string());
+            }
+            StreamFrame::Scalar { tag: open, text } => {
+                if tag != open {
+                    return Err(LLSDError::custom(format!("Mismatched closing tag: </{}>", tag)));
+                }
+                let value = self.parser.parse_scalar(tag, text.trim().to_string())?;
+                return self.deliver(value);
+            }
+        };
+
+        if tag != value.get_type().xml_tag() {
+            return Err(LLSDError::custom(format!("Mismatched closing tag: </{}>", tag)));
+        }
+        self.deliver(value)
+    }
+
+    /// Record the key for the next value of the enclosing map
+    fn set_key(&mut self, key: String) -> LLSDResult<()> {
+        match self.stack.last_mut() {
+            Some(StreamFrame::Map { key: pending, .. }) => {
+                *pending = Some(key);
+                Ok(())
+            }
+            _ => Err(LLSDError::custom("<key> outside of <map>")),
+        }
+    }
+
+    /// Attach a finished value to its parent container or the root
+    fn deliver(&mut self, value: LLSDValue) -> LLSDResult<()> {
+        match self.stack.last_mut() {
+            Some(StreamFrame::Array(items)) => {
+                items.push(value);
+                Ok(())
+            }
+            Some(StreamFrame::Map { map, key }) => match key.take() {
+                Some(key) => {
+                    map.insert(key, value);
+                    Ok(())
+                }
+                None => Err(LLSDError::custom("Map value without key")),
+            },
+            Some(_) => Err(LLSDError::custom("Element nested inside a scalar")),
+            None if self.root.is_none() => {
+                self.root = Some(value);
+                Ok(())
+            }
+            None => Err(LLSDError::custom("Multiple root values inside <llsd>")),
+        }
+    }
+}
+
+/// Date offsets to apply while serializing, found by the path being written
+struct OffsetLookup<'a> {
+    offsets: &'a HashMap<String, FixedOffset>,
+    path: Vec<String>,
+}
+
+impl OffsetLookup<'_> {
+    fn enter(&mut self, segment: impl ToString) {
+        if !self.offsets.is_empty() {
+            self.path.push(segment.to_string());
+        }
+    }
+
+    fn leave(&mut self) {
+        if !self.offsets.is_empty() {
+            self.path.pop();
+        }
+    }
+
+    fn current(&self) -> Option<FixedOffset> {
+        if self.offsets.is_empty() {
+            return None;
+        }
+        self.offsets.get(&self.path.join(".")).copied()
+    }
+}
+
+/// How `<real>` values are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RealFormat {
+    /// Shortest text that parses back to the same value
+    #[default]
+    Shortest,
+    /// A fixed number of digits after the decimal point
+    Fixed(usize),
+    /// 17 significant digits, matching the viewer's `%.17g`
+    Grisu17,
+}
+
+impl RealFormat {
+    /// Format a real. Non-finite values are written as `nan`, `inf` and
+    /// `-inf` in every mode, as the viewer does.
+    pub fn format(&self, value: f64) -> String {
+        if value.is_nan() {
+            return "nan".to_string();
+        }
+        if value.is_infinite() {
+            return if value > 0.0 { "inf" } else { "-inf" }.to_string();
+        }
+        match self {
+            RealFormat::Shortest => value.to_string(),
+            RealFormat::Fixed(digits) => format!("{:.*}", digits, value),
+            RealFormat::Grisu17 => format_g17(value),
+        }
+    }
+}
+
+/// C `printf("%.17g")`: 17 significant digits, trailing zeros removed,
+/// exponent form outside `1e-4 <= |v| < 1e17`
+fn format_g17(value: f64) -> String {
+    const PRECISION: i32 = 17;
+
+    let scientific = format!("{:.*e}", (PRECISION - 1) as usize, value);
+    let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+    let exponent: i32 = exponent.parse().unwrap_or(0);
+
+    fn strip_zeros(digits: &str) -> &str {
+        if digits.contains('.') {
+            digits.trim_end_matches('0').trim_end_matches('.')
+        } else {
+            digits
+        }
+    }
+
+    if !(-4..PRECISION).contains(&exponent) {
+        let sign = if exponent < 0 { '-' } else { '+' };
+        format!("{}e{}{:02}", strip_zeros(mantissa), sign, exponent.abs())
+    } else {
+        let decimals = (PRECISION - 1 - exponent) as usize;
+        strip_zeros(&format!("{:.*}", decimals, value)).to_string()
+    }
 }
 
 /// LLSD XML serializer
@@ -252,6 +946,10 @@ impl LLSDXmlParser {
 pub struct LLSDXmlSerializer {
     pretty_print: bool,
     indent_size: usize,
+    sorted_keys: bool,
+    real_format: RealFormat,
+    max_output_size: Option<usize>,
+    xml_declaration: bool,
 }
 
 impl Default for LLSDXmlSerializer {
@@ -259,6 +957,10 @@ impl Default for LLSDXmlSerializer {
         Self {
             pretty_print: false,
             indent_size: 2,
+            sorted_keys: false,
+            real_format: RealFormat::default(),
+            max_output_size: None,
+            xml_declaration: true,
         }
     }
 }
@@ -281,18 +983,59 @@ impl LLSDXmlSerializer {
         self
     }
 
+    /// Emit map keys in lexicographic order, so equal documents always
+    /// serialize to identical bytes (for golden files and signatures)
+    pub fn with_sorted_keys(mut self, sorted: bool) -> Self {
+        self.sorted_keys = sorted;
+        self
+    }
+
+    /// Choose how reals are written; see [`RealFormat`]
+    pub fn with_real_precision(mut self, format: RealFormat) -> Self {
+        self.real_format = format;
+        self
+    }
+
+    /// Fail with [`LLSDError::OutputTooLarge`] as soon as the XML would
+    /// grow past `bytes`
+    pub fn with_max_output_size(mut self, bytes: usize) -> Self {
+        self.max_output_size = Some(bytes);
+        self
+    }
+
+    /// Write the `<?xml version="1.0" encoding="UTF-8"?>` declaration. On
+    /// by default; turn off to embed the output in another document.
+    pub fn with_xml_declaration(mut self, declaration: bool) -> Self {
+        self.xml_declaration = declaration;
+        self
+    }
+
     /// Serialize LLSD to XML string
     pub fn serialize(&self, document: &LLSDDocument) -> LLSDResult<String> {
         let mut output = Vec::new();
-        let mut writer = Writer::new(Cursor::new(&mut output));
-        
+        let mut writer = Writer::new(LimitedWriter::new(Cursor::new(&mut output), self.max_output_size));
+        if let Err(err) = self.write_document(&mut writer, document) {
+            return Err(writer.get_ref().map_error(err));
+        }
+
+        String::from_utf8(output).map_err(LLSDError::from)
+    }
+
+    /// Write the declaration, `<llsd>` root and content
+    fn write_document<W: std::io::Write>(
+        &self,
+        writer: &mut Writer<W>,
+        document: &LLSDDocument,
+    ) -> LLSDResult<()> {
         // Write XML declaration
-        writer.write_event(Event::Decl(quick_xml::events::BytesDecl::new(
-            "1.0", Some("UTF-8"), None
-        )))?;
-        
-        if self.pretty_print {
-            writer.write_event(Event::Text(BytesText::new("\n")))?;
+        if self.xml_declaration {
+            writer.write_event(Event::Decl(quick_xml::events::BytesDecl::new(
+                "1.0", Some("UTF-8"), None
+            )))?;
+
+            if self.pretty_print {
+                writer.write_event(Event::Text(BytesText::new("\n")))?;
+            }
         }
 
         // Write LLSD root element
@@ -302,15 +1045,15 @@ impl LLSDXmlSerializer {
             writer.write_event(Event::Text(BytesText::new("\n")))?;
         }
 
-        self.write_value(&mut writer, document.content(), if self.pretty_print { 1 } else { 0 })?;
+        let mut offsets = OffsetLookup { offsets: document.date_offsets(), path: Vec::new() };
+        self.write_value(writer, document.content(), if self.pretty_print { 1 } else { 0 }, &mut offsets)?;
         
         if self.pretty_print {
             writer.write_event(Event::Text(BytesText::new("\n")))?;
         }
 
         writer.write_event(Event::End(BytesEnd::new("llsd")))?;
-
-        String::from_utf8(output).map_err(|e| LLSDError::from(e))
+        Ok(())
     }
 
     /// Write an LLSD value as XML
@@ -319,6 +1062,7 @@ impl LLSDXmlSerializer {
         writer: &mut Writer<W>,
         value: &LLSDValue,
         depth: usize,
+        offsets: &mut OffsetLookup<'_>,
     ) -> LLSDResult<()> {
         let indent = if self.pretty_print {
             " ".repeat(depth * self.indent_size)
@@ -330,60 +1074,21 @@ impl LLSDXmlSerializer {
             writer.write_event(Event::Text(BytesText::new(&indent)))?;
         }
 
+        let tag = value.get_type().xml_tag();
         match value {
             LLSDValue::Undefined => {
-                writer.write_event(Event::Empty(BytesStart::new("undef")))?;
-            }
-            LLSDValue::Boolean(b) => {
-                let content = if *b { "1" } else { "0" };
-                writer.write_event(Event::Start(BytesStart::new("boolean")))?;
-                writer.write_event(Event::Text(BytesText::new(content)))?;
-                writer.write_event(Event::End(BytesEnd::new("boolean")))?;
-            }
-            LLSDValue::Integer(i) => {
-                writer.write_event(Event::Start(BytesStart::new("integer")))?;
-                writer.write_event(Event::Text(BytesText::new(&i.to_string())))?;
-                writer.write_event(Event::End(BytesEnd::new("integer")))?;
-            }
-            LLSDValue::Real(r) => {
-                writer.write_event(Event::Start(BytesStart::new("real")))?;
-                writer.write_event(Event::Text(BytesText::new(&r.to_string())))?;
-                writer.write_event(Event::End(BytesEnd::new("real")))?;
-            }
-            LLSDValue::String(s) => {
-                writer.write_event(Event::Start(BytesStart::new("string")))?;
-                writer.write_event(Event::Text(BytesText::from_escaped(s)))?;
-                writer.write_event(Event::End(BytesEnd::new("string")))?;
-            }
-            LLSDValue::UUID(u) => {
-                writer.write_event(Event::Start(BytesStart::new("uuid")))?;
-                writer.write_event(Event::Text(BytesText::new(&u.to_string())))?;
-                writer.write_event(Event::End(BytesEnd::new("uuid")))?;
-            }
-            LLSDValue::Date(d) => {
-                writer.write_event(Event::Start(BytesStart::new("date")))?;
-                writer.write_event(Event::Text(BytesText::new(&d.to_rfc3339())))?;
-                writer.write_event(Event::End(BytesEnd::new("date")))?;
-            }
-            LLSDValue::URI(u) => {
-                writer.write_event(Event::Start(BytesStart::new("uri")))?;
-                writer.write_event(Event::Text(BytesText::from_escaped(u)))?;
-                writer.write_event(Event::End(BytesEnd::new("uri")))?;
-            }
-            LLSDValue::Binary(b) => {
-                let base64_str = base64::encode(b);
-                writer.write_event(Event::Start(BytesStart::new("binary")))?;
-                writer.write_event(Event::Text(BytesText::new(&base64_str)))?;
-                writer.write_event(Event::End(BytesEnd::new("binary")))?;
+                writer.write_event(Event::Empty(BytesStart::new(tag)))?;
             }
             LLSDValue::Array(arr) => {
-                writer.write_event(Event::Start(BytesStart::new("array")))?;
+                writer.write_event(Event::Start(BytesStart::new(tag)))?;
                 
-                for item in arr {
+                for (index, item) in arr.iter().enumerate() {
                     if self.pretty_print {
                         writer.write_event(Event::Text(BytesText::new("\n")))?;
                     }
-                    self.write_value(writer, item, depth + 1)?;
+                    offsets.enter(index);
+                    self.write_value(writer, item, depth + 1, offsets)?;
+                    offsets.leave();
                 }
                 
                 if self.pretty_print && !arr.is_empty() {
@@ -391,12 +1096,16 @@ impl LLSDXmlSerializer {
                     writer.write_event(Event::Text(BytesText::new(&indent)))?;
                 }
                 
-                writer.write_event(Event::End(BytesEnd::new("array")))?;
+                writer.write_event(Event::End(BytesEnd::new(tag)))?;
             }
             LLSDValue::Map(map) => {
-                writer.write_event(Event::Start(BytesStart::new("map")))?;
+                writer.write_event(Event::Start(BytesStart::new(tag)))?;
                 
-                for (key, val) in map {
+                let mut entries: Vec<(&String, &LLSDValue)> = map.iter().collect();
+                if self.sorted_keys {
+                    entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+                }
+                for (key, val) in entries {
                     if self.pretty_print {
                         writer.write_event(Event::Text(BytesText::new("\n")))?;
                         writer.write_event(Event::Text(BytesText::new(
@@ -405,14 +1114,16 @@ impl LLSDXmlSerializer {
                     }
                     
                     writer.write_event(Event::Start(BytesStart::new("key")))?;
-                    writer.write_event(Event::Text(BytesText::from_escaped(key)))?;
+                    writer.write_event(Event::Text(BytesText::new(key)))?;
                     writer.write_event(Event::End(BytesEnd::new("key")))?;
                     
                     if self.pretty_print {
                         writer.write_event(Event::Text(BytesText::new("\n")))?;
                     }
                     
-                    self.write_value(writer, val, depth + 1)?;
+                    offsets.enter(key);
+                    self.write_value(writer, val, depth + 1, offsets)?;
+                    offsets.leave();
                 }
                 
                 if self.pretty_print && !map.is_empty() {
@@ -420,7 +1131,13 @@ impl LLSDXmlSerializer {
                     writer.write_event(Event::Text(BytesText::new(&indent)))?;
                 }
                 
-                writer.write_event(Event::End(BytesEnd::new("map")))?;
+                writer.write_event(Event::End(BytesEnd::new(tag)))?;
+            }
+            scalar => {
+                let text = self.scalar_text(scalar, offsets);
+                writer.write_event(Event::Start(BytesStart::new(tag)))?;
+                writer.write_event(Event::Text(BytesText::new(&text)))?;
+                writer.write_event(Event::End(BytesEnd::new(tag)))?;
             }
         }
 
@@ -430,4 +1147,22 @@ impl LLSDXmlSerializer {
 
         Ok(())
     }
+
+    /// Text content of a scalar element, before XML escaping
+    fn scalar_text<'v>(&self, value: &'v LLSDValue, offsets: &OffsetLookup<'_>) -> Cow<'v, str> {
+        match value {
+            LLSDValue::Boolean(b) => Cow::Borrowed(if *b { "1" } else { "0" }),
+            LLSDValue::Integer(i) => Cow::Owned(i.to_string()),
+            LLSDValue::Integer64(i) => Cow::Owned(i.to_string()),
+            LLSDValue::Real(r) => Cow::Owned(self.real_format.format(*r)),
+            LLSDValue::String(s) | LLSDValue::URI(s) => Cow::Borrowed(s),
+            LLSDValue::UUID(u) => Cow::Owned(u.to_string()),
+            LLSDValue::Date(d) => Cow::Owned(match offsets.current() {
+                Some(offset) => d.with_timezone(&offset).to_rfc3339(),
+                None => d.to_rfc3339(),
+            }),
+            LLSDValue::Binary(b) => Cow::Owned(BASE64.encode(b)),
+            LLSDValue::Undefined | LLSDValue::Array(_) | LLSDValue::Map(_) => Cow::Borrowed(""),
+        }
+    }
 }
\ No newline at end of file
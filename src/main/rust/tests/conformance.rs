@@ -0,0 +1,202 @@
+/*!
+ * LLSD Conformance Tests
+ *
+ * Canonical LLSDValue fixtures round-tripped through every available format
+ * Copyright (C) 2024 Linden Lab
+ */
+
+use llsd::*;
+use std::collections::HashMap;
+use chrono::{TimeZone, Utc};
+use uuid::uuid;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+
+/// Serialization formats exercised by the harness
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Xml,
+    Json,
+    Binary,
+    Notation,
+}
+
+const FORMATS: &[Format] = &[Format::Xml, Format::Json, Format::Binary, Format::Notation];
+
+/// A named canonical value
+struct Fixture {
+    name: &'static str,
+    value: LLSDValue,
+}
+
+fn fixture(name: &'static str, value: LLSDValue) -> Fixture {
+    Fixture { name, value }
+}
+
+fn map(entries: Vec<(&str, LLSDValue)>) -> LLSDValue {
+    LLSDValue::Map(entries.into_iter().map(|(k, v)| (k.to_string(), v)).collect())
+}
+
+fn fixtures() -> Vec<Fixture> {
+    vec![
+        fixture("undefined", LLSDValue::Undefined),
+        fixture("boolean_true", LLSDValue::Boolean(true)),
+        fixture("boolean_false", LLSDValue::Boolean(false)),
+        fixture("integer_zero", LLSDValue::Integer(0)),
+        fixture("integer_min", LLSDValue::Integer(i32::MIN)),
+        fixture("integer_max", LLSDValue::Integer(i32::MAX)),
+        fixture("integer64_min", LLSDValue::Integer64(i64::MIN)),
+        fixture("integer64_max", LLSDValue::Integer64(i64::MAX)),
+        fixture("real", LLSDValue::Real(-1234.5678)),
+        fixture("real_tiny", LLSDValue::Real(f64::MIN_POSITIVE)),
+        fixture("real_nan", LLSDValue::Real(f64::NAN)),
+        fixture("real_infinity", LLSDValue::Real(f64::INFINITY)),
+        fixture("real_neg_infinity", LLSDValue::Real(f64::NEG_INFINITY)),
+        fixture("string", LLSDValue::String("Hello, Second Life".to_string())),
+        fixture("string_empty", LLSDValue::String(String::new())),
+        fixture("string_unicode", LLSDValue::String("你好世界 — café 🎉".to_string())),
+        fixture("string_markup", LLSDValue::String("a < b && c > d".to_string())),
+        fixture("uuid", LLSDValue::UUID(uuid!("550e8400-e29b-41d4-a716-446655440000"))),
+        fixture("uuid_nil", LLSDValue::UUID(uuid::Uuid::nil())),
+        fixture("date", LLSDValue::Date(Utc.timestamp_opt(1609459200, 500_000_000).single().unwrap())),
+        fixture("uri", LLSDValue::URI("https://example.com/cap?id=1".to_string())),
+        fixture("binary", LLSDValue::Binary(vec![0x00, 0xFF, 0x42, 0xAB])),
+        fixture("binary_empty", LLSDValue::Binary(Vec::new())),
+        fixture("binary_large", LLSDValue::Binary((0..65536u32).map(|i| (i % 251) as u8).collect())),
+        fixture("array_empty", LLSDValue::Array(Vec::new())),
+        fixture("map_empty", LLSDValue::Map(HashMap::new())),
+        fixture("array_scalars", LLSDValue::Array(vec![
+            LLSDValue::Integer(1),
+            LLSDValue::Real(2.5),
+            LLSDValue::String("three".to_string()),
+            LLSDValue::Boolean(true),
+        ])),
+        fixture("map_scalars", map(vec![
+            ("name", LLSDValue::String("Alice".to_string())),
+            ("age", LLSDValue::Integer(30)),
+            ("premium", LLSDValue::Boolean(false)),
+        ])),
+        fixture("map_unicode_keys", map(vec![
+            ("名前", LLSDValue::String("Alice".to_string())),
+            ("clé", LLSDValue::Integer(1)),
+        ])),
+        fixture("map_special_keys", map(vec![
+            ("a<b&c", LLSDValue::Integer(1)),
+            ("\"quoted\" 'key'", LLSDValue::Integer(2)),
+            ("</key>", LLSDValue::Integer(3)),
+            ("", LLSDValue::Integer(4)),
+        ])),
+        fixture("nested", map(vec![
+            ("agent", map(vec![
+                ("id", LLSDValue::UUID(uuid!("550e8400-e29b-41d4-a716-446655440001"))),
+                ("position", LLSDValue::Array(vec![
+                    LLSDValue::Real(128.0),
+                    LLSDValue::Real(128.0),
+                    LLSDValue::Real(25.0),
+                ])),
+            ])),
+            ("groups", LLSDValue::Array(vec![
+                map(vec![("name", LLSDValue::String("Builders".to_string()))]),
+                LLSDValue::Array(vec![LLSDValue::Array(Vec::new())]),
+            ])),
+        ])),
+    ]
+}
+
+/// Fixtures a format is currently known to mishandle. The harness asserts
+/// these still fail, so fixing one forces its removal from this list.
+/// Empty while every format handles every fixture.
+fn known_issue(_format: Format, _name: &str) -> Option<&'static str> {
+    None
+}
+
+fn round_trip(format: Format, value: &LLSDValue) -> LLSDResult<LLSDValue> {
+    let document = LLSDDocument::new(value.clone());
+    let parsed = match format {
+        Format::Xml => LLSDFactory::parse_xml(&LLSDFactory::serialize_xml(&document, false)?)?,
+        Format::Json => LLSDFactory::parse_json(&LLSDFactory::serialize_json(&document, false)?)?,
+        Format::Binary => LLSDFactory::parse_binary(&LLSDFactory::serialize_binary(&document)?)?,
+        Format::Notation => LLSDFactory::parse_notation(&LLSDFactory::serialize_notation(&document, false)?)?,
+    };
+    Ok(parsed.content().clone())
+}
+
+/// What a round trip through `format` should produce, or `None` if the
+/// format must reject the value.
+///
+/// JSON is lossy by design: binary becomes its base64 string, URIs survive
+/// only when they look like URIs, and non-finite reals cannot be written.
+fn expected(format: Format, value: &LLSDValue) -> Option<LLSDValue> {
+    if format != Format::Json {
+        return Some(value.clone());
+    }
+
+    match value {
+        LLSDValue::Real(r) if !r.is_finite() => None,
+        LLSDValue::Binary(b) => Some(LLSDValue::String(BASE64.encode(b))),
+        LLSDValue::Array(items) => items
+            .iter()
+            .map(|item| expected(format, item))
+            .collect::<Option<Vec<_>>>()
+            .map(LLSDValue::Array),
+        LLSDValue::Map(entries) => entries
+            .iter()
+            .map(|(k, v)| expected(format, v).map(|v| (k.clone(), v)))
+            .collect::<Option<HashMap<_, _>>>()
+            .map(LLSDValue::Map),
+        other => Some(other.clone()),
+    }
+}
+
+/// Structural equality that treats NaN as equal to itself
+fn conforms(a: &LLSDValue, b: &LLSDValue) -> bool {
+    match (a, b) {
+        (LLSDValue::Real(x), LLSDValue::Real(y)) => x == y || (x.is_nan() && y.is_nan()),
+        (LLSDValue::Array(x), LLSDValue::Array(y)) => {
+            x.len() == y.len() && x.iter().zip(y).all(|(x, y)| conforms(x, y))
+        }
+        (LLSDValue::Map(x), LLSDValue::Map(y)) => {
+            x.len() == y.len() && x.iter().all(|(k, v)| y.get(k).is_some_and(|w| conforms(v, w)))
+        }
+        _ => a == b,
+    }
+}
+
+#[test]
+fn test_fixtures_conform_across_formats() {
+    let mut failures = Vec::new();
+
+    for format in FORMATS {
+        for fixture in fixtures() {
+            let result = round_trip(*format, &fixture.value);
+            let ok = match (expected(*format, &fixture.value), &result) {
+                (Some(want), Ok(got)) => conforms(&want, got),
+                (None, Err(_)) => true,
+                _ => false,
+            };
+
+            match (ok, known_issue(*format, fixture.name)) {
+                (true, None) | (false, Some(_)) => {}
+                (false, None) => failures.push(format!(
+                    "{:?} / {}: got {:?}",
+                    format, fixture.name, result
+                )),
+                (true, Some(issue)) => failures.push(format!(
+                    "{:?} / {}: now passes, remove known issue \"{}\"",
+                    format, fixture.name, issue
+                )),
+            }
+        }
+    }
+
+    assert!(failures.is_empty(), "conformance failures:\n{}", failures.join("\n"));
+}
+
+#[test]
+fn test_fixture_names_are_unique() {
+    let mut names: Vec<&str> = fixtures().iter().map(|f| f.name).collect();
+    names.sort_unstable();
+    let count = names.len();
+    names.dedup();
+    assert_eq!(names.len(), count);
+}
@@ -6,11 +6,12 @@
  */
 
 #[cfg(test)]
+#[allow(clippy::approx_constant)]
 mod tests {
     use llsd::*;
     use std::collections::HashMap;
     use uuid::uuid;
-    use chrono::{DateTime, Utc, TimeZone};
+    use chrono::{Utc, TimeZone};
 
     #[test]
     fn test_llsd_document_creation() {
@@ -96,6 +97,109 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_set_path_create() {
+        let mut root = LLSDValue::Map(HashMap::new());
+
+        // set_path does not create intermediate nodes
+        assert!(!root.set_path("a.b.c", LLSDValue::Integer(1)));
+        assert_eq!(root, LLSDValue::Map(HashMap::new()));
+
+        assert!(root.set_path_create("a.b.c", LLSDValue::Integer(1)));
+        let expected = LLSDFactory::parse_json(r#"{"a":{"b":{"c":1}}}"#).unwrap();
+        assert_eq!(&root, expected.content());
+
+        // Existing maps are reused and siblings kept
+        assert!(root.set_path_create("a.b.d", LLSDValue::Integer(2)));
+        assert!(root.set_path_create("a.x", LLSDValue::Boolean(true)));
+        assert_eq!(root.get_path("a.b.c"), Some(&LLSDValue::Integer(1)));
+        assert_eq!(root.get_path("a.b.d"), Some(&LLSDValue::Integer(2)));
+
+        // Existing array elements can be addressed, but arrays never grow
+        assert!(root.set_path_create("list", LLSDValue::Array(vec![LLSDValue::Map(HashMap::new())])));
+        assert!(root.set_path_create("list.0.name", LLSDValue::String("first".to_string())));
+        assert_eq!(root.get_path("list.0.name"), Some(&LLSDValue::String("first".to_string())));
+        assert!(!root.set_path_create("list.1.name", LLSDValue::Integer(1)));
+        assert!(!root.set_path_create("list.1", LLSDValue::Integer(1)));
+
+        // Numeric segments against a missing node fail without side effects
+        let before = root.clone();
+        assert!(!root.set_path_create("fresh.0.name", LLSDValue::Integer(1)));
+        assert!(!root.set_path_create("fresh.0", LLSDValue::Integer(1)));
+        assert!(!root.set_path_create("a.b.c.deeper", LLSDValue::Integer(1)));
+        assert_eq!(root, before);
+    }
+
+    #[test]
+    fn test_vector_constructors() {
+        assert_eq!(
+            LLSDValue::vector3(1.0, -2.0, 3.5).unwrap(),
+            LLSDValue::Array(vec![LLSDValue::Real(1.0), LLSDValue::Real(-2.0), LLSDValue::Real(3.5)])
+        );
+        assert!(LLSDValue::color(1.0, 0.5, 0.25, 1.0).is_ok());
+
+        assert!(matches!(LLSDValue::vector3(f64::NAN, 0.0, 0.0), Err(LLSDError::ValidationError { .. })));
+        assert!(LLSDValue::vector4(0.0, f64::INFINITY, 0.0, 0.0).is_err());
+        assert!(LLSDValue::rotation(0.0, 0.0, f64::NEG_INFINITY, 1.0).is_err());
+        assert!(LLSDValue::color(1.5, 0.0, 0.0, 1.0).is_err());
+        assert!(LLSDValue::color(0.0, 0.0, 0.0, -0.1).is_err());
+        assert!(LLSDValue::color(f64::NAN, 0.0, 0.0, 1.0).is_err());
+    }
+
+    #[test]
+    fn test_leaves_mut() {
+        let mut root = LLSDValue::Map({
+            let mut map = HashMap::new();
+            map.insert("count".to_string(), LLSDValue::Integer(5));
+            map.insert("name".to_string(), LLSDValue::String("Alice".to_string()));
+            map.insert("empty".to_string(), LLSDValue::Array(Vec::new()));
+            map.insert("nested".to_string(), LLSDValue::Map({
+                let mut inner = HashMap::new();
+                inner.insert("scores".to_string(), LLSDValue::Array(vec![
+                    LLSDValue::Integer(95),
+                    LLSDValue::Real(92.5),
+                    LLSDValue::Integer(-87),
+                ]));
+                inner
+            }));
+            map
+        });
+
+        for leaf in root.leaves_mut() {
+            if let LLSDValue::Integer(i) = leaf {
+                *i = 0;
+            }
+        }
+
+        assert_eq!(root.get_path("count"), Some(&LLSDValue::Integer(0)));
+        assert_eq!(root.get_path("nested.scores.0"), Some(&LLSDValue::Integer(0)));
+        assert_eq!(root.get_path("nested.scores.1"), Some(&LLSDValue::Real(92.5)));
+        assert_eq!(root.get_path("nested.scores.2"), Some(&LLSDValue::Integer(0)));
+        assert_eq!(root.get_path("name"), Some(&LLSDValue::String("Alice".to_string())));
+
+        // Containers are preserved, including empty ones
+        assert_eq!(root.get_path("empty"), Some(&LLSDValue::Array(Vec::new())));
+        assert_eq!(root.get_path("nested.scores").and_then(|v| v.as_array()).map(|a| a.len()), Some(3));
+
+        // Leaves are yielded in array order and containers are never yielded
+        let mut array = LLSDValue::Array(vec![
+            LLSDValue::Real(1.5),
+            LLSDValue::Map(HashMap::new()),
+            LLSDValue::Real(-2.5),
+        ]);
+        array.for_each_leaf_mut(|leaf| {
+            if let LLSDValue::Real(r) = leaf {
+                *r = r.clamp(0.0, 1.0);
+            }
+        });
+        assert_eq!(array, LLSDValue::Array(vec![
+            LLSDValue::Real(1.0),
+            LLSDValue::Map(HashMap::new()),
+            LLSDValue::Real(0.0),
+        ]));
+        assert_eq!(array.leaves_mut().count(), 2);
+    }
+
     #[test]
     fn test_array_navigation() {
         let array = LLSDValue::Array(vec![
@@ -137,8 +241,41 @@ mod tests {
         assert_eq!(LLSDUtils::get_real(&test_data, "real", 0.0), 3.14);
         assert_eq!(LLSDUtils::get_real(&test_data, "integer", 0.0), 42.0);
 
-        assert_eq!(LLSDUtils::get_boolean(&test_data, "boolean", false), true);
-        assert_eq!(LLSDUtils::get_boolean(&test_data, "missing", false), false);
+        assert!(LLSDUtils::get_boolean(&test_data, "boolean", false));
+        assert!(!LLSDUtils::get_boolean(&test_data, "missing", false));
+    }
+
+    #[test]
+    fn test_looks_binary() {
+        // ASCII and valid Unicode text
+        assert!(!LLSDUtils::looks_binary(""));
+        assert!(!LLSDUtils::looks_binary("Hello, Second Life!"));
+        assert!(!LLSDUtils::looks_binary("line one\n\tline two\r\n"));
+        assert!(!LLSDUtils::looks_binary("unicode: 你好世界 — café 🎉"));
+        assert!(!LLSDUtils::looks_binary("a long sentence with a single bell \u{7} character"));
+
+        // Lossy-conversion traces and control-char-heavy content
+        assert!(LLSDUtils::looks_binary("PNG\u{FFFD}\u{FFFD}header"));
+        assert!(LLSDUtils::looks_binary("abc\0def"));
+        assert!(LLSDUtils::looks_binary("\u{1}\u{2}\u{3}ab\u{1b}\u{7f}cd"));
+
+        let xml = "<?xml version=\"1.0\"?><llsd><map>\
+            <key>text</key><string>plain text</string>\
+            <key>blob</key><string>\u{1}\u{2}\u{3}\u{4}xy</string>\
+            </map></llsd>";
+
+        let plain = LLSDFactory::parse_xml(xml).unwrap();
+        assert!(matches!(plain.content().get_path("blob"), Some(LLSDValue::String(_))));
+
+        let recovered = LLSDXmlParser::new().with_binary_string_recovery(true).parse(xml).unwrap();
+        assert_eq!(
+            recovered.content().get_path("blob"),
+            Some(&LLSDValue::Binary(vec![1, 2, 3, 4, b'x', b'y']))
+        );
+        assert_eq!(
+            recovered.content().get_path("text"),
+            Some(&LLSDValue::String("plain text".to_string()))
+        );
     }
 
     #[test]
@@ -211,7 +348,7 @@ mod tests {
         });
 
         assert_eq!(LLSDUtils::max_depth(&complex_structure), 5);
-        assert_eq!(LLSDUtils::count_elements(&complex_structure), 7);
+        assert_eq!(LLSDUtils::count_elements(&complex_structure), 8);
 
         // Test constraint validation
         assert!(LLSDUtils::validate_constraints(&complex_structure, 5, 10).is_ok());
@@ -219,6 +356,22 @@ mod tests {
         assert!(LLSDUtils::validate_constraints(&complex_structure, 5, 5).is_err());
     }
 
+    #[test]
+    fn test_count_elements() {
+        // Scalars and containers each count once, the root included
+        assert_eq!(LLSDUtils::count_elements(&LLSDValue::Integer(1)), 1);
+        assert_eq!(LLSDUtils::count_elements(&LLSDValue::Array(Vec::new())), 1);
+        assert_eq!(LLSDUtils::count_elements(&LLSDValue::Map(HashMap::new())), 1);
+
+        let pair = LLSDValue::Array(vec![LLSDValue::Integer(1), LLSDValue::Undefined]);
+        assert_eq!(LLSDUtils::count_elements(&pair), 3);
+
+        // Keys are not elements: one map, one nested array and its two values
+        let mut map = HashMap::new();
+        map.insert("pair".to_string(), pair);
+        assert_eq!(LLSDUtils::count_elements(&LLSDValue::Map(map)), 4);
+    }
+
     #[test]
     fn test_json_round_trip() {
         let original = LLSDValue::Map({
@@ -246,6 +399,42 @@ mod tests {
 
         // Note: JSON has limited type system, so some precision may be lost
         assert_eq!(parsed_document.get_type(), document.get_type());
+        assert!(LLSDUtils::equals_after_json(&original, parsed_document.content()));
+    }
+
+    #[test]
+    fn test_equals_after_json() {
+        let original = LLSDValue::Map({
+            let mut map = HashMap::new();
+            map.insert("id".to_string(), LLSDValue::UUID(uuid!("550e8400-e29b-41d4-a716-446655440000")));
+            map.insert("born".to_string(), LLSDValue::Date(Utc.timestamp_opt(1609459200, 0).single().unwrap()));
+            map.insert("home".to_string(), LLSDValue::URI("secondlife:///app/agent".to_string()));
+            map.insert("texture".to_string(), LLSDValue::Binary(vec![0x48, 0x65, 0x6C, 0x6C, 0x6F]));
+            map.insert("height".to_string(), LLSDValue::Real(2.0));
+            map.insert("handle".to_string(), LLSDValue::Integer64(42));
+            map.insert("nested".to_string(), LLSDValue::Array(vec![
+                LLSDValue::UUID(uuid!("550e8400-e29b-41d4-a716-446655440001")),
+                LLSDValue::Real(0.5),
+            ]));
+            map
+        });
+
+        let json = LLSDFactory::serialize_json(&LLSDDocument::new(original.clone()), false).unwrap();
+        let parsed = LLSDFactory::parse_json(&json).unwrap();
+
+        // Types collapse, so plain equality fails
+        assert_ne!(parsed.content(), &original);
+        assert!(LLSDUtils::equals_after_json(&original, parsed.content()));
+
+        // Real differences are still caught
+        let mut changed = parsed.content().clone();
+        assert!(changed.set_path("nested.1", LLSDValue::Real(0.25)));
+        assert!(!LLSDUtils::equals_after_json(&original, &changed));
+        let mut changed = parsed.content().clone();
+        assert!(changed.set_path("texture", LLSDValue::String("SGVsbG8h".to_string())));
+        assert!(!LLSDUtils::equals_after_json(&original, &changed));
+        assert!(!LLSDUtils::equals_after_json(&LLSDValue::Real(2.5), &LLSDValue::Integer(2)));
+        assert!(!LLSDUtils::equals_after_json(&LLSDValue::Boolean(true), &LLSDValue::Integer(1)));
     }
 
     #[test]
@@ -315,6 +504,32 @@ mod tests {
         assert_eq!(*parsed_document.content(), original);
     }
 
+    #[test]
+    fn test_i64_round_trip() {
+        for value in [i64::MAX, i64::MIN, -1, -1_099_511_627_776, 0] {
+            let original = LLSDValue::Map({
+                let mut map = HashMap::new();
+                map.insert("region_handle".to_string(), LLSDUtils::i64_to_llsd(value));
+                map
+            });
+            let document = LLSDDocument::new(original);
+
+            let binary = LLSDFactory::parse_binary(&LLSDFactory::serialize_binary(&document).unwrap()).unwrap();
+            let xml = LLSDFactory::parse_xml(&LLSDFactory::serialize_xml(&document, false).unwrap()).unwrap();
+            let json = LLSDFactory::parse_json(&LLSDFactory::serialize_json(&document, false).unwrap()).unwrap();
+
+            for parsed in [&binary, &xml, &json] {
+                let handle = parsed.content().get_path("region_handle").unwrap();
+                assert_eq!(LLSDUtils::llsd_to_i64(handle), Some(value));
+            }
+        }
+
+        assert_eq!(LLSDUtils::i64_to_llsd(1), LLSDValue::Binary(vec![0, 0, 0, 0, 0, 0, 0, 1]));
+        assert_eq!(LLSDUtils::llsd_to_i64(&LLSDValue::Integer(-5)), Some(-5));
+        assert_eq!(LLSDUtils::llsd_to_i64(&LLSDValue::Binary(vec![1, 2, 3])), None);
+        assert_eq!(LLSDUtils::llsd_to_i64(&LLSDValue::Boolean(true)), None);
+    }
+
     #[test]
     fn test_error_handling() {
         // Test invalid JSON
@@ -330,11 +545,38 @@ mod tests {
         assert!(LLSDFactory::parse_binary(&[]).is_err());
     }
 
+    #[test]
+    fn test_empty_and_whitespace_input() {
+        for input in ["", "   ", " \t\r\n "] {
+            assert!(matches!(LLSDFactory::parse_xml(input), Err(LLSDError::EmptyInput)));
+            assert!(matches!(LLSDFactory::parse_json(input), Err(LLSDError::EmptyInput)));
+            assert!(matches!(LLSDFactory::parse_notation(input), Err(LLSDError::EmptyInput)));
+            assert!(matches!(
+                LLSDFactory::parse(LLSDFormat::Notation, input.as_bytes()),
+                Err(LLSDError::EmptyInput)
+            ));
+        }
+
+        // Binary input is only empty with no bytes at all: 0x09 and 0x0A are
+        // the array and map type tags, not whitespace
+        assert!(matches!(LLSDFactory::parse_binary(&[]), Err(LLSDError::EmptyInput)));
+        let headerless = LLSDBinaryParser::new().without_magic_validation();
+        assert!(matches!(headerless.parse(&[]), Err(LLSDError::EmptyInput)));
+        assert!(matches!(headerless.parse(b"\t"), Err(LLSDError::UnexpectedEndOfData)));
+        assert!(matches!(headerless.parse(b"\n"), Err(LLSDError::UnexpectedEndOfData)));
+
+        // Truncated (but non-empty) binary data still reports end of data
+        assert!(matches!(
+            LLSDFactory::parse_binary(&[0x6C, 0x6C]),
+            Err(LLSDError::UnexpectedEndOfData)
+        ));
+    }
+
     #[test]
     fn test_large_structures() {
         // Create a large array
         let large_array: Vec<LLSDValue> = (0..1000)
-            .map(|i| LLSDValue::Integer(i))
+            .map(LLSDValue::Integer)
             .collect();
 
         let doc = LLSDDocument::new(LLSDValue::Array(large_array.clone()));
@@ -378,6 +620,1845 @@ mod tests {
         assert!(debug_string.contains("]"));
     }
 
+    #[test]
+    fn test_to_table() {
+        let settings = LLSDValue::Map({
+            let mut map = HashMap::new();
+            map.insert("draw_distance".to_string(), LLSDValue::Real(128.0));
+            map.insert("vsync".to_string(), LLSDValue::Boolean(true));
+            map.insert("max_avatars".to_string(), LLSDValue::Integer(30));
+            map.insert("skin".to_string(), LLSDValue::String("default".to_string()));
+            map
+        });
+
+        let table = LLSDUtils::to_table(&settings);
+        let expected = "\
+key            value
+-------------  -------
+draw_distance  128
+max_avatars    30
+skin           default
+vsync          true";
+        assert_eq!(table, expected);
+
+        let radar = LLSDValue::Array(vec![
+            LLSDValue::Map({
+                let mut map = HashMap::new();
+                map.insert("display_name".to_string(), LLSDValue::String("Alice".to_string()));
+                map.insert("distance".to_string(), LLSDValue::Real(15.5));
+                map.insert("position".to_string(), LLSDValue::vector3(128.0, 128.0, 25.0).unwrap());
+                map
+            }),
+            LLSDValue::Map({
+                let mut map = HashMap::new();
+                map.insert("display_name".to_string(), LLSDValue::String("Bob Resident".to_string()));
+                map.insert("distance".to_string(), LLSDValue::Real(3.0));
+                map.insert("is_typing".to_string(), LLSDValue::Boolean(true));
+                map
+            }),
+        ]);
+
+        let table = LLSDUtils::to_table(&radar);
+        let expected = "\
+#  display_name  distance  is_typing  position
+-  ------------  --------  ---------  --------------
+0  Alice         15.5                 [128, 128, 25]
+1  Bob Resident  3         true";
+        assert_eq!(table, expected);
+
+        // Nested structures fall back to the debug string
+        let nested = LLSDValue::Map({
+            let mut map = HashMap::new();
+            map.insert("inner".to_string(), settings.clone());
+            map
+        });
+        assert_eq!(LLSDUtils::to_table(&nested), LLSDUtils::to_debug_string(&nested, 0));
+    }
+
+    #[test]
+    fn test_zip_unzip_columns() {
+        let names = vec![
+            LLSDValue::String("Alice".to_string()),
+            LLSDValue::String("Bob".to_string()),
+        ];
+        let distances = vec![LLSDValue::Real(15.5), LLSDValue::Real(3.0)];
+        let positions = vec![
+            LLSDValue::vector3(128.0, 128.0, 25.0).unwrap(),
+            LLSDValue::vector3(10.0, 20.0, 30.0).unwrap(),
+        ];
+
+        let mut columns = HashMap::new();
+        columns.insert("name".to_string(), names.as_slice());
+        columns.insert("distance".to_string(), distances.as_slice());
+        columns.insert("position".to_string(), positions.as_slice());
+
+        let rows = LLSDUtils::zip_columns(columns);
+        assert_eq!(rows.get_path("1.name"), Some(&LLSDValue::String("Bob".to_string())));
+        assert_eq!(rows.get_path("0.distance"), Some(&LLSDValue::Real(15.5)));
+
+        let unzipped = LLSDUtils::unzip_rows(&rows);
+        assert_eq!(unzipped.len(), 3);
+        assert_eq!(unzipped["name"], names);
+        assert_eq!(unzipped["distance"], distances);
+        assert_eq!(unzipped["position"], positions);
+
+        // Ragged columns: the short column is absent from later rows,
+        // and unzipping pads it with Undefined
+        let short = vec![LLSDValue::Boolean(true)];
+        let mut ragged = HashMap::new();
+        ragged.insert("name".to_string(), names.as_slice());
+        ragged.insert("is_typing".to_string(), short.as_slice());
+        let rows = LLSDUtils::zip_columns(ragged);
+        assert_eq!(rows.as_array().map(|a| a.len()), Some(2));
+        assert_eq!(rows.get_path("1.is_typing"), None);
+        assert_eq!(
+            LLSDUtils::unzip_rows(&rows)["is_typing"],
+            vec![LLSDValue::Boolean(true), LLSDValue::Undefined]
+        );
+
+        assert!(LLSDUtils::unzip_rows(&LLSDValue::Integer(1)).is_empty());
+    }
+
+    fn xml_round_trip(value: &LLSDValue) -> LLSDValue {
+        let doc = LLSDFactory::create(value.clone());
+        let xml = LLSDFactory::serialize_xml(&doc, false).unwrap();
+        LLSDFactory::parse_xml(&xml).unwrap().content().clone()
+    }
+
+    #[test]
+    fn test_xml_nested_round_trip() {
+        let mut avatar = HashMap::new();
+        avatar.insert("name".to_string(), LLSDValue::String("Alice".to_string()));
+        avatar.insert("online".to_string(), LLSDValue::Boolean(true));
+        avatar.insert("nothing".to_string(), LLSDValue::Undefined);
+        let mut other = HashMap::new();
+        other.insert("name".to_string(), LLSDValue::String("Bob".to_string()));
+        other.insert("score".to_string(), LLSDValue::Real(2.5));
+
+        // Array of maps
+        let array_of_maps = LLSDValue::Array(vec![
+            LLSDValue::Map(avatar.clone()),
+            LLSDValue::Map(other),
+            LLSDValue::Map(HashMap::new()),
+        ]);
+        assert_eq!(xml_round_trip(&array_of_maps), array_of_maps);
+
+        // Map of arrays
+        let mut map_of_arrays = HashMap::new();
+        map_of_arrays.insert("ids".to_string(), LLSDValue::Array(vec![
+            LLSDValue::Integer(1),
+            LLSDValue::Integer(2),
+        ]));
+        map_of_arrays.insert("empty".to_string(), LLSDValue::Array(Vec::new()));
+        map_of_arrays.insert("avatars".to_string(), LLSDValue::Array(vec![LLSDValue::Map(avatar)]));
+        let map_of_arrays = LLSDValue::Map(map_of_arrays);
+        assert_eq!(xml_round_trip(&map_of_arrays), map_of_arrays);
+
+        // Arrays of arrays, three levels deep
+        let deep = LLSDValue::Array(vec![
+            LLSDValue::Array(vec![
+                LLSDValue::Array(vec![LLSDValue::Integer(1), LLSDValue::Undefined]),
+                LLSDValue::Array(Vec::new()),
+            ]),
+            LLSDValue::Array(vec![
+                LLSDValue::Array(vec![LLSDValue::String("leaf".to_string())]),
+            ]),
+            LLSDValue::Integer(3),
+        ]);
+        assert_eq!(xml_round_trip(&deep), deep);
+    }
+
+    #[test]
+    fn test_format_detection() {
+        let doc = LLSDFactory::create(LLSDValue::Integer(42));
+
+        // Serialized output of each codec
+        let xml = LLSDFactory::serialize_xml(&doc, true).unwrap();
+        assert_eq!(LLSDFormat::detect(xml.as_bytes()), Some(LLSDFormat::XML));
+        let json = LLSDFactory::serialize_json(&doc, true).unwrap();
+        assert_eq!(LLSDFormat::detect(json.as_bytes()), Some(LLSDFormat::JSON));
+        let binary = LLSDFactory::serialize_binary(&doc).unwrap();
+        assert_eq!(LLSDFormat::detect(&binary), Some(LLSDFormat::Binary));
+
+        // BOM and leading whitespace are skipped
+        assert_eq!(LLSDFormat::detect(b"\xEF\xBB\xBF  \n<llsd><undef /></llsd>"), Some(LLSDFormat::XML));
+        assert_eq!(LLSDFormat::detect(b"\xEF\xBB\xBF{\"a\": 1}"), Some(LLSDFormat::JSON));
+
+        // Header markers
+        assert_eq!(LLSDFormat::detect(b"<? LLSD/Binary ?>\n\0\0"), Some(LLSDFormat::Binary));
+        assert_eq!(LLSDFormat::detect(b"<?llsd/notation?>\n{}"), Some(LLSDFormat::Notation));
+        assert_eq!(LLSDFormat::detect(b"<?xml version=\"1.0\"?><llsd/>"), Some(LLSDFormat::XML));
+
+        // Notation values that are not JSON
+        assert_eq!(LLSDFormat::detect(b"{'name':'Alice','age':i30}"), Some(LLSDFormat::Notation));
+        assert_eq!(LLSDFormat::detect(b"[i1,r2.5,!]"), Some(LLSDFormat::Notation));
+        assert_eq!(LLSDFormat::detect(b"u550e8400-e29b-41d4-a716-446655440000"), Some(LLSDFormat::Notation));
+
+        // JSON is sniffed, not parsed: truncated and large payloads
+        assert_eq!(LLSDFormat::detect(b"[1, 2"), Some(LLSDFormat::JSON));
+        assert_eq!(LLSDFormat::detect(b"{\"name\": \"Al"), Some(LLSDFormat::JSON));
+        let large = format!("[{}", "{\"flag\": false, \"note\": \"caf\u{e9}\"},".repeat(1000));
+        assert_eq!(LLSDFormat::detect(large.as_bytes()), Some(LLSDFormat::JSON));
+
+        // Undetectable input
+        assert_eq!(LLSDFormat::detect(b""), None);
+        assert_eq!(LLSDFormat::detect(b" \t\n"), None);
+        assert_eq!(LLSDFormat::detect(b"hello world"), None);
+        assert_eq!(LLSDFormat::detect(&[0xFF, 0x00, 0x13, 0x37]), None);
+        assert_eq!(LLSDFormat::detect(b"<?llsd/yaml?>"), None);
+    }
+
+    #[test]
+    fn test_duration_round_trip() {
+        use std::time::Duration;
+
+        for duration in [
+            Duration::from_millis(16),
+            Duration::from_micros(250),
+            Duration::from_secs(5 * 3600 + 42 * 60 + 7),
+            Duration::ZERO,
+        ] {
+            let value = LLSDUtils::duration_to_llsd(duration);
+            assert_eq!(value, LLSDValue::Real(duration.as_secs_f64()));
+
+            let doc = LLSDFactory::create(value);
+            let binary = LLSDFactory::serialize_binary(&doc).unwrap();
+            let parsed = LLSDFactory::parse_binary(&binary).unwrap();
+            assert_eq!(LLSDUtils::llsd_to_duration(parsed.content()), Some(duration));
+
+            let json = LLSDFactory::serialize_json(&doc, false).unwrap();
+            let parsed = LLSDFactory::parse_json(&json).unwrap();
+            assert_eq!(LLSDUtils::llsd_to_duration(parsed.content()), Some(duration));
+        }
+
+        assert_eq!(LLSDUtils::llsd_to_duration(&LLSDValue::Integer(7200)), Some(Duration::from_secs(7200)));
+        assert_eq!(LLSDUtils::llsd_to_duration(&LLSDValue::Integer(-1)), None);
+        assert_eq!(LLSDUtils::llsd_to_duration(&LLSDValue::Real(f64::NAN)), None);
+        assert_eq!(LLSDUtils::llsd_to_duration(&LLSDValue::String("60".to_string())), None);
+    }
+
+    #[test]
+    fn test_integer64_round_trip() {
+        for value in [i64::MAX, i64::MIN, i32::MAX as i64 + 1] {
+            let original = LLSDValue::Integer64(value);
+            assert_eq!(original.get_type(), LLSDType::Integer64);
+            let doc = LLSDFactory::create(original.clone());
+
+            let binary = LLSDFactory::serialize_binary(&doc).unwrap();
+            assert_eq!(*LLSDFactory::parse_binary(&binary).unwrap().content(), original);
+
+            let xml = LLSDFactory::serialize_xml(&doc, false).unwrap();
+            assert_eq!(*LLSDFactory::parse_xml(&xml).unwrap().content(), original);
+
+            let json = LLSDFactory::serialize_json(&doc, false).unwrap();
+            assert_eq!(*LLSDFactory::parse_json(&json).unwrap().content(), original);
+        }
+
+        // JSON numbers beyond 32 bits stay exact instead of widening to Real
+        let parsed = LLSDFactory::parse_json("{\"balance\": 9007199254740993}").unwrap();
+        assert_eq!(parsed.content().get_path("balance"), Some(&LLSDValue::Integer64(9007199254740993)));
+
+        // Narrowing and widening getters
+        assert_eq!(LLSDValue::Integer64(42).as_integer(), Some(42));
+        assert_eq!(LLSDValue::Integer64(i64::MAX).as_integer(), None);
+        assert_eq!(LLSDValue::Integer(-7).as_integer64(), Some(-7));
+        assert_eq!(LLSDValue::Integer64(1 << 40).as_real(), Some((1u64 << 40) as f64));
+        assert_eq!(LLSDValue::from_i64(5), LLSDValue::Integer(5));
+        assert_eq!(LLSDValue::from_i64(1 << 40), LLSDValue::Integer64(1 << 40));
+    }
+
+    #[test]
+    fn test_parse_auto() {
+        let mut map = HashMap::new();
+        map.insert("name".to_string(), LLSDValue::String("Alice".to_string()));
+        map.insert("scores".to_string(), LLSDValue::Array(vec![LLSDValue::Integer(1), LLSDValue::Integer(2)]));
+        let doc = LLSDFactory::create(LLSDValue::Map(map));
+
+        let xml = LLSDFactory::serialize_xml(&doc, true).unwrap();
+        assert_eq!(LLSDFactory::parse_auto(xml.as_bytes()).unwrap(), (LLSDFormat::XML, doc.clone()));
+        let padded = format!("\n  {}", &xml[xml.find("<llsd").unwrap()..]);
+        assert_eq!(LLSDFactory::parse_auto(padded.as_bytes()).unwrap(), (LLSDFormat::XML, doc.clone()));
+
+        let json = LLSDFactory::serialize_json(&doc, false).unwrap();
+        assert_eq!(LLSDFactory::parse_auto(json.as_bytes()).unwrap(), (LLSDFormat::JSON, doc.clone()));
+        assert_eq!(
+            LLSDFactory::parse_auto(b"42").unwrap(),
+            (LLSDFormat::JSON, LLSDFactory::create(LLSDValue::Integer(42)))
+        );
+
+        let binary = LLSDFactory::serialize_binary(&doc).unwrap();
+        assert_eq!(LLSDFactory::parse_auto(&binary).unwrap(), (LLSDFormat::Binary, doc.clone()));
+
+        // Viewer-style binary: text header, then values without the magic
+        let mut headed = b"<? LLSD/Binary ?>\n".to_vec();
+        headed.extend_from_slice(&LLSDBinarySerializer::new().without_magic().serialize(&doc).unwrap());
+        assert_eq!(LLSDFactory::parse_auto(&headed).unwrap(), (LLSDFormat::Binary, doc.clone()));
+
+        // Ambiguity: both start with '[', but only the first is valid JSON
+        let (format, parsed) = LLSDFactory::parse_auto(b"[1, 2]").unwrap();
+        assert_eq!(format, LLSDFormat::JSON);
+        assert_eq!(parsed.content().as_array().map(|a| a.len()), Some(2));
+        let (format, parsed) = LLSDFactory::parse_auto(b"[i1, i2]").unwrap();
+        assert_eq!(format, LLSDFormat::Notation);
+        assert_eq!(parsed.content().get_path("1"), Some(&LLSDValue::Integer(2)));
+
+        let notation = LLSDFactory::serialize_notation(&doc, false).unwrap();
+        assert_eq!(LLSDFactory::parse_auto(notation.as_bytes()).unwrap(), (LLSDFormat::Notation, doc.clone()));
+        let headed = format!("<?llsd/notation?>\n{}", notation);
+        let (format, parsed) = LLSDFactory::parse_auto(headed.as_bytes()).unwrap();
+        assert_eq!((format, &parsed), (LLSDFormat::Notation, &doc));
+        assert_eq!(parsed.metadata()[METADATA_SOURCE_FORMAT], LLSDValue::String("notation".to_string()));
+        assert_eq!(parsed.metadata()[METADATA_BYTE_LENGTH], LLSDValue::from_i64(headed.len() as i64));
+
+        // Undetectable input
+        assert!(matches!(LLSDFactory::parse_auto(b""), Err(LLSDError::EmptyInput)));
+        assert!(matches!(LLSDFactory::parse_auto(b"  \n "), Err(LLSDError::EmptyInput)));
+        assert!(matches!(LLSDFactory::parse_auto(b"hello world"), Err(LLSDError::CustomError { .. })));
+        assert!(LLSDFactory::parse_auto(&[0x00, 0xFF, 0x13, 0x37]).is_err());
+    }
+
+    #[test]
+    fn test_content_types() {
+        for format in [LLSDFormat::XML, LLSDFormat::JSON, LLSDFormat::Binary, LLSDFormat::Notation] {
+            assert_eq!(LLSDFormat::from_content_type(format.content_type()), Some(format));
+        }
+        assert_eq!(LLSDFormat::XML.content_type(), "application/llsd+xml");
+        assert_eq!(LLSDFormat::Binary.content_type(), "application/llsd+binary");
+        assert_eq!(
+            LLSDFormat::from_content_type("application/llsd+json; charset=utf-8"),
+            Some(LLSDFormat::JSON)
+        );
+        assert_eq!(LLSDFormat::from_content_type(" Application/LLSD+XML ;charset=UTF-8"), Some(LLSDFormat::XML));
+        assert_eq!(LLSDFormat::from_content_type("application/xml"), None);
+        assert_eq!(LLSDFormat::from_content_type(""), None);
+
+        let doc = LLSDFactory::create(LLSDValue::Array(vec![LLSDValue::Integer(7), LLSDValue::String("seven".to_string())]));
+
+        let xml = LLSDFactory::serialize_xml(&doc, false).unwrap();
+        let parsed = LLSDFactory::parse_with_content_type("application/llsd+xml; charset=utf-8", xml.as_bytes()).unwrap();
+        assert_eq!(parsed, doc);
+
+        let json = LLSDFactory::serialize_json(&doc, false).unwrap();
+        assert_eq!(LLSDFactory::parse_with_content_type("application/llsd+json", json.as_bytes()).unwrap(), doc);
+
+        let binary = LLSDFactory::serialize_binary(&doc).unwrap();
+        assert_eq!(LLSDFactory::parse_with_content_type("application/llsd+binary", &binary).unwrap(), doc);
+
+        let notation = LLSDFactory::serialize_notation(&doc, false).unwrap();
+        assert_eq!(LLSDFactory::parse_with_content_type("application/llsd+notation", notation.as_bytes()).unwrap(), doc);
+
+        // The header is trusted over the payload
+        assert!(LLSDFactory::parse_with_content_type("application/llsd+json", &binary).is_err());
+        assert!(LLSDFactory::parse_with_content_type("text/html", xml.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_xml_stream_parser() {
+        let mut map = HashMap::new();
+        map.insert("name".to_string(), LLSDValue::String("Alice Resident".to_string()));
+        map.insert("id".to_string(), LLSDValue::UUID(uuid!("550e8400-e29b-41d4-a716-446655440000")));
+        map.insert("texture".to_string(), LLSDValue::Binary(vec![0x48, 0x65, 0x6C, 0x6C, 0x6F]));
+        map.insert("nothing".to_string(), LLSDValue::Undefined);
+        map.insert("scores".to_string(), LLSDValue::Array(vec![
+            LLSDValue::Integer(95),
+            LLSDValue::Real(87.5),
+            LLSDValue::Array(Vec::new()),
+            LLSDValue::Map(HashMap::new()),
+        ]));
+        let doc = LLSDFactory::create(LLSDValue::Map(map));
+        let xml = LLSDFactory::serialize_xml(&doc, true).unwrap();
+        let bytes = xml.as_bytes();
+
+        // Every possible split point, which covers mid-tag and mid-text
+        for split in 0..=bytes.len() {
+            let mut parser = LLSDXmlStreamParser::new();
+            parser.feed(&bytes[..split]);
+            parser.feed(&bytes[split..]);
+            assert!(parser.is_complete(), "split at {}", split);
+            assert_eq!(parser.finish().unwrap(), doc, "split at {}", split);
+        }
+
+        // One byte at a time, with a comment and CDATA section
+        let xml = "<?xml version=\"1.0\"?><!-- cap response --><llsd><map>\
+                   <key>a</key><string><![CDATA[x < y]]></string>\
+                   <key>b</key><undef><ignored /></undef>\
+                   <key>c</key><integer>5</integer></map></llsd>";
+        let mut parser = LLSDXmlStreamParser::new();
+        for byte in xml.as_bytes() {
+            assert!(!parser.is_complete());
+            parser.feed(std::slice::from_ref(byte));
+        }
+        let parsed = parser.finish().unwrap();
+        assert_eq!(parsed.content().get_path("a"), Some(&LLSDValue::String("x < y".to_string())));
+        assert_eq!(parsed.content().get_path("b"), Some(&LLSDValue::Undefined));
+        assert_eq!(parsed.content().get_path("c"), Some(&LLSDValue::Integer(5)));
+
+        // Incomplete, empty and malformed input
+        let mut parser = LLSDXmlStreamParser::new();
+        parser.feed(b"<llsd><array><integer>1</inte");
+        assert!(!parser.is_complete());
+        assert!(matches!(parser.finish(), Err(LLSDError::UnexpectedEndOfData)));
+
+        let mut parser = LLSDXmlStreamParser::new();
+        parser.feed(b"  \n");
+        assert!(matches!(parser.finish(), Err(LLSDError::EmptyInput)));
+
+        let mut parser = LLSDXmlStreamParser::new();
+        parser.feed(b"<llsd><array><integer>1</string></array></llsd>");
+        assert!(parser.finish().is_err());
+    }
+
+    #[test]
+    fn test_to_log_json() {
+        let mut map = HashMap::new();
+        map.insert("agent_id".to_string(), LLSDValue::UUID(uuid!("550e8400-e29b-41d4-a716-446655440000")));
+        map.insert("texture".to_string(), LLSDValue::Binary(vec![0xAB; 4096]));
+        map.insert("name".to_string(), LLSDValue::String("Alice".to_string()));
+        map.insert("velocity".to_string(), LLSDValue::Real(f64::INFINITY));
+        map.insert("history".to_string(), LLSDValue::Array((0..200).map(LLSDValue::Integer).collect()));
+        let value = LLSDValue::Map(map);
+
+        let full = LLSDUtils::to_log_json(&value, usize::MAX);
+        let json: serde_json::Value = serde_json::from_str(&full).unwrap();
+        assert_eq!(json["texture"], "<4096 bytes>");
+        assert_eq!(json["agent_id"], "550e8400-****-****-****-********0000");
+        assert_eq!(json["name"], "Alice");
+        assert_eq!(json["velocity"], "inf");
+        assert!(!full.contains("446655440000"));
+        assert!(full.contains('\n'));
+
+        for max_len in [0, 5, 14, 100, 500] {
+            let bounded = LLSDUtils::to_log_json(&value, max_len);
+            assert!(bounded.len() <= max_len, "{} > {}", bounded.len(), max_len);
+            if max_len >= 14 {
+                assert!(bounded.ends_with("...[truncated]"));
+            }
+        }
+
+        // Multi-byte characters are never split
+        let unicode = LLSDValue::String("你好世界".repeat(50));
+        for max_len in 20..40 {
+            let bounded = LLSDUtils::to_log_json(&unicode, max_len);
+            assert!(bounded.len() <= max_len);
+        }
+    }
+
+    #[test]
+    fn test_xml_sorted_keys() {
+        let keys: Vec<String> = (0..64).map(|i| format!("key_{:02}", (i * 37) % 64)).collect();
+        let build = |order: &mut dyn Iterator<Item = &String>| {
+            let mut inner = HashMap::new();
+            let mut outer = HashMap::with_capacity(1);
+            for key in order {
+                inner.insert(key.clone(), LLSDValue::String(key.to_uppercase()));
+                outer.insert(key.clone(), LLSDValue::Integer(key.len() as i32));
+            }
+            outer.insert("nested".to_string(), LLSDValue::Map(inner));
+            LLSDDocument::new(LLSDValue::Map(outer))
+        };
+        let forward = build(&mut keys.iter());
+        let backward = build(&mut keys.iter().rev());
+
+        for pretty in [false, true] {
+            let serializer = LLSDXmlSerializer::new().with_pretty_print(pretty).with_sorted_keys(true);
+            let a = serializer.serialize(&forward).unwrap();
+            let b = serializer.serialize(&backward).unwrap();
+            assert_eq!(a, b);
+            assert!(a.find("<key>key_00</key>").unwrap() < a.find("<key>key_01</key>").unwrap());
+            assert!(a.find("<key>key_63</key>").unwrap() < a.find("<key>nested</key>").unwrap());
+            assert_eq!(LLSDFactory::parse_xml(&a).unwrap(), forward);
+        }
+    }
+
+    #[test]
+    fn test_map_keys_consistent_across_formats() {
+        let keys = ["名前", "a<b&c", "&amp;", "\"quoted\" 'key'", "</key>", "tab\there", "🎉", ""];
+        let mut map = HashMap::new();
+        for (i, key) in keys.iter().enumerate() {
+            map.insert(key.to_string(), LLSDValue::Integer(i as i32));
+        }
+        let doc = LLSDDocument::new(LLSDValue::Map(map));
+
+        let xml = LLSDFactory::serialize_xml(&doc, true).unwrap();
+        let json = LLSDFactory::serialize_json(&doc, false).unwrap();
+        let binary = LLSDFactory::serialize_binary(&doc).unwrap();
+
+        let from_xml = LLSDFactory::parse_xml(&xml).unwrap();
+        let from_json = LLSDFactory::parse_json(&json).unwrap();
+        let from_binary = LLSDFactory::parse_binary(&binary).unwrap();
+
+        assert_eq!(from_xml, doc);
+        assert_eq!(from_json, doc);
+        assert_eq!(from_binary, doc);
+        assert!(xml.contains("<key>a&lt;b&amp;c</key>"));
+        assert!(xml.contains("<key>&amp;amp;</key>"));
+    }
+
+    #[test]
+    fn test_xml_preserve_date_offset() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<llsd><map>
+<key>login</key><date>2024-03-01T18:30:00+09:00</date>
+<key>history</key><array><date>2024-03-01T09:30:00Z</date><date>2024-02-29T20:00:00-05:00</date></array>
+</map></llsd>"#;
+
+        // Default: every date comes back as UTC
+        let plain = LLSDFactory::parse_xml(xml).unwrap();
+        assert!(plain.date_offsets().is_empty());
+        let utc = LLSDFactory::serialize_xml(&plain, false).unwrap();
+        assert!(utc.contains("<date>2024-03-01T09:30:00+00:00</date>"));
+        assert!(!utc.contains("+09:00"));
+
+        let parser = LLSDXmlParser::new().with_preserve_offset(true);
+        let doc = parser.parse(xml).unwrap();
+        assert_eq!(doc.content(), plain.content());
+        assert_eq!(doc.date_offset("login"), chrono::FixedOffset::east_opt(9 * 3600));
+        assert_eq!(doc.date_offset("history.1"), chrono::FixedOffset::west_opt(5 * 3600));
+        assert_eq!(
+            doc.date_with_offset("login").unwrap().to_rfc3339(),
+            "2024-03-01T18:30:00+09:00"
+        );
+
+        let written = LLSDFactory::serialize_xml(&doc, false).unwrap();
+        assert!(written.contains("<date>2024-03-01T18:30:00+09:00</date>"));
+        assert!(written.contains("<date>2024-02-29T20:00:00-05:00</date>"));
+        assert_eq!(parser.parse(&written).unwrap(), doc);
+
+        // A root date uses the empty path
+        let root = parser.parse("<llsd><date>2024-01-01T00:00:00+09:00</date></llsd>").unwrap();
+        assert!(LLSDFactory::serialize_xml(&root, false).unwrap().contains("+09:00"));
+
+        // Offsets are dropped once the content can be edited in place
+        let mut edited = doc.clone();
+        let map = edited.content_mut().as_map_mut().unwrap();
+        map.get_mut("history").unwrap().as_array_mut().unwrap().insert(0, LLSDValue::Undefined);
+        assert!(edited.date_offsets().is_empty());
+        assert!(!LLSDFactory::serialize_xml(&edited, false).unwrap().contains("-05:00"));
+    }
+
+    #[test]
+    fn test_xml_real_formats() {
+        assert_eq!(RealFormat::Grisu17.format(0.1), "0.10000000000000001");
+        assert_eq!(RealFormat::Grisu17.format(0.1 + 0.2), "0.30000000000000004");
+        assert_eq!(RealFormat::Grisu17.format(1.0), "1");
+        assert_eq!(RealFormat::Grisu17.format(-2.5), "-2.5");
+        assert_eq!(RealFormat::Grisu17.format(0.0001), "0.0001");
+        assert_eq!(RealFormat::Grisu17.format(1e300), "1.0000000000000001e+300");
+        assert_eq!(RealFormat::Grisu17.format(5e-324), "4.9406564584124654e-324");
+        assert_eq!(RealFormat::Fixed(3).format(3.14159), "3.142");
+        assert_eq!(RealFormat::Shortest.format(0.1), "0.1");
+        for format in [RealFormat::Shortest, RealFormat::Fixed(2), RealFormat::Grisu17] {
+            assert_eq!(format.format(f64::NAN), "nan");
+            assert_eq!(format.format(f64::INFINITY), "inf");
+            assert_eq!(format.format(f64::NEG_INFINITY), "-inf");
+        }
+
+        let values = [
+            0.1 + 0.2,
+            1.0 / 3.0,
+            -1234.5678,
+            f64::MIN_POSITIVE,
+            f64::MIN_POSITIVE / 3.0,
+            5e-324,
+            f64::MAX,
+            -0.0,
+            1e17,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+        ];
+        let doc = LLSDDocument::new(LLSDValue::Array(values.iter().map(|r| LLSDValue::Real(*r)).collect()));
+        for format in [RealFormat::Shortest, RealFormat::Grisu17] {
+            let xml = LLSDXmlSerializer::new().with_real_precision(format).serialize(&doc).unwrap();
+            assert_eq!(LLSDFactory::parse_xml(&xml).unwrap(), doc, "{:?}", format);
+        }
+
+        let nan = LLSDDocument::new(LLSDValue::Real(f64::NAN));
+        let xml = LLSDXmlSerializer::new().with_real_precision(RealFormat::Grisu17).serialize(&nan).unwrap();
+        assert!(xml.contains("<real>nan</real>"));
+        assert!(LLSDFactory::parse_xml(&xml).unwrap().content().as_real().unwrap().is_nan());
+
+        let fixed = LLSDXmlSerializer::new().with_real_precision(RealFormat::Fixed(3)).serialize(&doc).unwrap();
+        let parsed = LLSDFactory::parse_xml(&fixed).unwrap();
+        assert_eq!(parsed.content().get_path("2"), Some(&LLSDValue::Real(-1234.568)));
+    }
+
+    #[test]
+    fn test_matches_template() {
+        let message = LLSDFactory::parse_json(r#"{
+            "command": "get_avatar_data",
+            "agent_id": "550e8400-e29b-41d4-a716-446655440000",
+            "params": {"fields": ["name", "title"], "verbose": true}
+        }"#).unwrap();
+        let message = message.content();
+
+        let template = |json: &str| LLSDFactory::parse_json(json).unwrap().content().clone();
+
+        assert!(LLSDUtils::matches_template(message, &template(r#"{"command":"get_avatar_data"}"#)));
+        assert!(!LLSDUtils::matches_template(message, &template(r#"{"command":"set_avatar_data"}"#)));
+        assert!(!LLSDUtils::matches_template(message, &template(r#"{"missing":"get_avatar_data"}"#)));
+        assert!(LLSDUtils::matches_template(message, &template("{}")));
+
+        // Nested maps and arrays are matched as subsets
+        assert!(LLSDUtils::matches_template(message, &template(r#"{"params":{"verbose":true}}"#)));
+        assert!(LLSDUtils::matches_template(message, &template(r#"{"params":{"fields":["name"]}}"#)));
+        assert!(!LLSDUtils::matches_template(message, &template(r#"{"params":{"fields":["title"]}}"#)));
+        assert!(!LLSDUtils::matches_template(message, &template(r#"{"params":{"fields":["name","title","x"]}}"#)));
+
+        // Types must agree
+        assert!(!LLSDUtils::matches_template(message, &template(r#"{"params":[]}"#)));
+        assert!(!LLSDUtils::matches_template(&LLSDValue::Integer(1), &template(r#"{"command":"x"}"#)));
+    }
+
+    #[test]
+    fn test_type_names() {
+        let cases = [
+            (LLSDValue::Undefined, "undefined"),
+            (LLSDValue::Boolean(true), "boolean"),
+            (LLSDValue::Integer(1), "integer"),
+            (LLSDValue::Integer64(i64::MAX), "integer"),
+            (LLSDValue::Real(1.5), "real"),
+            (LLSDValue::String("s".to_string()), "string"),
+            (LLSDValue::UUID(uuid!("550e8400-e29b-41d4-a716-446655440000")), "uuid"),
+            (LLSDValue::Date(Utc.timestamp_opt(0, 0).unwrap()), "date"),
+            (LLSDValue::URI("https://example.com".to_string()), "uri"),
+            (LLSDValue::Binary(vec![1]), "binary"),
+            (LLSDValue::Map(HashMap::new()), "map"),
+            (LLSDValue::Array(Vec::new()), "array"),
+        ];
+        for (value, name) in &cases {
+            assert_eq!(value.type_name(), *name);
+        }
+    }
+
+    #[test]
+    fn test_from_kv() {
+        let pairs = [
+            ("RENDER.SHADOWS", "true"),
+            ("RENDER.DISTANCE", "256"),
+            ("RENDER.GAMMA", "2.2"),
+            ("RENDER.QUALITY.WATER", "FALSE"),
+            ("AGENT_ID", "550e8400-e29b-41d4-a716-446655440000"),
+            ("REGION_HANDLE", "1099511628032000"),
+            ("GRID", "agni"),
+            ("CHECKSUM", "d41d8cd98f00b204e9800998ecf8427e"),
+            ("LIMIT", "inf"),
+        ];
+        let value = LLSDUtils::from_kv(pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())));
+
+        assert_eq!(value.get_path("RENDER.SHADOWS"), Some(&LLSDValue::Boolean(true)));
+        assert_eq!(value.get_path("RENDER.DISTANCE"), Some(&LLSDValue::Integer(256)));
+        assert_eq!(value.get_path("RENDER.GAMMA"), Some(&LLSDValue::Real(2.2)));
+        assert_eq!(value.get_path("RENDER.QUALITY.WATER"), Some(&LLSDValue::Boolean(false)));
+        assert_eq!(
+            value.get_path("AGENT_ID"),
+            Some(&LLSDValue::UUID(uuid!("550e8400-e29b-41d4-a716-446655440000")))
+        );
+        assert_eq!(value.get_path("REGION_HANDLE"), Some(&LLSDValue::Integer64(1_099_511_628_032_000)));
+        assert_eq!(value.get_path("GRID"), Some(&LLSDValue::String("agni".to_string())));
+        assert_eq!(
+            value.get_path("CHECKSUM"),
+            Some(&LLSDValue::String("d41d8cd98f00b204e9800998ecf8427e".to_string()))
+        );
+        assert_eq!(value.get_path("LIMIT"), Some(&LLSDValue::String("inf".to_string())));
+        assert_eq!(value.get_path("RENDER").and_then(|v| v.as_map()).map(|m| m.len()), Some(4));
+
+        // Later keys win, nesting under an earlier scalar if they must
+        let value = LLSDUtils::from_kv(vec![
+            ("A".to_string(), "1".to_string()),
+            ("A.B".to_string(), "2".to_string()),
+            ("C".to_string(), "x".to_string()),
+            ("C".to_string(), "y".to_string()),
+        ]);
+        assert_eq!(value.get_path("A.B"), Some(&LLSDValue::Integer(2)));
+        assert_eq!(value.get_path("C"), Some(&LLSDValue::String("y".to_string())));
+    }
+
+    #[test]
+    fn test_structural_diff() {
+        let left = LLSDFactory::parse_json(r#"{
+            "agent": {"name": "Alice", "position": [128.0, 128.0, 25.0], "groups": ["Builders"]},
+            "region": "Ahern",
+            "stats": {"fps": 60}
+        }"#).unwrap();
+        let mut right = left.clone();
+        assert!(right.content_mut().set_path("agent.position.2", LLSDValue::Real(26.0)));
+
+        assert!(LLSDUtils::diff(left.content(), left.content()).is_empty());
+        assert_eq!(
+            LLSDUtils::diff(left.content(), right.content()),
+            vec![LLSDDiff { path: "agent.position.2".to_string(), reason: DiffReason::ValueMismatch }]
+        );
+
+        // Each kind of difference, reported at its own path in key order
+        assert!(right.content_mut().set_path("stats.fps", LLSDValue::String("60".to_string())));
+        assert!(right.content_mut().set_path("agent.groups", LLSDValue::Array(Vec::new())));
+        let map = right.content_mut().as_map_mut().unwrap();
+        map.remove("region");
+        map.insert("zone".to_string(), LLSDValue::Integer(1));
+        let diffs = LLSDUtils::diff(left.content(), right.content());
+        let summary: Vec<String> = diffs.iter().map(|d| d.to_string()).collect();
+        assert_eq!(summary, vec![
+            "agent.groups.0: MissingInRight",
+            "agent.position.2: ValueMismatch",
+            "region: MissingInRight",
+            "stats.fps: TypeMismatch",
+            "zone: MissingInLeft",
+        ]);
+
+        // Root scalars and NaN
+        assert_eq!(LLSDUtils::diff(&LLSDValue::Integer(1), &LLSDValue::Integer(2))[0].path, "");
+        assert!(LLSDUtils::diff(&LLSDValue::Real(f64::NAN), &LLSDValue::Real(f64::NAN)).is_empty());
+    }
+
+    #[test]
+    fn test_get_str_borrows() {
+        let mut item = HashMap::new();
+        item.insert("name".to_string(), LLSDValue::String("Blue Hat".to_string()));
+        item.insert("icon".to_string(), LLSDValue::URI("http://example.com/hat.png".to_string()));
+        item.insert("count".to_string(), LLSDValue::Integer(3));
+        let mut root = HashMap::new();
+        root.insert("item".to_string(), LLSDValue::Map(item));
+        let root = LLSDValue::Map(root);
+
+        let name = LLSDUtils::get_str(&root, "item.name").unwrap();
+        assert_eq!(name, "Blue Hat");
+        // The slice points at the string stored in the tree, not a copy
+        match root.get_path("item.name") {
+            Some(LLSDValue::String(stored)) => assert!(std::ptr::eq(name.as_ptr(), stored.as_ptr())),
+            other => panic!("Expected string, got {:?}", other),
+        }
+
+        assert_eq!(LLSDUtils::get_str(&root, "item.icon"), Some("http://example.com/hat.png"));
+        assert_eq!(LLSDUtils::get_str(&root, "item.count"), None);
+        assert_eq!(LLSDUtils::get_str(&root, "item.missing"), None);
+    }
+
+    #[test]
+    fn test_type_histogram_and_key_frequency() {
+        let item = |name: &str, count: i32| {
+            let mut map = HashMap::new();
+            map.insert("name".to_string(), LLSDValue::String(name.to_string()));
+            map.insert("count".to_string(), LLSDValue::Integer(count));
+            map.insert("id".to_string(), LLSDValue::UUID(uuid!("550e8400-e29b-41d4-a716-446655440000")));
+            LLSDValue::Map(map)
+        };
+        let mut folder = HashMap::new();
+        folder.insert("name".to_string(), LLSDValue::String("Hats".to_string()));
+        folder.insert("items".to_string(), LLSDValue::Array(vec![item("Blue", 1), item("Red", 2)]));
+        folder.insert("weight".to_string(), LLSDValue::Real(1.5));
+        let mut root = HashMap::new();
+        root.insert("folder".to_string(), LLSDValue::Map(folder));
+        root.insert("version".to_string(), LLSDValue::Integer64(1 << 40));
+        let root = LLSDValue::Map(root);
+
+        let histogram = LLSDUtils::type_histogram(&root);
+        assert_eq!(histogram[&LLSDType::Map], 4);
+        assert_eq!(histogram[&LLSDType::Array], 1);
+        assert_eq!(histogram[&LLSDType::String], 3);
+        assert_eq!(histogram[&LLSDType::Integer], 2);
+        assert_eq!(histogram[&LLSDType::Integer64], 1);
+        assert_eq!(histogram[&LLSDType::UUID], 2);
+        assert_eq!(histogram[&LLSDType::Real], 1);
+        assert!(!histogram.contains_key(&LLSDType::Boolean));
+        assert_eq!(histogram.values().sum::<usize>(), LLSDUtils::count_elements(&root));
+
+        let keys = LLSDUtils::key_frequency(&root);
+        assert_eq!(keys["name"], 3);
+        assert_eq!(keys["count"], 2);
+        assert_eq!(keys["id"], 2);
+        assert_eq!(keys["folder"], 1);
+        assert_eq!(keys.len(), 7);
+
+        assert!(LLSDUtils::key_frequency(&LLSDValue::Integer(1)).is_empty());
+        assert_eq!(LLSDUtils::type_histogram(&LLSDValue::Undefined)[&LLSDType::Unknown], 1);
+    }
+
+    #[test]
+    fn test_xml_lenient_maps() {
+        let commented = r#"<?xml version="1.0" encoding="UTF-8"?>
+<llsd><map>
+    <key>name</key> <!-- written by an old exporter -->
+    <string>Blue Hat</string>
+    <!-- count follows -->
+    <key>count</key><!-- --><integer>3</integer>
+</map></llsd>"#;
+        let parser = LLSDXmlParser::new();
+        let (document, warnings) = parser.parse_lenient(commented).unwrap();
+        assert_eq!(LLSDUtils::get_string(document.content(), "name", ""), "Blue Hat");
+        assert_eq!(LLSDUtils::get_integer(document.content(), "count", 0), 3);
+        assert!(warnings.is_empty());
+
+        let duplicate = r#"<llsd><map>
+    <key>stale</key><key>fresh</key><integer>1</integer>
+    <real>2.5</real><key>late</key>
+</map></llsd>"#;
+        assert!(parser.parse(duplicate).is_err());
+
+        let (document, warnings) = parser.parse_lenient(duplicate).unwrap();
+        let LLSDValue::Map(map) = document.content() else {
+            panic!("Expected map");
+        };
+        assert_eq!(map.len(), 2);
+        assert_eq!(map["fresh"], LLSDValue::Integer(1));
+        assert_eq!(map["late"], LLSDValue::Real(2.5));
+        assert!(!map.contains_key("stale"));
+
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].contains("'stale' replaced by 'fresh'"));
+        assert!(warnings[1].contains("'late' follows its value"));
+
+        // Configured as lenient, parse repairs the same maps silently
+        let lenient = LLSDXmlParser::new().with_lenient_maps(true);
+        assert_eq!(lenient.parse(duplicate).unwrap(), document);
+    }
+
+    #[test]
+    fn test_xml_parse_budget() {
+        let undefs = format!("<llsd><array>{}</array></llsd>", "<undef/>".repeat(20_000));
+        let parser = LLSDXmlParser::new()
+            .with_parse_budget(ParseBudget { max_bytes: Some(64 * 1024), max_duration: None });
+        assert!(matches!(parser.parse(&undefs), Err(LLSDError::ParseAborted { .. })));
+
+        let long = format!("<llsd><string>{}</string></llsd>", "x".repeat(100_000));
+        assert!(matches!(parser.parse(&long), Err(LLSDError::ParseAborted { .. })));
+
+        let parser = LLSDXmlParser::new().with_parse_budget(ParseBudget {
+            max_bytes: Some(64 * 1024),
+            max_duration: Some(std::time::Duration::from_secs(30)),
+        });
+        let doc = parser
+            .parse("<llsd><map><key>tags</key><array><string>a</string><string>b</string></array></map></llsd>")
+            .unwrap();
+        assert_eq!(doc.content().get_path("tags.1"), Some(&LLSDValue::String("b".to_string())));
+    }
+
+    #[test]
+    fn test_factory_binary_budget() {
+        let document = LLSDDocument::new(LLSDValue::Array(vec![LLSDValue::String("x".repeat(1000)); 100]));
+        let data = LLSDFactory::serialize_binary(&document).unwrap();
+        let tight = ParseBudget { max_bytes: Some(10_000), max_duration: None };
+        let roomy = ParseBudget {
+            max_bytes: Some(1 << 20),
+            max_duration: Some(std::time::Duration::from_secs(30)),
+        };
+
+        assert!(matches!(
+            LLSDFactory::parse_binary_with_budget(&data, tight),
+            Err(LLSDError::ParseAborted { .. })
+        ));
+        assert!(matches!(
+            LLSDFactory::parse_binary_reader_with_budget(&data[..], tight),
+            Err(LLSDError::ParseAborted { .. })
+        ));
+
+        for parsed in [
+            LLSDFactory::parse_binary_with_budget(&data, roomy).unwrap(),
+            LLSDFactory::parse_binary_reader_with_budget(&data[..], roomy).unwrap(),
+        ] {
+            assert_eq!(parsed, document);
+            assert_eq!(parsed.metadata()[METADATA_BYTE_LENGTH], LLSDValue::from_i64(data.len() as i64));
+        }
+    }
+
+    #[test]
+    fn test_vector_accessors() {
+        let position = LLSDValue::vector3(128.0, 64.5, 22.0).unwrap();
+        assert_eq!(position.as_vector3(), Some([128.0, 64.5, 22.0]));
+        assert_eq!(position.as_vector4(), None);
+
+        let rotation = LLSDValue::rotation(0.0, 0.0, 0.7071, 0.7071).unwrap();
+        assert_eq!(rotation.as_quaternion(), Some([0.0, 0.0, 0.7071, 0.7071]));
+        assert_eq!(rotation.as_vector4(), Some([0.0, 0.0, 0.7071, 0.7071]));
+        assert_eq!(rotation.as_vector3(), None);
+
+        // Integer components are coerced
+        let mixed = LLSDValue::Array(vec![
+            LLSDValue::Integer(1),
+            LLSDValue::Real(2.5),
+            LLSDValue::Integer64(3),
+        ]);
+        assert_eq!(mixed.as_vector3(), Some([1.0, 2.5, 3.0]));
+
+        // Wrong length, non-numeric elements and non-arrays
+        assert_eq!(LLSDValue::Array(vec![LLSDValue::Real(1.0); 2]).as_vector3(), None);
+        assert_eq!(LLSDValue::Array(vec![LLSDValue::Real(1.0); 5]).as_quaternion(), None);
+        let with_string = LLSDValue::Array(vec![
+            LLSDValue::Real(1.0),
+            LLSDValue::String("2".to_string()),
+            LLSDValue::Real(3.0),
+        ]);
+        assert_eq!(with_string.as_vector3(), None);
+        assert_eq!(LLSDValue::Real(1.0).as_vector3(), None);
+    }
+
+    #[test]
+    fn test_sort_array_by_path() {
+        let avatar = |name: &str, distance: Option<LLSDValue>| {
+            let mut map = HashMap::new();
+            map.insert("name".to_string(), LLSDValue::String(name.to_string()));
+            if let Some(distance) = distance {
+                map.insert("distance".to_string(), distance);
+            }
+            LLSDValue::Map(map)
+        };
+        let mut radar = LLSDValue::Array(vec![
+            avatar("far", Some(LLSDValue::Real(96.5))),
+            avatar("unknown", None),
+            avatar("near", Some(LLSDValue::Integer(3))),
+            avatar("mid", Some(LLSDValue::Real(15.25))),
+        ]);
+        let names = |radar: &LLSDValue| -> Vec<String> {
+            radar.as_array().unwrap()
+                .iter()
+                .map(|a| LLSDUtils::get_string(a, "name", ""))
+                .collect()
+        };
+
+        LLSDUtils::sort_array_by_path(&mut radar, "distance", true);
+        assert_eq!(names(&radar), vec!["near", "mid", "far", "unknown"]);
+
+        // Missing values stay last when descending
+        LLSDUtils::sort_array_by_path(&mut radar, "distance", false);
+        assert_eq!(names(&radar), vec!["far", "mid", "near", "unknown"]);
+
+        LLSDUtils::sort_array_by_path(&mut radar, "name", true);
+        assert_eq!(names(&radar), vec!["far", "mid", "near", "unknown"]);
+
+        let mut scalar = LLSDValue::Integer(5);
+        LLSDUtils::sort_array_by_path(&mut scalar, "distance", true);
+        assert_eq!(scalar, LLSDValue::Integer(5));
+    }
+
+    #[test]
+    fn test_project() {
+        let avatar = |name: &str, distance: f64| {
+            let mut map = HashMap::new();
+            map.insert("agent_id".to_string(), LLSDValue::UUID(uuid!("550e8400-e29b-41d4-a716-446655440000")));
+            map.insert("display_name".to_string(), LLSDValue::String(name.to_string()));
+            map.insert("distance".to_string(), LLSDValue::Real(distance));
+            map.insert("is_typing".to_string(), LLSDValue::Boolean(false));
+            LLSDValue::Map(map)
+        };
+        let radar = LLSDValue::Array(vec![avatar("Alice", 3.5), avatar("Bob", 12.0), LLSDValue::Integer(7)]);
+
+        let projected = LLSDUtils::project(&radar, &["display_name", "distance"]);
+        let rows = projected.as_array().unwrap();
+        assert_eq!(rows.len(), 3);
+        for (row, (name, distance)) in rows.iter().zip([("Alice", 3.5), ("Bob", 12.0)]) {
+            let map = row.as_map().unwrap();
+            assert_eq!(map.len(), 2);
+            assert_eq!(map["display_name"], LLSDValue::String(name.to_string()));
+            assert_eq!(map["distance"], LLSDValue::Real(distance));
+        }
+        assert_eq!(rows[2], LLSDValue::Integer(7));
+
+        // A single map is filtered directly; scalars pass through
+        let single = LLSDUtils::project(&avatar("Carol", 1.0), &["distance", "missing"]);
+        assert_eq!(single.as_map().unwrap().len(), 1);
+        assert_eq!(LLSDUtils::project(&LLSDValue::Real(1.0), &["distance"]), LLSDValue::Real(1.0));
+    }
+
+    #[test]
+    fn test_walk_visitors() {
+        struct UuidCounter {
+            paths: Vec<String>,
+        }
+        impl LLSDVisitor for UuidCounter {
+            fn visit_uuid(&mut self, path: &str, _value: &uuid::Uuid) {
+                self.paths.push(path.to_string());
+            }
+        }
+
+        struct Uppercase {
+            undefined: Vec<String>,
+        }
+        impl LLSDVisitorMut for Uppercase {
+            fn visit_undefined(&mut self, path: &str) {
+                self.undefined.push(path.to_string());
+            }
+            fn visit_string(&mut self, _path: &str, value: &mut String) {
+                *value = value.to_uppercase();
+            }
+        }
+
+        let id = uuid!("550e8400-e29b-41d4-a716-446655440000");
+        let mut agent = HashMap::new();
+        agent.insert("agent_id".to_string(), LLSDValue::UUID(id));
+        agent.insert("name".to_string(), LLSDValue::String("Alice".to_string()));
+        agent.insert("groups".to_string(), LLSDValue::Array(vec![
+            LLSDValue::UUID(id),
+            LLSDValue::String("builders".to_string()),
+            LLSDValue::URI("http://example.com/lower".to_string()),
+        ]));
+        let mut root = HashMap::new();
+        root.insert("agent".to_string(), LLSDValue::Map(agent));
+        root.insert("owner".to_string(), LLSDValue::UUID(id));
+        root.insert("parent".to_string(), LLSDValue::Undefined);
+        let mut root = LLSDValue::Map(root);
+
+        let mut counter = UuidCounter { paths: Vec::new() };
+        root.walk(&mut counter);
+        counter.paths.sort();
+        assert_eq!(counter.paths, vec!["agent.agent_id", "agent.groups.0", "owner"]);
+        for path in &counter.paths {
+            assert_eq!(root.get_path(path), Some(&LLSDValue::UUID(id)));
+        }
+
+        let mut uppercase = Uppercase { undefined: Vec::new() };
+        root.walk_mut(&mut uppercase);
+        assert_eq!(uppercase.undefined, vec!["parent"]);
+        assert_eq!(LLSDUtils::get_string(&root, "agent.name", ""), "ALICE");
+        assert_eq!(LLSDUtils::get_string(&root, "agent.groups.1", ""), "BUILDERS");
+        // URIs have their own callback and are left alone
+        assert_eq!(LLSDUtils::get_string(&root, "agent.groups.2", ""), "http://example.com/lower");
+
+        // Scalars at the root are visited at the empty path
+        let mut counter = UuidCounter { paths: Vec::new() };
+        LLSDValue::UUID(id).walk(&mut counter);
+        assert_eq!(counter.paths, vec![""]);
+    }
+
+    #[test]
+    fn test_merge_with_strategies() {
+        let ints = |values: &[i32]| LLSDValue::Array(values.iter().map(|v| LLSDValue::Integer(*v)).collect());
+        let appearance = |height: f64, attachments: LLSDValue| {
+            let mut map = HashMap::new();
+            map.insert("height".to_string(), LLSDValue::Real(height));
+            map.insert("attachments".to_string(), attachments);
+            LLSDValue::Map(map)
+        };
+        let base = appearance(1.8, ints(&[1, 2]));
+        let mut overlay = appearance(1.9, ints(&[2, 3]));
+        overlay.as_map_mut().unwrap().insert("skin".to_string(), LLSDValue::Integer(4));
+
+        let cases = [
+            (ScalarConflict::OverlayWins, ArrayMerge::Replace, 1.9, vec![2, 3]),
+            (ScalarConflict::OverlayWins, ArrayMerge::Concat, 1.9, vec![1, 2, 2, 3]),
+            (ScalarConflict::OverlayWins, ArrayMerge::Union, 1.9, vec![1, 2, 3]),
+            (ScalarConflict::BaseWins, ArrayMerge::Replace, 1.8, vec![1, 2]),
+            (ScalarConflict::BaseWins, ArrayMerge::Concat, 1.8, vec![1, 2, 2, 3]),
+            (ScalarConflict::BaseWins, ArrayMerge::Union, 1.8, vec![1, 2, 3]),
+        ];
+        for (on_scalar_conflict, on_array, height, attachments) in cases {
+            let strategy = MergeStrategy { on_scalar_conflict, on_array };
+            let mut merged = base.clone();
+            LLSDUtils::merge_with(&mut merged, &overlay, strategy);
+
+            assert_eq!(merged.get_path("height"), Some(&LLSDValue::Real(height)), "{:?}", strategy);
+            assert_eq!(merged.get_path("attachments"), Some(&ints(&attachments)), "{:?}", strategy);
+            // Keys only in the overlay are always added
+            assert_eq!(merged.get_path("skin"), Some(&LLSDValue::Integer(4)), "{:?}", strategy);
+        }
+
+        // Top-level arrays merge too
+        let concat = MergeStrategy { on_array: ArrayMerge::Concat, ..Default::default() };
+        let union = MergeStrategy { on_array: ArrayMerge::Union, ..Default::default() };
+        let mut merged = ints(&[1, 2]);
+        LLSDUtils::merge_with(&mut merged, &ints(&[2, 3]), concat);
+        assert_eq!(merged, ints(&[1, 2, 2, 3]));
+        let mut merged = ints(&[1, 2]);
+        LLSDUtils::merge_with(&mut merged, &ints(&[2, 3]), union);
+        assert_eq!(merged, ints(&[1, 2, 3]));
+
+        // Type mismatches are conflicts
+        let mut merged = ints(&[1]);
+        LLSDUtils::merge_with(&mut merged, &LLSDValue::Integer(5), MergeStrategy::default());
+        assert_eq!(merged, LLSDValue::Integer(5));
+        let keep = MergeStrategy { on_scalar_conflict: ScalarConflict::BaseWins, ..Default::default() };
+        let mut merged = ints(&[1]);
+        LLSDUtils::merge_with(&mut merged, &LLSDValue::Integer(5), keep);
+        assert_eq!(merged, ints(&[1]));
+
+        // The default strategy matches merge_maps
+        let mut expected = base.as_map().unwrap().clone();
+        LLSDUtils::merge_maps(&mut expected, overlay.as_map().unwrap());
+        let mut merged = base.clone();
+        LLSDUtils::merge_with(&mut merged, &overlay, MergeStrategy::default());
+        assert_eq!(merged, LLSDValue::Map(expected));
+    }
+
+    #[test]
+    fn test_try_get_path_errors() {
+        let mut agent = HashMap::new();
+        agent.insert("scores".to_string(), LLSDValue::Integer(10));
+        agent.insert("groups".to_string(), LLSDValue::Array(vec![LLSDValue::String("builders".to_string())]));
+        let mut root = HashMap::new();
+        root.insert("agent".to_string(), LLSDValue::Map(agent));
+        let root = LLSDValue::Map(root);
+
+        assert_eq!(root.try_get_path("agent.groups.0").unwrap(), &LLSDValue::String("builders".to_string()));
+        assert_eq!(root.try_get_path("agent.scores").ok(), root.get_path("agent.scores"));
+
+        // A missing key reports the path up to the failing segment
+        match root.try_get_path("agent.missing.deeper") {
+            Err(LLSDError::PathNotFound { path }) => assert_eq!(path, "agent.missing"),
+            other => panic!("Expected PathNotFound, got {:?}", other),
+        }
+        assert!(matches!(root.try_get_path("agent.groups.5"), Err(LLSDError::PathNotFound { .. })));
+
+        // Indexing into a scalar names the segment that is not a container
+        let err = root.try_get_path("agent.scores.first").unwrap_err();
+        assert!(matches!(err, LLSDError::TypeMismatch { .. }));
+        assert_eq!(err.to_string(), "Type mismatch: expected container at segment 'scores', got integer");
+
+        let err = root.try_get_path("agent.groups.first").unwrap_err();
+        assert!(matches!(err, LLSDError::TypeMismatch { .. }));
+        assert!(err.to_string().contains("'first' in 'agent.groups.first'"));
+
+        assert!(LLSDValue::Integer(1).try_get_path("a").unwrap_err().to_string().contains("root"));
+    }
+
+    #[test]
+    fn test_xml_max_output_size() {
+        let items: Vec<LLSDValue> = (0..100_000)
+            .map(|i| LLSDValue::String(format!("item {}", i)))
+            .collect();
+        let document = LLSDDocument::new(LLSDValue::Array(items));
+
+        let capped = LLSDXmlSerializer::new().with_max_output_size(1024);
+        assert!(matches!(
+            capped.serialize(&document),
+            Err(LLSDError::OutputTooLarge { limit: 1024 })
+        ));
+
+        let full = LLSDXmlSerializer::new().serialize(&document).unwrap();
+        let exact = LLSDXmlSerializer::new().with_max_output_size(full.len());
+        assert_eq!(exact.serialize(&document).unwrap(), full);
+        let short = LLSDXmlSerializer::new().with_max_output_size(full.len() - 1);
+        assert!(short.serialize(&document).is_err());
+    }
+
+    #[test]
+    fn test_xml_escapes_markup() {
+        let mut map = HashMap::new();
+        map.insert("a & b < c > d".to_string(), LLSDValue::String("a & b < c > d".to_string()));
+        map.insert("uri".to_string(), LLSDValue::URI("http://example.com/?a=1&b=<2>".to_string()));
+        let original = LLSDValue::Map(map);
+
+        for pretty in [false, true] {
+            let xml = LLSDFactory::serialize_xml(&LLSDDocument::new(original.clone()), pretty).unwrap();
+            assert!(xml.contains("<string>a &amp; b &lt; c &gt; d</string>"));
+            assert_eq!(LLSDFactory::parse_xml(&xml).unwrap().content(), &original);
+        }
+    }
+
+    #[test]
+    fn test_notation_round_trip() {
+        let original = LLSDValue::Map({
+            let mut map = HashMap::new();
+            map.insert("string".to_string(), LLSDValue::String("it's \"quoted\"\n你好".to_string()));
+            map.insert("integer".to_string(), LLSDValue::Integer(i32::MIN));
+            map.insert("integer64".to_string(), LLSDValue::Integer64(i64::MAX));
+            map.insert("real".to_string(), LLSDValue::Real(std::f64::consts::PI));
+            map.insert("boolean".to_string(), LLSDValue::Boolean(false));
+            map.insert("uuid".to_string(), LLSDValue::UUID(uuid!("550e8400-e29b-41d4-a716-446655440000")));
+            map.insert("date".to_string(), LLSDValue::Date(Utc.timestamp_opt(1609459200, 500_000_000).single().unwrap()));
+            map.insert("uri".to_string(), LLSDValue::URI("https://example.com/test?param=value".to_string()));
+            map.insert("binary".to_string(), LLSDValue::Binary(vec![0x00, 0xFF, 0x42, 0xAB, 0xCD, 0xEF]));
+            map.insert("empty_array".to_string(), LLSDValue::Array(Vec::new()));
+            map.insert("empty_map".to_string(), LLSDValue::Map(HashMap::new()));
+            map.insert("null".to_string(), LLSDValue::Undefined);
+            map.insert("nested".to_string(), LLSDValue::Map({
+                let mut nested = HashMap::new();
+                nested.insert("array".to_string(), LLSDValue::Array(vec![
+                    LLSDValue::Integer(1),
+                    LLSDValue::Integer(2),
+                    LLSDValue::Real(3.5),
+                ]));
+                nested
+            }));
+            map
+        });
+
+        let document = LLSDDocument::new(original.clone());
+
+        for pretty in [false, true] {
+            // Serialize to notation
+            let notation = LLSDFactory::serialize_notation(&document, pretty).unwrap();
+            assert_eq!(notation.contains('\n'), pretty);
+
+            // Parse back from notation
+            let parsed_document = LLSDFactory::parse_notation(&notation).unwrap();
+
+            assert_eq!(*parsed_document.content(), original);
+        }
+    }
+
+    #[test]
+    fn test_xml_tag_mapping() {
+        let samples = vec![
+            LLSDValue::Undefined,
+            LLSDValue::Boolean(true),
+            LLSDValue::Integer(7),
+            LLSDValue::Integer64(i64::MAX),
+            LLSDValue::Real(1.5),
+            LLSDValue::String("text".to_string()),
+            LLSDValue::UUID(uuid!("550e8400-e29b-41d4-a716-446655440000")),
+            LLSDValue::Date(Utc.timestamp_opt(1609459200, 0).single().unwrap()),
+            LLSDValue::URI("https://example.com/".to_string()),
+            LLSDValue::Binary(vec![1, 2, 3]),
+            LLSDValue::Map(HashMap::from([("k".to_string(), LLSDValue::Integer(1))])),
+            LLSDValue::Array(vec![LLSDValue::Integer(1)]),
+        ];
+
+        for value in samples {
+            let llsd_type = value.get_type();
+            let tag = llsd_type.xml_tag();
+            let expected_type = if llsd_type == LLSDType::Integer64 { LLSDType::Integer } else { llsd_type };
+            assert_eq!(LLSDType::from_xml_tag(tag), Some(expected_type), "{}", tag);
+
+            // The serializer writes the mapped tag and the parser reads it back
+            let xml = LLSDFactory::serialize_xml(&LLSDDocument::new(value.clone()), false).unwrap();
+            assert!(xml.contains(&format!("<{}", tag)), "{}", xml);
+            assert_eq!(LLSDFactory::parse_xml(&xml).unwrap().content(), &value);
+        }
+
+        assert_eq!(LLSDType::from_xml_tag("llsd"), None);
+        assert_eq!(LLSDType::from_xml_tag("key"), None);
+        assert_eq!(LLSDType::from_xml_tag("Integer"), None);
+    }
+
+    #[test]
+    fn test_query_string_round_trip() {
+        let mut map = HashMap::new();
+        map.insert("name".to_string(), LLSDValue::String("Alice Resident".to_string()));
+        map.insert("q".to_string(), LLSDValue::String("fish & chips = 50%+tax".to_string()));
+        map.insert("greeting".to_string(), LLSDValue::String("你好 café".to_string()));
+        map.insert("region id".to_string(), LLSDValue::UUID(uuid!("550e8400-e29b-41d4-a716-446655440000")));
+        map.insert("count".to_string(), LLSDValue::Integer(42));
+        map.insert("visible".to_string(), LLSDValue::Boolean(true));
+        map.insert("empty".to_string(), LLSDValue::String(String::new()));
+        let original = LLSDValue::Map(map);
+
+        let query = LLSDUtils::to_query_string(&original).unwrap();
+        assert!(query.starts_with("count=42&empty=&greeting=%E4%BD%A0%E5%A5%BD%20caf%C3%A9&"));
+        assert!(query.contains("q=fish%20%26%20chips%20%3D%2050%25%2Btax"));
+        assert!(!query.contains(' '));
+        assert_eq!(LLSDUtils::from_query_string(&query), original);
+
+        // Form-style spaces and a leading '?' are accepted
+        let parsed = LLSDUtils::from_query_string("?a=one+two&flag&b=%zz");
+        assert_eq!(parsed.get_path("a"), Some(&LLSDValue::String("one two".to_string())));
+        assert_eq!(parsed.get_path("flag"), Some(&LLSDValue::String(String::new())));
+        assert_eq!(parsed.get_path("b"), Some(&LLSDValue::String("%zz".to_string())));
+
+        let mut nested = HashMap::new();
+        nested.insert("list".to_string(), LLSDValue::Array(vec![LLSDValue::Integer(1)]));
+        assert!(LLSDUtils::to_query_string(&LLSDValue::Map(nested)).is_err());
+        assert!(LLSDUtils::to_query_string(&LLSDValue::Integer(1)).is_err());
+    }
+
+    #[test]
+    fn test_get_map_field() {
+        let id = uuid::Uuid::new_v4();
+        let date = Utc::now();
+        let mut map = HashMap::new();
+        map.insert("flag".to_string(), LLSDValue::Boolean(true));
+        map.insert("age".to_string(), LLSDValue::Integer(42));
+        map.insert("big".to_string(), LLSDValue::Integer64(1 << 40));
+        map.insert("height".to_string(), LLSDValue::Real(1.75));
+        map.insert("name".to_string(), LLSDValue::String("Test".to_string()));
+        map.insert("home".to_string(), LLSDValue::URI("http://example.com/".to_string()));
+        map.insert("id".to_string(), LLSDValue::UUID(id));
+        map.insert("born".to_string(), LLSDValue::Date(date));
+        map.insert("blob".to_string(), LLSDValue::Binary(vec![1, 2, 3]));
+        let value = LLSDValue::Map(map);
+
+        assert!(value.get_map_field::<bool>("flag").unwrap());
+        assert_eq!(value.get_map_field::<i32>("age").unwrap(), 42);
+        assert_eq!(value.get_map_field::<i64>("age").unwrap(), 42);
+        assert_eq!(value.get_map_field::<i64>("big").unwrap(), 1 << 40);
+        assert_eq!(value.get_map_field::<f64>("height").unwrap(), 1.75);
+        assert_eq!(value.get_map_field::<f64>("age").unwrap(), 42.0);
+        assert_eq!(value.get_map_field::<&str>("name").unwrap(), "Test");
+        assert_eq!(value.get_map_field::<String>("home").unwrap(), "http://example.com/");
+        assert_eq!(value.get_map_field::<uuid::Uuid>("id").unwrap(), id);
+        assert_eq!(value.get_map_field::<chrono::DateTime<Utc>>("born").unwrap(), date);
+        assert_eq!(value.get_map_field::<&[u8]>("blob").unwrap(), &[1, 2, 3]);
+        assert_eq!(value.get_map_field::<Vec<u8>>("blob").unwrap(), vec![1, 2, 3]);
+
+        match value.get_map_field::<i32>("weight") {
+            Err(LLSDError::MissingField { field }) => assert_eq!(field, "weight"),
+            other => panic!("expected MissingField, got {:?}", other),
+        }
+        match value.get_map_field::<i32>("name") {
+            Err(LLSDError::TypeMismatch { expected, actual }) => {
+                assert!(expected.contains("'name'"), "{}", expected);
+                assert_eq!(actual, "string");
+            }
+            other => panic!("expected TypeMismatch, got {:?}", other),
+        }
+        // Too wide for i32
+        assert!(matches!(value.get_map_field::<i32>("big"), Err(LLSDError::TypeMismatch { .. })));
+        assert!(matches!(
+            LLSDValue::Array(vec![]).get_map_field::<i32>("age"),
+            Err(LLSDError::TypeMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_xml_declaration_option() {
+        let mut map = HashMap::new();
+        map.insert("name".to_string(), LLSDValue::String("Test".to_string()));
+        let document = LLSDDocument::new(LLSDValue::Map(map));
+        let parser = LLSDXmlParser::new();
+
+        let with_decl = LLSDXmlSerializer::new().serialize(&document).unwrap();
+        assert!(with_decl.starts_with(r#"<?xml version="1.0" encoding="UTF-8"?>"#));
+
+        for pretty in [false, true] {
+            let without = LLSDXmlSerializer::new()
+                .with_pretty_print(pretty)
+                .with_xml_declaration(false)
+                .serialize(&document)
+                .unwrap();
+            assert!(without.starts_with("<llsd>"), "{}", without);
+            assert!(!without.contains("<?xml"));
+            assert_eq!(parser.parse(&without).unwrap(), document);
+        }
+        assert_eq!(parser.parse(&with_decl).unwrap(), document);
+    }
+
+    #[test]
+    fn test_document_metadata() {
+        let content = LLSDValue::Integer(7);
+        let plain = LLSDDocument::new(content.clone());
+        let mut annotated = LLSDDocument::new(content.clone())
+            .with_metadata(HashMap::from([("origin".to_string(), LLSDValue::String("cache".to_string()))]));
+        annotated.set_metadata_entry("attempt", LLSDValue::Integer(2));
+
+        assert_eq!(annotated.metadata().len(), 2);
+        assert_eq!(annotated.metadata()["attempt"], LLSDValue::Integer(2));
+        assert!(plain.metadata().is_empty());
+
+        // Metadata does not affect equality, only deep_eq
+        assert_eq!(plain, annotated);
+        assert!(!plain.deep_eq(&annotated));
+        assert!(annotated.deep_eq(&annotated.clone()));
+        assert_ne!(plain, LLSDDocument::new(LLSDValue::Integer(8)));
+
+        // Nor do recorded date offsets
+        let mut offset = plain.clone();
+        offset.set_date_offset("", chrono::FixedOffset::east_opt(3600).unwrap());
+        assert_eq!(plain, offset);
+        assert!(!plain.deep_eq(&offset));
+
+        // ...nor serialized output
+        assert_eq!(
+            LLSDFactory::serialize_xml(&plain, false).unwrap(),
+            LLSDFactory::serialize_xml(&annotated, false).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_factory_stamps_metadata() {
+        let document = LLSDDocument::new(LLSDValue::Array(vec![LLSDValue::Integer(1), LLSDValue::String("a".to_string())]));
+        let check = |parsed: &LLSDDocument, format: &str, len: usize| {
+            assert_eq!(parsed, &document);
+            assert_eq!(parsed.metadata()[METADATA_SOURCE_FORMAT], LLSDValue::String(format.to_string()));
+            assert_eq!(parsed.metadata()[METADATA_BYTE_LENGTH], LLSDValue::from_i64(len as i64));
+        };
+
+        let xml = LLSDFactory::serialize_xml(&document, false).unwrap();
+        check(&LLSDFactory::parse_xml(&xml).unwrap(), "xml", xml.len());
+        let json = LLSDFactory::serialize_json(&document, false).unwrap();
+        check(&LLSDFactory::parse_json(&json).unwrap(), "json", json.len());
+        let notation = LLSDFactory::serialize_notation(&document, false).unwrap();
+        check(&LLSDFactory::parse_notation(&notation).unwrap(), "notation", notation.len());
+        let binary = LLSDFactory::serialize_binary(&document).unwrap();
+        check(&LLSDFactory::parse_binary(&binary).unwrap(), "binary", binary.len());
+
+        let padded = format!("\n  {}", xml);
+        let (_, detected) = LLSDFactory::parse_auto(padded.as_bytes()).unwrap();
+        check(&detected, "xml", padded.len());
+
+        // Parsers used directly leave metadata empty
+        assert!(LLSDXmlParser::new().parse(&xml).unwrap().metadata().is_empty());
+    }
+
+    #[test]
+    fn test_coerce_to() {
+        let text = |s: &str| LLSDValue::String(s.to_string());
+
+        assert_eq!(text("42").coerce_to(LLSDType::Integer).unwrap(), LLSDValue::Integer(42));
+        assert_eq!(text("5000000000").coerce_to(LLSDType::Integer64).unwrap(), LLSDValue::Integer64(5_000_000_000));
+        assert!(text("5000000000").coerce_to(LLSDType::Integer).is_err());
+        assert_eq!(LLSDValue::Real(3.0).coerce_to(LLSDType::Integer).unwrap(), LLSDValue::Integer(3));
+        assert!(LLSDValue::Real(3.5).coerce_to(LLSDType::Integer).is_err());
+        assert_eq!(LLSDValue::Boolean(true).coerce_to(LLSDType::Integer).unwrap(), LLSDValue::Integer(1));
+
+        assert_eq!(text("TRUE").coerce_to(LLSDType::Boolean).unwrap(), LLSDValue::Boolean(true));
+        assert_eq!(LLSDValue::Integer(0).coerce_to(LLSDType::Boolean).unwrap(), LLSDValue::Boolean(false));
+        assert!(text("yes").coerce_to(LLSDType::Boolean).is_err());
+
+        assert_eq!(text("1.5").coerce_to(LLSDType::Real).unwrap(), LLSDValue::Real(1.5));
+        assert_eq!(LLSDValue::Integer(2).coerce_to(LLSDType::Real).unwrap(), LLSDValue::Real(2.0));
+        assert_eq!(LLSDValue::Integer(2).coerce_to(LLSDType::String).unwrap(), text("2"));
+
+        let id = uuid!("550e8400-e29b-41d4-a716-446655440000");
+        assert_eq!(text(&id.to_string()).coerce_to(LLSDType::UUID).unwrap(), LLSDValue::UUID(id));
+        assert_eq!(
+            text("2024-01-01T00:00:00Z").coerce_to(LLSDType::Date).unwrap(),
+            LLSDValue::Date(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap())
+        );
+        assert_eq!(
+            text("http://example.com/").coerce_to(LLSDType::URI).unwrap(),
+            LLSDValue::URI("http://example.com/".to_string())
+        );
+
+        let array = LLSDValue::Array(vec![LLSDValue::Integer(1)]);
+        assert_eq!(array.coerce_to(LLSDType::Array).unwrap(), array);
+        assert!(array.coerce_to(LLSDType::String).is_err());
+        assert!(LLSDValue::Undefined.coerce_to(LLSDType::Integer).is_err());
+        match text("abc").coerce_to(LLSDType::Real) {
+            Err(LLSDError::TypeMismatch { expected, actual }) => {
+                assert!(expected.contains("Real"));
+                assert_eq!(actual, "string 'abc'");
+            }
+            other => panic!("expected TypeMismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_value_entries() {
+        // One code path for both container kinds
+        fn describe(value: &LLSDValue) -> Vec<String> {
+            let mut lines: Vec<String> = value
+                .entries()
+                .map(|(key, child)| match key {
+                    LLSDKey::Str(name) => format!("{}={}", name, child.type_name()),
+                    LLSDKey::Index(index) => format!("[{}]={}", index, child.type_name()),
+                })
+                .collect();
+            lines.sort();
+            lines
+        }
+
+        let mut map = HashMap::new();
+        map.insert("name".to_string(), LLSDValue::String("Alice".to_string()));
+        map.insert("age".to_string(), LLSDValue::Integer(30));
+        let map = LLSDValue::Map(map);
+        assert_eq!(describe(&map), vec!["age=integer", "name=string"]);
+        assert_eq!(map.entries().len(), 2);
+
+        let array = LLSDValue::Array(vec![LLSDValue::Boolean(true), LLSDValue::Real(1.5)]);
+        assert_eq!(describe(&array), vec!["[0]=boolean", "[1]=real"]);
+        let keys: Vec<String> = array.entries().map(|(key, _)| key.to_string()).collect();
+        assert_eq!(keys, vec!["0", "1"]);
+
+        // Keys display as get_path segments
+        for (key, child) in map.entries() {
+            assert_eq!(map.get_path(&key.to_string()), Some(child));
+        }
+
+        for scalar in [LLSDValue::Undefined, LLSDValue::Integer(1), LLSDValue::String("x".to_string())] {
+            assert_eq!(scalar.entries().count(), 0);
+            assert_eq!(scalar.entries().len(), 0);
+        }
+    }
+
+    #[test]
+    fn test_deep_clone_safe() {
+        // Recursive drop would overflow on the deep values below
+        fn drop_iteratively(value: LLSDValue) {
+            let mut pending = vec![value];
+            while let Some(value) = pending.pop() {
+                match value {
+                    LLSDValue::Array(items) => pending.extend(items),
+                    LLSDValue::Map(map) => pending.extend(map.into_values()),
+                    _ => {}
+                }
+            }
+        }
+
+        let wide = LLSDValue::Map({
+            let mut map = HashMap::new();
+            map.insert("name".to_string(), LLSDValue::String("Alice".to_string()));
+            map.insert("empty".to_string(), LLSDValue::Array(Vec::new()));
+            map.insert("items".to_string(), LLSDValue::Array(vec![
+                LLSDValue::Integer(1),
+                LLSDValue::Map(HashMap::new()),
+                LLSDValue::Binary(vec![1, 2, 3]),
+                LLSDValue::Array(vec![LLSDValue::Undefined, LLSDValue::Real(2.5)]),
+            ]));
+            map
+        });
+        assert_eq!(LLSDUtils::deep_clone_safe(&wide), LLSDUtils::deep_clone(&wide));
+        assert_eq!(LLSDUtils::deep_clone_safe(&LLSDValue::Integer(7)), LLSDValue::Integer(7));
+
+        // Deep enough that the recursive Clone overflows a test thread's stack
+        const DEPTH: usize = 100_000;
+        let mut deep = LLSDValue::String("bottom".to_string());
+        for level in 0..DEPTH {
+            deep = if level % 2 == 0 {
+                LLSDValue::Array(vec![LLSDValue::Integer(level as i32), deep])
+            } else {
+                let mut map = HashMap::new();
+                map.insert("child".to_string(), deep);
+                LLSDValue::Map(map)
+            };
+        }
+
+        let copy = LLSDUtils::deep_clone_safe(&deep);
+        let (mut original, mut cloned) = (&deep, &copy);
+        let mut depth = 0;
+        loop {
+            match (original, cloned) {
+                (LLSDValue::Array(a), LLSDValue::Array(b)) => {
+                    assert_eq!(a.len(), 2);
+                    assert_eq!(a[0], b[0]);
+                    (original, cloned) = (&a[1], &b[1]);
+                }
+                (LLSDValue::Map(a), LLSDValue::Map(b)) => {
+                    assert_eq!(b.len(), 1);
+                    (original, cloned) = (&a["child"], &b["child"]);
+                }
+                (a, b) => {
+                    assert_eq!(a, b);
+                    break;
+                }
+            }
+            depth += 1;
+        }
+        assert_eq!(depth, DEPTH);
+
+        drop_iteratively(copy);
+        drop_iteratively(deep);
+    }
+
+    #[test]
+    fn test_get_string_opt() {
+        let root = LLSDValue::Map({
+            let mut map = HashMap::new();
+            map.insert("name".to_string(), LLSDValue::String("Blue Hat".to_string()));
+            map.insert("links".to_string(), LLSDValue::Array(vec![
+                LLSDValue::URI("http://example.com/hat.png".to_string()),
+            ]));
+            map.insert("count".to_string(), LLSDValue::Integer(3));
+            map.insert("empty".to_string(), LLSDValue::String(String::new()));
+            map
+        });
+
+        assert_eq!(LLSDUtils::get_string_opt(&root, "name"), Some("Blue Hat".to_string()));
+        assert_eq!(LLSDUtils::get_string_opt(&root, "links.0"), Some("http://example.com/hat.png".to_string()));
+        assert_eq!(LLSDUtils::get_string_opt(&root, "empty"), Some(String::new()));
+        assert_eq!(LLSDUtils::get_string_opt(&root, "missing"), None);
+        assert_eq!(LLSDUtils::get_string_opt(&root, "links.1"), None);
+        assert_eq!(LLSDUtils::get_string_opt(&root, "count"), None);
+        assert_eq!(LLSDUtils::get_string_opt(&root, "links"), None);
+
+        // get_string hides the same cases behind its default
+        assert_eq!(LLSDUtils::get_string(&root, "count", "none"), "none");
+    }
+
+    #[test]
+    fn test_document_merge() {
+        let mut base = LLSDFactory::parse_json(r#"{"name": "base", "tags": ["a"], "limits": {"max": 1}}"#).unwrap();
+        let overlay = LLSDFactory::parse_xml(
+            "<llsd><map><key>tags</key><array><string>a</string><string>b</string></array>\
+             <key>limits</key><map><key>min</key><integer>0</integer></map></map></llsd>",
+        )
+        .unwrap();
+
+        let strategy = MergeStrategy { on_array: ArrayMerge::Union, ..Default::default() };
+        base.merge(&overlay, strategy);
+
+        let content = base.content();
+        assert_eq!(content.get_path("name"), Some(&LLSDValue::String("base".to_string())));
+        assert_eq!(content.get_path("tags").and_then(|v| v.as_array()).map(|a| a.len()), Some(2));
+        assert_eq!(content.get_path("limits.max"), Some(&LLSDValue::Integer(1)));
+        assert_eq!(content.get_path("limits.min"), Some(&LLSDValue::Integer(0)));
+
+        // Own metadata is untouched; the overlay's is recorded as a breadcrumb
+        assert_eq!(base.metadata()[METADATA_SOURCE_FORMAT], LLSDValue::String("json".to_string()));
+        let merged_from = base.metadata()[METADATA_MERGED_FROM].as_array().unwrap();
+        assert_eq!(merged_from.len(), 1);
+        assert_eq!(merged_from[0].get_path(METADATA_SOURCE_FORMAT), Some(&LLSDValue::String("xml".to_string())));
+        assert_eq!(merged_from[0].as_map(), Some(overlay.metadata()));
+
+        // Later merges append, keeping the other document's history nested
+        let mut second = LLSDDocument::new(LLSDValue::Map(HashMap::new()));
+        second.merge(&LLSDFactory::parse_notation("{'extra':i1}").unwrap(), MergeStrategy::default());
+        base.merge(&second, MergeStrategy::default());
+        let merged_from = base.metadata()[METADATA_MERGED_FROM].as_array().unwrap();
+        assert_eq!(merged_from.len(), 2);
+        let nested = merged_from[1].get_path(METADATA_MERGED_FROM).and_then(|v| v.as_array()).unwrap();
+        assert_eq!(nested[0].get_path(METADATA_SOURCE_FORMAT), Some(&LLSDValue::String("notation".to_string())));
+        assert_eq!(base.content().get_path("extra"), Some(&LLSDValue::Integer(1)));
+    }
+
+    #[test]
+    fn test_document_merge_date_offsets() {
+        let parser = LLSDXmlParser::new().with_preserve_offset(true);
+        let mut base = parser
+            .parse(
+                "<llsd><map><key>login</key><date>2024-03-01T18:30:00+09:00</date>\
+                 <key>history</key><array><date>2024-02-29T20:00:00-05:00</date></array>\
+                 <key>joined</key><date>2020-01-01T01:00:00+01:00</date></map></llsd>",
+            )
+            .unwrap();
+        let overlay = LLSDFactory::parse_xml(
+            "<llsd><map><key>login</key><date>2024-03-02T00:00:00Z</date>\
+             <key>history</key><array><date>2024-03-02T00:00:00Z</date></array></map></llsd>",
+        )
+        .unwrap();
+
+        base.merge(&overlay, MergeStrategy::default());
+        assert_eq!(base.date_offset("login"), None);
+        assert_eq!(base.date_offset("history.0"), None);
+        assert_eq!(base.date_offset("joined"), chrono::FixedOffset::east_opt(3600));
+        assert!(LLSDFactory::serialize_xml(&base, false).unwrap().contains("2024-03-02T00:00:00+00:00"));
+
+        // Values kept from the base keep their offsets
+        let mut kept = parser.parse("<llsd><date>2024-03-01T18:30:00+09:00</date></llsd>").unwrap();
+        let strategy = MergeStrategy { on_scalar_conflict: ScalarConflict::BaseWins, ..Default::default() };
+        kept.merge(&LLSDDocument::new(LLSDValue::Integer(1)), strategy);
+        assert_eq!(kept.date_offset(""), chrono::FixedOffset::east_opt(9 * 3600));
+        kept.merge(&LLSDDocument::new(LLSDValue::Integer(1)), MergeStrategy::default());
+        assert!(kept.date_offsets().is_empty());
+    }
+
+    #[test]
+    fn test_probe_root_type() {
+        let samples = vec![
+            LLSDValue::Undefined,
+            LLSDValue::Boolean(true),
+            LLSDValue::Integer(42),
+            LLSDValue::Integer64(1 << 40),
+            LLSDValue::Real(2.5),
+            LLSDValue::String("hello".to_string()),
+            LLSDValue::UUID(uuid!("550e8400-e29b-41d4-a716-446655440000")),
+            LLSDValue::Date(Utc.timestamp_opt(1_700_000_000, 0).unwrap()),
+            LLSDValue::URI("http://example.com/".to_string()),
+            LLSDValue::Binary(vec![1, 2, 3]),
+            LLSDValue::Array(vec![LLSDValue::Integer(1)]),
+            LLSDValue::Map({
+                let mut map = HashMap::new();
+                map.insert("a".to_string(), LLSDValue::Integer(1));
+                map
+            }),
+            LLSDValue::Array(Vec::new()),
+            LLSDValue::Map(HashMap::new()),
+        ];
+
+        for value in samples {
+            let document = LLSDDocument::new(value.clone());
+
+            let binary = LLSDFactory::serialize_binary(&document).unwrap();
+            assert_eq!(LLSDBinaryParser::new().probe_root_type(&binary).unwrap(), value.get_type());
+
+            // Text formats probe as the full parse would type the root
+            let json = LLSDFactory::serialize_json(&document, false).unwrap();
+            let parsed = LLSDFactory::parse_json(&json).unwrap().get_type();
+            assert_eq!(LLSDJsonParser::probe_root_type(&json).unwrap(), parsed, "{}", json);
+
+            let xml = LLSDFactory::serialize_xml(&document, true).unwrap();
+            let expected = match value.get_type() {
+                LLSDType::Integer64 => LLSDType::Integer,
+                other => other,
+            };
+            assert_eq!(LLSDXmlParser::probe_root_type(&xml).unwrap(), expected, "{}", xml);
+        }
+
+        // Only the start of a container is read
+        assert_eq!(LLSDJsonParser::probe_root_type("  [1, 2, ").unwrap(), LLSDType::Array);
+        assert_eq!(LLSDXmlParser::probe_root_type("<llsd><map><key>a</key>").unwrap(), LLSDType::Map);
+        let mut truncated = LLSDFactory::serialize_binary(&LLSDDocument::new(LLSDValue::Array(vec![
+            LLSDValue::String("cut".to_string()),
+        ])))
+        .unwrap();
+        truncated.truncate(6);
+        assert_eq!(LLSDBinaryParser::new().probe_root_type(&truncated).unwrap(), LLSDType::Array);
+
+        assert!(matches!(LLSDBinaryParser::new().probe_root_type(b"nope\x0a"), Err(LLSDError::InvalidMagic)));
+        assert!(matches!(LLSDBinaryParser::new().probe_root_type(b""), Err(LLSDError::EmptyInput)));
+        assert!(matches!(LLSDBinaryParser::new().probe_root_type(b"\n"), Err(LLSDError::UnexpectedEndOfData)));
+
+        // The parser's byte order and header settings apply
+        let little = LLSDBinarySerializer::new()
+            .with_byte_order(ByteOrder::LittleEndian)
+            .serialize(&LLSDDocument::new(LLSDValue::Map(HashMap::new())))
+            .unwrap();
+        let little_parser = LLSDBinaryParser::new().with_byte_order(ByteOrder::LittleEndian);
+        assert_eq!(little_parser.probe_root_type(&little).unwrap(), LLSDType::Map);
+        assert!(matches!(LLSDBinaryParser::new().probe_root_type(&little), Err(LLSDError::InvalidMagic)));
+        let headerless = LLSDBinaryParser::new().without_magic_validation();
+        assert_eq!(headerless.probe_root_type(b"\n").unwrap(), LLSDType::Map);
+        assert_eq!(headerless.probe_root_type(&[0x09]).unwrap(), LLSDType::Array);
+        assert!(LLSDBinaryParser::new().probe_root_type(b"llsd").is_err());
+        assert!(matches!(LLSDJsonParser::probe_root_type("  "), Err(LLSDError::EmptyInput)));
+        assert!(LLSDXmlParser::probe_root_type("<llsd><bogus/></llsd>").is_err());
+        assert!(LLSDXmlParser::probe_root_type("<other/>").is_err());
+        assert!(LLSDXmlParser::probe_root_type("<llsd>").is_err());
+        assert_eq!(LLSDXmlParser::probe_root_type("<?xml version=\"1.0\"?><llsd></llsd>").unwrap(), LLSDType::Unknown);
+    }
+
+    #[test]
+    fn test_normalize() {
+        let map = |entries: Vec<(&str, LLSDValue)>| {
+            LLSDValue::Map(entries.into_iter().map(|(k, v)| (k.to_string(), v)).collect())
+        };
+
+        let value = map(vec![
+            ("gone", LLSDValue::Undefined),
+            ("real", LLSDValue::Real(3.0)),
+            ("fraction", LLSDValue::Real(2.5)),
+            ("wide", LLSDValue::Integer64(7)),
+            ("empty", LLSDValue::Array(Vec::new())),
+            ("hollow", map(vec![("inner", LLSDValue::Undefined)])),
+            ("list", LLSDValue::Array(vec![LLSDValue::Undefined, LLSDValue::Real(-4.0)])),
+        ]);
+
+        // The default changes nothing
+        assert_eq!(LLSDUtils::normalize(&value, NormalizeOptions::default()), value);
+
+        let dropped = LLSDUtils::normalize(&value, NormalizeOptions { drop_undefined: true, ..Default::default() });
+        assert_eq!(dropped.get_path("gone"), None);
+        assert_eq!(dropped.get_path("hollow"), Some(&LLSDValue::Map(HashMap::new())));
+        // Array positions are kept
+        assert_eq!(dropped.get_path("list.0"), Some(&LLSDValue::Undefined));
+
+        let numbers = LLSDUtils::normalize(&value, NormalizeOptions { canonical_numbers: true, ..Default::default() });
+        assert_eq!(numbers.get_path("real"), Some(&LLSDValue::Integer(3)));
+        assert_eq!(numbers.get_path("fraction"), Some(&LLSDValue::Real(2.5)));
+        assert_eq!(numbers.get_path("wide"), Some(&LLSDValue::Integer(7)));
+        assert_eq!(numbers.get_path("list.1"), Some(&LLSDValue::Integer(-4)));
+        assert_eq!(
+            LLSDUtils::normalize(&LLSDValue::Real(1e12), NormalizeOptions::all()),
+            LLSDValue::Integer64(1_000_000_000_000)
+        );
+        assert_eq!(LLSDUtils::normalize(&LLSDValue::Real(1e300), NormalizeOptions::all()), LLSDValue::Real(1e300));
+
+        let trimmed = LLSDUtils::normalize(&value, NormalizeOptions { trim_empty: true, ..Default::default() });
+        assert_eq!(trimmed.get_path("empty"), None);
+        // Not empty until its undefined entry is dropped
+        assert!(trimmed.get_path("hollow").is_some());
+        assert_eq!(LLSDUtils::normalize(&value, NormalizeOptions::all()).get_path("hollow"), None);
+
+        // Equivalent documents of different shape normalize equal
+        let from_json = LLSDFactory::parse_json(r#"{"id": 5.0, "tags": [], "extra": null, "pos": [1.0, 2.5]}"#).unwrap();
+        let from_xml = LLSDFactory::parse_xml(
+            "<llsd><map><key>pos</key><array><integer>1</integer><real>2.5</real></array>\
+             <key>id</key><integer>5</integer><key>meta</key><map><key>x</key><undef/></map></map></llsd>",
+        )
+        .unwrap();
+        assert_ne!(from_json.content(), from_xml.content());
+        assert_eq!(
+            LLSDUtils::normalize(from_json.content(), NormalizeOptions::all()),
+            LLSDUtils::normalize(from_xml.content(), NormalizeOptions::all())
+        );
+    }
+
+    #[test]
+    fn test_factory_format_dispatch() {
+        let mut map = HashMap::new();
+        map.insert("name".to_string(), LLSDValue::String("Alice".to_string()));
+        map.insert("id".to_string(), LLSDValue::UUID(uuid!("550e8400-e29b-41d4-a716-446655440000")));
+        map.insert("scores".to_string(), LLSDValue::Array(vec![LLSDValue::Integer(1), LLSDValue::Real(2.5)]));
+        let doc = LLSDFactory::create(LLSDValue::Map(map));
+
+        for format in [LLSDFormat::XML, LLSDFormat::JSON, LLSDFormat::Binary, LLSDFormat::Notation] {
+            for pretty in [false, true] {
+                let data = LLSDFactory::serialize(&doc, format, pretty).unwrap();
+                let parsed = LLSDFactory::parse(format, &data).unwrap();
+                assert_eq!(parsed, doc, "{:?}", format);
+                assert_eq!(parsed.metadata()[METADATA_SOURCE_FORMAT], LLSDValue::String(format.name().to_string()));
+                assert_eq!(LLSDFactory::parse_auto(&data).unwrap().0, format);
+            }
+        }
+
+        // The dispatchers match the format-specific functions
+        assert_eq!(
+            LLSDFactory::serialize(&doc, LLSDFormat::Binary, true).unwrap(),
+            LLSDFactory::serialize_binary(&doc).unwrap()
+        );
+        assert_eq!(
+            LLSDFactory::serialize(&doc, LLSDFormat::Notation, false).unwrap(),
+            LLSDFactory::serialize_notation(&doc, false).unwrap().into_bytes()
+        );
+
+        // Notation may carry raw bytes that are not UTF-8
+        let raw = LLSDFactory::parse(LLSDFormat::Notation, b"b(2)\"\xff\xfe\"").unwrap();
+        assert_eq!(raw.content(), &LLSDValue::Binary(vec![0xFF, 0xFE]));
+        assert!(LLSDFactory::parse(LLSDFormat::XML, b"<llsd>\xff</llsd>").is_err());
+
+        let binary = LLSDFactory::serialize(&doc, LLSDFormat::Binary, false).unwrap();
+        assert!(LLSDFactory::parse(LLSDFormat::JSON, &binary).is_err());
+    }
+
+    #[test]
+    fn test_apply_defaults() {
+        let defaults: HashMap<String, LLSDValue> = [
+            ("volume".to_string(), LLSDValue::Real(0.5)),
+            ("muted".to_string(), LLSDValue::Boolean(false)),
+            ("name".to_string(), LLSDValue::String("unnamed".to_string())),
+            ("camera".to_string(), LLSDValue::Map({
+                let mut camera = HashMap::new();
+                camera.insert("fov".to_string(), LLSDValue::Real(60.0));
+                camera.insert("offset".to_string(), LLSDValue::Map({
+                    let mut offset = HashMap::new();
+                    offset.insert("x".to_string(), LLSDValue::Real(0.0));
+                    offset.insert("z".to_string(), LLSDValue::Real(1.5));
+                    offset
+                }));
+                camera
+            })),
+        ]
+        .into_iter()
+        .collect();
+
+        let received = || {
+            let mut map = HashMap::new();
+            map.insert("name".to_string(), LLSDValue::String("Alice".to_string()));
+            map.insert("muted".to_string(), LLSDValue::Undefined);
+            map.insert("camera".to_string(), LLSDValue::Map({
+                let mut camera = HashMap::new();
+                camera.insert("fov".to_string(), LLSDValue::Real(90.0));
+                camera.insert("offset".to_string(), LLSDValue::Map({
+                    let mut offset = HashMap::new();
+                    offset.insert("x".to_string(), LLSDValue::Real(2.0));
+                    offset
+                }));
+                camera
+            }));
+            LLSDValue::Map(map)
+        };
+
+        let mut shallow = received();
+        LLSDUtils::apply_defaults(&mut shallow, &defaults);
+        assert_eq!(shallow.get_path("volume"), Some(&LLSDValue::Real(0.5)));
+        // Present keys are untouched, undefined or not
+        assert_eq!(shallow.get_path("name"), Some(&LLSDValue::String("Alice".to_string())));
+        assert_eq!(shallow.get_path("muted"), Some(&LLSDValue::Undefined));
+        assert_eq!(shallow.get_path("camera.fov"), Some(&LLSDValue::Real(90.0)));
+        assert_eq!(shallow.get_path("camera.offset.z"), None);
+
+        let mut deep = received();
+        LLSDUtils::apply_defaults_deep(&mut deep, &defaults);
+        assert_eq!(deep.get_path("volume"), Some(&LLSDValue::Real(0.5)));
+        assert_eq!(deep.get_path("camera.fov"), Some(&LLSDValue::Real(90.0)));
+        assert_eq!(deep.get_path("camera.offset.x"), Some(&LLSDValue::Real(2.0)));
+        assert_eq!(deep.get_path("camera.offset.z"), Some(&LLSDValue::Real(1.5)));
+
+        // Applying again changes nothing
+        let once = deep.clone();
+        LLSDUtils::apply_defaults_deep(&mut deep, &defaults);
+        assert_eq!(deep, once);
+
+        // A default map never replaces a present value of another type
+        let mut mismatched = LLSDValue::Map({
+            let mut map = HashMap::new();
+            map.insert("camera".to_string(), LLSDValue::String("fixed".to_string()));
+            map
+        });
+        LLSDUtils::apply_defaults_deep(&mut mismatched, &defaults);
+        assert_eq!(mismatched.get_path("camera"), Some(&LLSDValue::String("fixed".to_string())));
+
+        let mut scalar = LLSDValue::Integer(1);
+        LLSDUtils::apply_defaults_deep(&mut scalar, &defaults);
+        assert_eq!(scalar, LLSDValue::Integer(1));
+    }
+
     // Additional integration tests would go here...
     // These would test cross-format compatibility, performance characteristics, etc.
 }
\ No newline at end of file
@@ -0,0 +1,85 @@
+/*!
+ * LLSD Round-Trip Properties
+ *
+ * Property tests asserting parse(serialize(v)) == v over generated values
+ * Copyright (C) 2024 Linden Lab
+ */
+
+use llsd::*;
+use proptest::prelude::*;
+use chrono::{TimeZone, Utc};
+use uuid::Uuid;
+
+/// Finite reals only: NaN never compares equal, so it is covered by the
+/// conformance fixtures instead
+fn finite_real() -> impl Strategy<Value = f64> {
+    prop::num::f64::POSITIVE | prop::num::f64::NEGATIVE | prop::num::f64::ZERO
+}
+
+/// Scalar values, with strings drawn from `text`
+fn leaf(text: BoxedStrategy<String>) -> impl Strategy<Value = LLSDValue> {
+    prop_oneof![
+        Just(LLSDValue::Undefined),
+        any::<bool>().prop_map(LLSDValue::Boolean),
+        any::<i32>().prop_map(LLSDValue::Integer),
+        any::<i64>().prop_map(LLSDValue::Integer64),
+        finite_real().prop_map(LLSDValue::Real),
+        text.clone().prop_map(LLSDValue::String),
+        any::<[u8; 16]>().prop_map(|b| LLSDValue::UUID(Uuid::from_bytes(b))),
+        // Whole seconds: binary dates are f64 seconds, so arbitrary
+        // nanoseconds do not survive
+        (0i64..4_102_444_800).prop_map(|t| LLSDValue::Date(Utc.timestamp_opt(t, 0).unwrap())),
+        text.prop_map(LLSDValue::URI),
+        prop::collection::vec(any::<u8>(), 0..64).prop_map(LLSDValue::Binary),
+    ]
+}
+
+/// Bounded LLSD trees whose strings are drawn from `text`
+fn llsd_value(text: BoxedStrategy<String>) -> impl Strategy<Value = LLSDValue> {
+    leaf(text.clone()).prop_recursive(4, 64, 8, move |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..8).prop_map(LLSDValue::Array),
+            prop::collection::hash_map(text.clone(), inner, 0..8).prop_map(LLSDValue::Map),
+        ]
+    })
+}
+
+/// What an XML round trip yields. XML writes every integer as `<integer>`,
+/// so 64-bit values that fit in 32 bits come back as `Integer`.
+fn xml_expected(value: &LLSDValue) -> LLSDValue {
+    match value {
+        LLSDValue::Integer64(i) => LLSDValue::from_i64(*i),
+        LLSDValue::Array(items) => LLSDValue::Array(items.iter().map(xml_expected).collect()),
+        LLSDValue::Map(entries) => LLSDValue::Map(
+            entries.iter().map(|(k, v)| (k.clone(), xml_expected(v))).collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+proptest! {
+    #[test]
+    fn binary_round_trip(value in llsd_value(any::<String>().boxed())) {
+        let doc = LLSDDocument::new(value.clone());
+        let data = LLSDFactory::serialize_binary(&doc).unwrap();
+        let parsed = LLSDFactory::parse_binary(&data).unwrap();
+        prop_assert_eq!(parsed.content(), &value);
+    }
+
+    #[test]
+    fn binary_string_table_round_trip(value in llsd_value(any::<String>().boxed())) {
+        let doc = LLSDDocument::new(value.clone());
+        let data = LLSDBinarySerializer::new().with_string_table(true).serialize(&doc).unwrap();
+        let parsed = LLSDBinaryParser::new().parse(&data).unwrap();
+        prop_assert_eq!(parsed.content(), &value);
+    }
+
+    // Exception: strings avoid whitespace, which the XML parser trims
+    #[test]
+    fn xml_round_trip(value in llsd_value("[a-zA-Z0-9_.,:/<>&'\"-]{0,16}".boxed())) {
+        let doc = LLSDDocument::new(value.clone());
+        let xml = LLSDFactory::serialize_xml(&doc, false).unwrap();
+        let parsed = LLSDFactory::parse_xml(&xml).unwrap();
+        prop_assert_eq!(parsed.content(), &xml_expected(&value));
+    }
+}
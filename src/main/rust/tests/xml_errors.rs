@@ -0,0 +1,106 @@
+/*!
+ * LLSD XML Parser Error Tests
+ *
+ * Malformed documents must fail with a descriptive error, never a panic or
+ * a silently truncated value
+ * Copyright (C) 2024 Linden Lab
+ */
+
+use llsd::*;
+
+/// Parse `xml` strictly, expecting an error whose message contains `expected`
+fn assert_error(xml: &str, expected: &str) {
+    match LLSDXmlParser::new().parse(xml) {
+        Ok(document) => panic!("Parsed {:?} from malformed input:\n{}", document.content(), xml),
+        Err(err) => assert!(
+            err.to_string().contains(expected),
+            "Error '{}' does not mention '{}' for:\n{}",
+            err,
+            expected,
+            xml
+        ),
+    }
+}
+
+#[test]
+fn test_map_value_before_key() {
+    assert_error("<llsd><map><integer>1</integer><key>a</key></map></llsd>", "<integer> without a preceding <key>");
+    assert_error("<llsd><map><undef/><key>a</key></map></llsd>", "<undef> without a preceding <key>");
+}
+
+#[test]
+fn test_map_key_without_value() {
+    assert_error("<llsd><map><key>lonely</key></map></llsd>", "Map key 'lonely' has no value");
+    assert_error(
+        "<llsd><map><key>first</key><key>second</key><integer>2</integer></map></llsd>",
+        "Map key 'first' has no value",
+    );
+    assert_error("<llsd><map><key>a</key><integer>1</integer><key/></map></llsd>", "Map key '' has no value");
+}
+
+#[test]
+fn test_nested_array_of_maps() {
+    let xml = "<llsd><array>\
+        <map><key>id</key><integer>1</integer></map>\
+        <map><key>id</key><integer>2</integer><key>tags</key><array><string>x</string></array></map>\
+        </array></llsd>";
+    let document = LLSDXmlParser::new().parse(xml).unwrap();
+    assert_eq!(document.content().get_path("1.tags.0"), Some(&LLSDValue::String("x".to_string())));
+    assert_eq!(document.content().get_path("0.id"), Some(&LLSDValue::Integer(1)));
+
+    assert_error(
+        "<llsd><array><map><key>id</key><integer>1</integer></map><map><key>id</key></map></array></llsd>",
+        "Map key 'id' has no value",
+    );
+    assert_error(
+        "<llsd><array><map><string>x</string></map></array></llsd>",
+        "<string> without a preceding <key>",
+    );
+}
+
+#[test]
+fn test_mismatched_end_tags() {
+    assert_error("<llsd><integer>1</string></llsd>", "integer");
+    assert_error("<llsd><array><integer>1</integer></map></llsd>", "array");
+    assert_error("<llsd><map><key>a</key><integer>1</integer></array></llsd>", "map");
+    assert_error("<llsd><undef><integer>1</integer></real></llsd>", "undef");
+    assert_error("<llsd><string>a<b>bold</b></string></llsd>", "Unexpected <b> inside <string>");
+}
+
+#[test]
+fn test_premature_eof() {
+    assert_error("<llsd>", "Unterminated <llsd> element");
+    assert_error("<llsd><integer>1</integer>", "Unterminated <llsd> element");
+    assert_error("<llsd><string>unfinished", "Unterminated <string> element");
+    assert_error("<llsd><array><integer>1</integer>", "Unterminated <array> element");
+    assert_error("<llsd><map><key>a</key><integer>1</integer>", "Unterminated <map> element");
+    assert_error("<llsd><map><key>a", "Unterminated <key> element");
+    assert_error("<llsd><undef><integer>1</integer>", "Unterminated <undef> element");
+}
+
+#[test]
+fn test_extra_root_values() {
+    assert_error("<llsd><integer>1</integer><integer>2</integer></llsd>", "Unexpected <integer> after the root value");
+}
+
+#[test]
+fn test_well_formed_edge_cases() {
+    let parser = LLSDXmlParser::new();
+    assert!(parser.parse("<llsd></llsd>").unwrap().content().is_undefined());
+    assert_eq!(
+        parser.parse("<llsd><map><key>a</key><undef/></map></llsd>").unwrap().content().get_path("a"),
+        Some(&LLSDValue::Undefined)
+    );
+    assert_eq!(parser.parse("<llsd><map></map></llsd>").unwrap().content(), &LLSDValue::Map(Default::default()));
+}
+
+#[test]
+fn test_lenient_maps_still_recover() {
+    let parser = LLSDXmlParser::new();
+    let (document, warnings) = parser.parse_lenient("<llsd><map><key>lonely</key></map></llsd>").unwrap();
+    assert_eq!(document.content(), &LLSDValue::Map(Default::default()));
+    assert_eq!(warnings, vec!["Map key 'lonely' has no value".to_string()]);
+
+    // Truncation is never recoverable
+    assert!(parser.parse_lenient("<llsd><map><key>a</key><integer>1</integer>").is_err());
+}